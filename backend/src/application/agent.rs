@@ -1,15 +1,28 @@
+use crate::application::memory_extraction::MemoryExtractor;
+use crate::application::memory_rollup::MemoryRollup;
 use crate::infrastructure::config::AppConfig;
 use crate::domain::chat::ToolEvent;
+use crate::domain::memory::{MemoryScope, MemoryScoping};
 use crate::domain::ports::{
-    MemoryHit, MemoryPort, ModelPort, ModelRequest, ToolExecutionContext, ToolExecutorPort,
+    CheckpointPort, FileWatchPort, MemoryHit, MemoryPort, ModelPort, ModelRequest,
+    ToolExecutionContext, ToolExecutorPort, WorkspaceHit, WorkspaceSearchPort,
 };
 use crate::infrastructure::personality::PersonalitySource;
-use crate::domain::types::{Message, SessionState, ToolResult, Usage};
-use anyhow::Result;
+use crate::domain::tools::ToolPolicy;
+use crate::domain::types::{
+    render_plan, Message, PlanItem, SessionState, ToolResult, ToolSpec, Usage,
+};
+use anyhow::{anyhow, Result};
 use futures::StreamExt;
 use serde::Serialize;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+
+/// Highest-scoring memory lines added to the system prompt.
+const MEMORY_CONTEXT_HITS: usize = 6;
+/// Characters of each injected workspace passage; `search_workspace` returns full chunks.
+const WORKSPACE_EXCERPT_CHARS: usize = 400;
+const SEARCH_WORKSPACE_TOOL: &str = "search_workspace";
 
 #[derive(Clone, Debug)]
 pub struct AgentConfig {
@@ -40,6 +53,14 @@ pub struct AgentLoop {
     tools: Arc<dyn ToolExecutorPort>,
     personality: Arc<dyn PersonalitySource>,
     memory: Arc<dyn MemoryPort>,
+    checkpoints: Option<Arc<dyn CheckpointPort>>,
+    tool_policy: ToolPolicy,
+    workspace_index: Option<Arc<dyn WorkspaceSearchPort>>,
+    workspace_context_limit: usize,
+    memory_extractor: Option<Arc<MemoryExtractor>>,
+    memory_rollup: Option<Arc<MemoryRollup>>,
+    memory_scoping: MemoryScoping,
+    file_watcher: Option<Arc<dyn FileWatchPort>>,
     config: AgentConfig,
 }
 
@@ -47,6 +68,8 @@ pub struct AgentLoop {
 pub enum AgentStreamEvent {
     Delta(String),
     Tool(ToolEvent),
+    /// The session plan after a tool changed it.
+    Plan(Vec<PlanItem>),
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -70,10 +93,118 @@ impl AgentLoop {
             tools,
             personality,
             memory,
+            checkpoints: None,
+            tool_policy: ToolPolicy::default(),
+            workspace_index: None,
+            workspace_context_limit: 0,
+            memory_extractor: None,
+            memory_rollup: None,
+            memory_scoping: MemoryScoping::Global,
+            file_watcher: None,
             config,
         }
     }
 
+    pub fn with_checkpoints(mut self, checkpoints: Arc<dyn CheckpointPort>) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
+    pub fn checkpoints(&self) -> Option<Arc<dyn CheckpointPort>> {
+        self.checkpoints.clone()
+    }
+
+    pub fn with_tool_policy(mut self, tool_policy: ToolPolicy) -> Self {
+        self.tool_policy = tool_policy;
+        self
+    }
+
+    /// Injects the top `context_limit` workspace passages for each user message into the
+    /// system prompt (none when `0`).
+    pub fn with_workspace_index(
+        mut self,
+        index: Arc<dyn WorkspaceSearchPort>,
+        context_limit: usize,
+    ) -> Self {
+        self.workspace_index = Some(index);
+        self.workspace_context_limit = context_limit;
+        self
+    }
+
+    /// Schedules post-conversation memory extraction after every completed run.
+    pub fn with_memory_extractor(mut self, extractor: Arc<MemoryExtractor>) -> Self {
+        self.memory_extractor = Some(extractor);
+        self
+    }
+
+    /// Daily log rollup for this agent's memory, run by the scheduler and the rollup API.
+    pub fn with_memory_rollup(mut self, rollup: Arc<MemoryRollup>) -> Self {
+        self.memory_rollup = Some(rollup);
+        self
+    }
+
+    pub fn memory_rollup(&self) -> Option<Arc<MemoryRollup>> {
+        self.memory_rollup.clone()
+    }
+
+    /// How sessions are assigned a memory scope (everyone shares global memory by default).
+    pub fn with_memory_scoping(mut self, memory_scoping: MemoryScoping) -> Self {
+        self.memory_scoping = memory_scoping;
+        self
+    }
+
+    /// The memory scope `session` reads and writes.
+    pub fn memory_scope(&self, session: &SessionState) -> MemoryScope {
+        self.memory_scoping.scope_for(
+            &session.id,
+            session.channel.as_deref(),
+            session.user_id.as_deref(),
+        )
+    }
+
+    /// The watcher keeping this agent's personality and memory caches current; it stops when
+    /// the agent is dropped.
+    pub fn with_file_watcher(mut self, file_watcher: Arc<dyn FileWatchPort>) -> Self {
+        self.file_watcher = Some(file_watcher);
+        self
+    }
+
+    pub fn file_watcher(&self) -> Option<Arc<dyn FileWatchPort>> {
+        self.file_watcher.clone()
+    }
+
+    pub fn tool_policy(&self) -> &ToolPolicy {
+        &self.tool_policy
+    }
+
+    pub fn tools(&self) -> Arc<dyn ToolExecutorPort> {
+        self.tools.clone()
+    }
+
+    /// Registered tools enabled for `channel` (the default selection when `None`).
+    pub fn tool_specs(&self, channel: Option<&str>) -> Vec<ToolSpec> {
+        self.tool_policy
+            .for_channel(channel)
+            .filter(self.tools.specs())
+    }
+
+    pub fn memory(&self) -> Arc<dyn MemoryPort> {
+        self.memory.clone()
+    }
+
+    pub fn config(&self) -> &AgentConfig {
+        &self.config
+    }
+
+    /// Context for running tools on behalf of `session`, as the agent loop does; its memory is
+    /// narrowed to the scope `memory.scope` assigns the session.
+    pub fn tool_context(&self, session: &SessionState) -> ToolExecutionContext {
+        ToolExecutionContext::new(self.config.working_dir.clone(), self.memory.clone())
+            .with_session(session.id.clone())
+            .with_checkpoints(self.checkpoints.clone())
+            .with_memory_scope(self.memory_scope(session))
+    }
+
     pub async fn run(
         &self,
         session: &mut SessionState,
@@ -92,33 +223,63 @@ impl AgentLoop {
         F: FnMut(AgentStreamEvent),
     {
         let system_prompt = self.personality.system_prompt().await?;
-        let memory_context = match self.memory.search(&user_input).await {
+        let memory_scope = self.memory_scope(session);
+        let memory = self.memory.scoped(&memory_scope);
+        let curated_memory = match memory.prompt_memory().await {
+            Ok(curated) => curated,
+            Err(error) => {
+                tracing::warn!(error = %error, "curated memory unavailable; continuing without it");
+                None
+            }
+        };
+        let system_prompt = Self::with_curated_memory(&system_prompt, curated_memory.as_deref());
+        let memory_context = match memory.search(&user_input).await {
             Ok(hits) => hits,
             Err(error) => {
                 tracing::warn!(error = %error, "memory search failed; continuing without context");
                 Vec::new()
             }
         };
+        let tool_selection = self.tool_policy.for_channel(session.channel.as_deref());
+        // Injected passages are what `search_workspace` would return, so channels without the
+        // tool do not get them either.
+        let workspace_context = match &self.workspace_index {
+            Some(index)
+                if self.workspace_context_limit > 0
+                    && tool_selection.allows(SEARCH_WORKSPACE_TOOL) =>
+            {
+                match index.search(&user_input, self.workspace_context_limit).await {
+                    Ok(hits) => hits,
+                    Err(error) => {
+                        tracing::warn!(error = %error, "workspace search failed; continuing without context");
+                        Vec::new()
+                    }
+                }
+            }
+            _ => Vec::new(),
+        };
 
         tracing::debug!(
             session_id = %session.id,
             input_chars = user_input.chars().count(),
             memory_hits = memory_context.len(),
+            workspace_hits = workspace_context.len(),
             "agent run_stream start"
         );
 
+        let run_start = session.messages.len();
         let user_message = Message::user(user_input.clone());
         session.push_message(user_message);
 
-        let mut messages = vec![Message::system(Self::build_system_prompt(
-            &system_prompt,
-            &memory_context,
-        ))];
+        let base_prompt =
+            Self::build_system_prompt(&system_prompt, &memory_context, &workspace_context);
+        let mut messages = vec![Message::system(base_prompt.clone())];
         messages.extend(session.messages.clone());
 
         let mut usage = None;
         let mut finish_reason = None;
         let mut tool_events = Vec::new();
+        let tool_specs = tool_selection.filter(self.tools.specs());
 
         for iteration in 0..self.config.max_iterations {
             tracing::debug!(
@@ -127,6 +288,7 @@ impl AgentLoop {
                 max_iterations = self.config.max_iterations,
                 "agent iteration"
             );
+            messages[0] = Message::system(Self::with_plan_context(&base_prompt, &session.plan));
             Self::enforce_token_budget(&mut messages, self.config.token_budget);
 
             let mut stream = self
@@ -134,7 +296,7 @@ impl AgentLoop {
                 .chat_stream(ModelRequest {
                     model: self.config.model.clone(),
                     messages: messages.clone(),
-                    tools: self.tools.specs(),
+                    tools: tool_specs.clone(),
                     temperature: self.config.temperature,
                     max_tokens: self.config.max_tokens,
                 })
@@ -175,7 +337,10 @@ impl AgentLoop {
                         .take(160)
                         .collect::<String>()
                 );
-                let _ = self.memory.append_daily_log(&summary).await;
+                let _ = memory.append_daily_log(&summary).await;
+                if let Some(extractor) = &self.memory_extractor {
+                    extractor.schedule(session, run_start, memory_scope.clone());
+                }
                 tracing::info!(
                     session_id = %session.id,
                     assistant_chars = assistant_message.content.chars().count(),
@@ -191,8 +356,8 @@ impl AgentLoop {
             }
 
             finish_reason = Some("tool_calls".to_string());
-            let tool_context =
-                ToolExecutionContext::new(self.config.working_dir.clone(), self.memory.clone());
+            let plan = Arc::new(Mutex::new(session.plan.clone()));
+            let tool_context = self.tool_context(session).with_plan(plan.clone());
             tracing::debug!(
                 session_id = %session.id,
                 tool_calls = tool_calls.len(),
@@ -206,17 +371,21 @@ impl AgentLoop {
                     tool_call_id = %call.id,
                     "agent dispatch tool call"
                 );
-                let result = match self
-                    .tools
-                    .execute(&call.id, &call.name, call.arguments.clone(), &tool_context)
-                    .await
-                {
+                let outcome = if tool_selection.allows(&call.name) {
+                    self.tools
+                        .execute(&call.id, &call.name, call.arguments.clone(), &tool_context)
+                        .await
+                } else {
+                    Err(anyhow!("tool not enabled: {}", call.name))
+                };
+                let result = match outcome {
                     Ok(output) => output,
                     Err(error) => ToolResult {
                         tool_call_id: call.id.clone(),
                         name: call.name.clone(),
                         output: format!("tool error: {error}"),
                         is_error: true,
+                        truncation: None,
                     },
                 };
                 if result.is_error {
@@ -235,6 +404,17 @@ impl AgentLoop {
                 let tool_event = ToolEvent { call, result };
                 on_event(AgentStreamEvent::Tool(tool_event.clone()));
                 tool_events.push(tool_event);
+
+                let updated = plan.lock().map(|plan| plan.clone()).unwrap_or_default();
+                if updated != session.plan {
+                    tracing::debug!(
+                        session_id = %session.id,
+                        items = updated.len(),
+                        "session plan updated"
+                    );
+                    session.plan = updated.clone();
+                    on_event(AgentStreamEvent::Plan(updated));
+                }
             }
         }
 
@@ -255,21 +435,66 @@ impl AgentLoop {
         })
     }
 
-    pub fn build_system_prompt(personality_prompt: &str, memory_context: &[MemoryHit]) -> String {
+    pub fn build_system_prompt(
+        personality_prompt: &str,
+        memory_context: &[MemoryHit],
+        workspace_context: &[WorkspaceHit],
+    ) -> String {
         let mut prompt = personality_prompt.trim().to_string();
         if !memory_context.is_empty() {
-            let memory_block = memory_context
-                .iter()
-                .take(6)
+            let mut ranked: Vec<&MemoryHit> = memory_context.iter().collect();
+            ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+            let memory_block = ranked
+                .into_iter()
+                .take(MEMORY_CONTEXT_HITS)
                 .map(|hit| format!("- {}:{}: {}", hit.path, hit.line, hit.snippet))
                 .collect::<Vec<_>>()
                 .join("\n");
             prompt.push_str("\n\n# Relevant Memory Context\n");
             prompt.push_str(&memory_block);
         }
+        if !workspace_context.is_empty() {
+            let workspace_block = workspace_context
+                .iter()
+                .map(|hit| {
+                    let excerpt = hit.text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    format!(
+                        "- {}:{}-{}: {}",
+                        hit.path,
+                        hit.start_line,
+                        hit.end_line,
+                        excerpt.chars().take(WORKSPACE_EXCERPT_CHARS).collect::<String>()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            prompt.push_str("\n\n# Relevant Workspace Context\n");
+            prompt.push_str(&workspace_block);
+        }
         prompt
     }
 
+    /// `prompt` followed by the curated memory a backend puts in every prompt, if any.
+    pub fn with_curated_memory(prompt: &str, curated: Option<&str>) -> String {
+        match curated.map(str::trim) {
+            Some(curated) if !curated.is_empty() => {
+                format!("{}\n\n# Curated Memory\n{curated}", prompt.trim())
+            }
+            _ => prompt.to_string(),
+        }
+    }
+
+    /// `prompt` followed by the session plan, so the model sees its checklist every turn.
+    pub fn with_plan_context(prompt: &str, plan: &[PlanItem]) -> String {
+        if plan.is_empty() {
+            return prompt.to_string();
+        }
+        format!(
+            "{prompt}\n\n# Current Plan\nKeep this checklist current with todo_write as you work.\n{}",
+            render_plan(plan)
+        )
+    }
+
     pub fn enforce_token_budget(messages: &mut Vec<Message>, token_budget: u32) {
         while Self::estimate_tokens(messages) > token_budget && messages.len() > 2 {
             messages.remove(1);
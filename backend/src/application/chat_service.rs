@@ -52,6 +52,10 @@ impl ChatService {
 
         let (session_id, mut session) =
             self.resolve_session(command.session_id, command.channel.clone()).await;
+        if let Some(channel) = &command.channel {
+            session.channel = Some(channel.channel.clone());
+            session.user_id = Some(channel.user_id.clone());
+        }
         on_event(ChatEvent::Session {
             session_id: session_id.clone(),
         });
@@ -71,6 +75,7 @@ impl ChatService {
                     );
                     on_event(ChatEvent::Tool(tool));
                 }
+                AgentStreamEvent::Plan(plan) => on_event(ChatEvent::Plan(plan)),
             })
             .await;
 
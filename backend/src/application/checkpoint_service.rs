@@ -0,0 +1,57 @@
+use crate::domain::checkpoint::{Checkpoint, CheckpointDiff, CheckpointRestore};
+use crate::domain::ports::CheckpointPort;
+use crate::domain::AppError;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct CheckpointService {
+    store: Option<Arc<dyn CheckpointPort>>,
+}
+
+impl CheckpointService {
+    pub fn new(store: Option<Arc<dyn CheckpointPort>>) -> Self {
+        Self { store }
+    }
+
+    pub async fn list(&self, session_id: &str) -> Result<Vec<Checkpoint>, AppError> {
+        self.require_store()?
+            .list(session_id)
+            .await
+            .map_err(|error| map_internal(error, "list"))
+    }
+
+    pub async fn diff(
+        &self,
+        session_id: &str,
+        checkpoint_id: &str,
+    ) -> Result<CheckpointDiff, AppError> {
+        self.require_store()?
+            .diff(session_id, checkpoint_id)
+            .await
+            .map_err(|error| map_internal(error, "diff"))?
+            .ok_or_else(|| AppError::not_found("checkpoint not found"))
+    }
+
+    pub async fn restore(
+        &self,
+        session_id: &str,
+        checkpoint_id: &str,
+    ) -> Result<CheckpointRestore, AppError> {
+        self.require_store()?
+            .restore(session_id, checkpoint_id)
+            .await
+            .map_err(|error| map_internal(error, "restore"))?
+            .ok_or_else(|| AppError::not_found("checkpoint not found"))
+    }
+
+    fn require_store(&self) -> Result<Arc<dyn CheckpointPort>, AppError> {
+        self.store
+            .clone()
+            .ok_or_else(|| AppError::service_unavailable("checkpoints are disabled"))
+    }
+}
+
+fn map_internal(error: anyhow::Error, action: &str) -> AppError {
+    tracing::warn!(action, error = %error, "checkpoint endpoint failed");
+    AppError::internal(format!("checkpoint {action} failed"))
+}
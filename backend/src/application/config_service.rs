@@ -1,6 +1,7 @@
 use crate::runtime::config_runtime::ConfigRuntime;
 use crate::domain::config::{
-    ConfigMutationInput, ConfigMutationResponse, ConfigRestartInput, ConfigStateResponse,
+    CommandChangeRejected, ConfigMutationInput, ConfigMutationResponse, ConfigRestartInput,
+    ConfigStateResponse,
 };
 use crate::domain::{audit, AppError};
 use std::sync::Arc;
@@ -25,7 +26,7 @@ impl ConfigService {
         runtime
             .reset()
             .await
-            .map_err(|error| map_internal(error, "reset"))?;
+            .map_err(|error| map_error(error, "reset"))?;
         let state = build_config_state_response(&runtime).await;
         Ok(ConfigMutationResponse {
             ok: true,
@@ -50,7 +51,7 @@ impl ConfigService {
                 runtime
                     .apply_raw(&raw)
                     .await
-                    .map_err(|error| map_internal(error, "apply_raw"))?;
+                    .map_err(|error| map_error(error, "apply_raw"))?;
             }
             ConfigMutationInput::Structured(config) => {
                 let value = serde_json::to_value(&config).unwrap_or_default();
@@ -62,7 +63,7 @@ impl ConfigService {
                 runtime
                     .apply_structured(config)
                     .await
-                    .map_err(|error| map_internal(error, "apply_structured"))?;
+                    .map_err(|error| map_error(error, "apply_structured"))?;
             }
         }
 
@@ -84,7 +85,7 @@ impl ConfigService {
             ConfigRestartInput::Noop => runtime
                 .request_restart()
                 .await
-                .map_err(|error| map_internal(error, "request_restart"))?,
+                .map_err(|error| map_error(error, "request_restart"))?,
             ConfigRestartInput::Raw(raw) => {
                 tracing::info!(
                     action = "restart",
@@ -94,7 +95,7 @@ impl ConfigService {
                 runtime
                     .restart_after_apply_raw(&raw)
                     .await
-                    .map_err(|error| map_internal(error, "restart_after_apply_raw"))?
+                    .map_err(|error| map_error(error, "restart_after_apply_raw"))?
             }
             ConfigRestartInput::Structured(config) => {
                 let value = serde_json::to_value(&config).unwrap_or_default();
@@ -106,7 +107,7 @@ impl ConfigService {
                 runtime
                     .restart_after_apply_structured(config)
                     .await
-                    .map_err(|error| map_internal(error, "restart_after_apply_structured"))?
+                    .map_err(|error| map_error(error, "restart_after_apply_structured"))?
             }
         };
 
@@ -126,7 +127,11 @@ impl ConfigService {
     }
 }
 
-fn map_internal(error: anyhow::Error, action: &str) -> AppError {
+fn map_error(error: anyhow::Error, action: &str) -> AppError {
+    if let Some(rejected) = error.downcast_ref::<CommandChangeRejected>() {
+        tracing::warn!(action, error = %rejected, "config mutation rejected");
+        return AppError::bad_request(rejected.to_string());
+    }
     tracing::warn!(action, error = %error, "config endpoint failed");
     AppError::internal(format!("config {action} failed"))
 }
@@ -0,0 +1,317 @@
+//! Post-conversation memory extraction.
+//!
+//! After a run (or once a session goes idle) the new user/assistant turns are sent to the model
+//! together with the current `MEMORY.md`, asking for durable facts as JSON. Facts already in
+//! memory are skipped; the rest are written with [`MemoryEdit::Remember`], so they show up in
+//! the memory audit log like edits made by the memory tools.
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::domain::memory::{
+    EditOrigin, ExtractionTrigger, MemoryChange, MemoryEdit, MemoryExtractionOptions, MemoryScope,
+};
+use crate::domain::ports::{MemoryPort, ModelPort, ModelRequest};
+use crate::domain::types::{Message, Role, SessionState};
+
+/// Facts written per extraction at most.
+const MAX_EXTRACTED_FACTS: usize = 10;
+/// Characters of each message included in the extraction transcript.
+const MESSAGE_CHARS: usize = 2000;
+const EXTRACTION_MAX_TOKENS: u32 = 800;
+
+const EXTRACTION_PROMPT: &str = "You maintain the long-term memory of an assistant. From the conversation below, extract durable facts worth remembering across future conversations: stable user preferences, personal details the user shared, decisions, and project facts. Ignore small talk, one-off requests, and anything already present in the existing memory. Reply with JSON only, in the form {\"facts\": [{\"section\": \"Preferences\", \"fact\": \"one short self-contained sentence\"}]}. Reuse existing section headings where they fit. Reply {\"facts\": []} when there is nothing new.";
+
+#[derive(Debug, Deserialize)]
+struct ExtractionReply {
+    #[serde(default)]
+    facts: Vec<ExtractedFact>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExtractedFact {
+    section: String,
+    fact: String,
+}
+
+#[derive(Debug, Default)]
+struct SessionCursor {
+    /// Messages of the session already extracted, or being extracted.
+    extracted: usize,
+    /// Bumped on every scheduled run, so an idle timer only fires for the latest one.
+    generation: u64,
+}
+
+pub struct MemoryExtractor {
+    provider: Arc<dyn ModelPort>,
+    memory: Arc<dyn MemoryPort>,
+    model: String,
+    options: MemoryExtractionOptions,
+    cursors: Mutex<HashMap<String, SessionCursor>>,
+}
+
+impl MemoryExtractor {
+    /// `default_model` is used unless `options.model` names another one.
+    pub fn new(
+        provider: Arc<dyn ModelPort>,
+        memory: Arc<dyn MemoryPort>,
+        default_model: impl Into<String>,
+        options: MemoryExtractionOptions,
+    ) -> Self {
+        let model = options
+            .model
+            .clone()
+            .unwrap_or_else(|| default_model.into());
+        Self {
+            provider,
+            memory,
+            model,
+            options,
+            cursors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn options(&self) -> &MemoryExtractionOptions {
+        &self.options
+    }
+
+    /// Starts extraction for `session` into `scope` in the background according to the
+    /// trigger. `run_start` is the index of the run's first message, where extraction starts
+    /// unless earlier turns are still pending. Sessions on channels that are not enabled are
+    /// ignored.
+    pub fn schedule(
+        self: &Arc<Self>,
+        session: &SessionState,
+        run_start: usize,
+        scope: MemoryScope,
+    ) {
+        if !self.options.applies_to(session.channel.as_deref()) {
+            return;
+        }
+        let generation = {
+            let mut cursors = self
+                .cursors
+                .lock()
+                .unwrap_or_else(|error| error.into_inner());
+            let cursor = cursors
+                .entry(session.id.clone())
+                .or_insert_with(|| SessionCursor {
+                    extracted: run_start,
+                    generation: 0,
+                });
+            cursor.generation += 1;
+            cursor.generation
+        };
+        let extractor = self.clone();
+        let session_id = session.id.clone();
+        let messages = session.messages.clone();
+        let trigger = self.options.trigger;
+        let idle = self.options.idle;
+        tokio::spawn(async move {
+            if trigger == ExtractionTrigger::Idle {
+                tokio::time::sleep(idle).await;
+                if extractor.generation(&session_id) != generation {
+                    return;
+                }
+            }
+            if let Err(error) = extractor.extract(&session_id, &scope, &messages).await {
+                tracing::warn!(session_id = %session_id, error = %error, "memory extraction failed");
+            }
+            extractor.release(&session_id, generation, messages.len());
+        });
+    }
+
+    /// Sessions whose extraction state is kept: ones with a scheduled extraction, turns still
+    /// short of `min_turns`, or a failed extraction to retry.
+    pub fn tracked_sessions(&self) -> usize {
+        self.cursors
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .len()
+    }
+
+    /// Extracts facts from the messages added since the last extraction of `session_id` and
+    /// writes the new ones to the curated memory of `scope`.
+    pub async fn extract(
+        &self,
+        session_id: &str,
+        scope: &MemoryScope,
+        messages: &[Message],
+    ) -> Result<Vec<MemoryChange>> {
+        let Some(start) = self.claim(session_id, messages) else {
+            tracing::debug!(
+                session_id,
+                "memory extraction skipped; not enough new turns"
+            );
+            return Ok(Vec::new());
+        };
+        let result = self.extract_from(session_id, scope, messages, start).await;
+        if result.is_err() {
+            self.unclaim(session_id, start);
+        }
+        result
+    }
+
+    async fn extract_from(
+        &self,
+        session_id: &str,
+        scope: &MemoryScope,
+        messages: &[Message],
+        start: usize,
+    ) -> Result<Vec<MemoryChange>> {
+        let transcript = render_transcript(&messages[start..]);
+        let memory = self.memory.scoped(scope);
+        let curated = memory.read_curated().await?;
+
+        let response = self
+            .provider
+            .chat(ModelRequest {
+                model: self.model.clone(),
+                messages: vec![
+                    Message::system(EXTRACTION_PROMPT),
+                    Message::user(format!(
+                        "# Existing memory\n{curated}\n\n# Conversation\n{transcript}"
+                    )),
+                ],
+                tools: Vec::new(),
+                temperature: 0.0,
+                max_tokens: EXTRACTION_MAX_TOKENS,
+            })
+            .await?;
+        let facts = parse_reply(&response.message.content)?;
+        let proposed = facts.len();
+
+        let known = curated.to_lowercase();
+        let mut changes = Vec::new();
+        for fact in facts.into_iter().take(MAX_EXTRACTED_FACTS) {
+            if fact.fact.trim().is_empty() || known.contains(&fact.fact.trim().to_lowercase()) {
+                continue;
+            }
+            let edit = MemoryEdit::Remember {
+                section: fact.section,
+                fact: fact.fact,
+            };
+            let origin = EditOrigin {
+                session_id: Some(session_id.to_string()),
+                tool_call_id: None,
+            };
+            match memory.edit_curated(edit, origin).await {
+                Ok(change) if change.changed => changes.push(change),
+                Ok(_) => {}
+                Err(error) => {
+                    tracing::warn!(session_id, error = %error, "extracted fact not saved");
+                }
+            }
+        }
+        tracing::info!(
+            session_id,
+            model = %self.model,
+            messages = messages.len() - start,
+            proposed,
+            saved = changes.len(),
+            "memory extraction finished"
+        );
+        Ok(changes)
+    }
+
+    fn generation(&self, session_id: &str) -> u64 {
+        let cursors = self
+            .cursors
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        cursors
+            .get(session_id)
+            .map_or(0, |cursor| cursor.generation)
+    }
+
+    /// Marks `messages` as extracted and returns where the new ones start, or `None` when there
+    /// are fewer than `min_turns` new user messages.
+    fn claim(&self, session_id: &str, messages: &[Message]) -> Option<usize> {
+        let mut cursors = self
+            .cursors
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let cursor = cursors.entry(session_id.to_string()).or_default();
+        let start = cursor.extracted.min(messages.len());
+        let turns = messages[start..]
+            .iter()
+            .filter(|message| message.role == Role::User)
+            .count();
+        if turns == 0 || turns < self.options.min_turns {
+            return None;
+        }
+        cursor.extracted = messages.len();
+        Some(start)
+    }
+
+    /// Hands the messages from `start` back after a failed extraction, so the next one retries
+    /// them. Facts already in memory are skipped then, so an overlap with a newer claim is
+    /// harmless.
+    fn unclaim(&self, session_id: &str, start: usize) {
+        let mut cursors = self
+            .cursors
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let cursor = cursors
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionCursor {
+                extracted: start,
+                generation: 0,
+            });
+        cursor.extracted = cursor.extracted.min(start);
+    }
+
+    /// Forgets `session_id` once the extraction scheduled as `generation` covered all `len`
+    /// messages and no newer one was scheduled; the next run passes its own start.
+    fn release(&self, session_id: &str, generation: u64, len: usize) {
+        let mut cursors = self
+            .cursors
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        if cursors
+            .get(session_id)
+            .is_some_and(|cursor| cursor.generation == generation && cursor.extracted >= len)
+        {
+            cursors.remove(session_id);
+        }
+    }
+}
+
+fn render_transcript(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .filter_map(|message| {
+            let speaker = match message.role {
+                Role::User => "User",
+                Role::Assistant => "Assistant",
+                Role::System | Role::Tool => return None,
+            };
+            let content = message.content.trim();
+            if content.is_empty() {
+                return None;
+            }
+            Some(format!(
+                "{speaker}: {}",
+                content.chars().take(MESSAGE_CHARS).collect::<String>()
+            ))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The JSON object in the model reply, tolerating code fences and surrounding prose.
+fn parse_reply(content: &str) -> Result<Vec<ExtractedFact>> {
+    let start = content.find('{');
+    let end = content.rfind('}');
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(anyhow!("memory extraction reply is not JSON: {content}"));
+    };
+    if end < start {
+        return Err(anyhow!("memory extraction reply is not JSON: {content}"));
+    }
+    let reply: ExtractionReply = serde_json::from_str(&content[start..=end])
+        .map_err(|error| anyhow!("invalid memory extraction reply: {error}"))?;
+    Ok(reply.facts)
+}
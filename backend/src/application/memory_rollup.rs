@@ -0,0 +1,261 @@
+//! Daily log rollup.
+//!
+//! Daily logs (`YYYY-MM-DD.md`) of weeks or months that ended more than `after_days` ago are
+//! merged into one `digest-<period>.md` per period, either verbatim or summarised by the model,
+//! and the originals are then archived, deleted or kept per the retention mode. Every digest
+//! lists its source days, so re-running a rollup never adds a day twice.
+
+use anyhow::Result;
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::domain::memory::{
+    MemoryFile, RetentionMode, RollupDigest, RollupOptions, RollupPeriod, RollupReport,
+};
+use crate::domain::ports::{MemoryPort, ModelPort, ModelRequest};
+use crate::domain::types::Message;
+
+const SUMMARY_MAX_TOKENS: u32 = 1200;
+const SUMMARY_PROMPT: &str = "Summarise these daily conversation logs of an assistant into a concise digest. Keep durable facts, decisions, preferences and open tasks as short bullet points; drop small talk and repetition. Reply with the bullet list only.";
+
+pub struct MemoryRollup {
+    memory: Arc<dyn MemoryPort>,
+    summarizer: Option<(Arc<dyn ModelPort>, String)>,
+    options: RollupOptions,
+}
+
+impl MemoryRollup {
+    pub fn new(memory: Arc<dyn MemoryPort>, options: RollupOptions) -> Self {
+        Self {
+            memory,
+            summarizer: None,
+            options,
+        }
+    }
+
+    /// Model used for digests when `options.summarize` is set.
+    pub fn with_summarizer(
+        mut self,
+        provider: Arc<dyn ModelPort>,
+        model: impl Into<String>,
+    ) -> Self {
+        self.summarizer = Some((provider, model.into()));
+        self
+    }
+
+    pub fn options(&self) -> &RollupOptions {
+        &self.options
+    }
+
+    pub async fn run(&self, dry_run: bool) -> Result<RollupReport> {
+        self.run_at(Utc::now().date_naive(), dry_run).await
+    }
+
+    /// Rolls up the periods that ended at least `after_days` before `today`. A dry run only
+    /// reports what would be written and retired.
+    pub async fn run_at(&self, today: NaiveDate, dry_run: bool) -> Result<RollupReport> {
+        let cutoff = today - ChronoDuration::days(i64::from(self.options.after_days));
+        let files = self.memory.list_files().await?;
+
+        let mut periods: BTreeMap<String, Vec<(NaiveDate, &MemoryFile)>> = BTreeMap::new();
+        for file in &files {
+            let Some(date) = file.date else {
+                continue;
+            };
+            if period_end(date, self.options.period) <= cutoff {
+                periods
+                    .entry(period_key(date, self.options.period))
+                    .or_default()
+                    .push((date, file));
+            }
+        }
+
+        let mut digests = Vec::new();
+        for (period, mut logs) in periods {
+            logs.sort_by_key(|(date, _)| *date);
+            let path = format!("digest-{period}.md");
+            let existing = if files.iter().any(|file| file.path == path) {
+                Some(self.memory.get_file(&path, None, None).await?)
+            } else {
+                None
+            };
+            // Days already in the digest are only retired, never added again.
+            let new_logs: Vec<&(NaiveDate, &MemoryFile)> = logs
+                .iter()
+                .filter(|(date, _)| {
+                    existing
+                        .as_deref()
+                        .is_none_or(|digest| !sources_line(digest).contains(&date.to_string()))
+                })
+                .collect();
+
+            if new_logs.is_empty() && self.options.retention == RetentionMode::Keep {
+                continue;
+            }
+
+            let mut summarized = false;
+            if !new_logs.is_empty() && !dry_run {
+                let mut days = Vec::new();
+                for (date, file) in &new_logs {
+                    let content = self.memory.get_file(&file.path, None, None).await?;
+                    days.push((*date, content));
+                }
+                let (body, used_model) = self.digest_body(&period, &days).await;
+                summarized = used_model;
+                let content = render_digest(&period, existing.as_deref(), &days, &body);
+                self.memory.write_file(&path, &content).await?;
+            } else if !new_logs.is_empty() {
+                summarized = self.options.summarize && self.summarizer.is_some();
+            }
+
+            let sources: Vec<String> = logs.iter().map(|(_, file)| file.path.clone()).collect();
+            if !dry_run {
+                for source in &sources {
+                    match self.options.retention {
+                        RetentionMode::Archive => self.memory.remove_file(source, true).await?,
+                        RetentionMode::Delete => self.memory.remove_file(source, false).await?,
+                        RetentionMode::Keep => {}
+                    }
+                }
+            }
+            tracing::info!(
+                period = %period,
+                path = %path,
+                sources = sources.len(),
+                added = new_logs.len(),
+                summarized,
+                dry_run,
+                "memory rollup digest"
+            );
+            digests.push(RollupDigest {
+                period,
+                path,
+                sources,
+                summarized,
+            });
+        }
+
+        tracing::info!(
+            digests = digests.len(),
+            retention = ?self.options.retention,
+            dry_run,
+            "memory rollup finished"
+        );
+        Ok(RollupReport {
+            dry_run,
+            retention: self.options.retention,
+            digests,
+        })
+    }
+
+    /// The model summary when summarising is enabled and succeeds, else the logs verbatim.
+    async fn digest_body(&self, period: &str, days: &[(NaiveDate, String)]) -> (String, bool) {
+        let verbatim = days
+            .iter()
+            .map(|(date, content)| format!("## {date}\n{}", content.trim()))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let Some((provider, model)) = self.summarizer.as_ref().filter(|_| self.options.summarize)
+        else {
+            return (verbatim, false);
+        };
+        let request = ModelRequest {
+            model: model.clone(),
+            messages: vec![
+                Message::system(SUMMARY_PROMPT),
+                Message::user(verbatim.clone()),
+            ],
+            tools: Vec::new(),
+            temperature: 0.0,
+            max_tokens: SUMMARY_MAX_TOKENS,
+        };
+        match provider.chat(request).await {
+            Ok(response) if !response.message.content.trim().is_empty() => (
+                format!("## Summary\n{}", response.message.content.trim()),
+                true,
+            ),
+            Ok(_) => (verbatim, false),
+            Err(error) => {
+                tracing::warn!(period, error = %error, "memory rollup summary failed; keeping logs verbatim");
+                (verbatim, false)
+            }
+        }
+    }
+}
+
+fn period_key(date: NaiveDate, period: RollupPeriod) -> String {
+    match period {
+        RollupPeriod::Week => {
+            let week = date.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        }
+        RollupPeriod::Month => date.format("%Y-%m").to_string(),
+    }
+}
+
+/// Last day of the week (Sunday) or month containing `date`.
+fn period_end(date: NaiveDate, period: RollupPeriod) -> NaiveDate {
+    match period {
+        RollupPeriod::Week => {
+            date + ChronoDuration::days(6 - i64::from(date.weekday().num_days_from_monday()))
+        }
+        RollupPeriod::Month => {
+            let (year, month) = if date.month() == 12 {
+                (date.year() + 1, 1)
+            } else {
+                (date.year(), date.month() + 1)
+            };
+            NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(date) - ChronoDuration::days(1)
+        }
+    }
+}
+
+const SOURCES_PREFIX: &str = "Sources: ";
+
+fn sources_line(digest: &str) -> &str {
+    digest
+        .lines()
+        .find(|line| line.starts_with(SOURCES_PREFIX))
+        .unwrap_or_default()
+}
+
+fn render_digest(
+    period: &str,
+    existing: Option<&str>,
+    days: &[(NaiveDate, String)],
+    body: &str,
+) -> String {
+    let mut sources: Vec<String> = existing
+        .map(|digest| {
+            sources_line(digest)
+                .trim_start_matches(SOURCES_PREFIX)
+                .split(", ")
+                .filter(|source| !source.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    sources.extend(days.iter().map(|(date, _)| date.to_string()));
+    sources.sort();
+    sources.dedup();
+
+    let previous_body = existing
+        .map(|digest| {
+            digest
+                .lines()
+                .skip_while(|line| !line.starts_with("## "))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+    let body = if previous_body.trim().is_empty() {
+        body.to_string()
+    } else {
+        format!("{}\n\n{body}", previous_body.trim_end())
+    };
+    format!(
+        "# Memory digest {period}\n\n{SOURCES_PREFIX}{}\n\n{body}\n",
+        sources.join(", ")
+    )
+}
@@ -0,0 +1,107 @@
+use crate::domain::memory::{is_memory_path, MemoryFile};
+use crate::domain::ports::{MemoryHit, MemoryPort};
+use crate::domain::AppError;
+use std::sync::Arc;
+
+pub const DEFAULT_MEMORY_SEARCH_LIMIT: usize = 20;
+pub const MAX_MEMORY_SEARCH_LIMIT: usize = 100;
+
+#[derive(Clone)]
+pub struct MemoryService {
+    memory: Arc<dyn MemoryPort>,
+}
+
+impl MemoryService {
+    pub fn new(memory: Arc<dyn MemoryPort>) -> Self {
+        Self { memory }
+    }
+
+    pub async fn curated(&self) -> Result<String, AppError> {
+        self.memory
+            .read_curated()
+            .await
+            .map_err(|error| map_internal(error, "read"))
+    }
+
+    pub async fn replace_curated(&self, content: &str) -> Result<(), AppError> {
+        self.memory
+            .write_curated(content)
+            .await
+            .map_err(|error| map_internal(error, "write"))
+    }
+
+    pub async fn files(&self) -> Result<Vec<MemoryFile>, AppError> {
+        self.memory
+            .list_files()
+            .await
+            .map_err(|error| map_internal(error, "list"))
+    }
+
+    /// The file at `path`, or its 1-based inclusive line range when either end is given.
+    pub async fn file(
+        &self,
+        path: &str,
+        start_line: Option<usize>,
+        end_line: Option<usize>,
+    ) -> Result<String, AppError> {
+        let file = self.require_file(path).await?;
+        if start_line == Some(0) || end_line == Some(0) {
+            return Err(AppError::bad_request("line numbers start at 1"));
+        }
+        // `get_file` only slices when both ends are given.
+        let (start, end) = match (start_line, end_line) {
+            (None, None) => (None, None),
+            (start, end) => (Some(start.unwrap_or(1)), Some(end.unwrap_or(usize::MAX))),
+        };
+        self.memory
+            .get_file(&file.path, start, end)
+            .await
+            .map_err(|error| map_internal(error, "read"))
+    }
+
+    /// Deletes a daily log, or moves it to the archive; other memory files cannot be deleted
+    /// here.
+    pub async fn delete_daily_log(&self, path: &str, archive: bool) -> Result<(), AppError> {
+        let file = self.require_file(path).await?;
+        if file.date.is_none() {
+            return Err(AppError::bad_request(format!(
+                "only daily logs can be deleted: {path}"
+            )));
+        }
+        self.memory
+            .remove_file(&file.path, archive)
+            .await
+            .map_err(|error| map_internal(error, "delete"))
+    }
+
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<MemoryHit>, AppError> {
+        if query.trim().is_empty() {
+            return Err(AppError::bad_request("query must not be empty"));
+        }
+        let mut hits = self
+            .memory
+            .search(query)
+            .await
+            .map_err(|error| map_internal(error, "search"))?;
+        hits.truncate(limit.clamp(1, MAX_MEMORY_SEARCH_LIMIT));
+        Ok(hits)
+    }
+
+    async fn require_file(&self, path: &str) -> Result<MemoryFile, AppError> {
+        if !is_memory_path(path) {
+            return Err(AppError::bad_request(format!(
+                "invalid memory path: {path}"
+            )));
+        }
+        self.files()
+            .await?
+            .into_iter()
+            .find(|file| file.path == path)
+            .ok_or_else(|| AppError::not_found(format!("memory file not found: {path}")))
+    }
+}
+
+fn map_internal(error: anyhow::Error, action: &str) -> AppError {
+    tracing::warn!(action, error = %error, "memory endpoint failed");
+    AppError::internal(format!("memory {action} failed"))
+}
@@ -1,9 +1,19 @@
 pub mod agent;
 pub mod chat_service;
+pub mod checkpoint_service;
 pub mod config_service;
+pub mod memory_extraction;
+pub mod memory_rollup;
+pub mod memory_service;
 pub mod session_service;
+pub mod workspace_service;
 
 pub use agent::{AgentConfig, AgentLoop, AgentRunOutput, AgentStreamEvent};
 pub use chat_service::ChatService;
+pub use checkpoint_service::CheckpointService;
 pub use config_service::ConfigService;
+pub use memory_extraction::MemoryExtractor;
+pub use memory_rollup::MemoryRollup;
+pub use memory_service::MemoryService;
 pub use session_service::SessionService;
+pub use workspace_service::WorkspaceService;
@@ -0,0 +1,52 @@
+use crate::domain::ports::WorkspaceArchivePort;
+use crate::domain::workspace::{
+    ExportOptions, ImportOptions, ImportReport, InvalidArchive, WorkspaceExport,
+};
+use crate::domain::AppError;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct WorkspaceService {
+    archive: Option<Arc<dyn WorkspaceArchivePort>>,
+}
+
+impl WorkspaceService {
+    pub fn new(archive: Option<Arc<dyn WorkspaceArchivePort>>) -> Self {
+        Self { archive }
+    }
+
+    pub async fn export(&self, options: ExportOptions) -> Result<WorkspaceExport, AppError> {
+        self.require_archive()?
+            .export(options)
+            .await
+            .map_err(|error| map_internal(error, "export"))
+    }
+
+    pub async fn import(
+        &self,
+        archive: Vec<u8>,
+        options: ImportOptions,
+    ) -> Result<ImportReport, AppError> {
+        if archive.is_empty() {
+            return Err(AppError::bad_request("workspace archive is empty"));
+        }
+        self.require_archive()?
+            .import(archive, options)
+            .await
+            .map_err(|error| match error.downcast_ref::<InvalidArchive>() {
+                Some(invalid) => AppError::bad_request(invalid.to_string()),
+                None => map_internal(error, "import"),
+            })
+    }
+
+    fn require_archive(&self) -> Result<Arc<dyn WorkspaceArchivePort>, AppError> {
+        self.archive
+            .clone()
+            .ok_or_else(|| AppError::service_unavailable("workspace archives are not configured"))
+    }
+}
+
+fn map_internal(error: anyhow::Error, action: &str) -> AppError {
+    tracing::warn!(action, error = %error, "workspace endpoint failed");
+    AppError::internal(format!("workspace {action} failed"))
+}
@@ -1,7 +1,7 @@
 use crate::domain::types::{Message, Role};
 use serde_json::{Map, Value};
 
-const REDACTED: &str = "***REDACTED***";
+pub const REDACTED: &str = "***REDACTED***";
 
 pub fn redact_json(value: &Value) -> Value {
     match value {
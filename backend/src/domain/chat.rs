@@ -1,4 +1,4 @@
-use crate::domain::types::{ToolCall, ToolResult, Usage};
+use crate::domain::types::{PlanItem, ToolCall, ToolResult, Usage};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -14,6 +14,7 @@ pub enum ChatEvent {
     Session { session_id: String },
     Delta(String),
     Tool(ToolEvent),
+    Plan(Vec<PlanItem>),
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub id: String,
+    pub session_id: String,
+    pub tool_call_id: Option<String>,
+    pub tool_name: String,
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    /// Content hash of the file before the mutation; `None` when the file did not exist.
+    pub before_hash: Option<String>,
+    pub after_hash: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct FileSnapshot {
+    pub session_id: String,
+    pub tool_call_id: Option<String>,
+    pub tool_name: String,
+    pub path: String,
+    /// Raw bytes, so files that are not UTF-8 round-trip; `None` when the file did not exist.
+    pub before: Option<Vec<u8>>,
+    pub after: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointDiff {
+    pub checkpoint: Checkpoint,
+    pub diff: String,
+    pub additions: usize,
+    pub deletions: usize,
+    /// Whether the file on disk still matches the content written by this checkpoint.
+    pub current_matches_after: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointRestore {
+    pub restored: Checkpoint,
+    /// Checkpoint capturing the content that was overwritten by the restore, so it can be undone.
+    pub undo: Checkpoint,
+}
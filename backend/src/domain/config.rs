@@ -1,5 +1,6 @@
 use crate::infrastructure::config::AgentFileConfig;
 use serde::Serialize;
+use std::fmt;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ConfigStateResponse {
@@ -33,3 +34,24 @@ pub enum ConfigRestartInput {
     Raw(String),
     Structured(AgentFileConfig),
 }
+
+/// Set to `1` to let `/api/config/apply` add or change commands the server starts.
+pub const ALLOW_CONFIG_COMMANDS_ENV: &str = "CHAOS_BOT_ALLOW_CONFIG_COMMANDS";
+
+/// A config apply tried to add or change a command the server would start; nothing was
+/// applied or written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandChangeRejected(pub String);
+
+impl fmt::Display for CommandChangeRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "config apply cannot add or change {}; edit the config file and restart, or start \
+             the server with {}=1",
+            self.0, ALLOW_CONFIG_COMMANDS_ENV
+        )
+    }
+}
+
+impl std::error::Error for CommandChangeRejected {}
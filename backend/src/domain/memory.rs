@@ -0,0 +1,534 @@
+//! Section-level edits of the curated memory file.
+//!
+//! `MEMORY.md` is split on `## ` headings. Text before the first one (the `#` title and any
+//! notes) is kept as-is; each section owns the lines up to the next `## ` heading, so `###`
+//! sub-headings stay inside their section.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::domain::types::path_segment;
+
+pub const DEFAULT_EXTRACTION_IDLE_SECS: u64 = 300;
+pub const DEFAULT_EXTRACTION_MIN_TURNS: usize = 1;
+pub const DEFAULT_ROLLUP_AFTER_DAYS: u32 = 14;
+pub const DEFAULT_ROLLUP_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// One agent-requested change to `MEMORY.md`. Sections are matched by heading text,
+/// case-insensitively; `expected` is the section body the caller last saw and makes the edit
+/// fail if someone changed it since.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum MemoryEdit {
+    /// Appends `fact` as a bullet, creating the section when it is missing.
+    Remember { section: String, fact: String },
+    /// Replaces the body of the section, creating it when it is missing.
+    Update {
+        section: String,
+        content: String,
+        expected: Option<String>,
+    },
+    /// Removes bullets containing `fact`, or the whole section when `fact` is `None`.
+    Forget {
+        section: String,
+        fact: Option<String>,
+        expected: Option<String>,
+    },
+}
+
+impl MemoryEdit {
+    pub fn section(&self) -> &str {
+        match self {
+            Self::Remember { section, .. }
+            | Self::Update { section, .. }
+            | Self::Forget { section, .. } => section,
+        }
+    }
+
+    pub fn action(&self) -> &'static str {
+        match self {
+            Self::Remember { .. } => "remember",
+            Self::Update { .. } => "update",
+            Self::Forget { .. } => "forget",
+        }
+    }
+}
+
+/// Who asked for a [`MemoryEdit`], recorded in its [`MemoryChange`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditOrigin {
+    pub session_id: Option<String>,
+    pub tool_call_id: Option<String>,
+}
+
+/// Audit record of an applied [`MemoryEdit`]; `before`/`after` are the section bodies (`None`
+/// when the section did not exist before or was removed).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemoryChange {
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub section: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub session_id: Option<String>,
+    pub tool_call_id: Option<String>,
+    /// `false` when the edit left the file unchanged, e.g. remembering a known fact.
+    pub changed: bool,
+}
+
+/// When post-conversation memory extraction runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtractionTrigger {
+    /// As soon as a run finishes.
+    AfterRun,
+    /// Once the session has had no new run for the idle period.
+    Idle,
+}
+
+/// Settings for extracting durable facts from finished conversations into `MEMORY.md`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryExtractionOptions {
+    pub enabled: bool,
+    pub trigger: ExtractionTrigger,
+    pub idle: Duration,
+    /// Channels to extract from (`web` for sessions without one); `None` means all.
+    pub channels: Option<BTreeSet<String>>,
+    /// Model for the extraction request; the chat model when `None`.
+    pub model: Option<String>,
+    /// User turns a session needs since the last extraction.
+    pub min_turns: usize,
+}
+
+impl Default for MemoryExtractionOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            trigger: ExtractionTrigger::AfterRun,
+            idle: Duration::from_secs(DEFAULT_EXTRACTION_IDLE_SECS),
+            channels: None,
+            model: None,
+            min_turns: DEFAULT_EXTRACTION_MIN_TURNS,
+        }
+    }
+}
+
+impl MemoryExtractionOptions {
+    pub fn applies_to(&self, channel: Option<&str>) -> bool {
+        let channel = channel.unwrap_or("web");
+        self.enabled
+            && self
+                .channels
+                .as_ref()
+                .is_none_or(|channels| channels.contains(channel))
+    }
+}
+
+/// Whose memory a conversation reads and writes. Scoped memory has its own `MEMORY.md`, daily
+/// logs and audit log; searches in a scope also see the global memory.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MemoryScope {
+    #[default]
+    Global,
+    /// One person on a channel, e.g. a Telegram user across all their chats.
+    User {
+        channel: String,
+        user_id: String,
+    },
+    Session {
+        session_id: String,
+    },
+}
+
+impl MemoryScope {
+    /// Directory of the scope relative to the scopes root, with unsafe characters replaced;
+    /// `None` for the global scope.
+    pub fn relative_dir(&self) -> Option<String> {
+        match self {
+            Self::Global => None,
+            Self::User { channel, user_id } => Some(format!(
+                "users/{}/{}",
+                path_segment(channel),
+                path_segment(user_id)
+            )),
+            Self::Session { session_id } => Some(format!("sessions/{}", path_segment(session_id))),
+        }
+    }
+}
+
+/// How conversations are assigned a [`MemoryScope`] (`memory.scope`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryScoping {
+    /// Everyone shares the global memory.
+    #[default]
+    Global,
+    /// Channel users get their own scope; sessions without a channel user (the web UI) use
+    /// the global memory.
+    User,
+    /// Every session gets its own scope.
+    Session,
+}
+
+impl MemoryScoping {
+    pub fn scope_for(
+        self,
+        session_id: &str,
+        channel: Option<&str>,
+        user_id: Option<&str>,
+    ) -> MemoryScope {
+        match (self, channel, user_id) {
+            (Self::Global, _, _) => MemoryScope::Global,
+            (Self::User, Some(channel), Some(user_id)) => MemoryScope::User {
+                channel: channel.to_string(),
+                user_id: user_id.to_string(),
+            },
+            (Self::User, _, _) => MemoryScope::Global,
+            (Self::Session, _, _) => MemoryScope::Session {
+                session_id: session_id.to_string(),
+            },
+        }
+    }
+}
+
+/// Where curated memory is kept (`memory.backend`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryBackendKind {
+    /// `MEMORY.md` and markdown daily logs.
+    #[default]
+    Files,
+    /// Facts in a SQLite database; daily logs stay markdown files.
+    Sqlite,
+}
+
+/// One remembered fact of the structured memory backend; `subject` is the `MEMORY.md` section
+/// it is shown under.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemoryFact {
+    pub id: i64,
+    pub subject: String,
+    pub content: String,
+    pub source_session: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// 0 to 1; facts below the configured minimum stay searchable but are not put in prompts.
+    pub confidence: f32,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A file under the memory directory, or `MEMORY.md` itself.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryFile {
+    /// Relative to the memory directory; `MEMORY.md` for the curated file.
+    pub path: String,
+    pub bytes: u64,
+    pub modified: DateTime<Utc>,
+    /// Day of a `YYYY-MM-DD.md` daily log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+}
+
+/// Whether `path` names a visible file relative to the memory directory: not absolute and
+/// without `..` or hidden components such as the index and archive.
+pub fn is_memory_path(path: &str) -> bool {
+    !path.is_empty()
+        && std::path::Path::new(path)
+            .components()
+            .all(|component| match component {
+                std::path::Component::Normal(part) => !part.to_string_lossy().starts_with('.'),
+                _ => false,
+            })
+}
+
+/// How daily logs are grouped into digest files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupPeriod {
+    Week,
+    Month,
+}
+
+/// What happens to daily logs once they are in a digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionMode {
+    /// Move to the hidden archive directory, out of search.
+    Archive,
+    Delete,
+    /// Leave in place; the digest is added next to them.
+    Keep,
+}
+
+/// Settings for consolidating old daily logs into digests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RollupOptions {
+    /// Run on a schedule; a manual rollup works either way.
+    pub enabled: bool,
+    /// Daily logs younger than this many days are never rolled up.
+    pub after_days: u32,
+    pub period: RollupPeriod,
+    pub retention: RetentionMode,
+    pub interval: Duration,
+    /// Ask the model for a digest summary instead of concatenating the logs.
+    pub summarize: bool,
+}
+
+impl Default for RollupOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            after_days: DEFAULT_ROLLUP_AFTER_DAYS,
+            period: RollupPeriod::Week,
+            retention: RetentionMode::Archive,
+            interval: Duration::from_secs(DEFAULT_ROLLUP_INTERVAL_SECS),
+            summarize: false,
+        }
+    }
+}
+
+/// One digest written (or, in a dry run, planned) by a rollup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollupDigest {
+    /// `2026-W07` or `2026-02`.
+    pub period: String,
+    pub path: String,
+    pub sources: Vec<String>,
+    pub summarized: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollupReport {
+    pub dry_run: bool,
+    pub retention: RetentionMode,
+    pub digests: Vec<RollupDigest>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Section {
+    heading: String,
+    body: Vec<String>,
+}
+
+impl Section {
+    fn body_text(&self) -> String {
+        trim_blank(&self.body).join("\n")
+    }
+}
+
+/// `MEMORY.md` split into the preamble and its `## ` sections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CuratedMemory {
+    preamble: Vec<String>,
+    sections: Vec<Section>,
+}
+
+impl CuratedMemory {
+    pub fn parse(content: &str) -> Self {
+        let mut preamble = Vec::new();
+        let mut sections: Vec<Section> = Vec::new();
+        for line in content.lines() {
+            if let Some(heading) = section_heading(line) {
+                sections.push(Section {
+                    heading: heading.to_string(),
+                    body: Vec::new(),
+                });
+            } else if let Some(section) = sections.last_mut() {
+                section.body.push(line.to_string());
+            } else {
+                preamble.push(line.to_string());
+            }
+        }
+        Self { preamble, sections }
+    }
+
+    pub fn render(&self) -> String {
+        let mut blocks = Vec::new();
+        let preamble = trim_blank(&self.preamble);
+        if !preamble.is_empty() {
+            blocks.push(preamble.join("\n"));
+        }
+        for section in &self.sections {
+            let body = section.body_text();
+            if body.is_empty() {
+                blocks.push(format!("## {}", section.heading));
+            } else {
+                blocks.push(format!("## {}\n{body}", section.heading));
+            }
+        }
+        let mut rendered = blocks.join("\n\n");
+        rendered.push('\n');
+        rendered
+    }
+
+    pub fn headings(&self) -> Vec<&str> {
+        self.sections
+            .iter()
+            .map(|section| section.heading.as_str())
+            .collect()
+    }
+
+    /// Body of the section named `heading`, without surrounding blank lines.
+    pub fn section(&self, heading: &str) -> Option<String> {
+        self.position(heading)
+            .map(|index| self.sections[index].body_text())
+    }
+
+    /// Non-blank lines of the section named `heading` without list markers; empty when there
+    /// is no such section.
+    pub fn items(&self, heading: &str) -> Vec<String> {
+        self.section(heading)
+            .map(|body| list_items(&body))
+            .unwrap_or_default()
+    }
+
+    /// Applies `edit` and returns the section body before and after it.
+    pub fn apply(&mut self, edit: &MemoryEdit) -> Result<(Option<String>, Option<String>)> {
+        let heading = normalize_heading(edit.section())?;
+        let position = self.position(&heading);
+        let before = position.map(|index| self.sections[index].body_text());
+        let check_expected = |expected: &Option<String>| -> Result<()> {
+            match expected {
+                Some(expected)
+                    if expected.trim() != before.as_deref().unwrap_or_default().trim() =>
+                {
+                    Err(anyhow!(
+                        "section \"{heading}\" changed since it was read; read MEMORY.md again and retry"
+                    ))
+                }
+                _ => Ok(()),
+            }
+        };
+
+        match edit {
+            MemoryEdit::Remember { fact, .. } => {
+                let fact = single_line(fact, "fact")?;
+                let index = position.unwrap_or_else(|| self.push_section(&heading));
+                let section = &mut self.sections[index];
+                let known = section
+                    .body
+                    .iter()
+                    .any(|line| bullet_text(line).eq_ignore_ascii_case(&fact));
+                if !known {
+                    let mut body = trim_blank(&section.body).to_vec();
+                    body.push(format!("- {fact}"));
+                    section.body = body;
+                }
+            }
+            MemoryEdit::Update {
+                content, expected, ..
+            } => {
+                check_expected(expected)?;
+                if content.lines().any(|line| section_heading(line).is_some()) {
+                    return Err(anyhow!(
+                        "section content must not contain `## ` headings; use one update per section"
+                    ));
+                }
+                let index = position.unwrap_or_else(|| self.push_section(&heading));
+                self.sections[index].body = content.lines().map(str::to_string).collect();
+            }
+            MemoryEdit::Forget { fact, expected, .. } => {
+                check_expected(expected)?;
+                let index =
+                    position.ok_or_else(|| anyhow!("no section \"{heading}\" in MEMORY.md"))?;
+                match fact {
+                    None => {
+                        self.sections.remove(index);
+                    }
+                    Some(fact) => {
+                        let needle = fact.trim().to_lowercase();
+                        if needle.is_empty() {
+                            return Err(anyhow!("fact must not be empty"));
+                        }
+                        let section = &mut self.sections[index];
+                        let count = section.body.len();
+                        section
+                            .body
+                            .retain(|line| !bullet_text(line).to_lowercase().contains(&needle));
+                        if section.body.len() == count {
+                            return Err(anyhow!(
+                                "no line in section \"{heading}\" mentions \"{}\"",
+                                fact.trim()
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        let after = self
+            .position(&heading)
+            .map(|index| self.sections[index].body_text());
+        Ok((before, after))
+    }
+
+    fn position(&self, heading: &str) -> Option<usize> {
+        let heading = heading.trim();
+        self.sections
+            .iter()
+            .position(|section| section.heading.eq_ignore_ascii_case(heading))
+    }
+
+    fn push_section(&mut self, heading: &str) -> usize {
+        self.sections.push(Section {
+            heading: heading.to_string(),
+            body: Vec::new(),
+        });
+        self.sections.len() - 1
+    }
+}
+
+fn section_heading(line: &str) -> Option<&str> {
+    line.strip_prefix("## ").map(str::trim)
+}
+
+fn normalize_heading(heading: &str) -> Result<String> {
+    let heading = heading.trim().trim_start_matches('#').trim();
+    if heading.is_empty() || heading.contains('\n') {
+        return Err(anyhow!("section must be a single non-empty heading"));
+    }
+    Ok(heading.to_string())
+}
+
+fn single_line(text: &str, field: &str) -> Result<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = text.trim_start_matches("- ").trim().to_string();
+    if text.is_empty() {
+        return Err(anyhow!("{field} must not be empty"));
+    }
+    Ok(text)
+}
+
+/// Non-blank lines of `text` without their list markers.
+pub fn list_items(text: &str) -> Vec<String> {
+    text.lines()
+        .map(bullet_text)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Line text without a leading list marker.
+fn bullet_text(line: &str) -> &str {
+    let trimmed = line.trim();
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+        .unwrap_or(trimmed)
+        .trim()
+}
+
+fn trim_blank(lines: &[String]) -> &[String] {
+    let start = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(lines.len());
+    let end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(start, |end| end + 1);
+    &lines[start..end]
+}
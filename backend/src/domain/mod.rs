@@ -1,8 +1,13 @@
 pub mod audit;
 pub mod chat;
+pub mod checkpoint;
 pub mod config;
 pub mod error;
+pub mod memory;
 pub mod ports;
+pub mod tools;
 pub mod types;
+pub mod watch;
+pub mod workspace;
 
 pub use error::{AppError, ErrorCode};
@@ -1,5 +1,9 @@
 use crate::domain::chat::{ChannelDelivery, ChannelHealth, OutboundChannelMessage};
-use crate::domain::types::{Message, ToolCall, ToolResult, ToolSpec, Usage};
+use crate::domain::checkpoint::{Checkpoint, CheckpointDiff, CheckpointRestore, FileSnapshot};
+use crate::domain::memory::{EditOrigin, MemoryChange, MemoryEdit, MemoryFile, MemoryScope};
+use crate::domain::types::{Message, PlanItem, ToolCall, ToolResult, ToolSpec, Usage};
+use crate::domain::watch::WatchStatus;
+use crate::domain::workspace::{ExportOptions, ImportOptions, ImportReport, WorkspaceExport};
 use anyhow::Result;
 use async_trait::async_trait;
 use futures::Stream;
@@ -7,7 +11,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 pub type ModelStream = Pin<Box<dyn Stream<Item = Result<ModelStreamEvent>> + Send>>;
 
@@ -48,6 +52,9 @@ pub struct MemoryHit {
     pub path: String,
     pub line: usize,
     pub snippet: String,
+    /// Relevance to the query; higher is better.
+    #[serde(default)]
+    pub score: f32,
 }
 
 #[async_trait]
@@ -62,18 +69,132 @@ pub trait MemoryPort: Send + Sync {
     ) -> Result<String>;
     async fn read_curated(&self) -> Result<String>;
     async fn write_curated(&self, content: &str) -> Result<()>;
+    /// Applies a section-level edit to the curated file and records it in the audit log.
+    async fn edit_curated(&self, edit: MemoryEdit, origin: EditOrigin) -> Result<MemoryChange>;
+    /// The most recent curated-memory changes, oldest first.
+    async fn changes(&self, limit: usize) -> Result<Vec<MemoryChange>>;
+    /// `MEMORY.md` and every visible file under the memory directory, sorted by path.
+    async fn list_files(&self) -> Result<Vec<MemoryFile>>;
+    /// Creates or replaces a file under the memory directory.
+    async fn write_file(&self, relative_path: &str, content: &str) -> Result<()>;
+    /// Deletes a file under the memory directory, or moves it to the archive, which is kept
+    /// out of search.
+    async fn remove_file(&self, relative_path: &str, archive: bool) -> Result<()>;
     async fn ensure_layout(&self) -> Result<()>;
+    /// Curated memory to include in every system prompt, for backends that keep it apart from
+    /// search results.
+    async fn prompt_memory(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+    /// The same store narrowed to `scope`; the global scope returns an equivalent store.
+    fn scoped(&self, scope: &MemoryScope) -> Arc<dyn MemoryPort>;
+}
+
+/// Turns text into vectors for semantic search. Vectors are only comparable when they come
+/// from the same [`EmbeddingPort::id`].
+#[async_trait]
+pub trait EmbeddingPort: Send + Sync {
+    /// Identifies the embedding space (provider, model, dimensions); stored vectors are
+    /// discarded when it changes.
+    fn id(&self) -> String;
+    /// One vector per input text, in input order.
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// A chunk of a working-directory file matched by [`WorkspaceSearchPort::search`]; lines are
+/// 1-based and inclusive.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkspaceHit {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub score: f32,
+    pub text: String,
+}
+
+#[async_trait]
+pub trait WorkspaceSearchPort: Send + Sync {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<WorkspaceHit>>;
+}
+
+/// Watches personality and memory files so their caches follow external edits.
+pub trait FileWatchPort: Send + Sync {
+    fn status(&self) -> WatchStatus;
+}
+
+/// Packs the workspace into a single archive and restores it, possibly on another machine.
+#[async_trait]
+pub trait WorkspaceArchivePort: Send + Sync {
+    async fn export(&self, options: ExportOptions) -> Result<WorkspaceExport>;
+    /// Validates `archive` and writes its files unless `dry_run`; invalid archives fail with
+    /// [`crate::domain::workspace::InvalidArchive`].
+    async fn import(&self, archive: Vec<u8>, options: ImportOptions) -> Result<ImportReport>;
+}
+
+#[async_trait]
+pub trait CheckpointPort: Send + Sync {
+    async fn record(&self, snapshot: FileSnapshot) -> Result<Checkpoint>;
+    async fn list(&self, session_id: &str) -> Result<Vec<Checkpoint>>;
+    async fn diff(&self, session_id: &str, checkpoint_id: &str) -> Result<Option<CheckpointDiff>>;
+    async fn restore(
+        &self,
+        session_id: &str,
+        checkpoint_id: &str,
+    ) -> Result<Option<CheckpointRestore>>;
 }
 
 #[derive(Clone)]
 pub struct ToolExecutionContext {
     pub root_dir: PathBuf,
     pub memory: Arc<dyn MemoryPort>,
+    pub session_id: Option<String>,
+    pub tool_call_id: Option<String>,
+    pub checkpoints: Option<Arc<dyn CheckpointPort>>,
+    /// The session's plan, shared with the agent loop so `todo_write` edits reach the session.
+    pub plan: Option<Arc<Mutex<Vec<PlanItem>>>>,
+    /// Scope `memory` is narrowed to.
+    pub memory_scope: MemoryScope,
 }
 
 impl ToolExecutionContext {
     pub fn new(root_dir: PathBuf, memory: Arc<dyn MemoryPort>) -> Self {
-        Self { root_dir, memory }
+        Self {
+            root_dir,
+            memory,
+            session_id: None,
+            tool_call_id: None,
+            checkpoints: None,
+            plan: None,
+            memory_scope: MemoryScope::Global,
+        }
+    }
+
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn with_checkpoints(mut self, checkpoints: Option<Arc<dyn CheckpointPort>>) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
+    pub fn with_plan(mut self, plan: Arc<Mutex<Vec<PlanItem>>>) -> Self {
+        self.plan = Some(plan);
+        self
+    }
+
+    /// Narrows `memory` to `scope`, so memory tools read and write that scope.
+    pub fn with_memory_scope(mut self, scope: MemoryScope) -> Self {
+        self.memory = self.memory.scoped(&scope);
+        self.memory_scope = scope;
+        self
+    }
+
+    pub fn for_call(&self, tool_call_id: &str) -> Self {
+        let mut context = self.clone();
+        context.tool_call_id = Some(tool_call_id.to_string());
+        context
     }
 }
 
@@ -0,0 +1,149 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::domain::types::ToolSpec;
+
+pub const TOOL_PRESETS: &[&str] = &["all", "read_only", "coding", "memory", "none"];
+
+/// Tools that only page through other tools' output; available whenever any tool is.
+const SUPPORT_TOOLS: &[&str] = &["read_output"];
+
+const READ_ONLY_TOOLS: &[&str] = &[
+    "read",
+    "grep",
+    "find",
+    "ls",
+    "code_outline",
+    "find_symbol",
+    "search_workspace",
+    "git_status",
+    "git_diff",
+    "git_log",
+    "git_show",
+    "sql_query",
+    "sql_schema",
+    "todo_write",
+    "todo_read",
+    "memory_get",
+    "memory_search",
+];
+const CODING_TOOLS: &[&str] = &[
+    "read",
+    "write",
+    "edit",
+    "bash",
+    "grep",
+    "find",
+    "ls",
+    "code_outline",
+    "find_symbol",
+    "search_workspace",
+    "git_status",
+    "git_diff",
+    "git_log",
+    "git_show",
+    "git_commit",
+    "sql_query",
+    "sql_schema",
+    "todo_write",
+    "todo_read",
+    "memory_get",
+    "memory_search",
+    "memory_remember",
+    "memory_update",
+    "memory_forget",
+];
+const MEMORY_TOOLS: &[&str] = &[
+    "memory_get",
+    "memory_search",
+    "memory_remember",
+    "memory_update",
+    "memory_forget",
+];
+
+/// Built-in tool names for a preset; `None` for `all` (no restriction) or an unknown preset.
+pub fn preset_tools(preset: &str) -> Option<&'static [&'static str]> {
+    match preset {
+        "read_only" => Some(READ_ONLY_TOOLS),
+        "coding" => Some(CODING_TOOLS),
+        "memory" => Some(MEMORY_TOOLS),
+        "none" => Some(&[]),
+        _ => None,
+    }
+}
+
+/// Which registered tools a conversation may see and call. `allowed: None` means all.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ToolSelection {
+    pub allowed: Option<BTreeSet<String>>,
+}
+
+impl ToolSelection {
+    pub fn all() -> Self {
+        Self { allowed: None }
+    }
+
+    pub fn only<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut allowed: BTreeSet<String> = names.into_iter().map(Into::into).collect();
+        if !allowed.is_empty() {
+            allowed.extend(SUPPORT_TOOLS.iter().map(|name| name.to_string()));
+        }
+        Self {
+            allowed: Some(allowed),
+        }
+    }
+
+    /// Combines a preset with extra tool names. Without either, every tool is allowed; an
+    /// unknown preset allows nothing beyond `enabled`.
+    pub fn from_preset(preset: Option<&str>, enabled: Option<&[String]>) -> Self {
+        let preset_names: &[&str] = match preset {
+            None if enabled.is_none() => return Self::all(),
+            None => &[],
+            Some("all") => return Self::all(),
+            Some(preset) => preset_tools(preset).unwrap_or_else(|| {
+                tracing::warn!(preset, "unknown tool preset; enabling no preset tools");
+                &[]
+            }),
+        };
+        Self::only(
+            preset_names
+                .iter()
+                .map(|name| name.to_string())
+                .chain(enabled.unwrap_or_default().iter().cloned()),
+        )
+    }
+
+    pub fn allows(&self, name: &str) -> bool {
+        self.allowed
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(name))
+    }
+
+    pub fn filter(&self, specs: Vec<ToolSpec>) -> Vec<ToolSpec> {
+        let mut specs: Vec<ToolSpec> = specs
+            .into_iter()
+            .filter(|spec| self.allows(&spec.name))
+            .collect();
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        specs
+    }
+}
+
+/// Default tool selection plus per-channel overrides (keyed by channel name, e.g. `telegram`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ToolPolicy {
+    pub default: ToolSelection,
+    pub channels: BTreeMap<String, ToolSelection>,
+}
+
+impl ToolPolicy {
+    pub fn for_channel(&self, channel: Option<&str>) -> &ToolSelection {
+        channel
+            .and_then(|channel| self.channels.get(channel))
+            .unwrap_or(&self.default)
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -90,6 +91,19 @@ pub struct ToolResult {
     pub name: String,
     pub output: String,
     pub is_error: bool,
+    /// Set when the output exceeded the tool output budget and was replaced by a preview.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<ToolOutputTruncation>,
+}
+
+/// Describes a tool output that was spilled to disk; `handle` is accepted by `read_output`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolOutputTruncation {
+    pub handle: String,
+    pub total_bytes: usize,
+    pub total_lines: usize,
+    pub head_lines: usize,
+    pub tail_lines: usize,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -105,6 +119,15 @@ pub struct SessionState {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub messages: Vec<Message>,
+    /// Channel the session was last driven from (e.g. `telegram`); selects its tool policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Channel user the session belongs to; selects their memory scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// Checklist the agent maintains with `todo_write`.
+    #[serde(default)]
+    pub plan: Vec<PlanItem>,
 }
 
 impl SessionState {
@@ -115,6 +138,9 @@ impl SessionState {
             created_at: now,
             updated_at: now,
             messages: Vec::new(),
+            channel: None,
+            user_id: None,
+            plan: Vec::new(),
         }
     }
 
@@ -123,3 +149,67 @@ impl SessionState {
         self.updated_at = Utc::now();
     }
 }
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    Pending,
+    InProgress,
+    Done,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlanItem {
+    pub content: String,
+    pub status: PlanStatus,
+}
+
+/// Hex digits of the sha256 appended to a [`path_segment`] that had to be rewritten.
+const SEGMENT_HASH_LEN: usize = 8;
+
+/// `value` as a single file or directory name, used for session, channel and user ids in
+/// on-disk layouts. Ids of ASCII letters, digits, `-` and `_` are kept as they are; anything
+/// else becomes `_` followed by a short hash of the raw id, so `a.b`, `a_b` and `a/b` stay apart.
+pub fn path_segment(value: &str) -> String {
+    let segment: String = value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !segment.is_empty() && segment == value {
+        return segment;
+    }
+    let digest = Sha256::digest(value.as_bytes());
+    let hash: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("{segment}_{}", &hash[..SEGMENT_HASH_LEN])
+}
+
+/// Checklist lines (`[x]` done, `[~]` in progress, `[ ]` pending) and a progress count.
+pub fn render_plan(plan: &[PlanItem]) -> String {
+    if plan.is_empty() {
+        return "(no plan)".to_string();
+    }
+    let mut lines: Vec<String> = plan
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let marker = match item.status {
+                PlanStatus::Done => "[x]",
+                PlanStatus::InProgress => "[~]",
+                PlanStatus::Pending => "[ ]",
+            };
+            format!("{}. {marker} {}", index + 1, item.content)
+        })
+        .collect();
+    let done = plan
+        .iter()
+        .filter(|item| item.status == PlanStatus::Done)
+        .count();
+    lines.push(format!("({done}/{} done)", plan.len()));
+    lines.join("\n")
+}
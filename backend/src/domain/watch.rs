@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A directory watched for external edits and the cache it keeps current.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchTarget {
+    /// `personality` or `memory`.
+    pub name: String,
+    pub path: String,
+    pub recursive: bool,
+}
+
+/// One debounced batch of edits that invalidated a target's cache.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchChange {
+    pub timestamp: DateTime<Utc>,
+    pub target: String,
+    pub paths: Vec<String>,
+}
+
+/// Body of `GET /api/watch/status`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchStatus {
+    /// `false` when watching is disabled or the watcher failed to start; every request then
+    /// rereads the files instead.
+    pub enabled: bool,
+    pub debounce_ms: u64,
+    pub targets: Vec<WatchTarget>,
+    /// Batches seen since the watcher started.
+    pub changes: u64,
+    pub last_change: Option<DateTime<Utc>>,
+    /// The latest batches, oldest first.
+    pub recent: Vec<WatchChange>,
+    /// Last error reported by the watcher backend, if any.
+    pub error: Option<String>,
+}
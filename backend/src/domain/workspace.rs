@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How `config.json` and `.env` secrets are written into a workspace export.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretMode {
+    /// Secret values are replaced with the audit placeholder; an import keeps the local values.
+    #[default]
+    Redact,
+    /// Secret values are left out entirely.
+    Exclude,
+    /// Secrets are exported as-is.
+    Include,
+}
+
+impl SecretMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Redact => "redact",
+            Self::Exclude => "exclude",
+            Self::Include => "include",
+        }
+    }
+}
+
+impl std::str::FromStr for SecretMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "redact" => Ok(Self::Redact),
+            "exclude" => Ok(Self::Exclude),
+            "include" => Ok(Self::Include),
+            other => anyhow::bail!(
+                "unknown secrets mode `{other}` (expected redact, exclude or include)"
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub secrets: SecretMode,
+    /// Include `logs/`, which is skipped by default.
+    pub include_logs: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Report what would change without writing anything.
+    pub dry_run: bool,
+    /// Also restore `config.json` and `plugins/`, which can start commands and carry secrets;
+    /// they are skipped by default.
+    pub include_config: bool,
+}
+
+/// A packed workspace archive.
+#[derive(Clone, Debug)]
+pub struct WorkspaceExport {
+    /// Suggested download name, e.g. `chaos-bot-workspace-20260302T101500Z.tar.gz`.
+    pub file_name: String,
+    /// The gzip-compressed tarball.
+    pub archive: Vec<u8>,
+    pub files: usize,
+    /// Uncompressed size of the exported files.
+    pub bytes: u64,
+    pub secrets: SecretMode,
+}
+
+/// A file the import would overwrite, with a unified diff for small text files.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModifiedFile {
+    pub path: String,
+    pub diff: Option<String>,
+}
+
+/// Result of `POST /api/workspace/import`; with `dry_run` nothing was written.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub secrets: SecretMode,
+    /// Workspace-relative paths that do not exist locally yet.
+    pub added: Vec<String>,
+    pub modified: Vec<ModifiedFile>,
+    pub unchanged: usize,
+    /// `config.json` changed; it only takes effect after a config apply or restart.
+    pub restart_required: bool,
+    /// Config and plugin files in the archive left alone without `include_config`.
+    #[serde(default)]
+    pub skipped: Vec<String>,
+}
+
+/// The uploaded archive is not a valid workspace export; nothing was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidArchive(pub String);
+
+impl fmt::Display for InvalidArchive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid workspace archive: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidArchive {}
@@ -0,0 +1,321 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use similar::{ChangeTag, TextDiff};
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::domain::checkpoint::{Checkpoint, CheckpointDiff, CheckpointRestore, FileSnapshot};
+use crate::domain::ports::CheckpointPort;
+use crate::domain::types::path_segment;
+
+const BLOBS_DIR: &str = "blobs";
+const SESSIONS_DIR: &str = "sessions";
+
+/// File-backed checkpoint store.
+///
+/// Layout under `root`:
+/// - `blobs/<sha256>`: file contents, deduplicated by content hash
+/// - `sessions/<session_id>.json`: ordered checkpoint index for a session
+#[derive(Debug)]
+pub struct CheckpointStore {
+    root: PathBuf,
+    retention: usize,
+    lock: Mutex<()>,
+}
+
+impl CheckpointStore {
+    pub fn new(root: impl Into<PathBuf>, retention: usize) -> Self {
+        Self {
+            root: root.into(),
+            retention: retention.max(1),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn retention(&self) -> usize {
+        self.retention
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.root.join(BLOBS_DIR).join(hash)
+    }
+
+    fn index_path(&self, session_id: &str) -> PathBuf {
+        self.root
+            .join(SESSIONS_DIR)
+            .join(format!("{}.json", path_segment(session_id)))
+    }
+
+    async fn write_blob(&self, content: &[u8]) -> Result<String> {
+        let hash = content_hash(content);
+        let path = self.blob_path(&hash);
+        if !path.exists() {
+            fs::create_dir_all(self.root.join(BLOBS_DIR)).await?;
+            fs::write(&path, content)
+                .await
+                .with_context(|| format!("failed to write checkpoint blob: {}", path.display()))?;
+        }
+        Ok(hash)
+    }
+
+    async fn read_blob(&self, hash: &str) -> Result<Vec<u8>> {
+        let path = self.blob_path(hash);
+        fs::read(&path)
+            .await
+            .with_context(|| format!("checkpoint blob missing: {}", path.display()))
+    }
+
+    async fn load_index(&self, session_id: &str) -> Result<Vec<Checkpoint>> {
+        let path = self.index_path(session_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = fs::read_to_string(&path).await?;
+        serde_json::from_str(&raw)
+            .with_context(|| format!("invalid checkpoint index: {}", path.display()))
+    }
+
+    async fn save_index(&self, session_id: &str, checkpoints: &[Checkpoint]) -> Result<()> {
+        let path = self.index_path(session_id);
+        fs::create_dir_all(self.root.join(SESSIONS_DIR)).await?;
+        fs::write(&path, serde_json::to_string_pretty(checkpoints)?)
+            .await
+            .with_context(|| format!("failed to write checkpoint index: {}", path.display()))?;
+        Ok(())
+    }
+
+    async fn append(&self, snapshot: FileSnapshot) -> Result<Checkpoint> {
+        let before_hash = match &snapshot.before {
+            Some(content) => Some(self.write_blob(content).await?),
+            None => None,
+        };
+        let after_hash = self.write_blob(&snapshot.after).await?;
+
+        let checkpoint = Checkpoint {
+            id: Uuid::new_v4().to_string(),
+            session_id: snapshot.session_id.clone(),
+            tool_call_id: snapshot.tool_call_id,
+            tool_name: snapshot.tool_name,
+            path: snapshot.path,
+            created_at: Utc::now(),
+            before_hash,
+            after_hash,
+        };
+
+        let mut index = self.load_index(&snapshot.session_id).await?;
+        index.push(checkpoint.clone());
+        let pruned = if index.len() > self.retention {
+            let excess = index.len() - self.retention;
+            index.drain(..excess).count()
+        } else {
+            0
+        };
+        self.save_index(&snapshot.session_id, &index).await?;
+
+        if pruned > 0 {
+            let removed = self.collect_garbage().await?;
+            tracing::debug!(
+                session_id = %checkpoint.session_id,
+                pruned,
+                removed_blobs = removed,
+                "pruned file checkpoints past retention"
+            );
+        }
+
+        tracing::debug!(
+            session_id = %checkpoint.session_id,
+            checkpoint_id = %checkpoint.id,
+            tool_name = %checkpoint.tool_name,
+            path = %checkpoint.path,
+            "recorded file checkpoint"
+        );
+        Ok(checkpoint)
+    }
+
+    /// Removes blobs that are no longer referenced by any session index.
+    pub async fn collect_garbage(&self) -> Result<usize> {
+        let sessions_dir = self.root.join(SESSIONS_DIR);
+        let blobs_dir = self.root.join(BLOBS_DIR);
+        if !blobs_dir.exists() {
+            return Ok(0);
+        }
+
+        let mut referenced = HashSet::new();
+        if sessions_dir.exists() {
+            let mut entries = fs::read_dir(&sessions_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let raw = fs::read_to_string(entry.path()).await?;
+                let Ok(index) = serde_json::from_str::<Vec<Checkpoint>>(&raw) else {
+                    continue;
+                };
+                for checkpoint in index {
+                    referenced.extend(checkpoint.before_hash);
+                    referenced.insert(checkpoint.after_hash);
+                }
+            }
+        }
+
+        let mut removed = 0usize;
+        let mut entries = fs::read_dir(&blobs_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !referenced.contains(&name) {
+                fs::remove_file(entry.path()).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    async fn find(&self, session_id: &str, checkpoint_id: &str) -> Result<Option<Checkpoint>> {
+        Ok(self
+            .load_index(session_id)
+            .await?
+            .into_iter()
+            .find(|checkpoint| checkpoint.id == checkpoint_id))
+    }
+}
+
+#[async_trait]
+impl CheckpointPort for CheckpointStore {
+    async fn record(&self, snapshot: FileSnapshot) -> Result<Checkpoint> {
+        let _guard = self.lock.lock().await;
+        self.append(snapshot).await
+    }
+
+    async fn list(&self, session_id: &str) -> Result<Vec<Checkpoint>> {
+        let mut index = self.load_index(session_id).await?;
+        index.reverse();
+        Ok(index)
+    }
+
+    async fn diff(&self, session_id: &str, checkpoint_id: &str) -> Result<Option<CheckpointDiff>> {
+        let Some(checkpoint) = self.find(session_id, checkpoint_id).await? else {
+            return Ok(None);
+        };
+
+        let before = match &checkpoint.before_hash {
+            Some(hash) => self.read_blob(hash).await?,
+            None => Vec::new(),
+        };
+        let after = self.read_blob(&checkpoint.after_hash).await?;
+        let current_matches_after = match fs::read(&checkpoint.path).await {
+            Ok(current) => content_hash(&current) == checkpoint.after_hash,
+            Err(_) => false,
+        };
+
+        let (before, after) = (
+            String::from_utf8_lossy(&before),
+            String::from_utf8_lossy(&after),
+        );
+        let text_diff = TextDiff::from_lines(before.as_ref(), after.as_ref());
+        let (mut additions, mut deletions) = (0usize, 0usize);
+        for change in text_diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => additions += 1,
+                ChangeTag::Delete => deletions += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        let old_header = if checkpoint.before_hash.is_some() {
+            format!("a/{}", checkpoint.path)
+        } else {
+            "/dev/null".to_string()
+        };
+        let diff = text_diff
+            .unified_diff()
+            .context_radius(3)
+            .header(&old_header, &format!("b/{}", checkpoint.path))
+            .to_string();
+
+        Ok(Some(CheckpointDiff {
+            checkpoint,
+            diff,
+            additions,
+            deletions,
+            current_matches_after,
+        }))
+    }
+
+    async fn restore(
+        &self,
+        session_id: &str,
+        checkpoint_id: &str,
+    ) -> Result<Option<CheckpointRestore>> {
+        let _guard = self.lock.lock().await;
+        let Some(checkpoint) = self.find(session_id, checkpoint_id).await? else {
+            return Ok(None);
+        };
+
+        let path = PathBuf::from(&checkpoint.path);
+        let current = read_existing(&path).await?;
+        let restored_content = match &checkpoint.before_hash {
+            Some(hash) => {
+                let content = self.read_blob(hash).await?;
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+                fs::write(&path, &content)
+                    .await
+                    .with_context(|| format!("failed to restore file: {}", path.display()))?;
+                content
+            }
+            None => {
+                if current.is_some() {
+                    fs::remove_file(&path)
+                        .await
+                        .with_context(|| format!("failed to remove file: {}", path.display()))?;
+                }
+                Vec::new()
+            }
+        };
+
+        let undo = self
+            .append(FileSnapshot {
+                session_id: session_id.to_string(),
+                tool_call_id: None,
+                tool_name: "restore".to_string(),
+                path: checkpoint.path.clone(),
+                before: current,
+                after: restored_content,
+            })
+            .await?;
+
+        tracing::info!(
+            session_id,
+            checkpoint_id,
+            path = %checkpoint.path,
+            undo_checkpoint_id = %undo.id,
+            "restored file checkpoint"
+        );
+        Ok(Some(CheckpointRestore {
+            restored: checkpoint,
+            undo,
+        }))
+    }
+}
+
+/// The current content of `path`, or `None` when it does not exist. Any other read error is
+/// returned: recording an unreadable file as absent would make a restore delete it.
+pub async fn read_existing(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path).await {
+        Ok(content) => Ok(Some(content)),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+pub fn content_hash(content: impl AsRef<[u8]>) -> String {
+    let digest = Sha256::digest(content.as_ref());
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
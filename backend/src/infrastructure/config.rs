@@ -1,15 +1,36 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::domain::memory::{
+    ExtractionTrigger, MemoryBackendKind, MemoryExtractionOptions, MemoryScoping, RetentionMode,
+    RollupOptions, RollupPeriod,
+};
+use crate::domain::tools::{ToolPolicy, ToolSelection};
+use crate::infrastructure::embedding::DEFAULT_EMBEDDING_MODEL;
+use crate::infrastructure::file_watch::DEFAULT_WATCH_DEBOUNCE;
+use crate::infrastructure::mcp::{McpServerSpec, McpTransportSpec, DEFAULT_MCP_TIMEOUT_SECS};
+use crate::infrastructure::memory::DEFAULT_MAX_CURATED_BYTES;
+use crate::infrastructure::memory_facts::FactStoreOptions;
+use crate::infrastructure::memory_vectors::HybridSearchOptions;
 use crate::infrastructure::runtime_assets::{DEFAULT_AGENT_JSON, DEFAULT_ENV_EXAMPLE};
+use crate::infrastructure::tooling::{
+    FetchPolicy, OutputLimits, PluginSpec, SqlLimits, DEFAULT_PLUGIN_TIMEOUT_SECS,
+};
+use crate::infrastructure::workspace_index::WorkspaceIndexOptions;
 
 const DEFAULT_WORKSPACE_DIR: &str = ".chaos-bot";
 const DEFAULT_CONFIG_FILE_NAME: &str = "config.json";
 const LEGACY_CONFIG_FILE_NAME: &str = "agent.json";
+const DEFAULT_CHECKPOINT_RETENTION: usize = 50;
+/// Off by default: injected passages reach the model on every turn, whatever the channel.
+const DEFAULT_WORKSPACE_CONTEXT_RESULTS: usize = 0;
+const DEFAULT_WORKSPACE_INDEX_REFRESH: Duration = Duration::from_secs(60);
 
 #[derive(Clone, Debug)]
 pub struct AppConfig {
@@ -39,6 +60,45 @@ pub struct AppConfig {
     pub personality_dir: PathBuf,
     pub memory_dir: PathBuf,
     pub memory_file: PathBuf,
+    pub memory_embedding: Option<String>,
+    pub memory_embedding_model: String,
+    pub memory_embedding_base_url: Option<String>,
+    pub memory_hybrid: HybridSearchOptions,
+    pub memory_max_curated_bytes: usize,
+    pub memory_extraction: MemoryExtractionOptions,
+    pub memory_rollup: RollupOptions,
+    pub memory_scoping: MemoryScoping,
+    pub memory_backend: MemoryBackendKind,
+    pub memory_facts_db: PathBuf,
+    pub memory_facts: FactStoreOptions,
+    pub checkpoints_enabled: bool,
+    pub checkpoint_retention: usize,
+    pub checkpoints_dir: PathBuf,
+    pub tool_policy: ToolPolicy,
+    pub tool_output_limits: OutputLimits,
+    pub tool_output_dir: PathBuf,
+    pub fetch_enabled: bool,
+    pub git_tools_enabled: bool,
+    pub code_tools_enabled: bool,
+    pub code_index_dir: PathBuf,
+    pub git_commit_enabled: bool,
+    pub fetch_policy: FetchPolicy,
+    pub sql_tools_enabled: bool,
+    pub sql_limits: SqlLimits,
+    pub mcp_servers: Vec<McpServerSpec>,
+    pub workspace_index_enabled: bool,
+    pub workspace_index_dir: PathBuf,
+    pub workspace_index_options: WorkspaceIndexOptions,
+    pub workspace_index_context_results: usize,
+    /// How often the index is brought up to date in the background.
+    pub workspace_index_refresh: Duration,
+    /// Watch the personality and memory files and cache them between edits.
+    pub watch_enabled: bool,
+    pub watch_debounce: Duration,
+    pub workspace_index_embedding: Option<String>,
+    pub tool_plugins: Vec<PluginSpec>,
+    pub wasm_plugins_enabled: bool,
+    pub wasm_plugins_dir: PathBuf,
 }
 
 #[derive(Clone, Debug)]
@@ -79,6 +139,43 @@ impl Default for AppConfig {
             personality_dir: workspace.join("personality"),
             memory_dir: workspace.join("memory"),
             memory_file: workspace.join("MEMORY.md"),
+            memory_embedding: None,
+            memory_embedding_model: DEFAULT_EMBEDDING_MODEL.to_string(),
+            memory_embedding_base_url: None,
+            memory_hybrid: HybridSearchOptions::default(),
+            memory_max_curated_bytes: DEFAULT_MAX_CURATED_BYTES,
+            memory_extraction: MemoryExtractionOptions::default(),
+            memory_rollup: RollupOptions::default(),
+            memory_scoping: MemoryScoping::Global,
+            memory_backend: MemoryBackendKind::Files,
+            memory_facts_db: workspace.join("data/memory-facts.db"),
+            memory_facts: FactStoreOptions::default(),
+            checkpoints_enabled: true,
+            checkpoint_retention: DEFAULT_CHECKPOINT_RETENTION,
+            checkpoints_dir: workspace.join("data/checkpoints"),
+            tool_policy: ToolPolicy::default(),
+            tool_output_limits: OutputLimits::default(),
+            tool_output_dir: workspace.join("data/tool-output"),
+            fetch_enabled: true,
+            git_tools_enabled: true,
+            git_commit_enabled: false,
+            code_tools_enabled: true,
+            code_index_dir: workspace.join("data/code-index"),
+            fetch_policy: FetchPolicy::default(),
+            sql_tools_enabled: true,
+            sql_limits: SqlLimits::default(),
+            mcp_servers: Vec::new(),
+            workspace_index_enabled: true,
+            workspace_index_dir: workspace.join("data/workspace-index"),
+            workspace_index_options: WorkspaceIndexOptions::default(),
+            workspace_index_context_results: DEFAULT_WORKSPACE_CONTEXT_RESULTS,
+            workspace_index_refresh: DEFAULT_WORKSPACE_INDEX_REFRESH,
+            watch_enabled: true,
+            watch_debounce: DEFAULT_WATCH_DEBOUNCE,
+            workspace_index_embedding: None,
+            tool_plugins: Vec::new(),
+            wasm_plugins_enabled: true,
+            wasm_plugins_dir: workspace.join("plugins"),
             workspace,
         }
     }
@@ -191,6 +288,212 @@ impl AppConfig {
             config.log_dir = resolve_log_dir(&config.workspace, directory);
         }
 
+        if let Some(enabled) = file_config.checkpoints.enabled {
+            config.checkpoints_enabled = enabled;
+        }
+        if let Some(retention) = file_config.checkpoints.retention {
+            config.checkpoint_retention = retention.max(1);
+        }
+
+        config.tool_policy = ToolPolicy {
+            default: file_config.tools.selection.to_selection(),
+            channels: file_config
+                .tools
+                .channels
+                .iter()
+                .map(|(channel, selection)| (channel.clone(), selection.to_selection()))
+                .collect(),
+        };
+
+        if let Some(max_bytes) = file_config.tools.output.max_bytes {
+            config.tool_output_limits.max_bytes = max_bytes.max(1);
+        }
+        if let Some(head_lines) = file_config.tools.output.head_lines {
+            config.tool_output_limits.head_lines = head_lines;
+        }
+        if let Some(tail_lines) = file_config.tools.output.tail_lines {
+            config.tool_output_limits.tail_lines = tail_lines;
+        }
+
+        if let Some(enabled) = file_config.tools.git.enabled {
+            config.git_tools_enabled = enabled;
+        }
+        if let Some(allow_commit) = file_config.tools.git.allow_commit {
+            config.git_commit_enabled = allow_commit;
+        }
+        if let Some(enabled) = file_config.tools.code.enabled {
+            config.code_tools_enabled = enabled;
+        }
+        if let Some(index_dir) = file_config.tools.code.index_dir {
+            config.code_index_dir = resolve_log_dir(&config.workspace, index_dir);
+        }
+
+        let fetch = file_config.tools.fetch;
+        if let Some(enabled) = fetch.enabled {
+            config.fetch_enabled = enabled;
+        }
+        if let Some(allow_domains) = fetch.allow_domains {
+            config.fetch_policy.allow_domains = allow_domains;
+        }
+        if let Some(deny_domains) = fetch.deny_domains {
+            config.fetch_policy.deny_domains = deny_domains;
+        }
+        if let Some(allow_private_networks) = fetch.allow_private_networks {
+            config.fetch_policy.allow_private_networks = allow_private_networks;
+        }
+        if let Some(max_bytes) = fetch.max_bytes {
+            config.fetch_policy.max_bytes = max_bytes.max(1);
+        }
+        if let Some(timeout_secs) = fetch.timeout_secs {
+            config.fetch_policy.timeout = Duration::from_secs(timeout_secs.max(1));
+        }
+
+        let sql = file_config.tools.sql;
+        if let Some(enabled) = sql.enabled {
+            config.sql_tools_enabled = enabled;
+        }
+        if let Some(max_rows) = sql.max_rows {
+            config.sql_limits.max_rows = max_rows.max(1);
+        }
+        if let Some(timeout_secs) = sql.timeout_secs {
+            config.sql_limits.timeout = Duration::from_secs(timeout_secs.max(1));
+        }
+
+        config.tool_plugins = file_config
+            .tools
+            .plugins
+            .into_iter()
+            .filter_map(AgentToolPluginConfig::into_spec)
+            .collect();
+
+        if let Some(enabled) = file_config.tools.wasm.enabled {
+            config.wasm_plugins_enabled = enabled;
+        }
+        if let Some(directory) = file_config.tools.wasm.directory {
+            config.wasm_plugins_dir = resolve_log_dir(&config.workspace, directory);
+        }
+
+        let index = file_config.workspace_index;
+        if let Some(enabled) = index.enabled {
+            config.workspace_index_enabled = enabled;
+        }
+        if let Some(directory) = index.directory {
+            config.workspace_index_dir = resolve_log_dir(&config.workspace, directory);
+        }
+        if let Some(max_files) = index.max_files {
+            config.workspace_index_options.max_files = max_files;
+        }
+        if let Some(max_file_bytes) = index.max_file_bytes {
+            config.workspace_index_options.max_file_bytes = max_file_bytes;
+        }
+        if let Some(chunk_lines) = index.chunk_lines {
+            config.workspace_index_options.chunk_lines = chunk_lines.max(1);
+        }
+        if let Some(context_results) = index.context_results {
+            config.workspace_index_context_results = context_results;
+        }
+        if let Some(refresh_secs) = index.refresh_secs {
+            config.workspace_index_refresh = Duration::from_secs(refresh_secs.max(1));
+        }
+        config.workspace_index_embedding = index
+            .embedding
+            .filter(|embedding| !embedding.trim().is_empty() && embedding != "none");
+
+        if let Some(enabled) = file_config.watch.enabled {
+            config.watch_enabled = enabled;
+        }
+        if let Some(debounce_ms) = file_config.watch.debounce_ms {
+            config.watch_debounce = Duration::from_millis(debounce_ms);
+        }
+
+        let memory = file_config.memory;
+        config.memory_embedding = memory
+            .embedding
+            .filter(|embedding| !embedding.trim().is_empty() && embedding != "none");
+        if let Some(model) = memory.embedding_model {
+            config.memory_embedding_model = model;
+        }
+        if let Some(base_url) = memory.embedding_base_url {
+            config.memory_embedding_base_url = Some(base_url);
+        }
+        if let Some(vector_weight) = memory.vector_weight {
+            config.memory_hybrid.vector_weight = vector_weight.clamp(0.0, 1.0);
+        }
+        if let Some(min_similarity) = memory.min_similarity {
+            config.memory_hybrid.min_similarity = min_similarity;
+        }
+        if let Some(max_curated_bytes) = memory.max_curated_bytes {
+            config.memory_max_curated_bytes = max_curated_bytes.max(1);
+        }
+        if let Some(scope) = memory.scope {
+            config.memory_scoping = scope;
+        }
+        if let Some(backend) = memory.backend {
+            config.memory_backend = backend;
+        }
+        let facts = memory.facts;
+        if let Some(path) = facts.path {
+            config.memory_facts_db = resolve_log_dir(&config.workspace, path);
+        }
+        if let Some(prompt_limit) = facts.prompt_limit {
+            config.memory_facts.prompt_limit = prompt_limit;
+        }
+        if let Some(min_confidence) = facts.min_confidence {
+            config.memory_facts.min_confidence = min_confidence.clamp(0.0, 1.0);
+        }
+        if let Some(import_logs) = facts.import_logs {
+            config.memory_facts.import_logs = import_logs;
+        }
+        let extraction = memory.extraction;
+        if let Some(enabled) = extraction.enabled {
+            config.memory_extraction.enabled = enabled;
+        }
+        match extraction.trigger.as_deref() {
+            None | Some("run") => {}
+            Some("idle") => config.memory_extraction.trigger = ExtractionTrigger::Idle,
+            Some(other) => {
+                tracing::warn!(trigger = other, "unknown memory extraction trigger; using run");
+            }
+        }
+        if let Some(idle_secs) = extraction.idle_secs {
+            config.memory_extraction.idle = Duration::from_secs(idle_secs.max(1));
+        }
+        if let Some(channels) = extraction.channels {
+            config.memory_extraction.channels = Some(channels.into_iter().collect());
+        }
+        config.memory_extraction.model = extraction
+            .model
+            .filter(|model| !model.trim().is_empty());
+        if let Some(min_turns) = extraction.min_turns {
+            config.memory_extraction.min_turns = min_turns.max(1);
+        }
+        let rollup = memory.rollup;
+        if let Some(enabled) = rollup.enabled {
+            config.memory_rollup.enabled = enabled;
+        }
+        if let Some(after_days) = rollup.after_days {
+            config.memory_rollup.after_days = after_days;
+        }
+        if let Some(period) = rollup.period {
+            config.memory_rollup.period = period;
+        }
+        if let Some(retention) = rollup.retention {
+            config.memory_rollup.retention = retention;
+        }
+        if let Some(interval_hours) = rollup.interval_hours {
+            config.memory_rollup.interval = Duration::from_secs(interval_hours.max(1) * 60 * 60);
+        }
+        if let Some(summarize) = rollup.summarize {
+            config.memory_rollup.summarize = summarize;
+        }
+
+        config.mcp_servers = file_config
+            .mcp
+            .servers
+            .into_iter()
+            .filter_map(|(name, server)| server.into_spec(name))
+            .collect();
+
         if let Some(openai_api_key) = file_config.secrets.openai_api_key {
             config.openai_api_key = Some(openai_api_key);
         }
@@ -235,6 +538,43 @@ impl AppConfig {
             personality_dir: workspace.join("personality"),
             memory_dir: workspace.join("memory"),
             memory_file: workspace.join("MEMORY.md"),
+            memory_embedding: None,
+            memory_embedding_model: DEFAULT_EMBEDDING_MODEL.to_string(),
+            memory_embedding_base_url: None,
+            memory_hybrid: HybridSearchOptions::default(),
+            memory_max_curated_bytes: DEFAULT_MAX_CURATED_BYTES,
+            memory_extraction: MemoryExtractionOptions::default(),
+            memory_rollup: RollupOptions::default(),
+            memory_scoping: MemoryScoping::Global,
+            memory_backend: MemoryBackendKind::Files,
+            memory_facts_db: workspace.join("data/memory-facts.db"),
+            memory_facts: FactStoreOptions::default(),
+            checkpoints_enabled: true,
+            checkpoint_retention: DEFAULT_CHECKPOINT_RETENTION,
+            checkpoints_dir: workspace.join("data/checkpoints"),
+            tool_policy: ToolPolicy::default(),
+            tool_output_limits: OutputLimits::default(),
+            tool_output_dir: workspace.join("data/tool-output"),
+            fetch_enabled: true,
+            git_tools_enabled: true,
+            git_commit_enabled: false,
+            code_tools_enabled: true,
+            code_index_dir: workspace.join("data/code-index"),
+            fetch_policy: FetchPolicy::default(),
+            sql_tools_enabled: true,
+            sql_limits: SqlLimits::default(),
+            mcp_servers: Vec::new(),
+            workspace_index_enabled: true,
+            workspace_index_dir: workspace.join("data/workspace-index"),
+            workspace_index_options: WorkspaceIndexOptions::default(),
+            workspace_index_context_results: DEFAULT_WORKSPACE_CONTEXT_RESULTS,
+            workspace_index_refresh: DEFAULT_WORKSPACE_INDEX_REFRESH,
+            watch_enabled: true,
+            watch_debounce: DEFAULT_WATCH_DEBOUNCE,
+            workspace_index_embedding: None,
+            tool_plugins: Vec::new(),
+            wasm_plugins_enabled: true,
+            wasm_plugins_dir: workspace.join("plugins"),
             workspace,
         }
     }
@@ -245,6 +585,12 @@ impl AppConfig {
         self.personality_dir = self.workspace.join("personality");
         self.memory_dir = self.workspace.join("memory");
         self.memory_file = self.workspace.join("MEMORY.md");
+        self.checkpoints_dir = self.workspace.join("data/checkpoints");
+        self.tool_output_dir = self.workspace.join("data/tool-output");
+        self.wasm_plugins_dir = self.workspace.join("plugins");
+        self.code_index_dir = self.workspace.join("data/code-index");
+        self.workspace_index_dir = self.workspace.join("data/workspace-index");
+        self.memory_facts_db = self.workspace.join("data/memory-facts.db");
     }
 }
 
@@ -275,6 +621,12 @@ pub struct AgentFileConfig {
     pub server: AgentServerConfig,
     pub llm: AgentLlmConfig,
     pub channels: AgentChannelsConfig,
+    pub checkpoints: AgentCheckpointsConfig,
+    pub tools: AgentToolsConfig,
+    pub workspace_index: AgentWorkspaceIndexConfig,
+    pub watch: AgentWatchConfig,
+    pub memory: AgentMemoryConfig,
+    pub mcp: AgentMcpConfig,
     pub secrets: AgentSecretsConfig,
 }
 
@@ -320,6 +672,278 @@ pub struct AgentTelegramConfig {
     pub api_base_url: Option<String>,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentCheckpointsConfig {
+    pub enabled: Option<bool>,
+    pub retention: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolsConfig {
+    #[serde(flatten)]
+    pub selection: AgentToolSelectionConfig,
+    /// Per-channel selections (e.g. `telegram`) replacing the default for that channel.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub channels: BTreeMap<String, AgentToolSelectionConfig>,
+    pub output: AgentToolOutputConfig,
+    pub fetch: AgentToolFetchConfig,
+    pub git: AgentToolGitConfig,
+    pub code: AgentToolCodeConfig,
+    pub sql: AgentToolSqlConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<AgentToolPluginConfig>,
+    pub wasm: AgentToolWasmConfig,
+}
+
+/// A tool preset (`all`, `read_only`, `coding`, `memory`, `none`) and/or explicit tool names.
+/// With neither, every registered tool is enabled.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolSelectionConfig {
+    pub preset: Option<String>,
+    pub enabled: Option<Vec<String>>,
+}
+
+impl AgentToolSelectionConfig {
+    fn to_selection(&self) -> ToolSelection {
+        ToolSelection::from_preset(self.preset.as_deref(), self.enabled.as_deref())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolOutputConfig {
+    pub max_bytes: Option<usize>,
+    pub head_lines: Option<usize>,
+    pub tail_lines: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolFetchConfig {
+    pub enabled: Option<bool>,
+    pub allow_domains: Option<Vec<String>>,
+    pub deny_domains: Option<Vec<String>>,
+    pub allow_private_networks: Option<bool>,
+    pub max_bytes: Option<usize>,
+    pub timeout_secs: Option<u64>,
+}
+
+/// Git tools over the working directory; `allow_commit` opts into `git_commit`.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolGitConfig {
+    pub enabled: Option<bool>,
+    pub allow_commit: Option<bool>,
+}
+
+/// `code_outline` / `find_symbol`; the symbol index defaults to `<workspace>/data/code-index`.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolCodeConfig {
+    pub enabled: Option<bool>,
+    pub index_dir: Option<PathBuf>,
+}
+
+/// `sql_query` / `sql_schema`; `max_rows` caps the `limit` argument.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolSqlConfig {
+    pub enabled: Option<bool>,
+    pub max_rows: Option<usize>,
+    pub timeout_secs: Option<u64>,
+}
+
+/// WebAssembly plugins loaded from `directory` (default `<workspace>/plugins`).
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolWasmConfig {
+    pub enabled: Option<bool>,
+    pub directory: Option<PathBuf>,
+}
+
+/// An external executable tool. `description` and `parameters_schema` may be omitted when
+/// the executable answers `--describe`.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentToolPluginConfig {
+    pub name: String,
+    pub enabled: Option<bool>,
+    pub command: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    pub description: Option<String>,
+    pub parameters_schema: Option<serde_json::Value>,
+    pub timeout_secs: Option<u64>,
+}
+
+impl AgentToolPluginConfig {
+    fn into_spec(self) -> Option<PluginSpec> {
+        if self.enabled == Some(false) {
+            return None;
+        }
+        if self.name.trim().is_empty() || self.command.trim().is_empty() {
+            tracing::warn!(plugin = %self.name, "plugin needs both name and command; skipping");
+            return None;
+        }
+        Some(PluginSpec {
+            name: self.name,
+            command: self.command,
+            args: self.args,
+            env: self.env,
+            description: self.description,
+            parameters_schema: self.parameters_schema,
+            timeout: Duration::from_secs(
+                self.timeout_secs
+                    .unwrap_or(DEFAULT_PLUGIN_TIMEOUT_SECS)
+                    .max(1),
+            ),
+        })
+    }
+}
+
+/// Retrieval index over `working_dir` backing `search_workspace` and prompt context, refreshed
+/// every `refresh_secs`. `embedding` is `none` (BM25 only, default) or `hashed`.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentWorkspaceIndexConfig {
+    pub enabled: Option<bool>,
+    pub directory: Option<PathBuf>,
+    pub max_files: Option<usize>,
+    pub max_file_bytes: Option<u64>,
+    pub chunk_lines: Option<usize>,
+    pub context_results: Option<usize>,
+    pub refresh_secs: Option<u64>,
+    pub embedding: Option<String>,
+}
+
+/// `watch`: reload the personality files and memory index only when they change on disk,
+/// after `debounce_ms` without further edits; disabled, every request rereads them.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentWatchConfig {
+    pub enabled: Option<bool>,
+    pub debounce_ms: Option<u64>,
+}
+
+/// `memory`: semantic search over MEMORY.md and the daily logs, and limits for the memory
+/// write tools. `embedding` is `none` (keyword only, the default), `hashed` (offline) or
+/// `openai` (any OpenAI-compatible `/embeddings` endpoint); `max_curated_bytes` caps MEMORY.md.
+/// `extraction` saves durable facts from finished conversations; `rollup` consolidates old
+/// daily logs. `backend` is `files` (MEMORY.md, the default) or `sqlite` (structured facts,
+/// see `facts`).
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentMemoryConfig {
+    pub embedding: Option<String>,
+    pub embedding_model: Option<String>,
+    pub embedding_base_url: Option<String>,
+    pub vector_weight: Option<f32>,
+    pub min_similarity: Option<f32>,
+    pub max_curated_bytes: Option<usize>,
+    /// `global` (shared by everyone, the default), `user` (one memory per channel user) or
+    /// `session`.
+    pub scope: Option<MemoryScoping>,
+    pub backend: Option<MemoryBackendKind>,
+    pub facts: AgentMemoryFactsConfig,
+    pub extraction: AgentMemoryExtractionConfig,
+    pub rollup: AgentMemoryRollupConfig,
+}
+
+/// `memory.facts`: the SQLite fact store used by the `sqlite` backend. At most `prompt_limit`
+/// facts with at least `min_confidence` go into every system prompt; the first start imports
+/// MEMORY.md, and the daily logs too with `import_logs`.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentMemoryFactsConfig {
+    pub path: Option<PathBuf>,
+    pub prompt_limit: Option<usize>,
+    pub min_confidence: Option<f32>,
+    pub import_logs: Option<bool>,
+}
+
+/// `memory.rollup`: every `interval_hours`, daily logs of weeks or months (`period`) that ended
+/// `after_days` ago become digest files; `retention` is `archive`, `delete` or `keep`.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentMemoryRollupConfig {
+    pub enabled: Option<bool>,
+    pub after_days: Option<u32>,
+    pub period: Option<RollupPeriod>,
+    pub retention: Option<RetentionMode>,
+    pub interval_hours: Option<u64>,
+    pub summarize: Option<bool>,
+}
+
+/// `memory.extraction`: `trigger` is `run` (after every run, the default) or `idle` (after
+/// `idle_secs` without a new run); `channels` limits it to e.g. `["telegram", "web"]`.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentMemoryExtractionConfig {
+    pub enabled: Option<bool>,
+    pub trigger: Option<String>,
+    pub idle_secs: Option<u64>,
+    pub channels: Option<Vec<String>>,
+    pub model: Option<String>,
+    pub min_turns: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentMcpConfig {
+    pub servers: BTreeMap<String, AgentMcpServerConfig>,
+}
+
+/// One MCP server: either `command` (stdio) or `url` (streamable HTTP).
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct AgentMcpServerConfig {
+    pub enabled: Option<bool>,
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    pub timeout_secs: Option<u64>,
+}
+
+impl AgentMcpServerConfig {
+    fn into_spec(self, name: String) -> Option<McpServerSpec> {
+        if self.enabled == Some(false) {
+            return None;
+        }
+        let transport = match (self.command, self.url) {
+            (Some(command), _) => McpTransportSpec::Stdio {
+                command,
+                args: self.args,
+                env: self.env,
+            },
+            (None, Some(url)) => McpTransportSpec::Http {
+                url,
+                headers: self.headers,
+            },
+            (None, None) => {
+                tracing::warn!(server = %name, "mcp server has neither command nor url; skipping");
+                return None;
+            }
+        };
+        Some(McpServerSpec {
+            name,
+            transport,
+            timeout: Duration::from_secs(
+                self.timeout_secs.unwrap_or(DEFAULT_MCP_TIMEOUT_SECS).max(1),
+            ),
+        })
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 #[serde(default)]
 pub struct AgentSecretsConfig {
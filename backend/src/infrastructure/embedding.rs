@@ -0,0 +1,119 @@
+//! [`EmbeddingPort`] implementations: an OpenAI-compatible `/embeddings` client and the
+//! offline feature-hashing embedder shared with workspace search.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+pub use crate::domain::ports::EmbeddingPort;
+use crate::infrastructure::workspace_index::{HashedEmbedder, TextEmbedder};
+
+pub const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+#[async_trait]
+impl EmbeddingPort for HashedEmbedder {
+    fn id(&self) -> String {
+        TextEmbedder::id(self)
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts
+            .iter()
+            .map(|text| TextEmbedder::embed(self, text))
+            .collect())
+    }
+}
+
+/// Calls `POST {base_url}/embeddings` as defined by the OpenAI API, which most hosted and
+/// local servers (vLLM, Ollama, LM Studio) also accept.
+pub struct OpenAiEmbedding {
+    client: Client,
+    api_key: Option<String>,
+    base_url: String,
+    model: String,
+}
+
+impl OpenAiEmbedding {
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            api_key: None,
+            base_url: base_url.into(),
+            model: model.into(),
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+}
+
+#[async_trait]
+impl EmbeddingPort for OpenAiEmbedding {
+    fn id(&self) -> String {
+        format!(
+            "openai:{}@{}",
+            self.model,
+            self.base_url.trim_end_matches('/')
+        )
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut request = self
+            .client
+            .post(format!(
+                "{}/embeddings",
+                self.base_url.trim_end_matches('/')
+            ))
+            .json(&json!({"model": self.model, "input": texts}));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .context("failed to call embeddings endpoint")?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("embeddings API error {status}: {text}"));
+        }
+
+        let data: Value = response.json().await?;
+        let mut items = data
+            .get("data")
+            .and_then(|value| value.as_array())
+            .ok_or_else(|| anyhow!("embeddings response does not contain data"))?
+            .iter()
+            .enumerate()
+            .map(|(position, item)| {
+                let index = item
+                    .get("index")
+                    .and_then(|value| value.as_u64())
+                    .map_or(position, |index| index as usize);
+                let vector = item
+                    .get("embedding")
+                    .and_then(|value| value.as_array())
+                    .ok_or_else(|| anyhow!("embeddings response item has no embedding"))?
+                    .iter()
+                    .map(|value| value.as_f64().unwrap_or_default() as f32)
+                    .collect::<Vec<f32>>();
+                Ok((index, vector))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if items.len() != texts.len() {
+            return Err(anyhow!(
+                "embeddings response has {} vectors for {} inputs",
+                items.len(),
+                texts.len()
+            ));
+        }
+        items.sort_by_key(|(index, _)| *index);
+        Ok(items.into_iter().map(|(_, vector)| vector).collect())
+    }
+}
@@ -0,0 +1,259 @@
+//! Debounced watching of the personality and memory files.
+//!
+//! Caches that would otherwise reread their files on every request register as
+//! [`WatchedCache`]s. A notify watcher reports raw events under their roots; events are
+//! collected until `debounce` passes without a new one, then each cache affected by the batch
+//! is invalidated and the batch is logged and kept for [`FileWatchPort::status`]. A cache is
+//! only trusted while its watcher runs: starting the watcher calls `set_watched(true)` and
+//! dropping it `set_watched(false)`, so a stopped watcher falls back to per-request reads.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use notify::event::EventKind;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::domain::ports::FileWatchPort;
+use crate::domain::watch::{WatchChange, WatchStatus, WatchTarget};
+
+pub const DEFAULT_WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+/// Batches kept for the status endpoint.
+const RECENT_CHANGES: usize = 20;
+
+/// A cache kept current by a [`FileWatcher`].
+pub trait WatchedCache: Send + Sync {
+    /// Whether an edit of `path` makes the cache stale.
+    fn affected_by(&self, path: &Path) -> bool;
+    fn invalidate(&self, paths: &[PathBuf]);
+    /// Whether the cache may be reused between invalidations.
+    fn set_watched(&self, watched: bool);
+}
+
+struct Target {
+    name: String,
+    /// As configured; paths handed to the cache are under this root.
+    root: PathBuf,
+    /// Canonical form, which is what the watcher reports.
+    watched_root: PathBuf,
+    recursive: bool,
+    cache: Arc<dyn WatchedCache>,
+}
+
+impl Target {
+    /// `path` under the configured root when this target covers it.
+    fn rebase(&self, path: &Path) -> Option<PathBuf> {
+        let relative = path
+            .strip_prefix(&self.watched_root)
+            .or_else(|_| path.strip_prefix(&self.root))
+            .ok()?;
+        if relative.as_os_str().is_empty()
+            || (!self.recursive && relative.components().count() != 1)
+        {
+            return None;
+        }
+        let path = self.root.join(relative);
+        self.cache.affected_by(&path).then_some(path)
+    }
+}
+
+pub struct FileWatcher {
+    debounce: Duration,
+    targets: Arc<Vec<Target>>,
+    status: Arc<Mutex<WatchStatus>>,
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl FileWatcher {
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            targets: Arc::new(Vec::new()),
+            status: Arc::new(Mutex::new(WatchStatus::default())),
+            watcher: None,
+        }
+    }
+
+    /// Adds `root` (a directory) to watch for `cache`; without `recursive` only its direct
+    /// entries count. Must be called before [`FileWatcher::start`].
+    pub fn watch(
+        mut self,
+        name: impl Into<String>,
+        root: impl Into<PathBuf>,
+        recursive: bool,
+        cache: Arc<dyn WatchedCache>,
+    ) -> Self {
+        let root = root.into();
+        if let Some(targets) = Arc::get_mut(&mut self.targets) {
+            targets.push(Target {
+                name: name.into(),
+                watched_root: root.clone(),
+                root,
+                recursive,
+                cache,
+            });
+        }
+        self
+    }
+
+    /// Starts watching (creating missing roots) and the debounce task; needs a tokio runtime.
+    pub fn start(mut self) -> Result<Self> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+        let targets = Arc::get_mut(&mut self.targets).context("file watcher already started")?;
+        for target in targets.iter_mut() {
+            std::fs::create_dir_all(&target.root)
+                .with_context(|| format!("failed to create {}", target.root.display()))?;
+            target.watched_root =
+                std::fs::canonicalize(&target.root).unwrap_or_else(|_| target.root.clone());
+            let mode = if target.recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            watcher
+                .watch(&target.watched_root, mode)
+                .with_context(|| format!("failed to watch {}", target.root.display()))?;
+        }
+        for target in self.targets.iter() {
+            target.cache.set_watched(true);
+        }
+
+        {
+            let mut status = self.lock_status();
+            status.enabled = true;
+            status.debounce_ms = self.debounce.as_millis() as u64;
+            status.targets = self
+                .targets
+                .iter()
+                .map(|target| WatchTarget {
+                    name: target.name.clone(),
+                    path: target.root.display().to_string(),
+                    recursive: target.recursive,
+                })
+                .collect();
+        }
+        tracing::info!(
+            targets = self.targets.len(),
+            debounce_ms = self.debounce.as_millis() as u64,
+            "file watcher started"
+        );
+        tokio::spawn(run(
+            receiver,
+            self.targets.clone(),
+            self.status.clone(),
+            self.debounce,
+        ));
+        self.watcher = Some(watcher);
+        Ok(self)
+    }
+
+    pub fn status(&self) -> WatchStatus {
+        self.lock_status().clone()
+    }
+
+    fn lock_status(&self) -> std::sync::MutexGuard<'_, WatchStatus> {
+        self.status
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl FileWatchPort for FileWatcher {
+    fn status(&self) -> WatchStatus {
+        FileWatcher::status(self)
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        if self.watcher.take().is_some() {
+            for target in self.targets.iter() {
+                target.cache.set_watched(false);
+            }
+            tracing::info!("file watcher stopped");
+        }
+    }
+}
+
+/// Collects relevant paths until the watcher has been quiet for `debounce`, then invalidates
+/// the affected caches. Ends when the watcher is dropped.
+async fn run(
+    mut events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+    targets: Arc<Vec<Target>>,
+    status: Arc<Mutex<WatchStatus>>,
+    debounce: Duration,
+) {
+    let mut pending = BTreeSet::new();
+    loop {
+        let next = if pending.is_empty() {
+            events.recv().await
+        } else {
+            match tokio::time::timeout(debounce, events.recv()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    dispatch(&targets, &status, std::mem::take(&mut pending));
+                    continue;
+                }
+            }
+        };
+        match next {
+            None => break,
+            Some(Ok(event)) => {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    continue;
+                }
+                pending.extend(
+                    event
+                        .paths
+                        .into_iter()
+                        .filter(|path| targets.iter().any(|target| target.rebase(path).is_some())),
+                );
+            }
+            Some(Err(error)) => {
+                tracing::warn!(error = %error, "file watcher error");
+                status
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner())
+                    .error = Some(error.to_string());
+            }
+        }
+    }
+}
+
+fn dispatch(targets: &[Target], status: &Mutex<WatchStatus>, paths: BTreeSet<PathBuf>) {
+    for target in targets {
+        let changed: Vec<PathBuf> = paths
+            .iter()
+            .filter_map(|path| target.rebase(path))
+            .collect();
+        if changed.is_empty() {
+            continue;
+        }
+        target.cache.invalidate(&changed);
+        let paths: Vec<String> = changed
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        tracing::info!(target = %target.name, paths = ?paths, "watched files changed; cache invalidated");
+
+        let mut status = status.lock().unwrap_or_else(|error| error.into_inner());
+        let timestamp = Utc::now();
+        status.changes += 1;
+        status.last_change = Some(timestamp);
+        status.recent.push(WatchChange {
+            timestamp,
+            target: target.name.clone(),
+            paths,
+        });
+        if status.recent.len() > RECENT_CHANGES {
+            let excess = status.recent.len() - RECENT_CHANGES;
+            status.recent.drain(..excess);
+        }
+    }
+}
@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
 use std::path::{Path, PathBuf};
 use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
@@ -12,7 +13,21 @@ pub struct LoggingRuntime {
     pub log_file: PathBuf,
 }
 
+/// Where console logs go. MCP stdio mode keeps stdout free for protocol messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogConsole {
+    Stdout,
+    Stderr,
+}
+
 pub fn init_logging(config: &AppConfig) -> Result<LoggingRuntime> {
+    init_logging_with_console(config, LogConsole::Stdout)
+}
+
+pub fn init_logging_with_console(
+    config: &AppConfig,
+    console: LogConsole,
+) -> Result<LoggingRuntime> {
     std::fs::create_dir_all(&config.log_dir)
         .with_context(|| format!("failed to create log dir: {}", config.log_dir.display()))?;
     let removed = cleanup_old_logs(&config.log_dir, config.log_retention_days)?;
@@ -28,9 +43,14 @@ pub fn init_logging(config: &AppConfig) -> Result<LoggingRuntime> {
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(config.log_level.clone()));
 
+    let console_writer = match console {
+        LogConsole::Stdout => BoxMakeWriter::new(std::io::stdout),
+        LogConsole::Stderr => BoxMakeWriter::new(std::io::stderr),
+    };
+
     tracing_subscriber::registry()
         .with(env_filter)
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(console_writer))
         .with(
             tracing_subscriber::fmt::layer()
                 .with_ansi(false)
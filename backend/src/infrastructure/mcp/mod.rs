@@ -0,0 +1,355 @@
+//! Model Context Protocol client: exposes tools from external MCP servers in the
+//! `ToolRegistry`.
+
+mod transport;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::domain::types::ToolExecution;
+use crate::infrastructure::tooling::{Tool, ToolContext, ToolRegistry, MAX_TOOL_NAME_LEN};
+use transport::Transport;
+
+pub const PROTOCOL_VERSION: &str = "2025-03-26";
+pub const DEFAULT_MCP_TIMEOUT_SECS: u64 = 30;
+
+const TOOL_NAME_PREFIX: &str = "mcp";
+/// Hex digits of the hash that keeps sanitized or truncated names apart.
+const NAME_HASH_LEN: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct McpServerSpec {
+    pub name: String,
+    pub transport: McpTransportSpec,
+    pub timeout: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum McpTransportSpec {
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+    },
+    Http {
+        url: String,
+        headers: HashMap<String, String>,
+    },
+}
+
+/// A tool advertised by an MCP server through `tools/list`.
+#[derive(Clone, Debug)]
+pub struct McpToolInfo {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
+
+#[derive(Clone, Debug)]
+pub struct McpCallResult {
+    pub output: String,
+    pub is_error: bool,
+}
+
+/// Client for one MCP server. The connection is established lazily and re-established
+/// on the next request after the server process exits.
+pub struct McpClient {
+    spec: McpServerSpec,
+    connection: Mutex<Option<Arc<Transport>>>,
+    restarts: AtomicUsize,
+}
+
+impl McpClient {
+    pub fn new(spec: McpServerSpec) -> Self {
+        Self {
+            spec,
+            connection: Mutex::new(None),
+            restarts: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    /// Number of times a crashed server has been restarted.
+    pub fn restart_count(&self) -> usize {
+        self.restarts.load(Ordering::SeqCst)
+    }
+
+    async fn connection(&self) -> Result<Arc<Transport>> {
+        let mut slot = self.connection.lock().await;
+        if let Some(existing) = slot.as_ref() {
+            if existing.is_alive() {
+                return Ok(existing.clone());
+            }
+            self.restarts.fetch_add(1, Ordering::SeqCst);
+            tracing::warn!(server = %self.spec.name, "restarting crashed mcp server");
+        }
+
+        let transport = Arc::new(Transport::connect(&self.spec).await?);
+        let result = transport
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "chaos-bot", "version": env!("CARGO_PKG_VERSION")}
+                }),
+                self.spec.timeout,
+            )
+            .await
+            .map_err(|error| {
+                anyhow!(
+                    "mcp server {} failed to initialize: {error}",
+                    self.spec.name
+                )
+            })?;
+        transport
+            .notify("notifications/initialized", json!({}))
+            .await?;
+
+        let protocol_version = result
+            .get("protocolVersion")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        let server_name = result
+            .pointer("/serverInfo/name")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        tracing::info!(
+            server = %self.spec.name,
+            protocol_version,
+            server_name,
+            "mcp server connected"
+        );
+        *slot = Some(transport.clone());
+        Ok(transport)
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let transport = self.connection().await?;
+        transport.request(method, params, self.spec.timeout).await
+    }
+
+    pub async fn list_tools(&self) -> Result<Vec<McpToolInfo>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({"cursor": cursor}),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params).await?;
+            for tool in result
+                .get("tools")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default()
+            {
+                let Some(name) = tool.get("name").and_then(Value::as_str) else {
+                    continue;
+                };
+                tools.push(McpToolInfo {
+                    name: name.to_string(),
+                    description: tool
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    input_schema: tool
+                        .get("inputSchema")
+                        .cloned()
+                        .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+                });
+            }
+            cursor = result
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<McpCallResult> {
+        let arguments = if arguments.is_null() {
+            json!({})
+        } else {
+            arguments
+        };
+        let result = self
+            .request("tools/call", json!({"name": name, "arguments": arguments}))
+            .await?;
+        Ok(McpCallResult {
+            output: render_content(&result),
+            is_error: result
+                .get("isError")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        })
+    }
+}
+
+/// Flattens `tools/call` content blocks into text for the model.
+fn render_content(result: &Value) -> String {
+    let Some(content) = result.get("content").and_then(Value::as_array) else {
+        return result
+            .get("structuredContent")
+            .map(Value::to_string)
+            .unwrap_or_default();
+    };
+    content
+        .iter()
+        .map(|block| match block.get("type").and_then(Value::as_str) {
+            Some("text") => block
+                .get("text")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            Some("resource") => block
+                .pointer("/resource/text")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    format!(
+                        "[resource: {}]",
+                        block
+                            .pointer("/resource/uri")
+                            .and_then(Value::as_str)
+                            .unwrap_or("unknown")
+                    )
+                }),
+            Some(kind) => format!(
+                "[{kind}: {}]",
+                block
+                    .get("mimeType")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown")
+            ),
+            None => block.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Registry name for a remote tool: `mcp__<server>__<tool>`, limited to the characters and
+/// length model providers accept for function names. When either limit changes the name, a
+/// short hash of the original pair is appended, so `a.b` and `a_b` do not collide.
+pub fn namespaced_tool_name(server: &str, tool: &str) -> String {
+    let sanitize = |value: &str| -> String {
+        value
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                    ch
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    let mut name = format!(
+        "{TOOL_NAME_PREFIX}__{}__{}",
+        sanitize(server),
+        sanitize(tool)
+    );
+    if name.len() <= MAX_TOOL_NAME_LEN && name == format!("{TOOL_NAME_PREFIX}__{server}__{tool}") {
+        return name;
+    }
+    let digest = Sha256::digest(format!("{server}\0{tool}"));
+    let hash: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    // Sanitized names are ASCII, so any byte length is a character boundary.
+    name.truncate(MAX_TOOL_NAME_LEN - NAME_HASH_LEN - 1);
+    name.push('_');
+    name.push_str(&hash[..NAME_HASH_LEN]);
+    name
+}
+
+/// A remote MCP tool registered under a namespaced name.
+pub struct McpTool {
+    client: Arc<McpClient>,
+    name: String,
+    remote_name: String,
+    description: String,
+    schema: Value,
+}
+
+impl McpTool {
+    pub fn new(client: Arc<McpClient>, info: McpToolInfo) -> Self {
+        Self {
+            name: namespaced_tool_name(client.name(), &info.name),
+            description: if info.description.is_empty() {
+                format!("{} (MCP server {})", info.name, client.name())
+            } else {
+                info.description
+            },
+            remote_name: info.name,
+            schema: info.input_schema,
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.schema.clone()
+    }
+
+    async fn execute(&self, args: Value, _context: &ToolContext) -> Result<ToolExecution> {
+        let result = self.client.call_tool(&self.remote_name, args).await?;
+        Ok(ToolExecution {
+            name: self.name.clone(),
+            output: result.output,
+            is_error: result.is_error,
+        })
+    }
+}
+
+/// Connects to every configured server and registers its tools. Servers that fail to start
+/// or list tools are logged and skipped so one broken server does not block startup.
+pub async fn register_mcp_tools(registry: &mut ToolRegistry, servers: &[McpServerSpec]) -> usize {
+    let clients: Vec<Arc<McpClient>> = servers
+        .iter()
+        .cloned()
+        .map(|spec| Arc::new(McpClient::new(spec)))
+        .collect();
+    let listings =
+        futures::future::join_all(clients.iter().map(|client| client.list_tools())).await;
+
+    let mut registered = 0usize;
+    for (client, listing) in clients.into_iter().zip(listings) {
+        match listing {
+            Ok(tools) => {
+                tracing::info!(server = %client.name(), tools = tools.len(), "mcp tools registered");
+                let source = format!("mcp server {}", client.name());
+                for info in tools {
+                    if registry.register_external(McpTool::new(client.clone(), info), &source) {
+                        registered += 1;
+                    }
+                }
+            }
+            Err(error) => {
+                tracing::warn!(server = %client.name(), error = %error, "mcp server unavailable; skipping its tools");
+            }
+        }
+    }
+    registered
+}
@@ -0,0 +1,342 @@
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{oneshot, Mutex};
+
+use super::{McpServerSpec, McpTransportSpec};
+
+const SESSION_HEADER: &str = "mcp-session-id";
+
+type Pending = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Result<Value>>>>>;
+
+/// A live connection to one MCP server.
+pub(crate) enum Transport {
+    Stdio(StdioTransport),
+    Http(HttpTransport),
+}
+
+impl Transport {
+    pub(crate) async fn connect(spec: &McpServerSpec) -> Result<Self> {
+        match &spec.transport {
+            McpTransportSpec::Stdio { command, args, env } => Ok(Self::Stdio(
+                StdioTransport::spawn(&spec.name, command, args, env)?,
+            )),
+            McpTransportSpec::Http { url, headers } => Ok(Self::Http(HttpTransport::new(
+                url.clone(),
+                headers.clone(),
+                spec.timeout,
+            )?)),
+        }
+    }
+
+    pub(crate) async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value> {
+        match self {
+            Self::Stdio(transport) => transport.request(method, params, timeout).await,
+            Self::Http(transport) => transport.request(method, params).await,
+        }
+    }
+
+    pub(crate) async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let message = json!({"jsonrpc": "2.0", "method": method, "params": params});
+        match self {
+            Self::Stdio(transport) => transport.send(&message).await,
+            Self::Http(transport) => transport.post(&message).await.map(|_| ()),
+        }
+    }
+
+    pub(crate) fn is_alive(&self) -> bool {
+        match self {
+            Self::Stdio(transport) => transport.alive.load(Ordering::SeqCst),
+            Self::Http(_) => true,
+        }
+    }
+}
+
+/// Converts a JSON-RPC response object into its `result`, or an error for `error` responses.
+pub(crate) fn response_result(message: Value) -> Result<Value> {
+    if let Some(error) = message.get("error") {
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
+        let text = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(anyhow!("mcp error {code}: {text}"));
+    }
+    Ok(message.get("result").cloned().unwrap_or(Value::Null))
+}
+
+/// Newline-delimited JSON-RPC over a child process's stdin/stdout.
+pub(crate) struct StdioTransport {
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: Pending,
+    alive: Arc<AtomicBool>,
+    next_id: AtomicU64,
+    // Held so the process is killed when the transport is dropped.
+    _child: Child,
+}
+
+impl StdioTransport {
+    fn spawn(
+        server: &str,
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to start mcp server {server}: {command}"))?;
+
+        let stdin = Arc::new(Mutex::new(
+            child.stdin.take().context("mcp server stdin unavailable")?,
+        ));
+        let stdout = child
+            .stdout
+            .take()
+            .context("mcp server stdout unavailable")?;
+        let stderr = child
+            .stderr
+            .take()
+            .context("mcp server stderr unavailable")?;
+        let pending: Pending = Arc::default();
+        let alive = Arc::new(AtomicBool::new(true));
+
+        tokio::spawn(read_stdout(
+            server.to_string(),
+            BufReader::new(stdout),
+            stdin.clone(),
+            pending.clone(),
+            alive.clone(),
+        ));
+        let server_name = server.to_string();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::debug!(server = %server_name, "mcp server stderr: {line}");
+            }
+        });
+
+        Ok(Self {
+            stdin,
+            pending,
+            alive,
+            next_id: AtomicU64::new(1),
+            _child: child,
+        })
+    }
+
+    async fn send(&self, message: &Value) -> Result<()> {
+        if !self.alive.load(Ordering::SeqCst) {
+            return Err(anyhow!("mcp server is not running"));
+        }
+        write_message(&self.stdin, message).await
+    }
+
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .expect("mcp pending lock poisoned")
+            .insert(id, sender);
+
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        if let Err(error) = self.send(&message).await {
+            self.pending
+                .lock()
+                .expect("mcp pending lock poisoned")
+                .remove(&id);
+            return Err(error);
+        }
+
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(anyhow!("mcp server exited before responding to {method}")),
+            Err(_) => {
+                self.pending
+                    .lock()
+                    .expect("mcp pending lock poisoned")
+                    .remove(&id);
+                Err(anyhow!(
+                    "mcp request {method} timed out after {}s",
+                    timeout.as_secs_f32()
+                ))
+            }
+        }
+    }
+}
+
+async fn write_message(stdin: &Mutex<ChildStdin>, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(&line).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+async fn read_stdout(
+    server: String,
+    stdout: BufReader<tokio::process::ChildStdout>,
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: Pending,
+    alive: Arc<AtomicBool>,
+) {
+    let mut lines = stdout.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            tracing::debug!(server = %server, "ignoring non-json mcp output: {line}");
+            continue;
+        };
+
+        let id = message.get("id").cloned();
+        match (message.get("method").and_then(Value::as_str), id) {
+            // Server-initiated request; only `ping` is supported.
+            (Some(method), Some(id)) => {
+                let reply = if method == "ping" {
+                    json!({"jsonrpc": "2.0", "id": id, "result": {}})
+                } else {
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32601, "message": format!("method not found: {method}")}
+                    })
+                };
+                if let Err(error) = write_message(&stdin, &reply).await {
+                    tracing::debug!(server = %server, error = %error, "failed to answer mcp request");
+                }
+            }
+            (Some(method), None) => {
+                tracing::debug!(server = %server, method, "mcp notification");
+            }
+            (None, Some(id)) => {
+                let Some(id) = id.as_u64() else {
+                    continue;
+                };
+                let sender = pending
+                    .lock()
+                    .expect("mcp pending lock poisoned")
+                    .remove(&id);
+                if let Some(sender) = sender {
+                    let _ = sender.send(response_result(message));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    alive.store(false, Ordering::SeqCst);
+    // Dropping the senders fails every in-flight request.
+    pending.lock().expect("mcp pending lock poisoned").clear();
+    tracing::warn!(server = %server, "mcp server exited");
+}
+
+/// Streamable HTTP transport: each message is POSTed; responses arrive as JSON or SSE.
+pub(crate) struct HttpTransport {
+    url: String,
+    headers: HashMap<String, String>,
+    client: Client,
+    session_id: std::sync::Mutex<Option<String>>,
+    next_id: AtomicU64,
+}
+
+impl HttpTransport {
+    fn new(url: String, headers: HashMap<String, String>, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            url,
+            headers,
+            client: Client::builder().timeout(timeout).build()?,
+            session_id: std::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    async fn post(&self, message: &Value) -> Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(message);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let session_id = self
+            .session_id
+            .lock()
+            .expect("mcp session lock poisoned")
+            .clone();
+        if let Some(session_id) = session_id {
+            request = request.header(SESSION_HEADER, session_id);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("mcp request to {} failed", self.url))?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "mcp server {} returned status {}",
+                self.url,
+                response.status()
+            ));
+        }
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            *self.session_id.lock().expect("mcp session lock poisoned") =
+                Some(session_id.to_string());
+        }
+        Ok(response)
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        let response = self.post(&message).await?;
+        let is_sse = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        let body = response.text().await?;
+
+        if !is_sse {
+            return response_result(serde_json::from_str(&body)?);
+        }
+        for event in body.replace("\r\n", "\n").split("\n\n") {
+            let data = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(str::trim_start)
+                .collect::<Vec<_>>()
+                .join("\n");
+            let Ok(message) = serde_json::from_str::<Value>(&data) else {
+                continue;
+            };
+            if message.get("id").and_then(Value::as_u64) == Some(id) {
+                return response_result(message);
+            }
+        }
+        Err(anyhow!("mcp server sent no response for {method}"))
+    }
+}
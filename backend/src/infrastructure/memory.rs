@@ -1,29 +1,140 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
 use walkdir::WalkDir;
 
 pub use crate::domain::ports::MemoryHit;
 pub use crate::domain::ports::MemoryPort as MemoryBackend;
+use crate::domain::memory::{
+    is_memory_path, CuratedMemory, EditOrigin, MemoryChange, MemoryEdit, MemoryFile, MemoryScope,
+};
+use crate::domain::ports::EmbeddingPort;
+use crate::infrastructure::file_watch::WatchedCache;
+use crate::infrastructure::memory_index::MemoryIndex;
+use crate::infrastructure::memory_vectors::{blend_hits, HybridSearchOptions, MemoryVectorIndex};
 use crate::infrastructure::runtime_assets::DEFAULT_MEMORY_MD;
 
+/// Directory under `memory_dir` holding the search index; hidden so it is never listed or
+/// indexed as memory itself.
+const INDEX_DIR: &str = ".index";
+/// JSON lines of every [`MemoryChange`], next to the daily logs but hidden from search.
+const AUDIT_FILE: &str = ".audit.jsonl";
+/// Where [`MemoryStore::remove_file`] moves archived files, keeping their relative paths.
+const ARCHIVE_DIR: &str = ".archive";
+/// Root of the per-user and per-session memories, hidden from the global memory.
+const SCOPES_DIR: &str = ".scopes";
+const CURATED_NAME: &str = "MEMORY.md";
+const SCOPED_MEMORY_MD: &str = "# Long-Term Memory\n";
+
+pub const DEFAULT_MAX_CURATED_BYTES: usize = 16 * 1024;
+
 #[derive(Clone, Debug)]
 pub struct MemoryStore {
     memory_dir: PathBuf,
     curated_file: PathBuf,
+    index: Arc<MemoryIndex>,
+    vectors: Option<(Arc<MemoryVectorIndex>, HybridSearchOptions)>,
+    max_curated_bytes: usize,
+    /// Serialises read-modify-write edits of the curated file.
+    edit_lock: Arc<Mutex<()>>,
+    scope: MemoryScope,
+    /// The unscoped store, whose hits [`MemoryStore::search`] merges into a scope's own.
+    global: Option<Arc<MemoryStore>>,
+    /// Indexes of the scopes handed out so far, so each scope keeps a single copy in memory.
+    scopes: Arc<std::sync::Mutex<HashMap<MemoryScope, ScopeIndexes>>>,
+    /// Set while a file watcher marks the indexes stale on edits, so searches can skip
+    /// rescanning the memory files.
+    watched: Arc<AtomicBool>,
 }
 
+type ScopeIndexes = (Arc<MemoryIndex>, Option<Arc<MemoryVectorIndex>>);
+
 impl MemoryStore {
     pub fn new(memory_dir: impl Into<PathBuf>, curated_file: impl Into<PathBuf>) -> Self {
+        let memory_dir = memory_dir.into();
         Self {
-            memory_dir: memory_dir.into(),
+            index: Arc::new(MemoryIndex::new(memory_dir.join(INDEX_DIR))),
+            memory_dir,
             curated_file: curated_file.into(),
+            vectors: None,
+            max_curated_bytes: DEFAULT_MAX_CURATED_BYTES,
+            edit_lock: Arc::new(Mutex::new(())),
+            scope: MemoryScope::Global,
+            global: None,
+            scopes: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            watched: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Caps the size of `MEMORY.md` for [`MemoryStore::edit_curated`]; edits that shrink the
+    /// file are always allowed.
+    pub fn with_max_curated_bytes(mut self, max_curated_bytes: usize) -> Self {
+        self.max_curated_bytes = max_curated_bytes;
+        self
+    }
+
+    /// Adds a vector index so [`MemoryStore::search`] blends keyword and semantic ranking.
+    pub fn with_embeddings(
+        mut self,
+        embedder: Arc<dyn EmbeddingPort>,
+        options: HybridSearchOptions,
+    ) -> Self {
+        let vectors = MemoryVectorIndex::new(self.memory_dir.join(INDEX_DIR), embedder);
+        self.vectors = Some((Arc::new(vectors), options));
+        self
+    }
+
+    /// The store for `scope`, kept under `.scopes/` in the global memory directory. Scopes are
+    /// always resolved from the global store, so scoping a scoped store does not nest.
+    pub fn scoped(&self, scope: &MemoryScope) -> MemoryStore {
+        let root = self.global.as_deref().unwrap_or(self);
+        let Some(relative) = scope.relative_dir() else {
+            return root.clone();
+        };
+        let dir = root.memory_dir.join(SCOPES_DIR).join(relative);
+        let (index, vectors) = root
+            .scopes
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .entry(scope.clone())
+            .or_insert_with(|| {
+                (
+                    Arc::new(MemoryIndex::new(dir.join(INDEX_DIR))),
+                    root.vectors
+                        .as_ref()
+                        .map(|(vectors, _)| Arc::new(vectors.relocated(dir.join(INDEX_DIR)))),
+                )
+            })
+            .clone();
+        MemoryStore {
+            index,
+            vectors: vectors.zip(root.vectors.as_ref().map(|(_, options)| *options)),
+            curated_file: dir.join(CURATED_NAME),
+            memory_dir: dir,
+            max_curated_bytes: root.max_curated_bytes,
+            edit_lock: root.edit_lock.clone(),
+            scope: scope.clone(),
+            global: Some(Arc::new(root.clone())),
+            scopes: root.scopes.clone(),
+            watched: root.watched.clone(),
         }
     }
 
+    pub fn scope(&self) -> &MemoryScope {
+        &self.scope
+    }
+
+    pub fn max_curated_bytes(&self) -> usize {
+        self.max_curated_bytes
+    }
+
     pub fn memory_dir(&self) -> &Path {
         &self.memory_dir
     }
@@ -32,13 +143,26 @@ impl MemoryStore {
         &self.curated_file
     }
 
+    pub fn audit_file(&self) -> PathBuf {
+        self.memory_dir.join(AUDIT_FILE)
+    }
+
+    pub fn archive_dir(&self) -> PathBuf {
+        self.memory_dir.join(ARCHIVE_DIR)
+    }
+
     pub async fn ensure_layout(&self) -> Result<()> {
         if !self.memory_dir.exists() {
             fs::create_dir_all(&self.memory_dir).await?;
             tracing::debug!(memory_dir = %self.memory_dir.display(), "created memory directory");
         }
         if !self.curated_file.exists() {
-            fs::write(&self.curated_file, DEFAULT_MEMORY_MD).await?;
+            let template = if self.global.is_some() {
+                SCOPED_MEMORY_MD
+            } else {
+                DEFAULT_MEMORY_MD
+            };
+            fs::write(&self.curated_file, template).await?;
             tracing::debug!(curated_file = %self.curated_file.display(), "created curated memory file");
         }
         Ok(())
@@ -52,9 +176,80 @@ impl MemoryStore {
     pub async fn write_curated(&self, content: &str) -> Result<()> {
         self.ensure_layout().await?;
         fs::write(&self.curated_file, content).await?;
+        self.update_index(self.curated_file.clone()).await;
         Ok(())
     }
 
+    /// Applies `edit` to the current `MEMORY.md` under a lock, so concurrent edits of other
+    /// sections are never lost, then appends the change to the audit log.
+    pub async fn edit_curated(&self, edit: MemoryEdit, origin: EditOrigin) -> Result<MemoryChange> {
+        let _guard = self.edit_lock.lock().await;
+        let current = self.read_curated().await?;
+        let mut memory = CuratedMemory::parse(&current);
+        let (before, after) = memory.apply(&edit)?;
+        let updated = memory.render();
+        let changed = before != after;
+
+        if changed {
+            if updated.len() > self.max_curated_bytes && updated.len() > current.len() {
+                return Err(anyhow!(
+                    "MEMORY.md would grow to {} bytes, over the {} byte limit; consolidate or forget older entries first",
+                    updated.len(),
+                    self.max_curated_bytes
+                ));
+            }
+            fs::write(&self.curated_file, &updated).await?;
+            self.update_index(self.curated_file.clone()).await;
+        }
+
+        let change = MemoryChange {
+            timestamp: Utc::now(),
+            action: edit.action().to_string(),
+            section: edit.section().trim().trim_start_matches('#').trim().to_string(),
+            before,
+            after,
+            session_id: origin.session_id,
+            tool_call_id: origin.tool_call_id,
+            changed,
+        };
+        let mut audit = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.audit_file())
+            .await?;
+        let mut line = serde_json::to_string(&change)?;
+        line.push('\n');
+        audit.write_all(line.as_bytes()).await?;
+        audit.flush().await?;
+        tracing::info!(
+            action = %change.action,
+            section = %change.section,
+            changed,
+            session_id = ?change.session_id,
+            "curated memory edited"
+        );
+        Ok(change)
+    }
+
+    /// The last `limit` entries of the audit log, oldest first.
+    pub async fn changes(&self, limit: usize) -> Result<Vec<MemoryChange>> {
+        let path = self.audit_file();
+        let content = match fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(error).with_context(|| format!("failed to read {}", path.display()))
+            }
+        };
+        let mut changes: Vec<MemoryChange> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        let skip = changes.len().saturating_sub(limit);
+        changes.drain(..skip);
+        Ok(changes)
+    }
+
     pub async fn append_daily_log(&self, summary: &str) -> Result<PathBuf> {
         self.ensure_layout().await?;
         let file_path = self
@@ -68,7 +263,9 @@ impl MemoryStore {
             .await?;
         file.write_all(format!("- {}\n", summary.trim()).as_bytes())
             .await?;
+        file.flush().await?;
         tracing::debug!(path = %file_path.display(), "appended memory daily log");
+        self.update_index(file_path.clone()).await;
         Ok(file_path)
     }
 
@@ -78,16 +275,16 @@ impl MemoryStore {
         start_line: Option<usize>,
         end_line: Option<usize>,
     ) -> Result<String> {
-        let path = if relative_path == "MEMORY.md" {
-            self.curated_file.clone()
-        } else {
-            self.memory_dir.join(relative_path)
+        let path = match (self.locate(relative_path), &self.global) {
+            (Ok(path), _) if path.is_file() => path,
+            // Search hits of a scope include global files, so those stay readable.
+            (_, Some(global)) => {
+                return Box::pin(global.get_file(relative_path, start_line, end_line)).await;
+            }
+            (Ok(_), None) => return Err(anyhow!("memory file not found: {}", relative_path)),
+            (Err(error), None) => return Err(error),
         };
 
-        if !path.exists() {
-            return Err(anyhow!("memory file not found: {}", relative_path));
-        }
-
         let content = fs::read_to_string(path).await?;
         let lines: Vec<&str> = content.lines().collect();
 
@@ -103,32 +300,169 @@ impl MemoryStore {
         Ok(content)
     }
 
-    pub async fn search(&self, keyword: &str) -> Result<Vec<MemoryHit>> {
+    pub async fn list_files(&self) -> Result<Vec<MemoryFile>> {
         self.ensure_layout().await?;
-        let needle = keyword.trim().to_lowercase();
-        if needle.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        let mut hits = Vec::new();
+        let mut files = Vec::new();
         for path in self.all_memory_files() {
-            let content = match fs::read_to_string(&path).await {
-                Ok(value) => value,
-                Err(_) => continue,
+            let metadata = fs::metadata(&path).await?;
+            let relative = if path == self.curated_file {
+                CURATED_NAME.to_string()
+            } else {
+                path.strip_prefix(&self.memory_dir)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/")
             };
-            for (idx, line) in content.lines().enumerate() {
-                if line.to_lowercase().contains(&needle) {
-                    hits.push(MemoryHit {
-                        path: path.to_string_lossy().to_string(),
-                        line: idx + 1,
-                        snippet: line.to_string(),
-                    });
-                }
+            files.push(MemoryFile {
+                date: daily_log_date(&relative),
+                path: relative,
+                bytes: metadata.len(),
+                modified: metadata
+                    .modified()
+                    .map(DateTime::<Utc>::from)
+                    .unwrap_or_else(|_| Utc::now()),
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    pub async fn write_file(&self, relative_path: &str, content: &str) -> Result<()> {
+        if relative_path == CURATED_NAME {
+            return self.write_curated(content).await;
+        }
+        self.ensure_layout().await?;
+        let path = self.resolve(relative_path)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(&path, content).await?;
+        tracing::debug!(path = %path.display(), "wrote memory file");
+        self.update_index(path).await;
+        Ok(())
+    }
+
+    pub async fn remove_file(&self, relative_path: &str, archive: bool) -> Result<()> {
+        if relative_path == CURATED_NAME {
+            return Err(anyhow!("MEMORY.md cannot be removed"));
+        }
+        let path = self.resolve(relative_path)?;
+        if !path.is_file() {
+            return Err(anyhow!("memory file not found: {relative_path}"));
+        }
+        if archive {
+            let target = self.archive_dir().join(relative_path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).await?;
             }
+            fs::rename(&path, &target)
+                .await
+                .with_context(|| format!("failed to archive {}", path.display()))?;
+        } else {
+            fs::remove_file(&path).await?;
+        }
+        tracing::info!(path = %relative_path, archive, "removed memory file");
+        self.refresh_indexes().await;
+        Ok(())
+    }
+
+    /// Path of a visible file under the memory directory; rejects absolute paths, `..` and
+    /// hidden components such as the index and archive.
+    fn resolve(&self, relative_path: &str) -> Result<PathBuf> {
+        if !is_memory_path(relative_path) {
+            return Err(anyhow!("invalid memory path: {relative_path}"));
+        }
+        Ok(self.memory_dir.join(relative_path))
+    }
+
+    /// Like [`MemoryStore::resolve`], but also accepts `MEMORY.md` and the absolute paths of
+    /// this store's files that search hits carry.
+    fn locate(&self, path: &str) -> Result<PathBuf> {
+        let candidate = Path::new(path);
+        if path == CURATED_NAME || candidate == self.curated_file {
+            return Ok(self.curated_file.clone());
+        }
+        if candidate.is_absolute() {
+            let relative = candidate
+                .strip_prefix(&self.memory_dir)
+                .map_err(|_| anyhow!("invalid memory path: {path}"))?;
+            return self.resolve(&relative.to_string_lossy());
+        }
+        self.resolve(path)
+    }
+
+    /// Lines of `MEMORY.md` and the daily logs ranked against `query`, best first: BM25 alone,
+    /// or blended with vector similarity when embeddings are configured. Embedding failures
+    /// fall back to keyword ranking. A scope's hits are merged with the global memory's.
+    pub async fn search(&self, query: &str) -> Result<Vec<MemoryHit>> {
+        let mut hits = self.search_local(query).await?;
+        if let Some(global) = &self.global {
+            hits.extend(global.search_local(query).await?);
+            hits.sort_by(|a, b| b.score.total_cmp(&a.score));
         }
         Ok(hits)
     }
 
+    async fn search_local(&self, query: &str) -> Result<Vec<MemoryHit>> {
+        self.ensure_layout().await?;
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let index = self.index.clone();
+        let keyword_query = query.to_string();
+        if self.vectors.is_none() && self.watched.load(Ordering::SeqCst) && !index.is_stale() {
+            return tokio::task::spawn_blocking(move || index.search_current(&keyword_query))
+                .await?;
+        }
+        let files = self.all_memory_files();
+        let keyword_files = files.clone();
+        let keyword_hits =
+            tokio::task::spawn_blocking(move || index.search(&keyword_files, &keyword_query))
+                .await??;
+
+        let Some((vectors, options)) = &self.vectors else {
+            return Ok(keyword_hits);
+        };
+        match vectors.search(&files, query).await {
+            Ok(vector_hits) => Ok(blend_hits(keyword_hits, vector_hits, *options)),
+            Err(error) => {
+                tracing::warn!(error = %error, "memory vector search failed; using keyword ranking");
+                Ok(keyword_hits)
+            }
+        }
+    }
+
+    /// Keeps the indexes current after a write; a failure only costs freshness until the next
+    /// search re-checks the file.
+    async fn update_index(&self, path: PathBuf) {
+        let index = self.index.clone();
+        let result = tokio::task::spawn_blocking(move || index.update_file(&path)).await;
+        if let Err(error) = result.map_err(anyhow::Error::from).and_then(|inner| inner) {
+            tracing::warn!(error = %error, "memory index update failed");
+        }
+        if let Some((vectors, _)) = &self.vectors {
+            if let Err(error) = vectors.refresh(&self.all_memory_files()).await {
+                tracing::warn!(error = %error, "memory vector update failed");
+            }
+        }
+    }
+
+    /// Drops removed files from both indexes.
+    async fn refresh_indexes(&self) {
+        let files = self.all_memory_files();
+        let index = self.index.clone();
+        let keyword_files = files.clone();
+        let result = tokio::task::spawn_blocking(move || index.refresh(&keyword_files)).await;
+        if let Err(error) = result.map_err(anyhow::Error::from).and_then(|inner| inner) {
+            tracing::warn!(error = %error, "memory index refresh failed");
+        }
+        if let Some((vectors, _)) = &self.vectors {
+            if let Err(error) = vectors.refresh(&files).await {
+                tracing::warn!(error = %error, "memory vector refresh failed");
+            }
+        }
+    }
+
     fn all_memory_files(&self) -> Vec<PathBuf> {
         let mut files = Vec::new();
         if self.curated_file.exists() {
@@ -137,6 +471,9 @@ impl MemoryStore {
         if self.memory_dir.exists() {
             for entry in WalkDir::new(&self.memory_dir)
                 .into_iter()
+                .filter_entry(|entry| {
+                    entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+                })
                 .filter_map(std::result::Result::ok)
             {
                 let path = entry.path();
@@ -149,6 +486,42 @@ impl MemoryStore {
     }
 }
 
+impl WatchedCache for MemoryStore {
+    /// `MEMORY.md` and visible files under the memory directory, including scoped memories;
+    /// the index, archive and audit log are the store's own writes.
+    fn affected_by(&self, path: &Path) -> bool {
+        if path == self.curated_file {
+            return true;
+        }
+        let Ok(relative) = path.strip_prefix(&self.memory_dir) else {
+            return false;
+        };
+        !relative.as_os_str().is_empty()
+            && !relative.components().any(|component| {
+            let name = component.as_os_str();
+            name == INDEX_DIR || name == ARCHIVE_DIR || name == AUDIT_FILE
+        })
+    }
+
+    /// Marks the global and every scope's index stale; their next search rechecks the files.
+    fn invalidate(&self, _paths: &[PathBuf]) {
+        self.index.mark_stale();
+        for (index, _) in self
+            .scopes
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .values()
+        {
+            index.mark_stale();
+        }
+    }
+
+    fn set_watched(&self, watched: bool) {
+        self.invalidate(&[]);
+        self.watched.store(watched, Ordering::SeqCst);
+    }
+}
+
 #[async_trait]
 impl MemoryBackend for MemoryStore {
     async fn search(&self, keyword: &str) -> Result<Vec<MemoryHit>> {
@@ -176,7 +549,37 @@ impl MemoryBackend for MemoryStore {
         MemoryStore::write_curated(self, content).await
     }
 
+    async fn edit_curated(&self, edit: MemoryEdit, origin: EditOrigin) -> Result<MemoryChange> {
+        MemoryStore::edit_curated(self, edit, origin).await
+    }
+
+    async fn changes(&self, limit: usize) -> Result<Vec<MemoryChange>> {
+        MemoryStore::changes(self, limit).await
+    }
+
+    async fn list_files(&self) -> Result<Vec<MemoryFile>> {
+        MemoryStore::list_files(self).await
+    }
+
+    async fn write_file(&self, relative_path: &str, content: &str) -> Result<()> {
+        MemoryStore::write_file(self, relative_path, content).await
+    }
+
+    async fn remove_file(&self, relative_path: &str, archive: bool) -> Result<()> {
+        MemoryStore::remove_file(self, relative_path, archive).await
+    }
+
     async fn ensure_layout(&self) -> Result<()> {
         MemoryStore::ensure_layout(self).await
     }
+
+    fn scoped(&self, scope: &MemoryScope) -> Arc<dyn MemoryBackend> {
+        Arc::new(MemoryStore::scoped(self, scope))
+    }
+}
+
+/// Day of a top-level `YYYY-MM-DD.md` daily log.
+fn daily_log_date(relative_path: &str) -> Option<NaiveDate> {
+    let date = relative_path.strip_suffix(".md")?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
 }
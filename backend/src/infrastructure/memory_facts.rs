@@ -0,0 +1,854 @@
+//! Structured memory backed by SQLite.
+//!
+//! Curated memory is kept as one row per fact (subject, content, source session, timestamps,
+//! confidence and optional expiry) instead of lines of `MEMORY.md`. The store still speaks
+//! markdown at the [`MemoryPort`] boundary: `read_curated` renders the current facts with one
+//! `## ` section per subject, and curated edits are applied to that rendering and synced back
+//! row by row, so unchanged facts keep their timestamps. Full-text search runs on an FTS5
+//! table fed with [`memory_terms`], which keeps Chinese text searchable. Daily logs, digests
+//! and other files stay markdown in the wrapped [`MemoryStore`].
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::domain::memory::{
+    list_items, CuratedMemory, EditOrigin, MemoryChange, MemoryEdit, MemoryFact, MemoryFile,
+    MemoryScope,
+};
+use crate::domain::ports::{MemoryHit, MemoryPort};
+use crate::infrastructure::memory::MemoryStore;
+use crate::infrastructure::memory_index::memory_terms;
+
+pub const DEFAULT_FACT_PROMPT_LIMIT: usize = 40;
+pub const DEFAULT_FACT_MIN_CONFIDENCE: f32 = 0.5;
+/// Confidence of facts imported from daily logs, below the default prompt minimum.
+pub const LOG_FACT_CONFIDENCE: f32 = 0.3;
+
+const CURATED_NAME: &str = "MEMORY.md";
+const CURATED_TITLE: &str = "# Long-Term Memory";
+const SEARCH_LIMIT: usize = 50;
+const IMPORTED_KEY: &str = "imported";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS facts (
+    id INTEGER PRIMARY KEY,
+    scope TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    content TEXT NOT NULL,
+    source_session TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    expires_at TEXT
+);
+CREATE INDEX IF NOT EXISTS facts_scope ON facts(scope, id);
+CREATE VIRTUAL TABLE IF NOT EXISTS facts_fts USING fts5(terms);
+CREATE TABLE IF NOT EXISTS fact_changes (
+    id INTEGER PRIMARY KEY,
+    scope TEXT NOT NULL,
+    change TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// Which facts go into the system prompt and what the startup import covers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FactStoreOptions {
+    /// Most facts rendered into the system prompt; the most confident and recent win.
+    pub prompt_limit: usize,
+    /// Facts below this confidence are only found by search.
+    pub min_confidence: f32,
+    /// Whether the one-time import also turns daily log lines into facts.
+    pub import_logs: bool,
+}
+
+impl Default for FactStoreOptions {
+    fn default() -> Self {
+        Self {
+            prompt_limit: DEFAULT_FACT_PROMPT_LIMIT,
+            min_confidence: DEFAULT_FACT_MIN_CONFIDENCE,
+            import_logs: false,
+        }
+    }
+}
+
+/// A fact to add; the store assigns the id and timestamps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewFact {
+    pub subject: String,
+    pub content: String,
+    pub source_session: Option<String>,
+    pub confidence: f32,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl NewFact {
+    pub fn new(subject: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            content: content.into(),
+            source_session: None,
+            confidence: 1.0,
+            expires_at: None,
+        }
+    }
+}
+
+/// Result of importing markdown memory into the fact store.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FactImport {
+    pub imported: usize,
+    /// Facts already present with the same subject and content.
+    pub skipped: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct FactStore {
+    db: Arc<Mutex<Connection>>,
+    files: MemoryStore,
+    scope: MemoryScope,
+    options: FactStoreOptions,
+    /// Serialises curated edits, which read the facts before writing them.
+    edit_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl FactStore {
+    /// Opens (or creates) the database at `path`; `files` keeps daily logs and other files.
+    /// Expired facts are deleted on open.
+    pub fn open(
+        path: impl AsRef<Path>,
+        files: MemoryStore,
+        options: FactStoreOptions,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(path)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.execute_batch(SCHEMA)?;
+        let pruned = prune_expired(&connection, Utc::now())?;
+        tracing::info!(path = %path.display(), pruned, "memory fact store opened");
+        Ok(Self {
+            db: Arc::new(Mutex::new(connection)),
+            files,
+            scope: MemoryScope::Global,
+            options,
+            edit_lock: Arc::new(tokio::sync::Mutex::new(())),
+        })
+    }
+
+    pub fn files(&self) -> &MemoryStore {
+        &self.files
+    }
+
+    pub fn options(&self) -> FactStoreOptions {
+        self.options
+    }
+
+    /// The store for `scope`: its own facts and files, with global facts merged into search
+    /// and the prompt.
+    pub fn scoped(&self, scope: &MemoryScope) -> FactStore {
+        Self {
+            files: self.files.scoped(scope),
+            scope: scope.clone(),
+            ..self.clone()
+        }
+    }
+
+    /// Current (unexpired) facts of this scope, in `MEMORY.md` order.
+    pub async fn facts(&self) -> Result<Vec<MemoryFact>> {
+        let scope = scope_key(&self.scope);
+        self.with_db(move |connection| load_facts(connection, &scope, Utc::now()))
+            .await
+    }
+
+    pub async fn add_fact(&self, fact: NewFact) -> Result<MemoryFact> {
+        let scope = scope_key(&self.scope);
+        self.with_db(move |connection| {
+            let tx = connection.transaction()?;
+            let id = insert_fact(&tx, &scope, &fact, Utc::now())?;
+            let stored = load_fact(&tx, id)?;
+            tx.commit()?;
+            Ok(stored)
+        })
+        .await
+    }
+
+    /// Deletes expired facts of every scope and returns how many were removed.
+    pub async fn prune_expired(&self) -> Result<usize> {
+        self.with_db(|connection| prune_expired(connection, Utc::now()))
+            .await
+    }
+
+    /// Adds the sections of `MEMORY.md` as facts (subject = heading; text before the first
+    /// heading is skipped) and, with `include_logs`, every daily log line under
+    /// `Log YYYY-MM-DD` with [`LOG_FACT_CONFIDENCE`]. Facts already present are skipped, so
+    /// importing twice is harmless.
+    pub async fn import_markdown(&self, include_logs: bool) -> Result<FactImport> {
+        let curated = CuratedMemory::parse(&self.files.read_curated().await?);
+        let mut facts = Vec::new();
+        for heading in curated.headings() {
+            for item in curated.items(heading) {
+                facts.push(NewFact::new(heading, item));
+            }
+        }
+        if include_logs {
+            for file in self.files.list_files().await? {
+                let Some(date) = file.date else {
+                    continue;
+                };
+                let content = self.files.get_file(&file.path, None, None).await?;
+                for item in list_items(&content) {
+                    facts.push(NewFact {
+                        confidence: LOG_FACT_CONFIDENCE,
+                        ..NewFact::new(format!("Log {date}"), item)
+                    });
+                }
+            }
+        }
+
+        let scope = scope_key(&self.scope);
+        let report = self
+            .with_db(move |connection| {
+                let tx = connection.transaction()?;
+                let now = Utc::now();
+                let mut known: BTreeSet<(String, String)> =
+                    load_facts(&tx, &scope, now)?.iter().map(fact_key).collect();
+                let mut report = FactImport::default();
+                for fact in facts {
+                    let key = (fact.subject.to_lowercase(), fact.content.to_lowercase());
+                    if known.insert(key) {
+                        insert_fact(&tx, &scope, &fact, now)?;
+                        report.imported += 1;
+                    } else {
+                        report.skipped += 1;
+                    }
+                }
+                tx.commit()?;
+                Ok(report)
+            })
+            .await?;
+        tracing::info!(
+            imported = report.imported,
+            skipped = report.skipped,
+            include_logs,
+            "imported markdown memory into fact store"
+        );
+        Ok(report)
+    }
+
+    /// Runs [`FactStore::import_markdown`] the first time the database is used, so existing
+    /// markdown memory carries over when switching backends; `None` when already done.
+    pub async fn import_once(&self) -> Result<Option<FactImport>> {
+        let done = self
+            .with_db(|connection| {
+                Ok(connection
+                    .query_row(
+                        "SELECT value FROM meta WHERE key = ?1",
+                        [IMPORTED_KEY],
+                        |row| row.get::<_, String>(0),
+                    )
+                    .optional()?
+                    .is_some())
+            })
+            .await?;
+        if done {
+            return Ok(None);
+        }
+        let report = self.import_markdown(self.options.import_logs).await?;
+        self.with_db(|connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+                params![IMPORTED_KEY, timestamp(Utc::now())],
+            )?;
+            Ok(())
+        })
+        .await?;
+        Ok(Some(report))
+    }
+
+    pub async fn read_curated(&self) -> Result<String> {
+        Ok(render_curated(&self.facts().await?).0)
+    }
+
+    /// Replaces the curated facts with the sections of `content`; facts whose text is
+    /// unchanged are kept as they are.
+    pub async fn write_curated(&self, content: &str) -> Result<()> {
+        let _guard = self.edit_lock.lock().await;
+        let memory = CuratedMemory::parse(content);
+        let scope = scope_key(&self.scope);
+        self.with_db(move |connection| {
+            let tx = connection.transaction()?;
+            let now = Utc::now();
+            let facts = load_facts(&tx, &scope, now)?;
+            let mut subjects: Vec<String> = facts.iter().map(|fact| fact.subject.clone()).collect();
+            for heading in memory.headings() {
+                sync_subject(
+                    &tx,
+                    &scope,
+                    heading,
+                    &facts,
+                    &memory.items(heading),
+                    None,
+                    now,
+                )?;
+            }
+            subjects.retain(|subject| {
+                !memory
+                    .headings()
+                    .iter()
+                    .any(|heading| heading.eq_ignore_ascii_case(subject))
+            });
+            subjects.dedup();
+            for subject in subjects {
+                sync_subject(&tx, &scope, &subject, &facts, &[], None, now)?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Applies `edit` to the rendered facts with the same rules as `MEMORY.md` edits, then
+    /// stores the resulting section and records the change.
+    pub async fn edit_curated(&self, edit: MemoryEdit, origin: EditOrigin) -> Result<MemoryChange> {
+        let _guard = self.edit_lock.lock().await;
+        let scope = scope_key(&self.scope);
+        let max_curated_bytes = self.files.max_curated_bytes();
+        let change = self
+            .with_db(move |connection| {
+                let tx = connection.transaction()?;
+                let now = Utc::now();
+                let facts = load_facts(&tx, &scope, now)?;
+                let (current, _) = render_curated(&facts);
+                let mut memory = CuratedMemory::parse(&current);
+                let (before, after) = memory.apply(&edit)?;
+                let section = edit.section().trim().trim_start_matches('#').trim().to_string();
+                let changed = before != after;
+                if changed {
+                    let updated = memory.render();
+                    if updated.len() > max_curated_bytes && updated.len() > current.len() {
+                        return Err(anyhow!(
+                            "curated memory would grow to {} bytes, over the {} byte limit; consolidate or forget older entries first",
+                            updated.len(),
+                            max_curated_bytes
+                        ));
+                    }
+                    let items = memory.items(&section);
+                    sync_subject(
+                        &tx,
+                        &scope,
+                        &section,
+                        &facts,
+                        &items,
+                        origin.session_id.as_deref(),
+                        now,
+                    )?;
+                }
+                let change = MemoryChange {
+                    timestamp: now,
+                    action: edit.action().to_string(),
+                    section,
+                    before,
+                    after,
+                    session_id: origin.session_id,
+                    tool_call_id: origin.tool_call_id,
+                    changed,
+                };
+                tx.execute(
+                    "INSERT INTO fact_changes (scope, change) VALUES (?1, ?2)",
+                    params![scope, serde_json::to_string(&change)?],
+                )?;
+                tx.commit()?;
+                Ok(change)
+            })
+            .await?;
+        tracing::info!(
+            action = %change.action,
+            section = %change.section,
+            changed = change.changed,
+            session_id = ?change.session_id,
+            "memory facts edited"
+        );
+        Ok(change)
+    }
+
+    pub async fn changes(&self, limit: usize) -> Result<Vec<MemoryChange>> {
+        let scope = scope_key(&self.scope);
+        self.with_db(move |connection| {
+            let mut statement = connection.prepare(
+                "SELECT change FROM fact_changes WHERE scope = ?1 ORDER BY id DESC LIMIT ?2",
+            )?;
+            let mut changes = statement
+                .query_map(params![scope, limit as i64], |row| row.get::<_, String>(0))?
+                .filter_map(|row| row.ok())
+                .filter_map(|change| serde_json::from_str(&change).ok())
+                .collect::<Vec<MemoryChange>>();
+            changes.reverse();
+            Ok(changes)
+        })
+        .await
+    }
+
+    /// Facts of this scope and the global one matching `query`, merged with the daily log hits
+    /// of the file store. Fact hits point at their line of the rendered `MEMORY.md`. Both
+    /// sources are scaled so their best hit scores 1 before merging, since their raw scores
+    /// are not comparable.
+    pub async fn search(&self, query: &str) -> Result<Vec<MemoryHit>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let terms: BTreeSet<String> = memory_terms(query).into_iter().collect();
+        let mut hits = if terms.is_empty() {
+            Vec::new()
+        } else {
+            let scopes = self.visible_scopes();
+            self.with_db(move |connection| search_facts(connection, &scopes, &terms, Utc::now()))
+                .await?
+        };
+        normalize_scores(&mut hits);
+        let curated = self.files.curated_file().to_string_lossy().into_owned();
+        let mut log_hits: Vec<MemoryHit> = self
+            .files
+            .search(query)
+            .await?
+            .into_iter()
+            .filter(|hit| hit.path != curated && !hit.path.ends_with(CURATED_NAME))
+            .collect();
+        normalize_scores(&mut log_hits);
+        hits.extend(log_hits);
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(hits)
+    }
+
+    /// The most confident current facts of the global scope and this one, rendered as
+    /// `## subject` sections.
+    pub async fn prompt_memory(&self) -> Result<Option<String>> {
+        let scopes = self.visible_scopes();
+        let options = self.options;
+        let mut facts = self
+            .with_db(move |connection| {
+                let now = Utc::now();
+                let mut facts = Vec::new();
+                for scope in &scopes {
+                    facts.extend(load_facts(connection, scope, now)?);
+                }
+                Ok(facts)
+            })
+            .await?;
+        facts.retain(|fact| fact.confidence >= options.min_confidence);
+        if facts.is_empty() || options.prompt_limit == 0 {
+            return Ok(None);
+        }
+        let mut ranked: Vec<usize> = (0..facts.len()).collect();
+        ranked.sort_by(|&a, &b| {
+            facts[b]
+                .confidence
+                .total_cmp(&facts[a].confidence)
+                .then(facts[b].updated_at.cmp(&facts[a].updated_at))
+        });
+        let keep: BTreeSet<usize> = ranked.into_iter().take(options.prompt_limit).collect();
+        let mut index = 0;
+        facts.retain(|_| {
+            index += 1;
+            keep.contains(&(index - 1))
+        });
+
+        let sections = group_by_subject(&facts)
+            .into_iter()
+            .map(|(subject, facts)| {
+                let lines = facts
+                    .iter()
+                    .map(|fact| match fact.expires_at {
+                        Some(expires_at) => format!(
+                            "- {} (until {})",
+                            fact.content,
+                            expires_at.format("%Y-%m-%d")
+                        ),
+                        None => format!("- {}", fact.content),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("## {subject}\n{lines}")
+            })
+            .collect::<Vec<_>>();
+        Ok(Some(sections.join("\n\n")))
+    }
+
+    pub async fn get_file(
+        &self,
+        relative_path: &str,
+        start_line: Option<usize>,
+        end_line: Option<usize>,
+    ) -> Result<String> {
+        if relative_path != CURATED_NAME {
+            return self
+                .files
+                .get_file(relative_path, start_line, end_line)
+                .await;
+        }
+        let content = self.read_curated().await?;
+        match (start_line, end_line) {
+            (Some(start), Some(end)) => {
+                let lines: Vec<&str> = content.lines().collect();
+                let from = start.saturating_sub(1);
+                let to = end.min(lines.len());
+                if from >= to {
+                    return Ok(String::new());
+                }
+                Ok(lines[from..to].join("\n"))
+            }
+            _ => Ok(content),
+        }
+    }
+
+    /// The file store's listing, with `MEMORY.md` describing the rendered facts.
+    pub async fn list_files(&self) -> Result<Vec<MemoryFile>> {
+        let facts = self.facts().await?;
+        let (rendered, _) = render_curated(&facts);
+        let mut files = self.files.list_files().await?;
+        for file in &mut files {
+            if file.path == CURATED_NAME {
+                file.bytes = rendered.len() as u64;
+                if let Some(updated_at) = facts.iter().map(|fact| fact.updated_at).max() {
+                    file.modified = updated_at;
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn visible_scopes(&self) -> Vec<String> {
+        let mut scopes = vec![scope_key(&MemoryScope::Global)];
+        if self.scope != MemoryScope::Global {
+            scopes.push(scope_key(&self.scope));
+        }
+        scopes
+    }
+
+    async fn with_db<T, F>(&self, task: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = db.lock().unwrap_or_else(|error| error.into_inner());
+            task(&mut connection)
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl MemoryPort for FactStore {
+    async fn search(&self, keyword: &str) -> Result<Vec<MemoryHit>> {
+        FactStore::search(self, keyword).await
+    }
+
+    async fn append_daily_log(&self, summary: &str) -> Result<PathBuf> {
+        self.files.append_daily_log(summary).await
+    }
+
+    async fn get_file(
+        &self,
+        relative_path: &str,
+        start_line: Option<usize>,
+        end_line: Option<usize>,
+    ) -> Result<String> {
+        FactStore::get_file(self, relative_path, start_line, end_line).await
+    }
+
+    async fn read_curated(&self) -> Result<String> {
+        FactStore::read_curated(self).await
+    }
+
+    async fn write_curated(&self, content: &str) -> Result<()> {
+        FactStore::write_curated(self, content).await
+    }
+
+    async fn edit_curated(&self, edit: MemoryEdit, origin: EditOrigin) -> Result<MemoryChange> {
+        FactStore::edit_curated(self, edit, origin).await
+    }
+
+    async fn changes(&self, limit: usize) -> Result<Vec<MemoryChange>> {
+        FactStore::changes(self, limit).await
+    }
+
+    async fn list_files(&self) -> Result<Vec<MemoryFile>> {
+        FactStore::list_files(self).await
+    }
+
+    async fn write_file(&self, relative_path: &str, content: &str) -> Result<()> {
+        if relative_path == CURATED_NAME {
+            return FactStore::write_curated(self, content).await;
+        }
+        self.files.write_file(relative_path, content).await
+    }
+
+    async fn remove_file(&self, relative_path: &str, archive: bool) -> Result<()> {
+        self.files.remove_file(relative_path, archive).await
+    }
+
+    async fn ensure_layout(&self) -> Result<()> {
+        self.files.ensure_layout().await
+    }
+
+    async fn prompt_memory(&self) -> Result<Option<String>> {
+        FactStore::prompt_memory(self).await
+    }
+
+    fn scoped(&self, scope: &MemoryScope) -> Arc<dyn MemoryPort> {
+        Arc::new(FactStore::scoped(self, scope))
+    }
+}
+
+fn scope_key(scope: &MemoryScope) -> String {
+    scope.relative_dir().unwrap_or_default()
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(value: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|error| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, error.into())
+        })
+}
+
+fn fact_key(fact: &MemoryFact) -> (String, String) {
+    (fact.subject.to_lowercase(), fact.content.to_lowercase())
+}
+
+const FACT_COLUMNS: &str =
+    "id, subject, content, source_session, created_at, updated_at, confidence, expires_at";
+
+fn read_fact(row: &rusqlite::Row<'_>) -> rusqlite::Result<MemoryFact> {
+    let expires_at: Option<String> = row.get(7)?;
+    Ok(MemoryFact {
+        id: row.get(0)?,
+        subject: row.get(1)?,
+        content: row.get(2)?,
+        source_session: row.get(3)?,
+        created_at: parse_timestamp(&row.get::<_, String>(4)?)?,
+        updated_at: parse_timestamp(&row.get::<_, String>(5)?)?,
+        confidence: row.get::<_, f64>(6)? as f32,
+        expires_at: expires_at.as_deref().map(parse_timestamp).transpose()?,
+    })
+}
+
+fn load_fact(connection: &Connection, id: i64) -> Result<MemoryFact> {
+    Ok(connection.query_row(
+        &format!("SELECT {FACT_COLUMNS} FROM facts WHERE id = ?1"),
+        [id],
+        read_fact,
+    )?)
+}
+
+/// Unexpired facts of `scope` in insertion order.
+fn load_facts(connection: &Connection, scope: &str, now: DateTime<Utc>) -> Result<Vec<MemoryFact>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT {FACT_COLUMNS} FROM facts
+         WHERE scope = ?1 AND (expires_at IS NULL OR expires_at > ?2)
+         ORDER BY id"
+    ))?;
+    let facts = statement
+        .query_map(params![scope, timestamp(now)], read_fact)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(facts)
+}
+
+fn insert_fact(
+    tx: &Transaction<'_>,
+    scope: &str,
+    fact: &NewFact,
+    now: DateTime<Utc>,
+) -> Result<i64> {
+    let subject = fact.subject.trim();
+    let content = fact.content.trim();
+    if subject.is_empty() || content.is_empty() {
+        return Err(anyhow!("fact subject and content must not be empty"));
+    }
+    tx.execute(
+        "INSERT INTO facts
+         (scope, subject, content, source_session, created_at, updated_at, confidence, expires_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6, ?7)",
+        params![
+            scope,
+            subject,
+            content,
+            fact.source_session,
+            timestamp(now),
+            f64::from(fact.confidence.clamp(0.0, 1.0)),
+            fact.expires_at.map(timestamp),
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    tx.execute(
+        "INSERT INTO facts_fts (rowid, terms) VALUES (?1, ?2)",
+        params![id, memory_terms(&format!("{subject} {content}")).join(" ")],
+    )?;
+    Ok(id)
+}
+
+fn delete_fact(connection: &Connection, id: i64) -> Result<()> {
+    connection.execute("DELETE FROM facts WHERE id = ?1", [id])?;
+    connection.execute("DELETE FROM facts_fts WHERE rowid = ?1", [id])?;
+    Ok(())
+}
+
+fn prune_expired(connection: &Connection, now: DateTime<Utc>) -> Result<usize> {
+    let mut statement = connection
+        .prepare("SELECT id FROM facts WHERE expires_at IS NOT NULL AND expires_at <= ?1")?;
+    let expired = statement
+        .query_map([timestamp(now)], |row| row.get::<_, i64>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for id in &expired {
+        delete_fact(connection, *id)?;
+    }
+    Ok(expired.len())
+}
+
+/// Makes the facts of `subject` (matched case-insensitively) equal `items`: facts whose
+/// content is still listed are kept untouched, the rest are deleted and new items inserted.
+fn sync_subject(
+    tx: &Transaction<'_>,
+    scope: &str,
+    subject: &str,
+    facts: &[MemoryFact],
+    items: &[String],
+    source_session: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let mut existing: Vec<&MemoryFact> = facts
+        .iter()
+        .filter(|fact| fact.subject.eq_ignore_ascii_case(subject))
+        .collect();
+    let subject = existing
+        .first()
+        .map_or(subject, |fact| fact.subject.as_str())
+        .to_string();
+    let mut added = Vec::new();
+    for item in items {
+        match existing.iter().position(|fact| fact.content == *item) {
+            Some(index) => {
+                existing.remove(index);
+            }
+            None => added.push(item),
+        }
+    }
+    for fact in existing {
+        delete_fact(tx, fact.id)?;
+    }
+    for item in added {
+        let fact = NewFact {
+            source_session: source_session.map(str::to_string),
+            ..NewFact::new(subject.clone(), item.clone())
+        };
+        insert_fact(tx, scope, &fact, now)?;
+    }
+    Ok(())
+}
+
+/// Facts grouped by subject (case-insensitively), subjects in order of first appearance.
+fn group_by_subject(facts: &[MemoryFact]) -> Vec<(&str, Vec<&MemoryFact>)> {
+    let mut groups: Vec<(&str, Vec<&MemoryFact>)> = Vec::new();
+    for fact in facts {
+        match groups
+            .iter_mut()
+            .find(|(subject, _)| subject.eq_ignore_ascii_case(&fact.subject))
+        {
+            Some((_, group)) => group.push(fact),
+            None => groups.push((fact.subject.as_str(), vec![fact])),
+        }
+    }
+    groups
+}
+
+/// `MEMORY.md` rendering of `facts` and the 1-based line of each fact id in it.
+fn render_curated(facts: &[MemoryFact]) -> (String, HashMap<i64, usize>) {
+    let mut lines = vec![CURATED_TITLE.to_string()];
+    let mut positions = HashMap::new();
+    for (subject, group) in group_by_subject(facts) {
+        lines.push(String::new());
+        lines.push(format!("## {subject}"));
+        for fact in group {
+            lines.push(format!("- {}", fact.content));
+            positions.insert(fact.id, lines.len());
+        }
+    }
+    let mut rendered = lines.join("\n");
+    rendered.push('\n');
+    (rendered, positions)
+}
+
+/// Divides every score by the best one, so the top hit of a source scores 1.
+fn normalize_scores(hits: &mut [MemoryHit]) {
+    let best = hits.iter().map(|hit| hit.score).fold(0.0f32, f32::max);
+    if best > 0.0 {
+        for hit in hits {
+            hit.score /= best;
+        }
+    }
+}
+
+fn search_facts(
+    connection: &Connection,
+    scopes: &[String],
+    terms: &BTreeSet<String>,
+    now: DateTime<Utc>,
+) -> Result<Vec<MemoryHit>> {
+    let query = terms
+        .iter()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    let mut hits = Vec::new();
+    for scope in scopes {
+        let positions = render_curated(&load_facts(connection, scope, now)?).1;
+        let mut statement = connection.prepare(
+            "SELECT f.id, f.subject, f.content, f.confidence, bm25(facts_fts)
+             FROM facts_fts JOIN facts f ON f.id = facts_fts.rowid
+             WHERE facts_fts MATCH ?1 AND f.scope = ?2
+               AND (f.expires_at IS NULL OR f.expires_at > ?3)
+             ORDER BY bm25(facts_fts)
+             LIMIT ?4",
+        )?;
+        let rows = statement.query_map(
+            params![query, scope, timestamp(now), SEARCH_LIMIT as i64],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, f64>(3)?,
+                    row.get::<_, f64>(4)?,
+                ))
+            },
+        )?;
+        for row in rows {
+            let (id, subject, content, confidence, rank) = row?;
+            hits.push(MemoryHit {
+                path: CURATED_NAME.to_string(),
+                line: positions.get(&id).copied().unwrap_or(0),
+                snippet: format!("{subject}: {content}"),
+                // FTS5 bm25 is lower-is-better.
+                score: (-rank * confidence) as f32,
+            });
+        }
+    }
+    Ok(hits)
+}
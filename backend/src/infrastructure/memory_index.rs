@@ -0,0 +1,418 @@
+//! Inverted index over memory files (`MEMORY.md` and the daily logs).
+//!
+//! Every non-blank line is a document scored with BM25. Terms come from [`memory_terms`]:
+//! Chinese and Japanese runs are segmented with jieba, other text is split like workspace
+//! search, and stop words are dropped. The index is persisted to `<dir>/memory-index.json`,
+//! updated for a single file after each memory write and refreshed by mtime and size before
+//! every search, so external edits are picked up without rereading unchanged files. While a
+//! file watcher marks the index stale on edits, [`MemoryIndex::search_current`] skips that
+//! per-search scan until something changed.
+
+use anyhow::{anyhow, Context, Result};
+use jieba_rs::Jieba;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+
+use crate::domain::ports::MemoryHit;
+use crate::infrastructure::workspace_index::tokenize;
+
+const INDEX_VERSION: u32 = 1;
+const INDEX_FILE: &str = "memory-index.json";
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+const ENGLISH_STOP_WORDS: &[&str] = &[
+    "about", "above", "after", "again", "all", "also", "am", "an", "and", "any", "are", "as", "at",
+    "be", "been", "before", "being", "below", "between", "both", "but", "by", "can", "could",
+    "did", "do", "does", "doing", "down", "during", "each", "few", "for", "from", "further", "had",
+    "has", "have", "having", "he", "her", "here", "hers", "him", "his", "how", "if", "in", "into",
+    "is", "it", "its", "just", "me", "more", "most", "my", "no", "nor", "not", "now", "of", "off",
+    "on", "once", "only", "or", "other", "our", "ours", "out", "over", "own", "same", "she",
+    "should", "so", "some", "such", "than", "that", "the", "their", "theirs", "them", "then",
+    "there", "these", "they", "this", "those", "through", "to", "too", "under", "until", "up",
+    "us", "very", "was", "we", "were", "what", "when", "where", "which", "while", "who", "whom",
+    "why", "will", "with", "would", "you", "your", "yours",
+];
+
+/// Whitespace-separated; jieba emits these as standalone words.
+const CHINESE_STOP_WORDS: &str =
+    "的 了 和 是 在 我 有 就 不 人 都 一 一个 上 也 很 到 说 要 去 你 会 \
+     着 没有 看 好 自己 这 那 他 她 它 我们 你们 他们 吗 呢 吧 啊 与 及 或 把 被 \
+     让 给 对 从 什么 怎么 为什么 哪 哪里 这个 那个 还 又 但 而 之 其 以 于 个 过";
+
+fn is_stop_word(term: &str) -> bool {
+    static STOP_WORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    STOP_WORDS
+        .get_or_init(|| {
+            ENGLISH_STOP_WORDS
+                .iter()
+                .copied()
+                .chain(CHINESE_STOP_WORDS.split_whitespace())
+                .collect()
+        })
+        .contains(term)
+}
+
+fn jieba() -> &'static Jieba {
+    static JIEBA: OnceLock<Jieba> = OnceLock::new();
+    JIEBA.get_or_init(Jieba::new)
+}
+
+/// Han ideographs and kana, which are written without spaces and need a segmenter.
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{3040}'..='\u{30ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{20000}'..='\u{2ebef}'
+    )
+}
+
+/// Search terms of `text`: lowercased words (with camelCase parts, see [`tokenize`]) outside
+/// CJK runs, jieba search-mode words inside them, minus English and Chinese stop words.
+pub fn memory_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut push_run = |run: &str, cjk: bool| {
+        if cjk {
+            terms.extend(
+                jieba()
+                    .cut_for_search(run, true)
+                    .into_iter()
+                    .filter(|word| word.chars().all(char::is_alphanumeric))
+                    .filter(|word| !is_stop_word(word))
+                    .map(str::to_string),
+            );
+        } else {
+            terms.extend(tokenize(run).into_iter().filter(|term| !is_stop_word(term)));
+        }
+    };
+
+    let mut start = 0;
+    let mut run_cjk = false;
+    for (offset, ch) in text.char_indices() {
+        let cjk = is_cjk(ch);
+        if cjk != run_cjk {
+            push_run(&text[start..offset], run_cjk);
+            start = offset;
+            run_cjk = cjk;
+        }
+    }
+    push_run(&text[start..], run_cjk);
+    terms
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    files: BTreeMap<String, IndexedFile>,
+    /// term -> lines containing it.
+    postings: BTreeMap<String, Vec<Posting>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedFile {
+    modified_ms: u128,
+    size: u64,
+    /// Non-blank lines with at least one term, in line order.
+    lines: Vec<IndexedLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedLine {
+    line: usize,
+    text: String,
+    length: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Posting {
+    path: String,
+    line: usize,
+    count: u32,
+}
+
+impl IndexFile {
+    fn empty() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            ..Self::default()
+        }
+    }
+
+    fn remove_file(&mut self, key: &str) -> bool {
+        let Some(file) = self.files.remove(key) else {
+            return false;
+        };
+        let terms: BTreeSet<String> = file
+            .lines
+            .iter()
+            .flat_map(|line| memory_terms(&line.text))
+            .collect();
+        for term in terms {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                postings.retain(|posting| posting.path != key);
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        true
+    }
+
+    fn insert_file(&mut self, key: &str, modified_ms: u128, size: u64, content: &str) {
+        self.remove_file(key);
+        let mut lines = Vec::new();
+        for (index, text) in content.lines().enumerate() {
+            let terms = memory_terms(text);
+            if terms.is_empty() {
+                continue;
+            }
+            let mut counts: BTreeMap<String, u32> = BTreeMap::new();
+            for term in &terms {
+                *counts.entry(term.clone()).or_default() += 1;
+            }
+            for (term, count) in counts {
+                self.postings.entry(term).or_default().push(Posting {
+                    path: key.to_string(),
+                    line: index + 1,
+                    count,
+                });
+            }
+            lines.push(IndexedLine {
+                line: index + 1,
+                text: text.to_string(),
+                length: terms.len(),
+            });
+        }
+        self.files.insert(
+            key.to_string(),
+            IndexedFile {
+                modified_ms,
+                size,
+                lines,
+            },
+        );
+    }
+
+    fn line(&self, path: &str, line: usize) -> Option<&IndexedLine> {
+        let file = self.files.get(path)?;
+        file.lines
+            .binary_search_by_key(&line, |indexed| indexed.line)
+            .ok()
+            .map(|position| &file.lines[position])
+    }
+}
+
+/// Stat of a file as (mtime in ms, size), `None` when it is gone.
+pub(crate) fn file_stamp(path: &Path) -> Option<(u128, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified_ms = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_millis());
+    Some((modified_ms, metadata.len()))
+}
+
+#[derive(Debug)]
+pub struct MemoryIndex {
+    dir: PathBuf,
+    state: Mutex<Option<IndexFile>>,
+    /// Set until the first refresh and whenever a watcher reports an edit.
+    stale: AtomicBool,
+}
+
+impl MemoryIndex {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            state: Mutex::new(None),
+            stale: AtomicBool::new(true),
+        }
+    }
+
+    pub fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE)
+    }
+
+    /// Re-indexes one file after it was written, or drops it when it no longer exists. Blocking.
+    pub fn update_file(&self, path: &Path) -> Result<()> {
+        let mut state = self.lock()?;
+        let index = state.get_or_insert_with(|| self.load());
+        let key = path.to_string_lossy().to_string();
+        match file_stamp(path) {
+            Some((modified_ms, size)) => {
+                let content = std::fs::read_to_string(path).unwrap_or_default();
+                index.insert_file(&key, modified_ms, size, &content);
+            }
+            None => {
+                index.remove_file(&key);
+            }
+        }
+        self.persist(index)
+    }
+
+    /// Brings the index in line with `files`: changed files are re-read, missing ones dropped.
+    /// Returns how many files were re-indexed or removed. Blocking.
+    pub fn refresh(&self, files: &[PathBuf]) -> Result<usize> {
+        let mut state = self.lock()?;
+        self.refresh_locked(&mut state, files)
+    }
+
+    /// Makes the next [`MemoryIndex::search_current`] rescan the files.
+    pub fn mark_stale(&self) {
+        self.stale.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale.load(Ordering::SeqCst)
+    }
+
+    /// Lines matching `query` across `files`, best BM25 score first. Blocking.
+    pub fn search(&self, files: &[PathBuf], query: &str) -> Result<Vec<MemoryHit>> {
+        let mut state = self.lock()?;
+        self.refresh_locked(&mut state, files)?;
+        Ok(Self::score(state.as_ref(), query))
+    }
+
+    /// Like [`MemoryIndex::search`] over the files indexed so far, without checking them for
+    /// changes; for callers that [`MemoryIndex::mark_stale`] on edits and check
+    /// [`MemoryIndex::is_stale`] first. Blocking.
+    pub fn search_current(&self, query: &str) -> Result<Vec<MemoryHit>> {
+        let mut state = self.lock()?;
+        let index = state.get_or_insert_with(|| self.load());
+        Ok(Self::score(Some(index), query))
+    }
+
+    fn score(index: Option<&IndexFile>, query: &str) -> Vec<MemoryHit> {
+        let query_terms: BTreeSet<String> = memory_terms(query).into_iter().collect();
+        let Some(index) = index else {
+            return Vec::new();
+        };
+        if query_terms.is_empty() {
+            return Vec::new();
+        }
+
+        let (documents, total_length) = index
+            .files
+            .values()
+            .flat_map(|file| file.lines.iter())
+            .fold((0usize, 0usize), |(count, length), line| {
+                (count + 1, length + line.length)
+            });
+        if documents == 0 {
+            return Vec::new();
+        }
+        let total = documents as f32;
+        let average_length = (total_length as f32 / total).max(1.0);
+
+        let mut scores: HashMap<(&str, usize), f32> = HashMap::new();
+        for term in &query_terms {
+            let Some(postings) = index.postings.get(term) else {
+                continue;
+            };
+            let frequency = postings.len() as f32;
+            let idf = ((total - frequency + 0.5) / (frequency + 0.5) + 1.0).ln();
+            for posting in postings {
+                let Some(line) = index.line(&posting.path, posting.line) else {
+                    continue;
+                };
+                let count = posting.count as f32;
+                let norm = 1.0 - BM25_B + BM25_B * line.length as f32 / average_length;
+                *scores
+                    .entry((posting.path.as_str(), posting.line))
+                    .or_default() += idf * count * (BM25_K1 + 1.0) / (count + BM25_K1 * norm);
+            }
+        }
+
+        let mut scored: Vec<((&str, usize), f32)> = scores.into_iter().collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scored
+            .into_iter()
+            .filter_map(|((path, line), score)| {
+                let indexed = index.line(path, line)?;
+                Some(MemoryHit {
+                    path: path.to_string(),
+                    line,
+                    snippet: indexed.text.clone(),
+                    score,
+                })
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Option<IndexFile>>> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("memory index lock poisoned"))
+    }
+
+    fn load(&self) -> IndexFile {
+        std::fs::read(self.index_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<IndexFile>(&bytes).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .unwrap_or_else(IndexFile::empty)
+    }
+
+    fn refresh_locked(&self, state: &mut Option<IndexFile>, files: &[PathBuf]) -> Result<usize> {
+        self.stale.store(false, Ordering::SeqCst);
+        let index = state.get_or_insert_with(|| self.load());
+        let mut changed = 0;
+        let mut seen = BTreeSet::new();
+        for path in files {
+            let key = path.to_string_lossy().to_string();
+            let Some((modified_ms, size)) = file_stamp(path) else {
+                continue;
+            };
+            seen.insert(key.clone());
+            let fresh = index
+                .files
+                .get(&key)
+                .is_some_and(|file| file.modified_ms == modified_ms && file.size == size);
+            if fresh {
+                continue;
+            }
+            let content = std::fs::read_to_string(path).unwrap_or_default();
+            index.insert_file(&key, modified_ms, size, &content);
+            changed += 1;
+        }
+        let stale: Vec<String> = index
+            .files
+            .keys()
+            .filter(|key| !seen.contains(*key))
+            .cloned()
+            .collect();
+        for key in stale {
+            index.remove_file(&key);
+            changed += 1;
+        }
+
+        if changed > 0 {
+            self.persist(index)?;
+            tracing::debug!(
+                files = index.files.len(),
+                terms = index.postings.len(),
+                changed,
+                "memory index updated"
+            );
+        }
+        Ok(changed)
+    }
+
+    fn persist(&self, index: &IndexFile) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        let path = self.index_path();
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec(index)?)?;
+        std::fs::rename(&temp, &path)?;
+        Ok(())
+    }
+}
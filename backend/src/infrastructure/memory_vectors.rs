@@ -0,0 +1,332 @@
+//! Vector index over memory lines for semantic and hybrid search.
+//!
+//! Every non-blank line of `MEMORY.md` and the daily logs gets a vector from the configured
+//! [`EmbeddingPort`]. Vectors are persisted to `<dir>/memory-vectors.json` together with the
+//! embedder id, so switching models rebuilds the index. Changed files are detected by mtime and
+//! size like the keyword index; lines whose text did not change keep their vector, so appending
+//! to a daily log only embeds the new line.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::domain::ports::{EmbeddingPort, MemoryHit};
+use crate::infrastructure::memory_index::file_stamp;
+
+const INDEX_VERSION: u32 = 1;
+const INDEX_FILE: &str = "memory-vectors.json";
+/// Texts sent per embedding request.
+const EMBED_BATCH: usize = 64;
+
+pub const DEFAULT_VECTOR_WEIGHT: f32 = 0.5;
+pub const DEFAULT_MIN_SIMILARITY: f32 = 0.3;
+
+/// How keyword and vector scores are combined in hybrid memory search.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HybridSearchOptions {
+    /// Weight of vector similarity (0 to 1); normalised BM25 gets the rest.
+    pub vector_weight: f32,
+    /// Lines matched only by their vector must be at least this similar to the query.
+    pub min_similarity: f32,
+}
+
+impl Default for HybridSearchOptions {
+    fn default() -> Self {
+        Self {
+            vector_weight: DEFAULT_VECTOR_WEIGHT,
+            min_similarity: DEFAULT_MIN_SIMILARITY,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VectorFile {
+    version: u32,
+    embedder: String,
+    files: BTreeMap<String, VectorEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VectorEntry {
+    modified_ms: u128,
+    size: u64,
+    lines: Vec<VectorLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VectorLine {
+    line: usize,
+    text: String,
+    vector: Vec<f32>,
+}
+
+pub struct MemoryVectorIndex {
+    dir: PathBuf,
+    embedder: Arc<dyn EmbeddingPort>,
+    state: Mutex<Option<VectorFile>>,
+}
+
+impl std::fmt::Debug for MemoryVectorIndex {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("MemoryVectorIndex")
+            .field("dir", &self.dir)
+            .field("embedder", &self.embedder.id())
+            .finish()
+    }
+}
+
+impl MemoryVectorIndex {
+    pub fn new(dir: impl Into<PathBuf>, embedder: Arc<dyn EmbeddingPort>) -> Self {
+        Self {
+            dir: dir.into(),
+            embedder,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// An empty index in `dir` using the same embedder.
+    pub fn relocated(&self, dir: impl Into<PathBuf>) -> Self {
+        Self::new(dir, self.embedder.clone())
+    }
+
+    pub fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE)
+    }
+
+    /// Embeds the changed lines of `files` and drops files that are no longer listed.
+    /// Returns how many files were re-embedded or removed.
+    pub async fn refresh(&self, files: &[PathBuf]) -> Result<usize> {
+        let mut state = self.state.lock().await;
+        let index = state.get_or_insert_with(|| self.load());
+
+        let mut stale = Vec::new();
+        let mut seen = BTreeSet::new();
+        for path in files {
+            let key = path.to_string_lossy().to_string();
+            let Some(stamp) = file_stamp(path) else {
+                continue;
+            };
+            seen.insert(key.clone());
+            let fresh = index
+                .files
+                .get(&key)
+                .is_some_and(|entry| (entry.modified_ms, entry.size) == stamp);
+            if !fresh {
+                stale.push((key, path.as_path(), stamp));
+            }
+        }
+        let removed: Vec<String> = index
+            .files
+            .keys()
+            .filter(|key| !seen.contains(*key))
+            .cloned()
+            .collect();
+        let changed = stale.len() + removed.len();
+        for key in removed {
+            index.files.remove(&key);
+        }
+
+        for (key, path, (modified_ms, size)) in stale {
+            let lines = self.embed_file(path, index.files.get(&key)).await?;
+            index.files.insert(
+                key,
+                VectorEntry {
+                    modified_ms,
+                    size,
+                    lines,
+                },
+            );
+        }
+
+        if changed > 0 {
+            self.persist(index)?;
+            tracing::debug!(
+                files = index.files.len(),
+                changed,
+                embedder = %index.embedder,
+                "memory vectors updated"
+            );
+        }
+        Ok(changed)
+    }
+
+    /// Lines of `files` by cosine similarity to `query`, most similar first; `score` is the
+    /// similarity.
+    pub async fn search(&self, files: &[PathBuf], query: &str) -> Result<Vec<MemoryHit>> {
+        self.refresh(files).await?;
+        let query_vector = self
+            .embedder
+            .embed(&[query.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+
+        let state = self.state.lock().await;
+        let Some(index) = state.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let query_vector = query_vector.as_slice();
+        let mut hits: Vec<MemoryHit> = index
+            .files
+            .iter()
+            .flat_map(|(path, entry)| {
+                entry.lines.iter().map(move |line| MemoryHit {
+                    path: path.clone(),
+                    line: line.line,
+                    snippet: line.text.clone(),
+                    score: cosine(query_vector, &line.vector),
+                })
+            })
+            .filter(|hit| hit.score > 0.0)
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        Ok(hits)
+    }
+
+    fn load(&self) -> VectorFile {
+        let embedder = self.embedder.id();
+        std::fs::read(self.index_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<VectorFile>(&bytes).ok())
+            .filter(|index| index.version == INDEX_VERSION && index.embedder == embedder)
+            .unwrap_or_else(|| VectorFile {
+                version: INDEX_VERSION,
+                embedder,
+                files: BTreeMap::new(),
+            })
+    }
+
+    /// Vectors for every non-blank line of `path`, reusing those of unchanged lines.
+    async fn embed_file(
+        &self,
+        path: &Path,
+        previous: Option<&VectorEntry>,
+    ) -> Result<Vec<VectorLine>> {
+        let content = tokio::fs::read_to_string(path).await.unwrap_or_default();
+        let known: HashMap<&str, &[f32]> = previous
+            .map(|entry| {
+                entry
+                    .lines
+                    .iter()
+                    .map(|line| (line.text.as_str(), line.vector.as_slice()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let lines: Vec<(usize, &str)> = content
+            .lines()
+            .enumerate()
+            .filter(|(_, text)| text.chars().any(char::is_alphanumeric))
+            .map(|(index, text)| (index + 1, text))
+            .collect();
+        let missing: Vec<String> = lines
+            .iter()
+            .filter(|(_, text)| !known.contains_key(text))
+            .map(|(_, text)| text.to_string())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut embedded: HashMap<String, Vec<f32>> = HashMap::new();
+        for batch in missing.chunks(EMBED_BATCH) {
+            let vectors = self
+                .embedder
+                .embed(batch)
+                .await
+                .with_context(|| format!("failed to embed {}", path.display()))?;
+            embedded.extend(batch.iter().cloned().zip(vectors));
+        }
+
+        Ok(lines
+            .into_iter()
+            .filter_map(|(line, text)| {
+                let vector = match known.get(text) {
+                    Some(vector) => vector.to_vec(),
+                    None => embedded.get(text)?.clone(),
+                };
+                Some(VectorLine {
+                    line,
+                    text: text.to_string(),
+                    vector,
+                })
+            })
+            .collect())
+    }
+
+    fn persist(&self, index: &VectorFile) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        let path = self.index_path();
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec(index)?)?;
+        std::fs::rename(&temp, &path)?;
+        Ok(())
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot = a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+    let norm =
+        a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|y| y * y).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        dot / norm
+    } else {
+        0.0
+    }
+}
+
+/// Merges BM25 hits and vector hits for the same lines: BM25 is normalised by its best score
+/// and blended with similarity by `options.vector_weight`. Lines without a keyword match are
+/// kept only when they reach `options.min_similarity`.
+pub fn blend_hits(
+    keyword: Vec<MemoryHit>,
+    vector: Vec<MemoryHit>,
+    options: HybridSearchOptions,
+) -> Vec<MemoryHit> {
+    let weight = options.vector_weight.clamp(0.0, 1.0);
+    let max_bm25 = keyword.iter().map(|hit| hit.score).fold(0f32, f32::max);
+    let mut merged: BTreeMap<(String, usize), (MemoryHit, f32, f32)> = BTreeMap::new();
+    for hit in keyword {
+        let bm25 = if max_bm25 > 0.0 {
+            hit.score / max_bm25
+        } else {
+            0.0
+        };
+        merged.insert((hit.path.clone(), hit.line), (hit, bm25, 0.0));
+    }
+    for hit in vector {
+        let similarity = hit.score.max(0.0);
+        merged
+            .entry((hit.path.clone(), hit.line))
+            .and_modify(|(_, _, existing)| *existing = similarity)
+            .or_insert((hit, 0.0, similarity));
+    }
+
+    let mut hits: Vec<MemoryHit> = merged
+        .into_values()
+        .filter(|(_, bm25, similarity)| *bm25 > 0.0 || *similarity >= options.min_similarity)
+        .map(|(mut hit, bm25, similarity)| {
+            hit.score = (1.0 - weight) * bm25 + weight * similarity;
+            hit
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    hits
+}
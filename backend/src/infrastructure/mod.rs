@@ -1,9 +1,18 @@
 pub mod config;
 pub mod channels;
+pub mod checkpoints;
+pub mod embedding;
+pub mod file_watch;
 pub mod logging;
+pub mod mcp;
 pub mod memory;
+pub mod memory_facts;
+pub mod memory_index;
+pub mod memory_vectors;
 pub mod personality;
 pub mod runtime_assets;
 pub mod session_store;
 pub mod model;
 pub mod tooling;
+pub mod workspace_archive;
+pub mod workspace_index;
@@ -1,13 +1,26 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tokio::fs;
 
+use crate::infrastructure::file_watch::WatchedCache;
+
 const PERSONALITY_ORDER: [&str; 4] = ["SOUL.md", "IDENTITY.md", "USER.md", "AGENTS.md"];
 
 #[derive(Clone, Debug)]
 pub struct PersonalityLoader {
     dir: PathBuf,
+    cache: Arc<Mutex<SectionCache>>,
+}
+
+/// Sections read since the last invalidation; only reused while a file watcher runs.
+#[derive(Debug, Default)]
+struct SectionCache {
+    watched: bool,
+    /// Bumped on every invalidation, so a read that raced with an edit is not cached.
+    generation: u64,
+    sections: Option<Vec<(String, String)>>,
 }
 
 #[async_trait]
@@ -17,7 +30,10 @@ pub trait PersonalitySource: Send + Sync {
 
 impl PersonalityLoader {
     pub fn new(dir: impl Into<PathBuf>) -> Self {
-        Self { dir: dir.into() }
+        Self {
+            dir: dir.into(),
+            cache: Arc::new(Mutex::new(SectionCache::default())),
+        }
     }
 
     pub fn dir(&self) -> &Path {
@@ -25,6 +41,22 @@ impl PersonalityLoader {
     }
 
     pub async fn load_sections(&self) -> Result<Vec<(String, String)>> {
+        let generation = {
+            let cache = self.lock_cache();
+            if let (true, Some(sections)) = (cache.watched, &cache.sections) {
+                return Ok(sections.clone());
+            }
+            cache.generation
+        };
+        let sections = self.read_sections().await?;
+        let mut cache = self.lock_cache();
+        if cache.watched && cache.generation == generation {
+            cache.sections = Some(sections.clone());
+        }
+        Ok(sections)
+    }
+
+    async fn read_sections(&self) -> Result<Vec<(String, String)>> {
         let mut sections = Vec::new();
         for filename in PERSONALITY_ORDER {
             let path = self.dir.join(filename);
@@ -44,6 +76,32 @@ impl PersonalityLoader {
         }
         Ok(out)
     }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, SectionCache> {
+        self.cache.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl WatchedCache for PersonalityLoader {
+    fn affected_by(&self, path: &Path) -> bool {
+        path.parent() == Some(self.dir.as_path())
+            && path
+                .file_name()
+                .is_some_and(|name| PERSONALITY_ORDER.iter().any(|file| name == *file))
+    }
+
+    fn invalidate(&self, _paths: &[PathBuf]) {
+        let mut cache = self.lock_cache();
+        cache.generation += 1;
+        cache.sections = None;
+    }
+
+    fn set_watched(&self, watched: bool) {
+        let mut cache = self.lock_cache();
+        cache.watched = watched;
+        cache.generation += 1;
+        cache.sections = None;
+    }
 }
 
 #[async_trait]
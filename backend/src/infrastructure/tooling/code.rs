@@ -0,0 +1,614 @@
+//! Code structure tools backed by tree-sitter: `code_outline` for one file and
+//! `find_symbol` for definitions across the working directory.
+//!
+//! `find_symbol` keeps a per-root symbol index on disk (`<index_dir>/<root hash>.json`).
+//! Each search re-walks the tree, re-parses only files whose mtime or size changed and
+//! drops files that disappeared, so repeated lookups stay cheap.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tree_sitter::{Node, Parser};
+
+use super::walk::{display_path, is_searchable_size};
+use super::{resolve_existing_path, Tool, ToolContext, ToolRegistry, WalkFilters};
+use crate::domain::types::ToolExecution;
+use crate::infrastructure::checkpoints::content_hash;
+
+const INDEX_VERSION: u32 = 1;
+const DEFAULT_FIND_LIMIT: usize = 50;
+const MAX_FIND_LIMIT: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeLanguage {
+    Rust,
+    TypeScript,
+    Tsx,
+    Python,
+}
+
+impl CodeLanguage {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Self::Tsx),
+            "py" | "pyi" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::TypeScript => "typescript",
+            Self::Tsx => "tsx",
+            Self::Python => "python",
+        }
+    }
+
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+        }
+    }
+
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Rust => "::",
+            _ => ".",
+        }
+    }
+}
+
+/// A definition with its 1-based inclusive line range. `container` is the enclosing
+/// impl/trait/class/module name, if any.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeSymbol {
+    pub name: String,
+    pub kind: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub depth: usize,
+}
+
+impl CodeSymbol {
+    fn qualified_name(&self, language: CodeLanguage) -> String {
+        match &self.container {
+            Some(container) => format!("{container}{}{}", language.separator(), self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Parses `source` and returns its definitions in source order.
+pub fn extract_symbols(language: CodeLanguage, source: &str) -> Result<Vec<CodeSymbol>> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language.grammar())
+        .with_context(|| format!("failed to load {} grammar", language.name()))?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| anyhow!("failed to parse {} source", language.name()))?;
+    let mut symbols = Vec::new();
+    collect(
+        tree.root_node(),
+        source.as_bytes(),
+        language,
+        None,
+        false,
+        0,
+        &mut symbols,
+    );
+    Ok(symbols)
+}
+
+fn collect(
+    node: Node,
+    source: &[u8],
+    language: CodeLanguage,
+    container: Option<&str>,
+    in_type: bool,
+    depth: usize,
+    out: &mut Vec<CodeSymbol>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let Some(found) = classify(child, source, language, in_type) else {
+            collect(child, source, language, container, in_type, depth, out);
+            continue;
+        };
+        out.push(CodeSymbol {
+            name: found.name,
+            kind: found.kind.to_string(),
+            start_line: child.start_position().row + 1,
+            end_line: child.end_position().row + 1,
+            container: container.map(str::to_string),
+            depth,
+        });
+        if let Some(scope) = found.scope {
+            let in_type = found.kind != "module";
+            collect(
+                child,
+                source,
+                language,
+                Some(&scope),
+                in_type,
+                depth + 1,
+                out,
+            );
+        }
+    }
+}
+
+struct Classified {
+    name: String,
+    kind: &'static str,
+    /// Name children are reported under; `None` for definitions whose bodies are not
+    /// descended into (functions, constants, ...).
+    scope: Option<String>,
+}
+
+fn classify(
+    node: Node,
+    source: &[u8],
+    language: CodeLanguage,
+    in_type: bool,
+) -> Option<Classified> {
+    let text = |node: Node| node.utf8_text(source).ok().map(str::to_string);
+    let field = |name: &str| node.child_by_field_name(name).and_then(text);
+    let function_kind = if in_type { "method" } else { "function" };
+    let leaf = |kind: &'static str| {
+        field("name").map(|name| Classified {
+            name,
+            kind,
+            scope: None,
+        })
+    };
+    let scoped = |kind: &'static str| {
+        field("name").map(|name| Classified {
+            scope: Some(name.clone()),
+            name,
+            kind,
+        })
+    };
+
+    match (language, node.kind()) {
+        (CodeLanguage::Rust, "function_item" | "function_signature_item") => leaf(function_kind),
+        (CodeLanguage::Rust, "struct_item") => leaf("struct"),
+        (CodeLanguage::Rust, "enum_item") => leaf("enum"),
+        (CodeLanguage::Rust, "union_item") => leaf("union"),
+        (CodeLanguage::Rust, "type_item") => leaf("type"),
+        (CodeLanguage::Rust, "const_item") => leaf("const"),
+        (CodeLanguage::Rust, "static_item") => leaf("static"),
+        (CodeLanguage::Rust, "macro_definition") => leaf("macro"),
+        (CodeLanguage::Rust, "trait_item") => scoped("trait"),
+        (CodeLanguage::Rust, "mod_item") => scoped("module"),
+        (CodeLanguage::Rust, "impl_item") => {
+            let target = field("type")?;
+            let name = match field("trait") {
+                Some(trait_name) => format!("{trait_name} for {target}"),
+                None => target.clone(),
+            };
+            Some(Classified {
+                name,
+                kind: "impl",
+                scope: Some(target),
+            })
+        }
+        (
+            CodeLanguage::TypeScript | CodeLanguage::Tsx,
+            "function_declaration" | "generator_function_declaration",
+        ) => leaf("function"),
+        (
+            CodeLanguage::TypeScript | CodeLanguage::Tsx,
+            "method_definition" | "method_signature" | "abstract_method_signature",
+        ) => leaf("method"),
+        (
+            CodeLanguage::TypeScript | CodeLanguage::Tsx,
+            "class_declaration" | "abstract_class_declaration",
+        ) => scoped("class"),
+        (CodeLanguage::TypeScript | CodeLanguage::Tsx, "interface_declaration") => {
+            scoped("interface")
+        }
+        (CodeLanguage::TypeScript | CodeLanguage::Tsx, "type_alias_declaration") => leaf("type"),
+        (CodeLanguage::TypeScript | CodeLanguage::Tsx, "enum_declaration") => leaf("enum"),
+        (CodeLanguage::TypeScript | CodeLanguage::Tsx, "internal_module" | "module") => {
+            scoped("module")
+        }
+        (CodeLanguage::TypeScript | CodeLanguage::Tsx, "variable_declarator") => {
+            let value = node.child_by_field_name("value")?;
+            matches!(
+                value.kind(),
+                "arrow_function" | "function_expression" | "function" | "generator_function"
+            )
+            .then(|| leaf("function"))
+            .flatten()
+        }
+        (CodeLanguage::Python, "function_definition") => leaf(function_kind),
+        (CodeLanguage::Python, "class_definition") => scoped("class"),
+        _ => None,
+    }
+}
+
+fn render_outline(display: &str, language: CodeLanguage, symbols: &[CodeSymbol]) -> String {
+    let mut lines = vec![format!(
+        "{display} ({}, {} symbols)",
+        language.name(),
+        symbols.len()
+    )];
+    for symbol in symbols {
+        lines.push(format!(
+            "{}{}-{} {} {}",
+            "  ".repeat(symbol.depth + 1),
+            symbol.start_line,
+            symbol.end_line,
+            symbol.kind,
+            symbol.name
+        ));
+    }
+    lines.join("\n")
+}
+
+pub struct CodeOutlineTool;
+
+#[async_trait]
+impl Tool for CodeOutlineTool {
+    fn name(&self) -> &'static str {
+        "code_outline"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the functions, types, classes and impls defined in a Rust, TypeScript/JavaScript or Python file, with line ranges"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"}
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let input_path = args
+            .get("path")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| anyhow!("code_outline.path is required"))?;
+        let path = resolve_existing_path(&context.root_dir, input_path)?;
+        let language = CodeLanguage::from_path(&path)
+            .ok_or_else(|| anyhow!("code_outline does not support this file type: {input_path}"))?;
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("failed to read {input_path}"))?;
+        let symbols =
+            tokio::task::spawn_blocking(move || extract_symbols(language, &source)).await??;
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output: render_outline(&display_path(&context.root_dir, &path), language, &symbols),
+            is_error: false,
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    root: String,
+    files: BTreeMap<String, IndexedFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedFile {
+    modified_ms: u128,
+    size: u64,
+    symbols: Vec<CodeSymbol>,
+}
+
+/// Outcome of bringing an index up to date.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndexRefresh {
+    pub files: usize,
+    pub parsed: usize,
+    pub removed: usize,
+}
+
+/// One symbol found by [`SymbolIndex::search`], with its path relative to the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolMatch {
+    pub path: String,
+    pub qualified_name: String,
+    pub symbol: CodeSymbol,
+}
+
+/// On-disk symbol indexes, one JSON file per working directory under `dir`.
+pub struct SymbolIndex {
+    dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl SymbolIndex {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn index_path(&self, root: &Path) -> PathBuf {
+        let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let hash = content_hash(root.to_string_lossy().as_bytes());
+        self.dir.join(format!("{}.json", &hash[..16]))
+    }
+
+    /// Re-parses changed files under `root` and persists the index. Blocking; callers on
+    /// the runtime use `spawn_blocking`.
+    pub fn refresh(&self, root: &Path) -> Result<IndexRefresh> {
+        self.refreshed(root).map(|(_, refresh)| refresh)
+    }
+
+    /// Definitions under `root` whose name (or `Container::name`) contains `query`,
+    /// case-insensitively. Exact matches sort first, then prefixes, then by path.
+    pub fn search(
+        &self,
+        root: &Path,
+        query: &str,
+        kind: Option<&str>,
+        within: Option<&str>,
+    ) -> Result<Vec<SymbolMatch>> {
+        let (index, _) = self.refreshed(root)?;
+        let needle = query.to_lowercase();
+        let mut matches: Vec<(u8, SymbolMatch)> = Vec::new();
+        for (path, file) in index.files {
+            if within.is_some_and(|prefix| !Path::new(&path).starts_with(prefix)) {
+                continue;
+            }
+            let Some(language) = CodeLanguage::from_path(Path::new(&path)) else {
+                continue;
+            };
+            for symbol in file.symbols {
+                if kind.is_some_and(|kind| symbol.kind != kind) {
+                    continue;
+                }
+                let qualified_name = symbol.qualified_name(language);
+                let name = symbol.name.to_lowercase();
+                let qualified = qualified_name.to_lowercase();
+                let rank = if symbol.name == query || qualified_name == query {
+                    0
+                } else if name == needle || qualified == needle {
+                    1
+                } else if name.starts_with(&needle) {
+                    2
+                } else if name.contains(&needle) || qualified.contains(&needle) {
+                    3
+                } else {
+                    continue;
+                };
+                matches.push((
+                    rank,
+                    SymbolMatch {
+                        path: path.clone(),
+                        qualified_name,
+                        symbol,
+                    },
+                ));
+            }
+        }
+        matches.sort_by(|(a_rank, a), (b_rank, b)| {
+            a_rank
+                .cmp(b_rank)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.symbol.start_line.cmp(&b.symbol.start_line))
+        });
+        Ok(matches.into_iter().map(|(_, found)| found).collect())
+    }
+
+    fn refreshed(&self, root: &Path) -> Result<(IndexFile, IndexRefresh)> {
+        let _guard = self
+            .lock
+            .lock()
+            .map_err(|_| anyhow!("symbol index lock poisoned"))?;
+        let index_path = self.index_path(root);
+        let root_key = root.to_string_lossy().to_string();
+        let mut index = std::fs::read(&index_path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<IndexFile>(&bytes).ok())
+            .filter(|index| index.version == INDEX_VERSION && index.root == root_key)
+            .unwrap_or_else(|| IndexFile {
+                version: INDEX_VERSION,
+                root: root_key,
+                files: BTreeMap::new(),
+            });
+
+        let mut refresh = IndexRefresh::default();
+        let mut seen = BTreeSet::new();
+        for entry in WalkFilters::default().walk(root) {
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) || !is_searchable_size(&entry)
+            {
+                continue;
+            }
+            let Some(language) = CodeLanguage::from_path(entry.path()) else {
+                continue;
+            };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let modified_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |elapsed| elapsed.as_millis());
+            let relative = display_path(root, entry.path());
+            seen.insert(relative.clone());
+
+            let fresh = index
+                .files
+                .get(&relative)
+                .is_some_and(|file| file.modified_ms == modified_ms && file.size == metadata.len());
+            if fresh {
+                continue;
+            }
+            let symbols = match std::fs::read_to_string(entry.path())
+                .map_err(anyhow::Error::from)
+                .and_then(|source| extract_symbols(language, &source))
+            {
+                Ok(symbols) => symbols,
+                Err(error) => {
+                    tracing::debug!(path = %relative, error = %error, "symbol index skipped file");
+                    Vec::new()
+                }
+            };
+            index.files.insert(
+                relative,
+                IndexedFile {
+                    modified_ms,
+                    size: metadata.len(),
+                    symbols,
+                },
+            );
+            refresh.parsed += 1;
+        }
+
+        let before = index.files.len();
+        index.files.retain(|path, _| seen.contains(path));
+        refresh.removed = before - index.files.len();
+        refresh.files = index.files.len();
+
+        if refresh.parsed > 0 || refresh.removed > 0 {
+            std::fs::create_dir_all(&self.dir)
+                .with_context(|| format!("failed to create {}", self.dir.display()))?;
+            let temp = index_path.with_extension("json.tmp");
+            std::fs::write(&temp, serde_json::to_vec(&index)?)?;
+            std::fs::rename(&temp, &index_path)?;
+            tracing::debug!(
+                root = %root.display(),
+                files = refresh.files,
+                parsed = refresh.parsed,
+                removed = refresh.removed,
+                "symbol index updated"
+            );
+        }
+        Ok((index, refresh))
+    }
+}
+
+pub struct FindSymbolTool {
+    index: Arc<SymbolIndex>,
+}
+
+impl FindSymbolTool {
+    pub fn new(index: Arc<SymbolIndex>) -> Self {
+        Self { index }
+    }
+}
+
+#[async_trait]
+impl Tool for FindSymbolTool {
+    fn name(&self) -> &'static str {
+        "find_symbol"
+    }
+
+    fn description(&self) -> &'static str {
+        "Find where functions, types, classes or methods are defined in the working directory (Rust, TypeScript/JavaScript, Python)"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "description": "Symbol name or substring; `Type::method` / `Class.method` also match"},
+                "kind": {"type": "string", "description": "Only this kind (function, method, struct, enum, trait, impl, class, interface, type, module, ...)"},
+                "path": {"type": "string", "description": "Only search under this directory"},
+                "limit": {"type": "integer", "minimum": 1, "maximum": MAX_FIND_LIMIT}
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let query = args
+            .get("name")
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| anyhow!("find_symbol.name is required"))?
+            .to_string();
+        let kind = args
+            .get("kind")
+            .and_then(|value| value.as_str())
+            .map(str::to_string);
+        let limit = args
+            .get("limit")
+            .and_then(|value| value.as_u64())
+            .map_or(DEFAULT_FIND_LIMIT, |limit| limit as usize)
+            .clamp(1, MAX_FIND_LIMIT);
+        let within = match args.get("path").and_then(|value| value.as_str()) {
+            Some(path) => {
+                let resolved = resolve_existing_path(&context.root_dir, path)?;
+                let root = std::fs::canonicalize(&context.root_dir)?;
+                let relative = display_path(&root, &std::fs::canonicalize(resolved)?);
+                Some(relative).filter(|relative| !relative.is_empty())
+            }
+            None => None,
+        };
+
+        let index = self.index.clone();
+        let root = context.root_dir.clone();
+        let query_for_search = query.clone();
+        let matches = tokio::task::spawn_blocking(move || {
+            index.search(&root, &query_for_search, kind.as_deref(), within.as_deref())
+        })
+        .await??;
+
+        let output = if matches.is_empty() {
+            format!("no symbols matching {query}")
+        } else {
+            let mut lines: Vec<String> = matches
+                .iter()
+                .take(limit)
+                .map(|found| {
+                    format!(
+                        "{}:{}-{} {} {}",
+                        found.path,
+                        found.symbol.start_line,
+                        found.symbol.end_line,
+                        found.symbol.kind,
+                        found.qualified_name
+                    )
+                })
+                .collect();
+            if matches.len() > limit {
+                lines.push(format!(
+                    "[showing {limit} of {} matches; narrow with kind or path]",
+                    matches.len()
+                ));
+            }
+            lines.join("\n")
+        };
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output,
+            is_error: false,
+        })
+    }
+}
+
+/// Registers `code_outline` and `find_symbol`, the latter indexing into `index_dir`.
+pub fn register_code_tools(registry: &mut ToolRegistry, index_dir: PathBuf) {
+    registry.register(CodeOutlineTool);
+    registry.register(FindSymbolTool::new(Arc::new(SymbolIndex::new(index_dir))));
+}
@@ -0,0 +1,486 @@
+//! Plain-text extraction for documents the `read` tool cannot show verbatim.
+//!
+//! The format is detected from magic bytes first (PDF, ZIP containers, OLE) and the file
+//! extension second. Extraction is pure Rust: `pdf-extract` for PDF, `calamine` for
+//! spreadsheets, `quick-xml` over the ZIP parts of DOCX and EPUB, `html2text` for HTML.
+
+use anyhow::{anyhow, Context, Result};
+use calamine::{Data, Reader};
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use super::is_binary;
+
+const HTML_WRAP_WIDTH: usize = 100;
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const OLE_MAGIC: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Pdf,
+    Docx,
+    /// XLSX, XLS or ODS workbooks.
+    Spreadsheet,
+    /// Delimited text rendered as a table.
+    Csv {
+        delimiter: u8,
+    },
+    Epub,
+    Html,
+    Text,
+}
+
+impl DocumentFormat {
+    /// `None` for binary content in a format we cannot extract.
+    pub fn detect(path: &Path, bytes: &[u8]) -> Option<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        if bytes.starts_with(b"%PDF-") {
+            return Some(Self::Pdf);
+        }
+        if bytes.starts_with(ZIP_MAGIC) {
+            return detect_zip(bytes);
+        }
+        if bytes.starts_with(OLE_MAGIC) {
+            return (extension == "xls").then_some(Self::Spreadsheet);
+        }
+        if is_binary(bytes) {
+            return None;
+        }
+        Some(match extension.as_str() {
+            "csv" => Self::Csv { delimiter: b',' },
+            "tsv" => Self::Csv { delimiter: b'\t' },
+            "html" | "htm" | "xhtml" => Self::Html,
+            _ => Self::Text,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+            Self::Spreadsheet => "spreadsheet",
+            Self::Csv { .. } => "csv",
+            Self::Epub => "epub",
+            Self::Html => "html",
+            Self::Text => "text",
+        }
+    }
+}
+
+fn detect_zip(bytes: &[u8]) -> Option<DocumentFormat> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).ok()?;
+    if archive.by_name("word/document.xml").is_ok() {
+        return Some(DocumentFormat::Docx);
+    }
+    if archive.by_name("xl/workbook.xml").is_ok() {
+        return Some(DocumentFormat::Spreadsheet);
+    }
+    let mimetype = archive.by_name("mimetype").ok().and_then(|mut file| {
+        let mut text = String::new();
+        file.read_to_string(&mut text).ok().map(|_| text)
+    });
+    match mimetype.as_deref().map(str::trim) {
+        Some("application/epub+zip") => Some(DocumentFormat::Epub),
+        Some("application/vnd.oasis.opendocument.spreadsheet") => Some(DocumentFormat::Spreadsheet),
+        _ if archive.by_name("META-INF/container.xml").is_ok() => Some(DocumentFormat::Epub),
+        _ => None,
+    }
+}
+
+/// Which part of a document to extract.
+#[derive(Clone, Debug, Default)]
+pub struct DocumentSelection {
+    /// Page ranges like `2` or `1-3,7` (PDF pages, EPUB chapters).
+    pub pages: Option<String>,
+    /// Sheet name or 1-based index for workbooks.
+    pub sheet: Option<String>,
+    /// Return HTML and CSV source verbatim instead of converting it.
+    pub raw: bool,
+}
+
+/// Extracted text of `bytes`, read from `path`. Blocking.
+pub fn extract_document(
+    path: &Path,
+    bytes: &[u8],
+    selection: &DocumentSelection,
+) -> Result<String> {
+    let mut format = DocumentFormat::detect(path, bytes).ok_or_else(|| {
+        anyhow!(
+            "read: {} is a binary file in an unsupported format (supported: pdf, docx, xlsx, xls, ods, csv, epub, html, text)",
+            path.display()
+        )
+    })?;
+    if selection.raw && matches!(format, DocumentFormat::Html | DocumentFormat::Csv { .. }) {
+        format = DocumentFormat::Text;
+    }
+    if selection.pages.is_some() && !matches!(format, DocumentFormat::Pdf | DocumentFormat::Epub) {
+        return Err(anyhow!("read.pages only applies to pdf and epub files"));
+    }
+    if selection.sheet.is_some() && format != DocumentFormat::Spreadsheet {
+        return Err(anyhow!("read.sheet only applies to spreadsheet files"));
+    }
+    let text = match format {
+        DocumentFormat::Pdf => extract_pdf(bytes, selection.pages.as_deref()),
+        DocumentFormat::Docx => extract_docx(bytes),
+        DocumentFormat::Spreadsheet => extract_spreadsheet(bytes, selection.sheet.as_deref()),
+        DocumentFormat::Csv { delimiter } => extract_csv(bytes, delimiter),
+        DocumentFormat::Epub => extract_epub(bytes, selection.pages.as_deref()),
+        DocumentFormat::Html => Ok(html_to_text(bytes)),
+        DocumentFormat::Text => Ok(String::from_utf8_lossy(bytes).into_owned()),
+    };
+    text.with_context(|| {
+        format!(
+            "failed to extract {} text from {}",
+            format.name(),
+            path.display()
+        )
+    })
+}
+
+/// 1-based page numbers selected by `spec`, in order and without duplicates.
+pub fn parse_page_ranges(spec: &str, total: usize) -> Result<Vec<usize>> {
+    let mut pages = Vec::new();
+    for part in spec
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => (part, part),
+        };
+        let parse = |value: &str| {
+            value
+                .parse::<usize>()
+                .ok()
+                .filter(|page| *page >= 1)
+                .ok_or_else(|| anyhow!("read.pages: invalid page range: {part}"))
+        };
+        let start = parse(start)?;
+        let end = if end.is_empty() { total } else { parse(end)? };
+        if start > end || end > total {
+            return Err(anyhow!(
+                "read.pages: {part} is out of range (document has {total} pages)"
+            ));
+        }
+        for page in start..=end {
+            if !pages.contains(&page) {
+                pages.push(page);
+            }
+        }
+    }
+    if pages.is_empty() {
+        return Err(anyhow!("read.pages: no pages selected"));
+    }
+    Ok(pages)
+}
+
+fn select_pages(pages: Vec<String>, spec: Option<&str>, label: &str) -> Result<String> {
+    let total = pages.len();
+    let selected = match spec {
+        Some(spec) => parse_page_ranges(spec, total)?,
+        None => (1..=total).collect(),
+    };
+    Ok(selected
+        .into_iter()
+        .map(|page| {
+            format!(
+                "--- {label} {page} of {total} ---\n{}",
+                pages[page - 1].trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n"))
+}
+
+fn extract_pdf(bytes: &[u8], pages: Option<&str>) -> Result<String> {
+    // pdf-extract panics on some malformed inputs; surface those as errors.
+    let extracted = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(bytes))
+        .map_err(|_| anyhow!("malformed pdf"))?
+        .map_err(|error| anyhow!("{error}"))?;
+    if extracted.is_empty() {
+        return Err(anyhow!("pdf has no pages"));
+    }
+    select_pages(extracted, pages, "page")
+}
+
+fn zip_part(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Vec<u8>> {
+    let mut file = archive
+        .by_name(name)
+        .with_context(|| format!("missing {name}"))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn attribute(element: &BytesStart<'_>, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.as_ref() == name.as_bytes())
+        .and_then(|attribute| {
+            let value = String::from_utf8_lossy(&attribute.value).into_owned();
+            quick_xml::escape::unescape(&value)
+                .ok()
+                .map(|value| value.into_owned())
+        })
+}
+
+/// Paragraphs of `word/document.xml`; headings get `#` prefixes and tables become
+/// markdown rows.
+fn extract_docx(bytes: &[u8]) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let xml = zip_part(&mut archive, "word/document.xml")?;
+    let mut reader = quick_xml::Reader::from_reader(xml.as_slice());
+    let mut buffer = Vec::new();
+
+    let mut output = String::new();
+    let mut paragraph = String::new();
+    let mut heading = 0usize;
+    let mut in_text = false;
+    let mut cell: Option<String> = None;
+    let mut row: Option<Vec<String>> = None;
+    let mut table_rows = 0usize;
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"t" => in_text = true,
+                b"tab" => paragraph.push('\t'),
+                b"br" => paragraph.push(if cell.is_some() { ' ' } else { '\n' }),
+                b"pStyle" => {
+                    heading = attribute(&element, "w:val")
+                        .and_then(|style| {
+                            style
+                                .strip_prefix("Heading")
+                                .and_then(|level| level.parse().ok())
+                        })
+                        .unwrap_or(0);
+                }
+                b"tbl" => table_rows = 0,
+                b"tr" => row = Some(Vec::new()),
+                b"tc" => cell = Some(String::new()),
+                _ => {}
+            },
+            Event::Text(text) if in_text => paragraph.push_str(&text.unescape()?),
+            Event::End(element) => match element.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let text = std::mem::take(&mut paragraph);
+                    match cell.as_mut() {
+                        Some(cell) => {
+                            if !cell.is_empty() && !text.is_empty() {
+                                cell.push(' ');
+                            }
+                            cell.push_str(text.trim());
+                        }
+                        None => {
+                            if heading > 0 && !text.trim().is_empty() {
+                                output.push_str(&"#".repeat(heading.min(6)));
+                                output.push(' ');
+                            }
+                            output.push_str(&text);
+                            output.push('\n');
+                        }
+                    }
+                    heading = 0;
+                }
+                b"tc" => {
+                    if let (Some(row), Some(cell)) = (row.as_mut(), cell.take()) {
+                        row.push(cell);
+                    }
+                }
+                b"tr" => {
+                    if let Some(cells) = row.take() {
+                        output.push_str(&markdown_row(&cells));
+                        if table_rows == 0 {
+                            output.push_str(&markdown_separator(cells.len()));
+                        }
+                        table_rows += 1;
+                    }
+                }
+                b"tbl" => output.push('\n'),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+    Ok(output.trim_end().to_string())
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| cell.replace('|', "\\|").replace('\n', " "))
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn markdown_separator(columns: usize) -> String {
+    format!("|{}\n", " --- |".repeat(columns.max(1)))
+}
+
+/// Rows as a markdown table with the first row as header, padded to the widest row.
+pub(super) fn markdown_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut output = String::new();
+    for (index, row) in rows.iter().enumerate() {
+        let mut row = row.clone();
+        row.resize(columns, String::new());
+        output.push_str(&markdown_row(&row));
+        if index == 0 {
+            output.push_str(&markdown_separator(columns));
+        }
+    }
+    output.trim_end().to_string()
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn extract_spreadsheet(bytes: &[u8], sheet: Option<&str>) -> Result<String> {
+    let mut workbook = calamine::open_workbook_auto_from_rs(Cursor::new(bytes))?;
+    let names = workbook.sheet_names();
+    let selected = match sheet {
+        Some(sheet) => {
+            let name = names
+                .iter()
+                .find(|name| name.eq_ignore_ascii_case(sheet))
+                .cloned()
+                .or_else(|| {
+                    sheet
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| index.checked_sub(1))
+                        .and_then(|index| names.get(index).cloned())
+                })
+                .ok_or_else(|| {
+                    anyhow!(
+                        "read.sheet: no sheet {sheet} (sheets: {})",
+                        names.join(", ")
+                    )
+                })?;
+            vec![name]
+        }
+        None => names,
+    };
+
+    let mut sections = Vec::new();
+    for name in selected {
+        let range = workbook.worksheet_range(&name)?;
+        let rows: Vec<Vec<String>> = range
+            .rows()
+            .map(|row| row.iter().map(cell_text).collect())
+            .filter(|row: &Vec<String>| row.iter().any(|cell| !cell.is_empty()))
+            .collect();
+        let body = if rows.is_empty() {
+            "(empty sheet)".to_string()
+        } else {
+            markdown_table(&rows)
+        };
+        sections.push(format!("## Sheet: {name}\n{body}"));
+    }
+    Ok(sections.join("\n\n"))
+}
+
+fn extract_csv(bytes: &[u8], delimiter: u8) -> Result<String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes);
+    let mut rows = Vec::new();
+    for record in reader.byte_records() {
+        let record = record?;
+        rows.push(
+            record
+                .iter()
+                .map(|field| String::from_utf8_lossy(field).trim().to_string())
+                .collect(),
+        );
+    }
+    Ok(markdown_table(&rows))
+}
+
+fn html_to_text(bytes: &[u8]) -> String {
+    html2text::from_read(bytes, HTML_WRAP_WIDTH)
+        .unwrap_or_else(|_| String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Spine documents of an EPUB in reading order, each converted from XHTML.
+fn extract_epub(bytes: &[u8], chapters: Option<&str>) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let container = zip_part(&mut archive, "META-INF/container.xml")?;
+    let package_path = first_attribute(&container, b"rootfile", "full-path")?
+        .ok_or_else(|| anyhow!("container.xml has no rootfile"))?;
+    let package = zip_part(&mut archive, &package_path)?;
+    let base = package_path
+        .rsplit_once('/')
+        .map(|(directory, _)| format!("{directory}/"))
+        .unwrap_or_default();
+
+    let mut manifest = HashMap::new();
+    let mut spine = Vec::new();
+    let mut reader = quick_xml::Reader::from_reader(package.as_slice());
+    let mut buffer = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"item" => {
+                    if let (Some(id), Some(href)) =
+                        (attribute(&element, "id"), attribute(&element, "href"))
+                    {
+                        manifest.insert(id, href);
+                    }
+                }
+                b"itemref" => spine.extend(attribute(&element, "idref")),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+
+    let mut documents = Vec::new();
+    for id in spine {
+        let Some(href) = manifest.get(&id) else {
+            continue;
+        };
+        let href = href.split('#').next().unwrap_or(href);
+        let content = zip_part(&mut archive, &format!("{base}{href}"))?;
+        documents.push(html_to_text(&content));
+    }
+    if documents.is_empty() {
+        return Err(anyhow!("epub has no readable chapters"));
+    }
+    select_pages(documents, chapters, "chapter")
+}
+
+fn first_attribute(xml: &[u8], tag: &[u8], name: &str) -> Result<Option<String>> {
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    let mut buffer = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref() == tag =>
+            {
+                return Ok(attribute(&element, name));
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+        buffer.clear();
+    }
+}
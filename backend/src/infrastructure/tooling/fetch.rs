@@ -0,0 +1,347 @@
+//! `fetch` tool: retrieves a URL over HTTP(S) under a domain and network policy.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Response, Url};
+use serde_json::{json, Value};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use super::{Tool, ToolContext};
+use crate::domain::types::ToolExecution;
+
+pub const DEFAULT_FETCH_MAX_BYTES: usize = 1024 * 1024;
+pub const DEFAULT_FETCH_TIMEOUT_SECS: u64 = 15;
+
+const MAX_REDIRECTS: usize = 5;
+const HTML_WRAP_WIDTH: usize = 100;
+const USER_AGENT: &str = concat!("chaos-bot/", env!("CARGO_PKG_VERSION"));
+
+/// Which URLs the `fetch` tool may retrieve and how much it may read.
+///
+/// Domain entries match the host itself and any subdomain (`example.com` matches
+/// `docs.example.com`). The deny list wins over the allow list; an empty allow list permits
+/// every domain not denied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FetchPolicy {
+    pub allow_domains: Vec<String>,
+    pub deny_domains: Vec<String>,
+    pub allow_private_networks: bool,
+    pub max_bytes: usize,
+    pub timeout: Duration,
+}
+
+impl Default for FetchPolicy {
+    fn default() -> Self {
+        Self {
+            allow_domains: Vec::new(),
+            deny_domains: Vec::new(),
+            allow_private_networks: false,
+            max_bytes: DEFAULT_FETCH_MAX_BYTES,
+            timeout: Duration::from_secs(DEFAULT_FETCH_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl FetchPolicy {
+    /// Validates `url` and resolves its host. The returned addresses are pinned for the
+    /// request so a second DNS answer cannot bypass the private network check.
+    async fn check(&self, url: &Url) -> Result<Vec<SocketAddr>> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(anyhow!("fetch: unsupported url scheme: {}", url.scheme()));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("fetch: url has no host: {url}"))?
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+
+        if self
+            .deny_domains
+            .iter()
+            .any(|pattern| domain_matches(host, pattern))
+        {
+            return Err(anyhow!("fetch: domain is denied: {host}"));
+        }
+        if !self.allow_domains.is_empty()
+            && !self
+                .allow_domains
+                .iter()
+                .any(|pattern| domain_matches(host, pattern))
+        {
+            return Err(anyhow!("fetch: domain is not in the allow list: {host}"));
+        }
+
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| anyhow!("fetch: url has no port: {url}"))?;
+        let addrs: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+            Ok(ip) => vec![SocketAddr::new(ip, port)],
+            Err(_) => tokio::net::lookup_host((host, port))
+                .await
+                .with_context(|| format!("fetch: failed to resolve {host}"))?
+                .collect(),
+        };
+        if addrs.is_empty() {
+            return Err(anyhow!("fetch: no addresses found for {host}"));
+        }
+        if !self.allow_private_networks {
+            if let Some(blocked) = addrs.iter().find(|addr| is_private_ip(addr.ip())) {
+                return Err(anyhow!(
+                    "fetch: {host} resolves to a private or local address ({})",
+                    blocked.ip()
+                ));
+            }
+        }
+        Ok(addrs)
+    }
+}
+
+fn domain_matches(host: &str, pattern: &str) -> bool {
+    let pattern = pattern
+        .trim()
+        .trim_start_matches("*.")
+        .trim_start_matches('.')
+        .trim_end_matches('.')
+        .to_ascii_lowercase();
+    !pattern.is_empty()
+        && (host == pattern
+            || host
+                .strip_suffix(&pattern)
+                .is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+pub fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_ipv4(ip),
+        IpAddr::V6(ip) => is_private_ipv6(ip),
+    }
+}
+
+fn is_private_ipv4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || first == 0
+        // Carrier-grade NAT, 100.64.0.0/10.
+        || (first == 100 && (second & 0xc0) == 64)
+}
+
+fn is_private_ipv6(ip: Ipv6Addr) -> bool {
+    if let Some(mapped) = ip.to_ipv4_mapped() {
+        return is_private_ipv4(mapped);
+    }
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local, fc00::/7.
+        || (first & 0xfe00) == 0xfc00
+        // Link local, fe80::/10.
+        || (first & 0xffc0) == 0xfe80
+}
+
+pub struct FetchTool {
+    policy: FetchPolicy,
+}
+
+impl FetchTool {
+    pub fn new(policy: FetchPolicy) -> Self {
+        Self { policy }
+    }
+
+    fn client_for(&self, url: &Url, addrs: &[SocketAddr]) -> Result<Client> {
+        let mut builder = Client::builder()
+            .redirect(Policy::none())
+            .timeout(self.policy.timeout)
+            .user_agent(USER_AGENT);
+        if let Some(domain) = url.domain() {
+            builder = builder.resolve_to_addrs(domain, addrs);
+        }
+        Ok(builder.build()?)
+    }
+
+    async fn fetch(&self, url: Url) -> Result<(Url, Response)> {
+        let mut url = url;
+        for _ in 0..=MAX_REDIRECTS {
+            let addrs = self.policy.check(&url).await?;
+            let response = self
+                .client_for(&url, &addrs)?
+                .get(url.clone())
+                .send()
+                .await
+                .with_context(|| format!("fetch: request failed: {url}"))?;
+
+            if !response.status().is_redirection() {
+                return Ok((url, response));
+            }
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| anyhow!("fetch: redirect without location from {url}"))?;
+            let next = url
+                .join(location)
+                .with_context(|| format!("fetch: invalid redirect location: {location}"))?;
+            tracing::debug!(from = %url, to = %next, "fetch following redirect");
+            url = next;
+        }
+        Err(anyhow!("fetch: too many redirects (max {MAX_REDIRECTS})"))
+    }
+
+    async fn read_body(&self, mut response: Response) -> Result<(Vec<u8>, bool)> {
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            let remaining = self.policy.max_bytes - body.len();
+            if chunk.len() > remaining {
+                body.extend_from_slice(&chunk[..remaining]);
+                return Ok((body, true));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok((body, false))
+    }
+}
+
+enum BodyKind {
+    Html,
+    Text,
+}
+
+fn classify_content_type(content_type: &str) -> Option<BodyKind> {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match essence.as_str() {
+        "text/html" | "application/xhtml+xml" => Some(BodyKind::Html),
+        ""
+        | "application/json"
+        | "application/xml"
+        | "application/javascript"
+        | "application/x-yaml"
+        | "application/yaml" => Some(BodyKind::Text),
+        other
+            if other.starts_with("text/")
+                || other.ends_with("+json")
+                || other.ends_with("+xml") =>
+        {
+            Some(BodyKind::Text)
+        }
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl Tool for FetchTool {
+    fn name(&self) -> &'static str {
+        "fetch"
+    }
+
+    fn description(&self) -> &'static str {
+        "Fetch a web page or text resource over HTTP(S); HTML is converted to readable text"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {"type": "string"},
+                "raw": {"type": "boolean", "description": "Return HTML source instead of extracted text"}
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, args: Value, _context: &ToolContext) -> Result<ToolExecution> {
+        let raw_url = args
+            .get("url")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| anyhow!("fetch.url is required"))?;
+        let raw = args.get("raw").and_then(Value::as_bool).unwrap_or(false);
+        self.get(raw_url, raw).await
+    }
+}
+
+impl FetchTool {
+    /// Fetches `raw_url` under the policy; `raw` keeps HTML source instead of extracting text.
+    pub async fn get(&self, raw_url: &str, raw: bool) -> Result<ToolExecution> {
+        let url = Url::parse(raw_url).with_context(|| format!("fetch: invalid url: {raw_url}"))?;
+
+        let (final_url, status, content_type, body, truncated) =
+            tokio::time::timeout(self.policy.timeout, async {
+                let (final_url, response) = self.fetch(url).await?;
+                let status = response.status();
+                let content_type = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                let (body, truncated) = self.read_body(response).await?;
+                anyhow::Ok((final_url, status, content_type, body, truncated))
+            })
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "fetch: timed out after {}s",
+                    self.policy.timeout.as_secs_f32()
+                )
+            })??;
+
+        let text = match classify_content_type(&content_type) {
+            Some(BodyKind::Html) if !raw => html2text::from_read(body.as_slice(), HTML_WRAP_WIDTH)
+                .unwrap_or_else(|_| String::from_utf8_lossy(&body).to_string()),
+            Some(_) => String::from_utf8_lossy(&body).to_string(),
+            None => {
+                return Err(anyhow!(
+                    "fetch: unsupported content type: {content_type} ({} bytes)",
+                    body.len()
+                ))
+            }
+        };
+
+        tracing::debug!(
+            url = %final_url,
+            status = status.as_u16(),
+            content_type = %content_type,
+            bytes = body.len(),
+            truncated,
+            "fetch completed"
+        );
+
+        let mut output = format!(
+            "URL: {final_url}\nStatus: {}\nContent-Type: {}\n",
+            status.as_u16(),
+            if content_type.is_empty() {
+                "unknown"
+            } else {
+                content_type.as_str()
+            }
+        );
+        if truncated {
+            output.push_str(&format!(
+                "[body truncated at {} bytes]\n",
+                self.policy.max_bytes
+            ));
+        }
+        output.push('\n');
+        output.push_str(text.trim());
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output,
+            is_error: !status.is_success(),
+        })
+    }
+}
@@ -0,0 +1,482 @@
+//! Read-mostly git tools over the working directory.
+//!
+//! Every command runs `git -C <root_dir>` and is limited to the working directory with a
+//! `-- .` pathspec unless the model passes explicit paths, which are confined the same way
+//! as the file tools. `git_commit` is registered only when `tools.git.allow_commit` is set.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use std::process::Output;
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::{ensure_within_root, Tool, ToolContext, ToolRegistry};
+use crate::domain::types::ToolExecution;
+
+const GIT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_LOG_LIMIT: u64 = 20;
+const MAX_LOG_LIMIT: u64 = 200;
+
+/// Registers `git_status`, `git_diff`, `git_log` and `git_show`, plus `git_commit` when
+/// `allow_commit` is set.
+pub fn register_git_tools(registry: &mut ToolRegistry, allow_commit: bool) {
+    registry.register(GitStatusTool);
+    registry.register(GitDiffTool);
+    registry.register(GitLogTool);
+    registry.register(GitShowTool);
+    if allow_commit {
+        registry.register(GitCommitTool);
+    }
+}
+
+async fn git(root_dir: &Path, args: &[String]) -> Result<Output> {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(root_dir)
+        .args([
+            "--no-pager",
+            "-c",
+            "color.ui=never",
+            "-c",
+            "core.quotepath=off",
+        ])
+        .args(["-c", "status.relativePaths=true"])
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .kill_on_drop(true);
+    tokio::time::timeout(GIT_TIMEOUT, command.output())
+        .await
+        .map_err(|_| anyhow!("git {} timed out", args.first().map_or("", String::as_str)))?
+        .context("failed to run git")
+}
+
+/// Runs git and returns stdout, or an error carrying git's own message (e.g. "not a git
+/// repository") on failure.
+async fn git_stdout(root_dir: &Path, args: &[String]) -> Result<String> {
+    let output = git(root_dir, args).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git failed: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Maps a model-supplied path to a pathspec relative to `root_dir`, rejecting pathspec magic
+/// and anything outside the working directory. The path does not need to exist (deleted
+/// files still show up in diffs), but existing paths are also checked through symlinks.
+fn confine_pathspec(root_dir: &Path, input: &str) -> Result<String> {
+    let input = input.trim();
+    if input.is_empty() || input.starts_with(':') || input.starts_with('-') {
+        return Err(anyhow!("invalid git path: {input}"));
+    }
+    let root = std::fs::canonicalize(root_dir)
+        .with_context(|| format!("cannot canonicalize root: {}", root_dir.display()))?;
+    let path = Path::new(input);
+    let relative = if path.is_absolute() {
+        let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        absolute
+            .strip_prefix(&root)
+            .map(Path::to_path_buf)
+            .map_err(|_| anyhow!("path escapes working directory: {input}"))?
+    } else {
+        path.to_path_buf()
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if normalized.pop() => {}
+            _ => return Err(anyhow!("path escapes working directory: {input}")),
+        }
+    }
+    let full = root.join(&normalized);
+    if full.exists() {
+        ensure_within_root(&root, &full)?;
+    }
+    if normalized.as_os_str().is_empty() {
+        return Ok(".".to_string());
+    }
+    Ok(normalized.to_string_lossy().into_owned())
+}
+
+/// `paths` as a string or array of strings, confined to the root; `["."]` when absent.
+fn pathspecs(tool: &str, root_dir: &Path, args: &Value) -> Result<Vec<String>> {
+    let raw: Vec<&str> = match args.get("paths").or_else(|| args.get("path")) {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(path)) => vec![path.as_str()],
+        Some(Value::Array(paths)) => paths
+            .iter()
+            .map(|path| {
+                path.as_str()
+                    .ok_or_else(|| anyhow!("{tool}.paths must be strings"))
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => return Err(anyhow!("{tool}.paths must be a string or array")),
+    };
+    if raw.is_empty() {
+        return Ok(vec![".".to_string()]);
+    }
+    raw.into_iter()
+        .map(|path| confine_pathspec(root_dir, path))
+        .collect()
+}
+
+/// A revision argument; refuses anything git could parse as an option.
+fn revision<'a>(tool: &str, args: &'a Value, key: &str) -> Result<Option<&'a str>> {
+    match args
+        .get(key)
+        .and_then(|value| value.as_str())
+        .map(str::trim)
+    {
+        None | Some("") => Ok(None),
+        Some(rev) if rev.starts_with('-') || rev.chars().any(char::is_whitespace) => {
+            Err(anyhow!("{tool}.{key} is not a valid revision: {rev}"))
+        }
+        Some(rev) => Ok(Some(rev)),
+    }
+}
+
+fn execution(name: &str, output: String) -> ToolExecution {
+    ToolExecution {
+        name: name.to_string(),
+        output,
+        is_error: false,
+    }
+}
+
+fn strings<const N: usize>(args: [&str; N]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+pub struct GitStatusTool;
+
+#[async_trait]
+impl Tool for GitStatusTool {
+    fn name(&self) -> &'static str {
+        "git_status"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show the current branch and staged, unstaged and untracked files in the working directory"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "paths": {"type": "array", "items": {"type": "string"}, "description": "Limit to these paths"}
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let mut command = strings([
+            "status",
+            "--short",
+            "--branch",
+            "--untracked-files=all",
+            "--",
+        ]);
+        command.extend(pathspecs(self.name(), &context.root_dir, &args)?);
+        let stdout = git_stdout(&context.root_dir, &command).await?;
+        Ok(execution(self.name(), format_status(&stdout)))
+    }
+}
+
+/// Groups `--short` status output (paths relative to the working directory) into branch,
+/// staged, unstaged, untracked and conflicted lists.
+fn format_status(short: &str) -> String {
+    let mut branch = None;
+    let (mut staged, mut unstaged, mut untracked, mut conflicted) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+
+    for line in short.lines() {
+        if let Some(header) = line.strip_prefix("## ") {
+            branch = Some(header.to_string());
+            continue;
+        }
+        if line.len() < 4 {
+            continue;
+        }
+        let (code, path) = line.split_at(2);
+        let path = path.trim_start();
+        let mut chars = code.chars();
+        let (index, worktree) = (chars.next().unwrap_or(' '), chars.next().unwrap_or(' '));
+        match (index, worktree) {
+            ('?', '?') => untracked.push(path.to_string()),
+            ('!', '!') => {}
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => {
+                conflicted.push(format!("{code} {path}"))
+            }
+            _ => {
+                if index != ' ' {
+                    staged.push(format!("{} {path}", status_word(index)));
+                }
+                if worktree != ' ' {
+                    unstaged.push(format!("{} {path}", status_word(worktree)));
+                }
+            }
+        }
+    }
+
+    let mut out = format!("branch: {}\n", branch.as_deref().unwrap_or("(unknown)"));
+    if staged.is_empty() && unstaged.is_empty() && untracked.is_empty() && conflicted.is_empty() {
+        out.push_str("working tree clean\n");
+        return out;
+    }
+    for (title, entries) in [
+        ("conflicted", &conflicted),
+        ("staged", &staged),
+        ("unstaged", &unstaged),
+        ("untracked", &untracked),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("{title} ({}):\n", entries.len()));
+        for entry in entries {
+            out.push_str(&format!("  {entry}\n"));
+        }
+    }
+    out
+}
+
+fn status_word(code: char) -> &'static str {
+    match code {
+        'M' => "modified:",
+        'A' => "added:   ",
+        'D' => "deleted: ",
+        'R' => "renamed: ",
+        'C' => "copied:  ",
+        'T' => "typechg: ",
+        _ => "changed: ",
+    }
+}
+
+pub struct GitDiffTool;
+
+#[async_trait]
+impl Tool for GitDiffTool {
+    fn name(&self) -> &'static str {
+        "git_diff"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show a diff of working tree changes (or staged changes), with a file summary first"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "staged": {"type": "boolean", "description": "Diff the index against HEAD instead of the working tree against the index"},
+                "base": {"type": "string", "description": "Compare against this revision instead (e.g. HEAD~1)"},
+                "paths": {"type": "array", "items": {"type": "string"}, "description": "Limit to these paths"},
+                "context_lines": {"type": "integer", "minimum": 0}
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let mut options = Vec::new();
+        if args.get("staged").and_then(Value::as_bool).unwrap_or(false) {
+            options.push("--cached".to_string());
+        }
+        if let Some(lines) = args.get("context_lines").and_then(Value::as_u64) {
+            options.push(format!("--unified={}", lines.min(50)));
+        }
+        if let Some(base) = revision(self.name(), &args, "base")? {
+            options.push(base.to_string());
+        }
+        let mut paths = vec!["--".to_string()];
+        paths.extend(pathspecs(self.name(), &context.root_dir, &args)?);
+
+        let stat_args: Vec<String> = [
+            strings(["diff", "--relative", "--stat"]),
+            options.clone(),
+            paths.clone(),
+        ]
+        .concat();
+        let stat = git_stdout(&context.root_dir, &stat_args).await?;
+        if stat.trim().is_empty() {
+            return Ok(execution(self.name(), "no changes".to_string()));
+        }
+        let patch_args: Vec<String> = [
+            strings(["diff", "--relative", "--no-ext-diff"]),
+            options,
+            paths,
+        ]
+        .concat();
+        let patch = git_stdout(&context.root_dir, &patch_args).await?;
+        Ok(execution(
+            self.name(),
+            format!("{}\n{}", stat.trim_end(), patch),
+        ))
+    }
+}
+
+pub struct GitLogTool;
+
+#[async_trait]
+impl Tool for GitLogTool {
+    fn name(&self) -> &'static str {
+        "git_log"
+    }
+
+    fn description(&self) -> &'static str {
+        "List recent commits (hash, date, author, subject), optionally for a revision or paths"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "limit": {"type": "integer", "minimum": 1, "maximum": MAX_LOG_LIMIT},
+                "rev": {"type": "string", "description": "Revision or range (default HEAD)"},
+                "paths": {"type": "array", "items": {"type": "string"}}
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let limit = args
+            .get("limit")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_LOG_LIMIT)
+            .clamp(1, MAX_LOG_LIMIT);
+        let mut command = vec![
+            "log".to_string(),
+            format!("--max-count={limit}"),
+            "--date=short".to_string(),
+            "--format=%h %ad %an%x09%s".to_string(),
+        ];
+        if let Some(rev) = revision(self.name(), &args, "rev")? {
+            command.push(rev.to_string());
+        }
+        command.push("--".to_string());
+        command.extend(pathspecs(self.name(), &context.root_dir, &args)?);
+
+        let stdout = git_stdout(&context.root_dir, &command).await?;
+        let output = if stdout.trim().is_empty() {
+            "no commits".to_string()
+        } else {
+            stdout
+        };
+        Ok(execution(self.name(), output))
+    }
+}
+
+pub struct GitShowTool;
+
+#[async_trait]
+impl Tool for GitShowTool {
+    fn name(&self) -> &'static str {
+        "git_show"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show one commit: metadata, message, changed files and patch"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "rev": {"type": "string", "description": "Commit to show (default HEAD)"},
+                "stat_only": {"type": "boolean", "description": "Omit the patch"},
+                "paths": {"type": "array", "items": {"type": "string"}}
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let rev = revision(self.name(), &args, "rev")?.unwrap_or("HEAD");
+        let mut command = strings([
+            "show",
+            "--no-ext-diff",
+            "--date=iso",
+            "--format=commit %H%nAuthor: %an <%ae>%nDate:   %ad%n%n%B",
+            "--stat",
+        ]);
+        if !args
+            .get("stat_only")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            command.push("--patch".to_string());
+        }
+        command.push(rev.to_string());
+        command.push("--".to_string());
+        command.extend(pathspecs(self.name(), &context.root_dir, &args)?);
+
+        let stdout = git_stdout(&context.root_dir, &command).await?;
+        Ok(execution(self.name(), stdout))
+    }
+}
+
+pub struct GitCommitTool;
+
+#[async_trait]
+impl Tool for GitCommitTool {
+    fn name(&self) -> &'static str {
+        "git_commit"
+    }
+
+    fn description(&self) -> &'static str {
+        "Commit changes in the working directory. Stages the given paths (or all changes with all=true) first"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "message": {"type": "string"},
+                "paths": {"type": "array", "items": {"type": "string"}, "description": "Stage these paths before committing"},
+                "all": {"type": "boolean", "description": "Stage every change under the working directory"}
+            },
+            "required": ["message"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let message = args
+            .get("message")
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|message| !message.is_empty())
+            .ok_or_else(|| anyhow!("git_commit.message is required"))?;
+        let all = args.get("all").and_then(Value::as_bool).unwrap_or(false);
+        let has_paths = args.get("paths").is_some_and(|paths| !paths.is_null());
+
+        if all || has_paths {
+            let mut add = strings(["add", "--all", "--"]);
+            add.extend(pathspecs(self.name(), &context.root_dir, &args)?);
+            git_stdout(&context.root_dir, &add).await?;
+        }
+
+        let staged = git(&context.root_dir, &strings(["diff", "--cached", "--quiet"])).await?;
+        if staged.status.success() {
+            return Ok(ToolExecution {
+                name: self.name().to_string(),
+                output: "nothing staged to commit".to_string(),
+                is_error: true,
+            });
+        }
+
+        let commit = strings(["commit", "--quiet", "--message", message]);
+        git_stdout(&context.root_dir, &commit).await?;
+        let summary = git_stdout(
+            &context.root_dir,
+            &strings(["show", "--stat", "--format=committed %h %s", "HEAD"]),
+        )
+        .await?;
+        tracing::info!(session_id = ?context.session_id, "git_commit created commit");
+        Ok(execution(self.name(), summary))
+    }
+}
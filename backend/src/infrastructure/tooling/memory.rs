@@ -0,0 +1,145 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Tool, ToolContext};
+use crate::domain::memory::{EditOrigin, MemoryChange, MemoryEdit};
+use crate::domain::types::ToolExecution;
+
+fn required_str<'a>(args: &'a Value, tool: &str, field: &str) -> Result<&'a str> {
+    args.get(field)
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| anyhow!("{tool}.{field} is required"))
+}
+
+fn optional_string(args: &Value, field: &str) -> Option<String> {
+    args.get(field)
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+}
+
+async fn apply(tool: &str, edit: MemoryEdit, context: &ToolContext) -> Result<ToolExecution> {
+    let origin = EditOrigin {
+        session_id: context.session_id.clone(),
+        tool_call_id: context.tool_call_id.clone(),
+    };
+    let change = context.memory.edit_curated(edit, origin).await?;
+    Ok(ToolExecution {
+        name: tool.to_string(),
+        output: describe(&change),
+        is_error: false,
+    })
+}
+
+fn describe(change: &MemoryChange) -> String {
+    if !change.changed {
+        return format!("MEMORY.md already has this under \"{}\"", change.section);
+    }
+    match &change.after {
+        Some(after) => format!("Section \"{}\" is now:\n{after}", change.section),
+        None => format!("Removed section \"{}\"", change.section),
+    }
+}
+
+/// `memory_remember`: appends one fact to a section of MEMORY.md.
+pub struct MemoryRememberTool;
+
+#[async_trait]
+impl Tool for MemoryRememberTool {
+    fn name(&self) -> &'static str {
+        "memory_remember"
+    }
+
+    fn description(&self) -> &'static str {
+        "Save a durable fact, preference or decision to long-term memory (MEMORY.md) as a bullet under a section heading. Keep facts short and self-contained; the section is created if missing"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "section": {"type": "string", "description": "Section heading, e.g. \"Preferences\""},
+                "fact": {"type": "string", "description": "One line to remember"}
+            },
+            "required": ["section", "fact"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let edit = MemoryEdit::Remember {
+            section: required_str(&args, self.name(), "section")?.to_string(),
+            fact: required_str(&args, self.name(), "fact")?.to_string(),
+        };
+        apply(self.name(), edit, context).await
+    }
+}
+
+/// `memory_update`: rewrites one section of MEMORY.md.
+pub struct MemoryUpdateTool;
+
+#[async_trait]
+impl Tool for MemoryUpdateTool {
+    fn name(&self) -> &'static str {
+        "memory_update"
+    }
+
+    fn description(&self) -> &'static str {
+        "Replace the body of one MEMORY.md section, e.g. to merge or correct facts. Pass the body you read as `expected` so the update fails instead of overwriting a concurrent change"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "section": {"type": "string"},
+                "content": {"type": "string", "description": "New section body without the heading"},
+                "expected": {"type": "string", "description": "Current section body as last read"}
+            },
+            "required": ["section", "content"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let edit = MemoryEdit::Update {
+            section: required_str(&args, self.name(), "section")?.to_string(),
+            content: required_str(&args, self.name(), "content")?.to_string(),
+            expected: optional_string(&args, "expected"),
+        };
+        apply(self.name(), edit, context).await
+    }
+}
+
+/// `memory_forget`: drops facts or a whole section from MEMORY.md.
+pub struct MemoryForgetTool;
+
+#[async_trait]
+impl Tool for MemoryForgetTool {
+    fn name(&self) -> &'static str {
+        "memory_forget"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove outdated or wrong entries from MEMORY.md: the lines of a section that contain `fact`, or the whole section when `fact` is omitted"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "section": {"type": "string"},
+                "fact": {"type": "string", "description": "Text of the lines to remove"},
+                "expected": {"type": "string", "description": "Current section body as last read"}
+            },
+            "required": ["section"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let edit = MemoryEdit::Forget {
+            section: required_str(&args, self.name(), "section")?.to_string(),
+            fact: optional_string(&args, "fact"),
+            expected: optional_string(&args, "expected"),
+        };
+        apply(self.name(), edit, context).await
+    }
+}
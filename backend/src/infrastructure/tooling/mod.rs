@@ -1,7 +1,11 @@
+use crate::domain::checkpoint::FileSnapshot;
 use crate::domain::ports::{ToolExecutionContext, ToolExecutorPort};
 use crate::domain::types::{ToolExecution, ToolResult, ToolSpec};
+use crate::infrastructure::checkpoints::read_existing;
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
+use globset::Glob;
+use regex::{Regex, RegexBuilder};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -9,34 +13,122 @@ use std::sync::Arc;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
-use walkdir::WalkDir;
+
+mod code;
+mod document;
+mod fetch;
+mod git;
+mod memory;
+mod output;
+mod plan;
+mod plugin;
+mod sql;
+mod walk;
+mod wasm;
+mod workspace;
+
+pub use code::{
+    extract_symbols, register_code_tools, CodeLanguage, CodeOutlineTool, CodeSymbol,
+    FindSymbolTool, IndexRefresh, SymbolIndex, SymbolMatch,
+};
+pub use document::{extract_document, parse_page_ranges, DocumentFormat, DocumentSelection};
+pub use fetch::{is_private_ip, FetchPolicy, FetchTool};
+pub use git::{
+    register_git_tools, GitCommitTool, GitDiffTool, GitLogTool, GitShowTool, GitStatusTool,
+};
+pub use memory::{MemoryForgetTool, MemoryRememberTool, MemoryUpdateTool};
+pub use output::{OutputLimits, OutputPage, OutputSpill};
+pub use plan::{TodoReadTool, TodoWriteTool};
+pub use plugin::{register_plugin_tools, PluginSpec, PluginTool, DEFAULT_PLUGIN_TIMEOUT_SECS};
+pub use sql::{
+    register_sql_tools, SqlLimits, SqlQueryTool, SqlSchemaTool, DEFAULT_SQL_MAX_ROWS,
+    DEFAULT_SQL_TIMEOUT_SECS,
+};
+use walk::{display_path, filter_schema_properties, is_searchable_size, merge_properties};
+pub use walk::{is_binary, Page, WalkFilters};
+pub use wasm::{
+    register_wasm_plugins, wasm_engine, WasmCapabilities, WasmLimits, WasmPluginManifest,
+    WasmPluginTool, DEFAULT_WASM_FUEL, DEFAULT_WASM_MEMORY_MB, DEFAULT_WASM_TIMEOUT_SECS,
+};
+pub use workspace::SearchWorkspaceTool;
 
 pub type ToolContext = ToolExecutionContext;
 
 #[async_trait]
 pub trait Tool: Send + Sync {
-    fn name(&self) -> &'static str;
-    fn description(&self) -> &'static str;
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
     fn parameters_schema(&self) -> Value;
     async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution>;
 }
 
+/// Longest function name the model providers accept.
+pub const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Whether providers accept `name` as a function name: 1-64 ASCII letters, digits, `_` or `-`.
+pub fn is_valid_tool_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_TOOL_NAME_LEN
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+}
+
 #[derive(Clone, Default)]
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn Tool>>,
+    output: Option<Arc<OutputSpill>>,
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            output: None,
         }
     }
 
+    /// Applies a shared output budget to every dispatched tool and registers `read_output`
+    /// for paging through outputs that exceeded it.
+    pub fn with_output_spill(mut self, spill: OutputSpill) -> Self {
+        let spill = Arc::new(spill);
+        self.register(ReadOutputTool {
+            spill: spill.clone(),
+        });
+        self.output = Some(spill);
+        self
+    }
+
     pub fn register<T: Tool + 'static>(&mut self, tool: T) {
         self.tools.insert(tool.name().to_string(), Arc::new(tool));
     }
 
+    /// Registers a tool supplied from outside the binary (`source` names the plugin or server).
+    /// Unlike [`ToolRegistry::register`] it never replaces a tool that is already registered,
+    /// so a plugin cannot shadow a built-in; invalid and taken names are logged and skipped.
+    pub fn register_external<T: Tool + 'static>(&mut self, tool: T, source: &str) -> bool {
+        let name = tool.name();
+        if !is_valid_tool_name(name) {
+            tracing::warn!(
+                tool = %name,
+                source,
+                "tool name must be 1-64 ASCII letters, digits, `_` or `-`; skipping"
+            );
+            return false;
+        }
+        // `read_output` is only added by `with_output_spill`, after external tools.
+        if self.contains(name) || name == READ_OUTPUT_TOOL {
+            tracing::warn!(tool = %name, source, "tool name is already registered; skipping");
+            return false;
+        }
+        self.register(tool);
+        true
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tools.contains_key(name)
+    }
+
     pub fn register_coding_tools(&mut self) {
         self.register(ReadTool);
         self.register(WriteTool);
@@ -54,12 +146,21 @@ impl ToolRegistry {
     pub fn register_memory_tools(&mut self) {
         self.register(MemoryGetTool);
         self.register(MemorySearchTool);
+        self.register(MemoryRememberTool);
+        self.register(MemoryUpdateTool);
+        self.register(MemoryForgetTool);
+    }
+
+    pub fn register_plan_tools(&mut self) {
+        self.register(TodoWriteTool);
+        self.register(TodoReadTool);
     }
 
     pub fn register_default_tools(&mut self) {
         self.register_coding_tools();
         self.register_read_only_tools();
         self.register_memory_tools();
+        self.register_plan_tools();
     }
 
     pub fn specs(&self) -> Vec<ToolSpec> {
@@ -87,19 +188,31 @@ impl ToolRegistry {
             .ok_or_else(|| anyhow!("tool not found: {}", name))?
             .clone();
 
-        let output = tool.execute(args, context).await?;
+        let output = tool.execute(args, &context.for_call(tool_call_id)).await?;
         tracing::debug!(
             tool_call_id,
             tool_name = name,
             is_error = output.is_error,
+            output_bytes = output.output.len(),
             "tool execution completed"
         );
 
+        // `read_output` pages within the budget itself, so it is never spilled again.
+        let (text, truncation) = match &self.output {
+            Some(spill) if name != READ_OUTPUT_TOOL => {
+                spill
+                    .apply(context.session_id.as_deref(), output.output)
+                    .await?
+            }
+            _ => (output.output, None),
+        };
+
         Ok(ToolResult {
             tool_call_id: tool_call_id.to_string(),
             name: name.to_string(),
-            output: output.output,
+            output: text,
             is_error: output.is_error,
+            truncation,
         })
     }
 }
@@ -182,6 +295,70 @@ fn resolve_write_path_unrestricted(root_dir: &Path, input_path: &str) -> Result<
     Ok(path)
 }
 
+/// The content of `path` before a mutation, read only when it will be checkpointed. `None`
+/// means no checkpoint: no session or store is attached, or the file could not be read
+/// (recording it as absent would make a restore delete it); the mutation goes ahead anyway.
+async fn checkpoint_before(
+    context: &ToolContext,
+    tool_name: &str,
+    path: &Path,
+) -> Option<Option<Vec<u8>>> {
+    if context.checkpoints.is_none() || context.session_id.is_none() {
+        return None;
+    }
+    match read_existing(path).await {
+        Ok(before) => Some(before),
+        Err(error) => {
+            tracing::warn!(
+                tool_name,
+                path = %path.display(),
+                error = %error,
+                "skipping checkpoint for unreadable file"
+            );
+            None
+        }
+    }
+}
+
+/// Records the pre-mutation state of `path` when both a session and a checkpoint store
+/// are attached to the context. Failures are logged and never block the mutation itself.
+async fn record_checkpoint(
+    context: &ToolContext,
+    tool_name: &str,
+    path: &Path,
+    before: Option<Vec<u8>>,
+    after: &[u8],
+) {
+    let (Some(store), Some(session_id)) = (&context.checkpoints, &context.session_id) else {
+        return;
+    };
+    let canonical = match (
+        path.parent()
+            .and_then(|parent| std::fs::canonicalize(parent).ok()),
+        path.file_name(),
+    ) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    };
+    let snapshot = FileSnapshot {
+        session_id: session_id.clone(),
+        tool_call_id: context.tool_call_id.clone(),
+        tool_name: tool_name.to_string(),
+        path: canonical.display().to_string(),
+        before,
+        after: after.to_vec(),
+    };
+    if let Err(error) = store.record(snapshot).await {
+        tracing::warn!(
+            session_id = %session_id,
+            tool_name,
+            path = %path.display(),
+            error = %error,
+            "failed to record file checkpoint"
+        );
+    }
+}
+
 pub fn slice_lines(content: &str, start_line: Option<usize>, end_line: Option<usize>) -> String {
     let lines: Vec<&str> = content.lines().collect();
     match (start_line, end_line) {
@@ -215,7 +392,7 @@ impl Tool for ReadTool {
     }
 
     fn description(&self) -> &'static str {
-        "Read a text file from the working directory"
+        "Read a file from the working directory. Text files are returned as-is; PDF, DOCX, XLSX/XLS/ODS, CSV/TSV, EPUB and HTML are converted to text (tables as markdown). Use pages (\"2\", \"1-3,7\") for PDF pages or EPUB chapters, sheet (name or 1-based index) for workbooks, and raw to get HTML/CSV source verbatim"
     }
 
     fn parameters_schema(&self) -> Value {
@@ -224,7 +401,10 @@ impl Tool for ReadTool {
             "properties": {
                 "path": {"type": "string"},
                 "start_line": {"type": "integer", "minimum": 1},
-                "end_line": {"type": "integer", "minimum": 1}
+                "end_line": {"type": "integer", "minimum": 1},
+                "pages": {"type": "string"},
+                "sheet": {"type": "string"},
+                "raw": {"type": "boolean"}
             },
             "required": ["path"]
         })
@@ -244,8 +424,26 @@ impl Tool for ReadTool {
             .and_then(|value| value.as_u64())
             .map(|v| v as usize);
 
+        let selection = DocumentSelection {
+            pages: args.get("pages").and_then(|value| match value {
+                Value::Number(page) => Some(page.to_string()),
+                other => other.as_str().map(str::to_string),
+            }),
+            sheet: args.get("sheet").and_then(|value| match value {
+                Value::Number(index) => Some(index.to_string()),
+                other => other.as_str().map(str::to_string),
+            }),
+            raw: args
+                .get("raw")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false),
+        };
+
         let resolved = resolve_existing_path(&context.root_dir, path)?;
-        let content = fs::read_to_string(resolved).await?;
+        let bytes = fs::read(&resolved).await?;
+        let content =
+            tokio::task::spawn_blocking(move || extract_document(&resolved, &bytes, &selection))
+                .await??;
         let output = slice_lines(&content, start_line, end_line);
 
         Ok(ToolExecution {
@@ -295,6 +493,13 @@ impl Tool for WriteTool {
             .unwrap_or(false);
 
         let resolved = resolve_write_path_unrestricted(&context.root_dir, path)?;
+        if let Some(before) = checkpoint_before(context, self.name(), &resolved).await {
+            let after = match (&before, append) {
+                (Some(existing), true) => [existing.as_slice(), content.as_bytes()].concat(),
+                _ => content.as_bytes().to_vec(),
+            };
+            record_checkpoint(context, self.name(), &resolved, before, &after).await;
+        }
         if append {
             let mut file = fs::OpenOptions::new()
                 .create(true)
@@ -363,6 +568,14 @@ impl Tool for EditTool {
         }
 
         let updated = content.replace(find, replace);
+        record_checkpoint(
+            context,
+            self.name(),
+            &resolved,
+            Some(content.into_bytes()),
+            updated.as_bytes(),
+        )
+        .await;
         fs::write(&resolved, updated).await?;
         let final_path = std::fs::canonicalize(&resolved).unwrap_or(resolved.clone());
 
@@ -434,71 +647,291 @@ impl Tool for BashTool {
 
         Ok(ToolExecution {
             name: self.name().to_string(),
-            output: combined.chars().take(8_000).collect(),
+            output: combined,
             is_error: !output.status.success(),
         })
     }
 }
 
-pub struct GrepTool;
+const READ_OUTPUT_TOOL: &str = "read_output";
+const DEFAULT_READ_OUTPUT_LINES: usize = 200;
+
+pub struct ReadOutputTool {
+    spill: Arc<OutputSpill>,
+}
 
 #[async_trait]
-impl Tool for GrepTool {
+impl Tool for ReadOutputTool {
     fn name(&self) -> &'static str {
-        "grep"
+        READ_OUTPUT_TOOL
     }
 
     fn description(&self) -> &'static str {
-        "Search for a text pattern inside files"
+        "Page through a truncated tool output by its handle"
     }
 
     fn parameters_schema(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
-                "pattern": {"type": "string"},
-                "path": {"type": "string"}
+                "handle": {"type": "string"},
+                "offset": {"type": "integer", "minimum": 1, "description": "1-based line to start from"},
+                "limit": {"type": "integer", "minimum": 1, "description": "Maximum number of lines"}
             },
-            "required": ["pattern"]
+            "required": ["handle"]
         })
     }
 
     async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let handle = args
+            .get("handle")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| anyhow!("read_output.handle is required"))?;
+        let offset = args
+            .get("offset")
+            .and_then(|value| value.as_u64())
+            .map(|value| value as usize)
+            .unwrap_or(1);
+        let limit = args
+            .get("limit")
+            .and_then(|value| value.as_u64())
+            .map(|value| value as usize)
+            .unwrap_or(DEFAULT_READ_OUTPUT_LINES);
+
+        let page = self
+            .spill
+            .read(context.session_id.as_deref(), handle, offset, limit)
+            .await?;
+        if page.first_line > page.total_lines {
+            return Err(anyhow!(
+                "read_output.offset {} is past the end of the output ({} lines)",
+                page.first_line,
+                page.total_lines
+            ));
+        }
+        let mut output = page.text;
+        if page.last_line < page.total_lines {
+            output.push_str(&format!(
+                "\n[showing lines {}-{} of {}{}; continue with offset={}]",
+                page.first_line,
+                page.last_line,
+                page.total_lines,
+                if page.continues {
+                    "; the last line continues"
+                } else {
+                    ""
+                },
+                page.last_line + 1
+            ));
+        }
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output,
+            is_error: false,
+        })
+    }
+}
+
+pub struct GrepTool;
+
+impl GrepTool {
+    fn build_matcher(args: &Value) -> Result<Regex> {
         let pattern = args
             .get("pattern")
             .and_then(|value| value.as_str())
-            .ok_or_else(|| anyhow!("grep.pattern is required"))?
-            .to_lowercase();
-        let target_path = args
-            .get("path")
+            .ok_or_else(|| anyhow!("grep.pattern is required"))?;
+        let mode = args
+            .get("mode")
             .and_then(|value| value.as_str())
-            .unwrap_or(".");
+            .unwrap_or("literal");
+        let case_sensitive = args
+            .get("case_sensitive")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
 
-        let root = resolve_existing_path(&context.root_dir, target_path)?;
-        let mut matches = Vec::new();
-
-        for entry in WalkDir::new(root)
-            .into_iter()
-            .filter_map(std::result::Result::ok)
-        {
-            let path = entry.path();
-            if !path.is_file() {
+        let source = match mode {
+            "literal" => regex::escape(pattern),
+            "regex" => pattern.to_string(),
+            other => return Err(anyhow!("grep.mode must be literal or regex, got {other}")),
+        };
+        RegexBuilder::new(&source)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .with_context(|| format!("grep.pattern is not a valid regex: {pattern}"))
+    }
+
+    fn search(
+        display_root: &Path,
+        search_root: &Path,
+        matcher: &Regex,
+        filters: &WalkFilters,
+        context_lines: usize,
+        page: Page,
+    ) -> String {
+        let mut output = Vec::new();
+        let mut seen = 0usize;
+        let mut shown = 0usize;
+        let mut truncated = false;
+        let mut binary_skipped = 0usize;
+
+        let targets: Box<dyn Iterator<Item = PathBuf>> = if search_root.is_file() {
+            Box::new(std::iter::once(search_root.to_path_buf()))
+        } else {
+            Box::new(
+                filters
+                    .walk(search_root)
+                    .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+                    .filter(is_searchable_size)
+                    .filter(|entry| {
+                        filters.matches(
+                            entry
+                                .path()
+                                .strip_prefix(search_root)
+                                .unwrap_or(entry.path()),
+                        )
+                    })
+                    .map(|entry| entry.into_path()),
+            )
+        };
+
+        'files: for path in targets {
+            let Ok(bytes) = std::fs::read(&path) else {
                 continue;
-            }
-            let content = match fs::read_to_string(path).await {
-                Ok(value) => value,
-                Err(_) => continue,
             };
-            for (idx, line) in content.lines().enumerate() {
-                if line.to_lowercase().contains(&pattern) {
-                    matches.push(format!("{}:{}:{}", path.display(), idx + 1, line.trim()));
+            if is_binary(&bytes) {
+                binary_skipped += 1;
+                continue;
+            }
+            let content = String::from_utf8_lossy(&bytes);
+            let lines: Vec<&str> = content.lines().collect();
+            let display = display_path(display_root, &path);
+
+            let mut selected = Vec::new();
+            for (idx, line) in lines.iter().enumerate() {
+                if !matcher.is_match(line) {
+                    continue;
+                }
+                seen += 1;
+                if seen <= page.offset {
+                    continue;
                 }
+                if seen > page.end() {
+                    truncated = true;
+                    if !selected.is_empty() {
+                        Self::render_file(&mut output, &display, &lines, &selected, context_lines);
+                    }
+                    break 'files;
+                }
+                selected.push(idx);
+                shown += 1;
+            }
+            if !selected.is_empty() {
+                Self::render_file(&mut output, &display, &lines, &selected, context_lines);
             }
         }
 
+        if truncated {
+            output.push(page.truncation_marker(shown, "matches"));
+        }
+        if binary_skipped > 0 {
+            output.push(format!("[skipped {binary_skipped} binary files]"));
+        }
+        output.join("\n")
+    }
+
+    /// Renders matches ripgrep-style: `path:line:text` for matches, `path-line-text` for
+    /// context lines and `--` between non-adjacent groups.
+    fn render_file(
+        output: &mut Vec<String>,
+        display: &str,
+        lines: &[&str],
+        matches: &[usize],
+        context_lines: usize,
+    ) {
+        let mut last_printed: Option<usize> = None;
+        for (position, &idx) in matches.iter().enumerate() {
+            let from = idx.saturating_sub(context_lines);
+            let to = (idx + context_lines).min(lines.len().saturating_sub(1));
+            let from = match last_printed {
+                Some(last) if last >= from => last + 1,
+                Some(_) if context_lines > 0 => {
+                    output.push("--".to_string());
+                    from
+                }
+                _ => from,
+            };
+            for (line_idx, line) in lines.iter().enumerate().take(to + 1).skip(from) {
+                let is_match = line_idx == idx || matches[position..].contains(&line_idx);
+                let separator = if is_match { ':' } else { '-' };
+                output.push(format!(
+                    "{display}{separator}{}{separator}{}",
+                    line_idx + 1,
+                    line.trim_end()
+                ));
+            }
+            last_printed = Some(to);
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for GrepTool {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+
+    fn description(&self) -> &'static str {
+        "Search file contents (literal or regex), respecting .gitignore/.ignore"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        let mut schema = json!({
+            "type": "object",
+            "properties": {
+                "pattern": {"type": "string"},
+                "path": {"type": "string"},
+                "mode": {"type": "string", "enum": ["literal", "regex"]},
+                "case_sensitive": {"type": "boolean"},
+                "context": {"type": "integer", "minimum": 0, "maximum": 10}
+            },
+            "required": ["pattern"]
+        });
+        merge_properties(&mut schema, filter_schema_properties());
+        schema
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let matcher = Self::build_matcher(&args)?;
+        let filters = WalkFilters::from_args(&args, "grep")?;
+        let page = Page::from_args(&args, 200);
+        let context_lines = args
+            .get("context")
+            .and_then(|value| value.as_u64())
+            .unwrap_or(0)
+            .min(10) as usize;
+        let target_path = args
+            .get("path")
+            .and_then(|value| value.as_str())
+            .unwrap_or(".");
+
+        let root = resolve_existing_path(&context.root_dir, target_path)?;
+        let display_root = context.root_dir.clone();
+        let output = tokio::task::spawn_blocking(move || {
+            Self::search(
+                &display_root,
+                &root,
+                &matcher,
+                &filters,
+                context_lines,
+                page,
+            )
+        })
+        .await?;
+
         Ok(ToolExecution {
             name: self.name().to_string(),
-            output: matches.into_iter().take(200).collect::<Vec<_>>().join("\n"),
+            output,
             is_error: false,
         })
     }
@@ -506,6 +939,12 @@ impl Tool for GrepTool {
 
 pub struct FindTool;
 
+impl FindTool {
+    fn has_glob_meta(pattern: &str) -> bool {
+        pattern.contains(['*', '?', '[', '{'])
+    }
+}
+
 #[async_trait]
 impl Tool for FindTool {
     fn name(&self) -> &'static str {
@@ -513,18 +952,24 @@ impl Tool for FindTool {
     }
 
     fn description(&self) -> &'static str {
-        "Find files by path pattern"
+        "Find files by glob or path substring, respecting .gitignore/.ignore"
     }
 
     fn parameters_schema(&self) -> Value {
-        json!({
+        let mut schema = json!({
             "type": "object",
             "properties": {
-                "pattern": {"type": "string"},
-                "path": {"type": "string"}
+                "pattern": {
+                    "type": "string",
+                    "description": "Glob (e.g. **/*.rs) or case-insensitive path substring"
+                },
+                "path": {"type": "string"},
+                "type": {"type": "string", "enum": ["any", "file", "dir"]}
             },
             "required": ["pattern"]
-        })
+        });
+        merge_properties(&mut schema, filter_schema_properties());
+        schema
     }
 
     async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
@@ -532,28 +977,86 @@ impl Tool for FindTool {
             .get("pattern")
             .and_then(|value| value.as_str())
             .ok_or_else(|| anyhow!("find.pattern is required"))?
-            .to_lowercase();
+            .to_string();
+        let kind = args
+            .get("type")
+            .and_then(|value| value.as_str())
+            .unwrap_or("any")
+            .to_string();
         let target_path = args
             .get("path")
             .and_then(|value| value.as_str())
             .unwrap_or(".");
+        let filters = WalkFilters::from_args(&args, "find")?;
+        let page = Page::from_args(&args, 500);
+
+        let glob = if Self::has_glob_meta(&pattern) {
+            Some(
+                Glob::new(&pattern)
+                    .with_context(|| format!("find.pattern: invalid glob: {pattern}"))?
+                    .compile_matcher(),
+            )
+        } else {
+            None
+        };
+        let needle = pattern.to_lowercase();
 
         let root = resolve_existing_path(&context.root_dir, target_path)?;
-        let mut files = Vec::new();
-
-        for entry in WalkDir::new(root)
-            .into_iter()
-            .filter_map(std::result::Result::ok)
-        {
-            let path = entry.path();
-            if path.to_string_lossy().to_lowercase().contains(&pattern) {
-                files.push(path.display().to_string());
+        let display_root = context.root_dir.clone();
+        let output = tokio::task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            let mut seen = 0usize;
+            let mut truncated = false;
+            for entry in filters.walk(&root) {
+                let is_dir = entry.file_type().is_some_and(|kind| kind.is_dir());
+                match kind.as_str() {
+                    "file" if is_dir => continue,
+                    "dir" if !is_dir => continue,
+                    _ => {}
+                }
+                let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+                if !filters.matches(relative) {
+                    continue;
+                }
+                let matched = match &glob {
+                    Some(glob) => {
+                        glob.is_match(relative)
+                            || entry
+                                .file_name()
+                                .to_str()
+                                .is_some_and(|name| glob.is_match(name))
+                    }
+                    None => relative.to_string_lossy().to_lowercase().contains(&needle),
+                };
+                if !matched {
+                    continue;
+                }
+                seen += 1;
+                if seen <= page.offset {
+                    continue;
+                }
+                if seen > page.end() {
+                    truncated = true;
+                    break;
+                }
+                let marker = if is_dir { "/" } else { "" };
+                files.push(format!(
+                    "{}{}",
+                    display_path(&display_root, entry.path()),
+                    marker
+                ));
             }
-        }
+            if truncated {
+                let shown = files.len();
+                files.push(page.truncation_marker(shown, "entries"));
+            }
+            files.join("\n")
+        })
+        .await?;
 
         Ok(ToolExecution {
             name: self.name().to_string(),
-            output: files.into_iter().take(500).collect::<Vec<_>>().join("\n"),
+            output,
             is_error: false,
         })
     }
@@ -568,16 +1071,18 @@ impl Tool for LsTool {
     }
 
     fn description(&self) -> &'static str {
-        "List files and directories"
+        "List files and directories, hiding ignored entries unless requested"
     }
 
     fn parameters_schema(&self) -> Value {
-        json!({
+        let mut schema = json!({
             "type": "object",
             "properties": {
                 "path": {"type": "string"}
             }
-        })
+        });
+        merge_properties(&mut schema, filter_schema_properties());
+        schema
     }
 
     async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
@@ -585,21 +1090,39 @@ impl Tool for LsTool {
             .get("path")
             .and_then(|value| value.as_str())
             .unwrap_or(".");
+        let filters = WalkFilters::from_args(&args, "ls")?.with_max_depth(1);
+        let page = Page::from_args(&args, 1000);
 
         let root = resolve_existing_path(&context.root_dir, target_path)?;
-        let mut items = fs::read_dir(root).await?;
-        let mut lines = Vec::new();
-
-        while let Some(entry) = items.next_entry().await? {
-            let file_type = entry.file_type().await?;
-            let marker = if file_type.is_dir() { "/" } else { "" };
-            lines.push(format!("{}{}", entry.file_name().to_string_lossy(), marker));
-        }
+        let output = tokio::task::spawn_blocking(move || {
+            let mut lines = Vec::new();
+            let mut truncated = false;
+            let entries = filters.walk(&root).filter(|entry| {
+                filters.matches(entry.path().strip_prefix(&root).unwrap_or(entry.path()))
+            });
+            for (idx, entry) in entries.enumerate() {
+                if idx < page.offset {
+                    continue;
+                }
+                if idx >= page.end() {
+                    truncated = true;
+                    break;
+                }
+                let is_dir = entry.file_type().is_some_and(|kind| kind.is_dir());
+                let marker = if is_dir { "/" } else { "" };
+                lines.push(format!("{}{}", entry.file_name().to_string_lossy(), marker));
+            }
+            if truncated {
+                let shown = lines.len();
+                lines.push(page.truncation_marker(shown, "entries"));
+            }
+            lines.join("\n")
+        })
+        .await?;
 
-        lines.sort();
         Ok(ToolExecution {
             name: self.name().to_string(),
-            output: lines.join("\n"),
+            output,
             is_error: false,
         })
     }
@@ -653,6 +1176,9 @@ impl Tool for MemoryGetTool {
     }
 }
 
+const DEFAULT_MEMORY_SEARCH_LIMIT: usize = 10;
+const MAX_MEMORY_SEARCH_LIMIT: usize = 50;
+
 pub struct MemorySearchTool;
 
 #[async_trait]
@@ -662,14 +1188,15 @@ impl Tool for MemorySearchTool {
     }
 
     fn description(&self) -> &'static str {
-        "Search keyword over MEMORY.md and memory/*.md"
+        "Search MEMORY.md and memory/*.md; returns the best-matching lines ranked by relevance"
     }
 
     fn parameters_schema(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
-                "keyword": {"type": "string"}
+                "keyword": {"type": "string", "description": "Words or a question to look up"},
+                "limit": {"type": "integer", "minimum": 1, "maximum": MAX_MEMORY_SEARCH_LIMIT}
             },
             "required": ["keyword"]
         })
@@ -680,8 +1207,14 @@ impl Tool for MemorySearchTool {
             .get("keyword")
             .and_then(|value| value.as_str())
             .ok_or_else(|| anyhow!("memory_search.keyword is required"))?;
+        let limit = args
+            .get("limit")
+            .and_then(|value| value.as_u64())
+            .map_or(DEFAULT_MEMORY_SEARCH_LIMIT, |limit| limit as usize)
+            .clamp(1, MAX_MEMORY_SEARCH_LIMIT);
 
-        let hits = context.memory.search(keyword).await?;
+        let mut hits = context.memory.search(keyword).await?;
+        hits.truncate(limit);
         let output = serde_json::to_string_pretty(&hits)?;
 
         Ok(ToolExecution {
@@ -691,3 +1224,69 @@ impl Tool for MemorySearchTool {
         })
     }
 }
+
+pub struct MemoryReadCuratedTool;
+
+#[async_trait]
+impl Tool for MemoryReadCuratedTool {
+    fn name(&self) -> &'static str {
+        "memory_read_curated"
+    }
+
+    fn description(&self) -> &'static str {
+        "Read the full curated memory file (MEMORY.md)"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    async fn execute(&self, _args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output: context.memory.read_curated().await?,
+            is_error: false,
+        })
+    }
+}
+
+pub struct MemoryWriteCuratedTool;
+
+#[async_trait]
+impl Tool for MemoryWriteCuratedTool {
+    fn name(&self) -> &'static str {
+        "memory_write_curated"
+    }
+
+    fn description(&self) -> &'static str {
+        "Replace the curated memory file (MEMORY.md) with new content"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "content": {"type": "string"}
+            },
+            "required": ["content"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let content = args
+            .get("content")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| anyhow!("memory_write_curated.content is required"))?;
+
+        context.memory.write_curated(content).await?;
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output: format!("wrote {} bytes to curated memory", content.len()),
+            is_error: false,
+        })
+    }
+}
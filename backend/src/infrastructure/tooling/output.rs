@@ -0,0 +1,300 @@
+//! Shared output budget for tool results.
+//!
+//! Outputs larger than the budget are written to a per-session spill area and replaced by a
+//! head/tail preview that carries a handle for the `read_output` tool. Previews and pages
+//! count lines the way `read_output` does: a line longer than a third of the budget is split
+//! into several, so every part of minified JSON or a base64 blob can be reached by offset.
+
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::fs;
+use uuid::Uuid;
+
+use crate::domain::types::{path_segment, ToolOutputTruncation};
+
+pub const DEFAULT_OUTPUT_MAX_BYTES: usize = 16 * 1024;
+pub const DEFAULT_OUTPUT_HEAD_LINES: usize = 60;
+pub const DEFAULT_OUTPUT_TAIL_LINES: usize = 30;
+
+/// Spilled outputs kept per session; older ones are removed when new ones are written.
+const MAX_SPILLED_OUTPUTS_PER_SESSION: usize = 32;
+const SHARED_SESSION_DIR: &str = "_shared";
+const HANDLE_PREFIX: &str = "out-";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputLimits {
+    pub max_bytes: usize,
+    pub head_lines: usize,
+    pub tail_lines: usize,
+}
+
+impl Default for OutputLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_OUTPUT_MAX_BYTES,
+            head_lines: DEFAULT_OUTPUT_HEAD_LINES,
+            tail_lines: DEFAULT_OUTPUT_TAIL_LINES,
+        }
+    }
+}
+
+/// A window of lines read back from a spilled output.
+#[derive(Clone, Debug)]
+pub struct OutputPage {
+    pub text: String,
+    /// 1-based line number of the first returned line.
+    pub first_line: usize,
+    /// 1-based line number of the last returned line (0 when `first_line` is past the end).
+    pub last_line: usize,
+    pub total_lines: usize,
+    /// The last returned line is part of a longer line that continues on the next one.
+    pub continues: bool,
+}
+
+#[derive(Debug)]
+pub struct OutputSpill {
+    root: PathBuf,
+    limits: OutputLimits,
+}
+
+impl OutputSpill {
+    pub fn new(root: impl Into<PathBuf>, limits: OutputLimits) -> Self {
+        Self {
+            root: root.into(),
+            limits: OutputLimits {
+                max_bytes: limits.max_bytes.max(1),
+                ..limits
+            },
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn limits(&self) -> OutputLimits {
+        self.limits
+    }
+
+    /// Returns `output` unchanged when it fits the budget; otherwise stores it and returns a
+    /// preview together with the truncation metadata.
+    pub async fn apply(
+        &self,
+        session_id: Option<&str>,
+        output: String,
+    ) -> Result<(String, Option<ToolOutputTruncation>)> {
+        if output.len() <= self.limits.max_bytes {
+            return Ok((output, None));
+        }
+
+        let lines = segments(&output, self.segment_bytes());
+        let total_lines = lines.len();
+        let head_budget = self.limits.max_bytes * 2 / 3;
+        let tail_budget = self.limits.max_bytes - head_budget;
+
+        let head = take_within(&lines, self.limits.head_lines, head_budget);
+        let remaining = &lines[head.len()..];
+        let mut tail: Vec<Segment<'_>> = take_within(
+            &remaining.iter().rev().copied().collect::<Vec<_>>(),
+            self.limits.tail_lines.min(remaining.len()),
+            tail_budget,
+        )
+        .to_vec();
+        tail.reverse();
+        if head.len() + tail.len() == total_lines {
+            // Only line endings were dropped; there is nothing left to page through.
+            return Ok((join_segments(&lines), None));
+        }
+
+        let handle = format!("{HANDLE_PREFIX}{}", Uuid::new_v4().simple());
+        let dir = self.session_dir(session_id);
+        fs::create_dir_all(&dir).await?;
+        let path = dir.join(format!("{handle}.txt"));
+        fs::write(&path, &output)
+            .await
+            .with_context(|| format!("failed to spill tool output: {}", path.display()))?;
+        self.prune(&dir).await;
+
+        let truncation = ToolOutputTruncation {
+            handle: handle.clone(),
+            total_bytes: output.len(),
+            total_lines,
+            head_lines: head.len(),
+            tail_lines: tail.len(),
+        };
+
+        let mut preview = join_segments(head);
+        preview.push_str(&format!(
+            "\n[output truncated: {} bytes, {} lines total; showing first {} and last {} lines. \
+             Use read_output with handle=\"{}\" and offset={} to read the rest]",
+            truncation.total_bytes,
+            truncation.total_lines,
+            truncation.head_lines,
+            truncation.tail_lines,
+            handle,
+            truncation.head_lines + 1,
+        ));
+        if !tail.is_empty() {
+            preview.push('\n');
+            preview.push_str(&join_segments(&tail));
+        }
+
+        tracing::debug!(
+            session_id = session_id.unwrap_or(SHARED_SESSION_DIR),
+            handle = %handle,
+            total_bytes = truncation.total_bytes,
+            total_lines = truncation.total_lines,
+            "spilled oversized tool output"
+        );
+        Ok((preview, Some(truncation)))
+    }
+
+    /// Reads up to `limit` lines starting at the 1-based line `offset`, stopping early if the
+    /// page would exceed the output budget.
+    pub async fn read(
+        &self,
+        session_id: Option<&str>,
+        handle: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<OutputPage> {
+        if !is_valid_handle(handle) {
+            return Err(anyhow!("invalid output handle: {handle}"));
+        }
+        let path = self.session_dir(session_id).join(format!("{handle}.txt"));
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|_| anyhow!("output handle not found: {handle}"))?;
+
+        let lines = segments(&content, self.segment_bytes());
+        let first_line = offset.max(1);
+        let selected = take_within(
+            lines.get(first_line - 1..).unwrap_or_default(),
+            limit.max(1),
+            self.limits.max_bytes,
+        );
+        Ok(OutputPage {
+            last_line: if selected.is_empty() {
+                0
+            } else {
+                first_line + selected.len() - 1
+            },
+            text: join_segments(selected),
+            continues: selected.last().is_some_and(|segment| segment.continues),
+            first_line,
+            total_lines: lines.len(),
+        })
+    }
+
+    /// Longest line kept whole; a third of the budget, so one always fits the tail preview.
+    fn segment_bytes(&self) -> usize {
+        (self.limits.max_bytes / 3).max(1)
+    }
+
+    fn session_dir(&self, session_id: Option<&str>) -> PathBuf {
+        let name = session_id
+            .map(path_segment)
+            .unwrap_or_else(|| SHARED_SESSION_DIR.to_string());
+        self.root.join(name)
+    }
+
+    async fn prune(&self, dir: &Path) {
+        let Ok(mut entries) = fs::read_dir(dir).await else {
+            return;
+        };
+        let mut files: Vec<(SystemTime, PathBuf)> = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let modified = entry
+                .metadata()
+                .await
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((modified, entry.path()));
+        }
+        if files.len() <= MAX_SPILLED_OUTPUTS_PER_SESSION {
+            return;
+        }
+        files.sort_by_key(|(modified, _)| *modified);
+        let excess = files.len() - MAX_SPILLED_OUTPUTS_PER_SESSION;
+        for (_, path) in files.into_iter().take(excess) {
+            if let Err(error) = fs::remove_file(&path).await {
+                tracing::warn!(path = %path.display(), error = %error, "failed to prune spilled output");
+            }
+        }
+    }
+}
+
+/// One line as counted by previews and `read_output`: a whole line of the output, or a piece
+/// of a line longer than the segment size.
+#[derive(Clone, Copy, Debug)]
+struct Segment<'a> {
+    text: &'a str,
+    /// The next segment continues the same line.
+    continues: bool,
+}
+
+/// Splits `content` into lines, cutting lines longer than `max_bytes` at character boundaries.
+fn segments(content: &str, max_bytes: usize) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    for line in content.lines() {
+        let mut rest = line;
+        while rest.len() > max_bytes {
+            let mut end = max_bytes;
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            if end == 0 {
+                // A single character wider than the segment size.
+                end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+            }
+            segments.push(Segment {
+                text: &rest[..end],
+                continues: true,
+            });
+            rest = &rest[end..];
+        }
+        segments.push(Segment {
+            text: rest,
+            continues: false,
+        });
+    }
+    segments
+}
+
+/// Joins segments back into text; pieces of one line are joined without a newline.
+fn join_segments(segments: &[Segment<'_>]) -> String {
+    let mut text = String::new();
+    for (index, segment) in segments.iter().enumerate() {
+        text.push_str(segment.text);
+        if !segment.continues && index + 1 < segments.len() {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// The longest prefix of at most `max_lines` segments whose joined size stays within
+/// `max_bytes`.
+fn take_within<'a, 'b>(
+    segments: &'b [Segment<'a>],
+    max_lines: usize,
+    max_bytes: usize,
+) -> &'b [Segment<'a>] {
+    let mut used = 0usize;
+    let mut count = 0usize;
+    for segment in segments.iter().take(max_lines) {
+        used += segment.text.len() + usize::from(count > 0);
+        if used > max_bytes {
+            break;
+        }
+        count += 1;
+    }
+    &segments[..count]
+}
+
+fn is_valid_handle(handle: &str) -> bool {
+    handle
+        .strip_prefix(HANDLE_PREFIX)
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|ch| ch.is_ascii_alphanumeric()))
+}
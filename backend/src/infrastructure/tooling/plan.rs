@@ -0,0 +1,139 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use super::{Tool, ToolContext};
+use crate::domain::types::{render_plan, PlanItem, PlanStatus, ToolExecution};
+
+const MAX_PLAN_ITEMS: usize = 50;
+const MAX_ITEM_CHARS: usize = 300;
+
+fn session_plan(tool: &str, context: &ToolContext) -> Result<Arc<Mutex<Vec<PlanItem>>>> {
+    context
+        .plan
+        .clone()
+        .ok_or_else(|| anyhow!("{tool} is only available inside a chat session"))
+}
+
+fn parse_status(tool: &str, value: Option<&Value>) -> Result<PlanStatus> {
+    match value.and_then(|value| value.as_str()).unwrap_or("pending") {
+        "pending" => Ok(PlanStatus::Pending),
+        "in_progress" => Ok(PlanStatus::InProgress),
+        "done" | "completed" => Ok(PlanStatus::Done),
+        other => Err(anyhow!(
+            "{tool}: unknown status {other} (expected pending, in_progress or done)"
+        )),
+    }
+}
+
+/// `todo_write`: replaces the session plan with the given checklist.
+pub struct TodoWriteTool;
+
+#[async_trait]
+impl Tool for TodoWriteTool {
+    fn name(&self) -> &'static str {
+        "todo_write"
+    }
+
+    fn description(&self) -> &'static str {
+        "Replace the session's task checklist. Use it for multi-step work: list every step, keep exactly one in_progress while working, and mark steps done as soon as they are finished"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "todos": {
+                    "type": "array",
+                    "maxItems": MAX_PLAN_ITEMS,
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "content": {"type": "string"},
+                            "status": {"type": "string", "enum": ["pending", "in_progress", "done"]}
+                        },
+                        "required": ["content", "status"]
+                    }
+                }
+            },
+            "required": ["todos"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let todos = args
+            .get("todos")
+            .and_then(|value| value.as_array())
+            .ok_or_else(|| anyhow!("todo_write.todos is required"))?;
+        if todos.len() > MAX_PLAN_ITEMS {
+            return Err(anyhow!(
+                "todo_write: at most {MAX_PLAN_ITEMS} items, got {}",
+                todos.len()
+            ));
+        }
+        let items = todos
+            .iter()
+            .map(|todo| {
+                let content = todo
+                    .get("content")
+                    .and_then(|value| value.as_str())
+                    .map(|content| content.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .filter(|content| !content.is_empty())
+                    .ok_or_else(|| anyhow!("todo_write: every item needs content"))?;
+                Ok(PlanItem {
+                    content: content.chars().take(MAX_ITEM_CHARS).collect(),
+                    status: parse_status("todo_write", todo.get("status"))?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let plan = session_plan(self.name(), context)?;
+        let output = {
+            let mut plan = plan
+                .lock()
+                .map_err(|_| anyhow!("session plan lock poisoned"))?;
+            *plan = items;
+            render_plan(&plan)
+        };
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output,
+            is_error: false,
+        })
+    }
+}
+
+/// `todo_read`: the current session plan.
+pub struct TodoReadTool;
+
+#[async_trait]
+impl Tool for TodoReadTool {
+    fn name(&self) -> &'static str {
+        "todo_read"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show the session's task checklist with each step's status"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({"type": "object", "properties": {}})
+    }
+
+    async fn execute(&self, _args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let plan = session_plan(self.name(), context)?;
+        let output = render_plan(
+            &plan
+                .lock()
+                .map_err(|_| anyhow!("session plan lock poisoned"))?,
+        );
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output,
+            is_error: false,
+        })
+    }
+}
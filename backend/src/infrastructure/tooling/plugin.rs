@@ -0,0 +1,221 @@
+//! External executable tools declared under `tools.plugins`.
+//!
+//! A plugin receives its arguments as JSON on stdin and prints a JSON `ToolExecution`
+//! (`{"output": "...", "is_error": false}`) on stdout. Plugins without a configured
+//! description or schema are asked for them by running `<command> --describe`.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Output, Stdio};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use super::{Tool, ToolContext, ToolRegistry};
+use crate::domain::types::ToolExecution;
+
+pub const DEFAULT_PLUGIN_TIMEOUT_SECS: u64 = 30;
+
+const DESCRIBE_ARG: &str = "--describe";
+/// Variables passed through from the backend environment; everything else is scrubbed.
+const INHERITED_ENV: &[&str] = &["PATH", "HOME", "LANG", "LC_ALL", "TZ", "TMPDIR"];
+const MAX_STDERR_CHARS: usize = 2000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginSpec {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub description: Option<String>,
+    pub parameters_schema: Option<Value>,
+    pub timeout: Duration,
+}
+
+/// `--describe` response; every field is optional so config values can fill the gaps.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PluginDescription {
+    description: Option<String>,
+    #[serde(alias = "parameters", alias = "inputSchema")]
+    parameters_schema: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    output: String,
+    #[serde(default)]
+    is_error: bool,
+}
+
+pub struct PluginTool {
+    spec: PluginSpec,
+    description: String,
+    schema: Value,
+}
+
+impl PluginTool {
+    /// Resolves the description and schema, running `--describe` when either is missing
+    /// from the config.
+    pub async fn load(spec: PluginSpec) -> Result<Self> {
+        let described = if spec.description.is_none() || spec.parameters_schema.is_none() {
+            describe(&spec).await?
+        } else {
+            PluginDescription::default()
+        };
+        let description = spec
+            .description
+            .clone()
+            .or(described.description)
+            .unwrap_or_else(|| format!("External tool {}", spec.name));
+        let schema = spec
+            .parameters_schema
+            .clone()
+            .or(described.parameters_schema)
+            .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+        Ok(Self {
+            spec,
+            description,
+            schema,
+        })
+    }
+}
+
+fn command_for(spec: &PluginSpec, cwd: Option<&Path>) -> Command {
+    let mut command = Command::new(&spec.command);
+    command
+        .args(&spec.args)
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    for key in INHERITED_ENV {
+        if let Ok(value) = std::env::var(key) {
+            command.env(key, value);
+        }
+    }
+    command.envs(&spec.env);
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+    command
+}
+
+/// Runs the plugin with `input` on stdin and waits for it to exit within the timeout.
+async fn run(spec: &PluginSpec, mut command: Command, input: &[u8]) -> Result<Output> {
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("failed to start plugin {}: {}", spec.name, spec.command))?;
+    let mut stdin = child.stdin.take().context("plugin stdin unavailable")?;
+
+    let write = async move {
+        // A plugin may exit without reading stdin; a broken pipe is not an error then.
+        let _ = stdin.write_all(input).await;
+    };
+    let run = async {
+        let ((), output) = tokio::join!(write, child.wait_with_output());
+        output
+    };
+    tokio::time::timeout(spec.timeout, run)
+        .await
+        .map_err(|_| {
+            anyhow!(
+                "plugin {} timed out after {}s",
+                spec.name,
+                spec.timeout.as_secs_f32()
+            )
+        })?
+        .with_context(|| format!("plugin {} failed", spec.name))
+}
+
+async fn describe(spec: &PluginSpec) -> Result<PluginDescription> {
+    let mut command = command_for(spec, None);
+    command.arg(DESCRIBE_ARG);
+    let output = run(spec, command, b"").await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "plugin {} --describe exited with {}: {}",
+            spec.name,
+            output.status,
+            stderr_excerpt(&output)
+        ));
+    }
+    serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("plugin {} --describe returned invalid JSON", spec.name))
+}
+
+fn stderr_excerpt(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr)
+        .trim()
+        .chars()
+        .take(MAX_STDERR_CHARS)
+        .collect()
+}
+
+#[async_trait]
+impl Tool for PluginTool {
+    fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.schema.clone()
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let input = serde_json::to_vec(&args)?;
+        let output = run(
+            &self.spec,
+            command_for(&self.spec, Some(&context.root_dir)),
+            &input,
+        )
+        .await?;
+
+        match serde_json::from_slice::<PluginResponse>(&output.stdout) {
+            Ok(response) => Ok(ToolExecution {
+                name: self.spec.name.clone(),
+                output: response.output,
+                is_error: response.is_error || !output.status.success(),
+            }),
+            Err(_) if !output.status.success() => Err(anyhow!(
+                "plugin {} exited with {}: {}",
+                self.spec.name,
+                output.status,
+                stderr_excerpt(&output)
+            )),
+            Err(error) => Err(anyhow!(
+                "plugin {} returned invalid JSON: {error}",
+                self.spec.name
+            )),
+        }
+    }
+}
+
+/// Loads every configured plugin into the registry. Plugins that fail to describe
+/// themselves, or whose name is invalid or already taken, are logged and skipped.
+pub async fn register_plugin_tools(registry: &mut ToolRegistry, plugins: &[PluginSpec]) -> usize {
+    let mut registered = 0usize;
+    for spec in plugins {
+        match PluginTool::load(spec.clone()).await {
+            Ok(tool) => {
+                if registry.register_external(tool, &format!("plugin {}", spec.command)) {
+                    tracing::info!(plugin = %spec.name, command = %spec.command, "plugin tool registered");
+                    registered += 1;
+                }
+            }
+            Err(error) => {
+                tracing::warn!(plugin = %spec.name, error = %error, "plugin unavailable; skipping");
+            }
+        }
+    }
+    registered
+}
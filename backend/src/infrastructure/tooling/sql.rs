@@ -0,0 +1,391 @@
+//! Read-only SQL over SQLite databases and CSV files in the working directory.
+//!
+//! Databases are opened with `SQLITE_OPEN_READ_ONLY`; CSV files are mounted as `temp`
+//! virtual tables named after the file stem, after which the connection is switched to
+//! `query_only`. Statements must be a single SELECT that SQLite itself reports as read-only,
+//! and a progress handler interrupts queries that run past the timeout.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::document::markdown_table;
+use super::{resolve_existing_path, Tool, ToolContext, ToolRegistry};
+use crate::domain::types::ToolExecution;
+
+pub const DEFAULT_SQL_MAX_ROWS: usize = 200;
+pub const DEFAULT_SQL_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_QUERY_ROWS: usize = 50;
+const MAX_CELL_CHARS: usize = 200;
+
+/// Row cap and wall-clock budget for `sql_query`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SqlLimits {
+    pub max_rows: usize,
+    pub timeout: Duration,
+}
+
+impl Default for SqlLimits {
+    fn default() -> Self {
+        Self {
+            max_rows: DEFAULT_SQL_MAX_ROWS,
+            timeout: Duration::from_secs(DEFAULT_SQL_TIMEOUT_SECS),
+        }
+    }
+}
+
+pub fn register_sql_tools(registry: &mut ToolRegistry, limits: SqlLimits) {
+    registry.register(SqlQueryTool::new(limits));
+    registry.register(SqlSchemaTool);
+}
+
+fn source_properties() -> Value {
+    json!({
+        "database": {"type": "string", "description": "SQLite database file"},
+        "csv": {
+            "type": "array",
+            "items": {"type": "string"},
+            "description": "CSV files to mount as tables named after the file stem (first row is the header)"
+        }
+    })
+}
+
+/// Paths of the `database` and `csv` arguments, resolved inside the working directory.
+fn sources(tool: &str, args: &Value, root_dir: &Path) -> Result<(Option<PathBuf>, Vec<PathBuf>)> {
+    let database = args
+        .get("database")
+        .and_then(|value| value.as_str())
+        .map(|path| resolve_existing_path(root_dir, path))
+        .transpose()?;
+    let csv = match args.get("csv") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(path)) => vec![resolve_existing_path(root_dir, path)?],
+        Some(Value::Array(paths)) => paths
+            .iter()
+            .map(|path| {
+                path.as_str()
+                    .ok_or_else(|| anyhow!("{tool}.csv must be a list of paths"))
+                    .and_then(|path| resolve_existing_path(root_dir, path))
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => return Err(anyhow!("{tool}.csv must be a list of paths")),
+    };
+    if database.is_none() && csv.is_empty() {
+        return Err(anyhow!("{tool} needs a database or csv files"));
+    }
+    Ok((database, csv))
+}
+
+/// `sales-2024.csv` -> `sales_2024`.
+fn table_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut name: String = stem
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with(|ch: char| ch.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// A read-only connection with every CSV file mounted. Blocking.
+fn open(database: Option<&Path>, csv: &[PathBuf]) -> Result<Connection> {
+    let connection = match database {
+        Some(path) => Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?,
+        None => Connection::open_in_memory()?,
+    };
+    if !csv.is_empty() {
+        rusqlite::vtab::csvtab::load_module(&connection)?;
+    }
+    let mut mounted = Vec::new();
+    for path in csv {
+        let name = table_name(path);
+        if mounted.contains(&name) {
+            return Err(anyhow!("two csv files map to the same table name: {name}"));
+        }
+        connection.execute_batch(&format!(
+            "CREATE VIRTUAL TABLE temp.{} USING csv(filename='{}', header=yes)",
+            quote_identifier(&name),
+            path.to_string_lossy().replace('\'', "''")
+        ))?;
+        mounted.push(name);
+    }
+    connection.pragma_update(None, "query_only", true)?;
+    Ok(connection)
+}
+
+fn render_value(value: ValueRef<'_>) -> String {
+    let text = match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(value) => value.to_string(),
+        ValueRef::Real(value) => value.to_string(),
+        ValueRef::Text(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        ValueRef::Blob(bytes) => format!("<blob {} bytes>", bytes.len()),
+    };
+    if text.chars().count() > MAX_CELL_CHARS {
+        let truncated: String = text.chars().take(MAX_CELL_CHARS).collect();
+        format!("{truncated}…")
+    } else {
+        text
+    }
+}
+
+/// Rejects anything but a single read-only SELECT (or `WITH ... SELECT`, `VALUES`).
+fn ensure_select(query: &str) -> Result<()> {
+    let keyword = query
+        .trim_start_matches(|ch: char| ch.is_whitespace() || ch == '(')
+        .split(|ch: char| !ch.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+    if !matches!(keyword.as_str(), "SELECT" | "WITH" | "VALUES") {
+        return Err(anyhow!("sql_query only runs SELECT statements"));
+    }
+    Ok(())
+}
+
+/// Runs `query` and renders at most `limit` rows as a markdown table. Blocking.
+fn run_query(
+    connection: &Connection,
+    query: &str,
+    limit: usize,
+    timeout: Duration,
+) -> Result<String> {
+    ensure_select(query)?;
+    let started = Instant::now();
+    connection.progress_handler(1000, Some(move || started.elapsed() > timeout));
+
+    let mut statement = connection.prepare(query).map_err(|error| match error {
+        rusqlite::Error::MultipleStatement => anyhow!("sql_query runs a single statement"),
+        other => anyhow!("sql_query: {other}"),
+    })?;
+    if !statement.readonly() {
+        return Err(anyhow!("sql_query only runs SELECT statements"));
+    }
+    let columns: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let column_count = columns.len();
+
+    let mut table = vec![columns];
+    let mut truncated = false;
+    let mut rows = statement
+        .query([])
+        .map_err(|error| query_error(error, timeout))?;
+    while let Some(row) = rows.next().map_err(|error| query_error(error, timeout))? {
+        if table.len() > limit {
+            truncated = true;
+            break;
+        }
+        table.push(
+            (0..column_count)
+                .map(|index| row.get_ref(index).map(render_value))
+                .collect::<rusqlite::Result<_>>()?,
+        );
+    }
+
+    let count = table.len() - 1;
+    if count == 0 {
+        return Ok(format!("(no rows)\ncolumns: {}", table[0].join(", ")));
+    }
+    let mut output = markdown_table(&table);
+    if truncated {
+        output.push_str(&format!(
+            "\n[showing first {count} rows; add LIMIT/WHERE or raise limit for more]"
+        ));
+    } else {
+        output.push_str(&format!(
+            "\n({count} row{})",
+            if count == 1 { "" } else { "s" }
+        ));
+    }
+    Ok(output)
+}
+
+fn query_error(error: rusqlite::Error, timeout: Duration) -> anyhow::Error {
+    match error.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::OperationInterrupted) => {
+            anyhow!("sql_query timed out after {}s", timeout.as_secs_f32())
+        }
+        _ => anyhow!("sql_query: {error}"),
+    }
+}
+
+/// Tables and views with their columns, optionally only `table`. Blocking.
+fn describe_schema(connection: &Connection, table: Option<&str>) -> Result<String> {
+    let mut statement = connection.prepare(
+        "SELECT type, name FROM sqlite_schema WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'
+         UNION ALL
+         SELECT type, name FROM sqlite_temp_schema WHERE type IN ('table', 'view')
+         ORDER BY name",
+    )?;
+    let objects: Vec<(String, String)> = statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let objects: Vec<(String, String)> = objects
+        .into_iter()
+        .filter(|(_, name)| table.is_none_or(|table| name.eq_ignore_ascii_case(table)))
+        .collect();
+    if objects.is_empty() {
+        return Ok(match table {
+            Some(table) => format!("no table named {table}"),
+            None => "no tables".to_string(),
+        });
+    }
+
+    let mut sections = Vec::new();
+    for (kind, name) in objects {
+        let mut columns = connection.prepare(&format!(
+            "SELECT name, type, \"notnull\", pk FROM pragma_table_info({})",
+            quote_literal(&name)
+        ))?;
+        let lines: Vec<String> = columns
+            .query_map([], |row| {
+                let column: String = row.get(0)?;
+                let declared: String = row.get(1)?;
+                let not_null: bool = row.get(2)?;
+                let primary_key: i64 = row.get(3)?;
+                let mut line = format!("  {column}");
+                if !declared.is_empty() {
+                    line.push(' ');
+                    line.push_str(&declared);
+                }
+                if primary_key > 0 {
+                    line.push_str(" primary key");
+                }
+                if not_null {
+                    line.push_str(" not null");
+                }
+                Ok(line)
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        sections.push(format!("{kind} {name}\n{}", lines.join("\n")));
+    }
+    Ok(sections.join("\n"))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// `sql_query`: one read-only SELECT against a SQLite database and/or CSV files.
+pub struct SqlQueryTool {
+    limits: SqlLimits,
+}
+
+impl SqlQueryTool {
+    pub fn new(limits: SqlLimits) -> Self {
+        Self { limits }
+    }
+}
+
+#[async_trait]
+impl Tool for SqlQueryTool {
+    fn name(&self) -> &'static str {
+        "sql_query"
+    }
+
+    fn description(&self) -> &'static str {
+        "Run one read-only SELECT against a SQLite database and/or CSV files (mounted as tables named after the file stem; CSV columns are TEXT, CAST for numeric comparisons). Returns a markdown table"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        let mut properties = source_properties();
+        properties["query"] = json!({"type": "string"});
+        properties["limit"] =
+            json!({"type": "integer", "minimum": 1, "maximum": self.limits.max_rows});
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let query = args
+            .get("query")
+            .and_then(|value| value.as_str())
+            .map(|query| query.trim().trim_end_matches(';').trim().to_string())
+            .filter(|query| !query.is_empty())
+            .ok_or_else(|| anyhow!("sql_query.query is required"))?;
+        let limit = args
+            .get("limit")
+            .and_then(|value| value.as_u64())
+            .map_or(DEFAULT_QUERY_ROWS, |limit| limit as usize)
+            .clamp(1, self.limits.max_rows.max(1));
+        let (database, csv) = sources("sql_query", &args, &context.root_dir)?;
+        let timeout = self.limits.timeout;
+
+        let output = tokio::task::spawn_blocking(move || {
+            let connection = open(database.as_deref(), &csv)?;
+            run_query(&connection, &query, limit, timeout)
+        })
+        .await??;
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output,
+            is_error: false,
+        })
+    }
+}
+
+/// `sql_schema`: tables, views and columns of a SQLite database and/or CSV files.
+pub struct SqlSchemaTool;
+
+#[async_trait]
+impl Tool for SqlSchemaTool {
+    fn name(&self) -> &'static str {
+        "sql_schema"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the tables, views and columns of a SQLite database and/or CSV files, optionally for one table"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        let mut properties = source_properties();
+        properties["table"] = json!({"type": "string"});
+        json!({
+            "type": "object",
+            "properties": properties
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let table = args
+            .get("table")
+            .and_then(|value| value.as_str())
+            .map(str::to_string);
+        let (database, csv) = sources("sql_schema", &args, &context.root_dir)?;
+
+        let output = tokio::task::spawn_blocking(move || {
+            let connection = open(database.as_deref(), &csv)?;
+            describe_schema(&connection, table.as_deref())
+        })
+        .await??;
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output,
+            is_error: false,
+        })
+    }
+}
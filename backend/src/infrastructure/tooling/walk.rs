@@ -0,0 +1,192 @@
+//! Shared traversal and filtering for the `grep`, `find` and `ls` tools.
+
+use anyhow::{anyhow, Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::{DirEntry, WalkBuilder};
+use serde_json::{json, Value};
+use std::path::Path;
+
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+const MAX_SEARCH_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Traversal filters parsed from tool arguments.
+///
+/// `.gitignore`, `.ignore` and git exclude files are honoured unless `no_ignore` is set;
+/// hidden entries are skipped unless `hidden` is set. `.git` is never traversed.
+#[derive(Clone, Debug, Default)]
+pub struct WalkFilters {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    pub hidden: bool,
+    pub no_ignore: bool,
+    pub max_depth: Option<usize>,
+}
+
+impl WalkFilters {
+    pub fn from_args(args: &Value, tool: &str) -> Result<Self> {
+        Ok(Self {
+            include: parse_globs(args.get("include"), tool, "include")?,
+            exclude: parse_globs(args.get("exclude"), tool, "exclude")?,
+            hidden: args.get("hidden").and_then(Value::as_bool).unwrap_or(false),
+            no_ignore: args
+                .get("no_ignore")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            max_depth: None,
+        })
+    }
+
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Entries under `root` in a stable (name-sorted) order, excluding `root` itself.
+    pub fn walk(&self, root: &Path) -> impl Iterator<Item = DirEntry> {
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .hidden(!self.hidden)
+            .ignore(!self.no_ignore)
+            .git_ignore(!self.no_ignore)
+            .git_global(!self.no_ignore)
+            .git_exclude(!self.no_ignore)
+            .parents(!self.no_ignore)
+            .require_git(false)
+            .max_depth(self.max_depth)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(|entry| entry.file_name() != ".git");
+        builder
+            .build()
+            .filter_map(std::result::Result::ok)
+            .filter(|entry| entry.depth() > 0)
+    }
+
+    /// Applies include/exclude globs to a path relative to the search root.
+    pub fn matches(&self, relative: &Path) -> bool {
+        if let Some(include) = &self.include {
+            if !include.is_match(relative) {
+                return false;
+            }
+        }
+        if let Some(exclude) = &self.exclude {
+            if exclude.is_match(relative) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn parse_globs(value: Option<&Value>, tool: &str, field: &str) -> Result<Option<GlobSet>> {
+    let patterns: Vec<String> = match value {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::String(single)) => vec![single.clone()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow!("{tool}.{field} must contain only strings"))
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => {
+            return Err(anyhow!(
+                "{tool}.{field} must be a string or array of strings"
+            ))
+        }
+    };
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            Glob::new(&pattern)
+                .with_context(|| format!("{tool}.{field}: invalid glob: {pattern}"))?,
+        );
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Offset/limit pagination shared by the listing tools.
+#[derive(Clone, Copy, Debug)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Page {
+    pub fn from_args(args: &Value, default_limit: usize) -> Self {
+        let offset = args.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let limit = args
+            .get("max_results")
+            .and_then(Value::as_u64)
+            .map(|value| value as usize)
+            .unwrap_or(default_limit)
+            .max(1);
+        Self { offset, limit }
+    }
+
+    pub fn end(&self) -> usize {
+        self.offset + self.limit
+    }
+
+    /// Marker appended when more items exist past this page.
+    pub fn truncation_marker(&self, shown: usize, unit: &str) -> String {
+        format!(
+            "[truncated: showing {unit} {}-{}; more results available, continue with offset={}]",
+            self.offset + 1,
+            self.offset + shown,
+            self.offset + shown
+        )
+    }
+}
+
+/// JSON schema fragments for the shared filter and paging arguments.
+pub fn filter_schema_properties() -> Value {
+    json!({
+        "include": {
+            "type": "array",
+            "items": {"type": "string"},
+            "description": "Glob patterns a path must match (relative to the search path)"
+        },
+        "exclude": {
+            "type": "array",
+            "items": {"type": "string"},
+            "description": "Glob patterns to skip"
+        },
+        "hidden": {"type": "boolean", "description": "Include hidden files and directories"},
+        "no_ignore": {"type": "boolean", "description": "Do not respect .gitignore/.ignore files"},
+        "offset": {"type": "integer", "minimum": 0, "description": "Continuation offset from a truncated result"},
+        "max_results": {"type": "integer", "minimum": 1}
+    })
+}
+
+pub fn merge_properties(schema: &mut Value, extra: Value) {
+    if let (Some(properties), Value::Object(extra)) = (
+        schema.get_mut("properties").and_then(Value::as_object_mut),
+        extra,
+    ) {
+        properties.extend(extra);
+    }
+}
+
+/// Heuristic binary detection: a NUL byte within the first 8 KiB.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+pub fn is_searchable_size(entry: &DirEntry) -> bool {
+    entry
+        .metadata()
+        .map(|metadata| metadata.len() <= MAX_SEARCH_FILE_BYTES)
+        .unwrap_or(false)
+}
+
+pub fn display_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
@@ -0,0 +1,420 @@
+//! WebAssembly plugin tools for code that is not fully trusted.
+//!
+//! Each plugin lives in `<plugins dir>/<name>/` with a `manifest.json` and a wasm32-wasi
+//! command module. The module reads its arguments as JSON on stdin and writes either a JSON
+//! `ToolExecution` or plain text to stdout. It only gets the capabilities its manifest
+//! declares:
+//!
+//! - `read_working_dir` / `write_working_dir`: the working directory preopened as `.`
+//! - `memory_search`: the `chaos.memory_search` host function
+//! - `http_hosts`: the `chaos.http_get` host function, limited to those hosts
+//!
+//! Host functions take a UTF-8 `(ptr, len)` argument and return the length of a result
+//! staged on the host (read it with `chaos.result_read(ptr, len)`), or `-1` when the
+//! capability is not granted and `-2` when the call failed; the staged result then holds the
+//! error message.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use wasmtime::{
+    Caller, Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap,
+};
+use wasmtime_wasi::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{DirPerms, FilePerms, I32Exit, WasiCtxBuilder};
+
+use super::{FetchPolicy, FetchTool, Tool, ToolContext, ToolRegistry};
+use crate::domain::ports::MemoryPort;
+use crate::domain::types::ToolExecution;
+
+pub const DEFAULT_WASM_FUEL: u64 = 1_000_000_000;
+pub const DEFAULT_WASM_MEMORY_MB: usize = 64;
+pub const DEFAULT_WASM_TIMEOUT_SECS: u64 = 10;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const HOST_MODULE: &str = "chaos";
+const MAX_STDIO_BYTES: usize = 1024 * 1024;
+/// Fuel consumed between cooperative yields, so the wall-clock timeout can fire.
+const FUEL_YIELD_INTERVAL: u64 = 100_000;
+const DENIED: i32 = -1;
+const FAILED: i32 = -2;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct WasmCapabilities {
+    pub read_working_dir: bool,
+    pub write_working_dir: bool,
+    pub memory_search: bool,
+    pub http_hosts: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct WasmLimits {
+    pub fuel: u64,
+    pub memory_mb: usize,
+    pub timeout_secs: u64,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: DEFAULT_WASM_FUEL,
+            memory_mb: DEFAULT_WASM_MEMORY_MB,
+            timeout_secs: DEFAULT_WASM_TIMEOUT_SECS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WasmPluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_parameters_schema")]
+    pub parameters_schema: Value,
+    /// Module file relative to the plugin directory; defaults to `<name>.wasm`.
+    #[serde(default)]
+    pub module: Option<String>,
+    #[serde(default)]
+    pub capabilities: WasmCapabilities,
+    #[serde(default)]
+    pub limits: WasmLimits,
+}
+
+fn default_parameters_schema() -> Value {
+    json!({"type": "object", "properties": {}})
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    output: String,
+    #[serde(default)]
+    is_error: bool,
+}
+
+struct PluginState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+    memory: Option<Arc<dyn MemoryPort>>,
+    http: Option<Arc<FetchTool>>,
+    /// Result of the last host call, waiting for `chaos.result_read`.
+    staged: Vec<u8>,
+}
+
+pub struct WasmPluginTool {
+    manifest: WasmPluginManifest,
+    engine: Engine,
+    module: Module,
+    linker: Arc<Linker<PluginState>>,
+    http: Option<Arc<FetchTool>>,
+}
+
+impl WasmPluginTool {
+    /// Loads and compiles the plugin in `dir`.
+    pub fn load(engine: &Engine, dir: &Path) -> Result<Self> {
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let raw = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+        let manifest: WasmPluginManifest = serde_json::from_str(&raw)
+            .with_context(|| format!("invalid plugin manifest: {}", manifest_path.display()))?;
+        if manifest.name.trim().is_empty() {
+            return Err(anyhow!(
+                "plugin manifest has no name: {}",
+                manifest_path.display()
+            ));
+        }
+
+        let module_path = dir.join(
+            manifest
+                .module
+                .clone()
+                .unwrap_or_else(|| format!("{}.wasm", manifest.name)),
+        );
+        super::ensure_within_root(dir, &module_path)?;
+        let module = Module::from_file(engine, &module_path)
+            .with_context(|| format!("failed to compile {}", module_path.display()))?;
+
+        let http = (!manifest.capabilities.http_hosts.is_empty()).then(|| {
+            Arc::new(FetchTool::new(FetchPolicy {
+                allow_domains: manifest.capabilities.http_hosts.clone(),
+                ..FetchPolicy::default()
+            }))
+        });
+
+        Ok(Self {
+            linker: Arc::new(build_linker(engine)?),
+            engine: engine.clone(),
+            module,
+            http,
+            manifest,
+        })
+    }
+
+    pub fn manifest(&self) -> &WasmPluginManifest {
+        &self.manifest
+    }
+
+    fn wasi_context(
+        &self,
+        root_dir: &Path,
+        stdin: Vec<u8>,
+        stdout: &MemoryOutputPipe,
+        stderr: &MemoryOutputPipe,
+    ) -> Result<WasiP1Ctx> {
+        let capabilities = &self.manifest.capabilities;
+        let mut builder = WasiCtxBuilder::new();
+        builder
+            .stdin(MemoryInputPipe::new(stdin))
+            .stdout(stdout.clone())
+            .stderr(stderr.clone())
+            .args(&[&self.manifest.name]);
+        if capabilities.write_working_dir {
+            builder.preopened_dir(root_dir, ".", DirPerms::all(), FilePerms::all())?;
+        } else if capabilities.read_working_dir {
+            builder.preopened_dir(root_dir, ".", DirPerms::READ, FilePerms::READ)?;
+        }
+        Ok(builder.build_p1())
+    }
+}
+
+fn build_linker(engine: &Engine) -> Result<Linker<PluginState>> {
+    let mut linker: Linker<PluginState> = Linker::new(engine);
+    preview1::add_to_linker_async(&mut linker, |state| &mut state.wasi)?;
+
+    linker.func_wrap_async(
+        HOST_MODULE,
+        "memory_search",
+        |mut caller: Caller<'_, PluginState>, (ptr, len): (i32, i32)| {
+            Box::new(async move {
+                let Some(memory) = caller.data().memory.clone() else {
+                    return stage(&mut caller, DENIED, "capability not granted: memory_search");
+                };
+                let query = match read_guest_str(&mut caller, ptr, len) {
+                    Ok(query) => query,
+                    Err(error) => return stage(&mut caller, FAILED, &error.to_string()),
+                };
+                match memory.search(&query).await {
+                    Ok(hits) => {
+                        let encoded = serde_json::to_string(&hits).unwrap_or_default();
+                        stage(&mut caller, 0, &encoded)
+                    }
+                    Err(error) => stage(&mut caller, FAILED, &error.to_string()),
+                }
+            })
+        },
+    )?;
+
+    linker.func_wrap_async(
+        HOST_MODULE,
+        "http_get",
+        |mut caller: Caller<'_, PluginState>, (ptr, len): (i32, i32)| {
+            Box::new(async move {
+                let Some(http) = caller.data().http.clone() else {
+                    return stage(&mut caller, DENIED, "capability not granted: http");
+                };
+                let url = match read_guest_str(&mut caller, ptr, len) {
+                    Ok(url) => url,
+                    Err(error) => return stage(&mut caller, FAILED, &error.to_string()),
+                };
+                match http.get(&url, false).await {
+                    Ok(result) => stage(&mut caller, 0, &result.output),
+                    Err(error) => stage(&mut caller, FAILED, &error.to_string()),
+                }
+            })
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "result_read",
+        |mut caller: Caller<'_, PluginState>, ptr: i32, len: i32| -> i32 {
+            let staged = std::mem::take(&mut caller.data_mut().staged);
+            let count = staged.len().min(len.max(0) as usize);
+            let Some(memory) = guest_memory(&mut caller) else {
+                return FAILED;
+            };
+            match memory.write(&mut caller, ptr as usize, &staged[..count]) {
+                Ok(()) => count as i32,
+                Err(_) => FAILED,
+            }
+        },
+    )?;
+
+    Ok(linker)
+}
+
+/// Stages `message` for `chaos.result_read`; returns its length, or `code` when negative.
+fn stage(caller: &mut Caller<'_, PluginState>, code: i32, message: &str) -> i32 {
+    caller.data_mut().staged = message.as_bytes().to_vec();
+    if code < 0 {
+        code
+    } else {
+        message.len() as i32
+    }
+}
+
+fn guest_memory(caller: &mut Caller<'_, PluginState>) -> Option<wasmtime::Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+}
+
+fn read_guest_str(caller: &mut Caller<'_, PluginState>, ptr: i32, len: i32) -> Result<String> {
+    let memory = guest_memory(caller).ok_or_else(|| anyhow!("plugin exports no memory"))?;
+    let mut buffer = vec![0u8; len.max(0) as usize];
+    memory
+        .read(&*caller, ptr.max(0) as usize, &mut buffer)
+        .map_err(|_| anyhow!("argument out of bounds"))?;
+    String::from_utf8(buffer).map_err(|_| anyhow!("argument is not valid UTF-8"))
+}
+
+#[async_trait]
+impl Tool for WasmPluginTool {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn description(&self) -> &str {
+        &self.manifest.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.manifest.parameters_schema.clone()
+    }
+
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolExecution> {
+        let name = &self.manifest.name;
+        let limits = &self.manifest.limits;
+        let stdout = MemoryOutputPipe::new(MAX_STDIO_BYTES);
+        let stderr = MemoryOutputPipe::new(MAX_STDIO_BYTES);
+        let wasi = self.wasi_context(
+            &context.root_dir,
+            serde_json::to_vec(&args)?,
+            &stdout,
+            &stderr,
+        )?;
+
+        let state = PluginState {
+            wasi,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(limits.memory_mb.saturating_mul(1024 * 1024))
+                .instances(1)
+                .build(),
+            memory: self
+                .manifest
+                .capabilities
+                .memory_search
+                .then(|| context.memory.clone()),
+            http: self.http.clone(),
+            staged: Vec::new(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(limits.fuel)?;
+        store.fuel_async_yield_interval(Some(FUEL_YIELD_INTERVAL))?;
+
+        let run = async {
+            let instance = self
+                .linker
+                .instantiate_async(&mut store, &self.module)
+                .await?;
+            let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
+            start.call_async(&mut store, ()).await
+        };
+        let outcome = tokio::time::timeout(Duration::from_secs(limits.timeout_secs.max(1)), run)
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "wasm plugin {name} timed out after {}s",
+                    limits.timeout_secs
+                )
+            })?;
+
+        let exit_code = match outcome {
+            Ok(()) => 0,
+            Err(error) => {
+                if let Some(exit) = error.downcast_ref::<I32Exit>() {
+                    exit.0
+                } else if matches!(error.downcast_ref::<Trap>(), Some(Trap::OutOfFuel)) {
+                    return Err(anyhow!("wasm plugin {name} ran out of fuel"));
+                } else {
+                    return Err(anyhow!("wasm plugin {name} failed: {error:#}"));
+                }
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&stdout.contents()).to_string();
+        let stderr = String::from_utf8_lossy(&stderr.contents())
+            .trim()
+            .to_string();
+        tracing::debug!(
+            plugin = %name,
+            exit_code,
+            fuel_left = store.get_fuel().unwrap_or(0),
+            "wasm plugin finished"
+        );
+
+        let (output, is_error) = match serde_json::from_str::<PluginResponse>(&stdout) {
+            Ok(response) => (response.output, response.is_error),
+            Err(_) if stdout.trim().is_empty() && exit_code != 0 => (stderr, true),
+            Err(_) => (stdout, false),
+        };
+        Ok(ToolExecution {
+            name: name.clone(),
+            output,
+            is_error: is_error || exit_code != 0,
+        })
+    }
+}
+
+pub fn wasm_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.async_support(true).consume_fuel(true);
+    Engine::new(&config)
+}
+
+/// Loads every plugin directory under `plugins_dir`. Plugins that fail to load are logged and
+/// skipped.
+pub fn register_wasm_plugins(registry: &mut ToolRegistry, plugins_dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(plugins_dir) else {
+        return 0;
+    };
+    let engine = match wasm_engine() {
+        Ok(engine) => engine,
+        Err(error) => {
+            tracing::warn!(error = %error, "failed to create wasm engine; skipping wasm plugins");
+            return 0;
+        }
+    };
+
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .collect();
+    dirs.sort();
+
+    let mut registered = 0usize;
+    for dir in dirs {
+        match WasmPluginTool::load(&engine, &dir) {
+            Ok(tool) => {
+                let name = tool.manifest.name.clone();
+                let capabilities = format!("{:?}", tool.manifest.capabilities);
+                let source = format!("wasm plugin {}", dir.display());
+                if registry.register_external(tool, &source) {
+                    tracing::info!(plugin = %name, capabilities = %capabilities, "wasm plugin registered");
+                    registered += 1;
+                }
+            }
+            Err(error) => {
+                tracing::warn!(dir = %dir.display(), error = %format!("{error:#}"), "wasm plugin unavailable; skipping");
+            }
+        }
+    }
+    registered
+}
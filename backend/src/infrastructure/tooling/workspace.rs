@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use super::{Tool, ToolContext};
+use crate::domain::ports::WorkspaceSearchPort;
+use crate::domain::types::ToolExecution;
+
+const DEFAULT_SEARCH_LIMIT: usize = 5;
+const MAX_SEARCH_LIMIT: usize = 20;
+
+/// `search_workspace`: ranked passages from the working-directory retrieval index.
+pub struct SearchWorkspaceTool {
+    index: Arc<dyn WorkspaceSearchPort>,
+}
+
+impl SearchWorkspaceTool {
+    pub fn new(index: Arc<dyn WorkspaceSearchPort>) -> Self {
+        Self { index }
+    }
+}
+
+#[async_trait]
+impl Tool for SearchWorkspaceTool {
+    fn name(&self) -> &'static str {
+        "search_workspace"
+    }
+
+    fn description(&self) -> &'static str {
+        "Search documents and code in the working directory by meaning and keywords; returns the best matching passages with line ranges"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string"},
+                "limit": {"type": "integer", "minimum": 1, "maximum": MAX_SEARCH_LIMIT}
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, args: Value, _context: &ToolContext) -> Result<ToolExecution> {
+        let query = args
+            .get("query")
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|query| !query.is_empty())
+            .ok_or_else(|| anyhow!("search_workspace.query is required"))?;
+        let limit = args
+            .get("limit")
+            .and_then(|value| value.as_u64())
+            .map_or(DEFAULT_SEARCH_LIMIT, |limit| limit as usize)
+            .clamp(1, MAX_SEARCH_LIMIT);
+
+        let hits = self.index.search(query, limit).await?;
+        let output = if hits.is_empty() {
+            format!("no workspace passages matching {query}")
+        } else {
+            hits.iter()
+                .map(|hit| {
+                    format!(
+                        "{}:{}-{} (score {:.2})\n{}",
+                        hit.path, hit.start_line, hit.end_line, hit.score, hit.text
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        };
+
+        Ok(ToolExecution {
+            name: self.name().to_string(),
+            output,
+            is_error: false,
+        })
+    }
+}
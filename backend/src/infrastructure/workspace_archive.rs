@@ -0,0 +1,769 @@
+//! Workspace backup and restore as a single `tar.gz`.
+//!
+//! An archive holds `manifest.json` followed by every exported file under `workspace/`. The
+//! manifest records the format version, how secrets were handled and a sha256 per file, so an
+//! import can reject anything it did not produce before touching the disk. Logs are only
+//! exported on request, and tool output and search indexes are left out because they are
+//! rebuilt on demand. Secrets live in `config.json` and `.env` files: depending on
+//! [`SecretMode`] their values are kept, replaced by the [`audit::REDACTED`] placeholder via
+//! [`audit::redact_json`], or dropped. Importing a redacted archive keeps the local secret
+//! for every placeholder, so restoring a backup never wipes credentials. `config.json` and the
+//! WASM plugins directory can start commands, so an import leaves them alone unless
+//! [`ImportOptions::include_config`] is set. Imports only add or overwrite files; nothing local
+//! is deleted. Archive paths never pass through a symlink in the
+//! workspace, and every file is staged before any is replaced, so a failed import leaves the
+//! workspace as it was.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::domain::audit::{self, REDACTED};
+use crate::domain::ports::WorkspaceArchivePort;
+use crate::domain::workspace::{
+    ExportOptions, ImportOptions, ImportReport, InvalidArchive, ModifiedFile, SecretMode,
+    WorkspaceExport,
+};
+use crate::infrastructure::config::{AgentFileConfig, AppConfig};
+
+pub const ARCHIVE_FORMAT: &str = "chaos-bot-workspace";
+pub const ARCHIVE_VERSION: u32 = 1;
+/// Largest compressed archive accepted by an import.
+pub const MAX_ARCHIVE_BYTES: usize = 256 * 1024 * 1024;
+const MAX_UNPACKED_BYTES: u64 = 1024 * 1024 * 1024;
+const MAX_ENTRIES: usize = 100_000;
+/// Files larger than this are reported as modified without a diff.
+const MAX_DIFF_BYTES: usize = 64 * 1024;
+const MANIFEST_PATH: &str = "manifest.json";
+const FILES_PREFIX: &str = "workspace/";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    version: u32,
+    created_at: DateTime<Utc>,
+    secrets: SecretMode,
+    #[serde(default)]
+    include_logs: bool,
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    path: String,
+    bytes: u64,
+    sha256: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SecretFile {
+    /// `config.json` (or the legacy `agent.json`).
+    Config,
+    /// `.env` and `.env.*` at the workspace root, except `.env.example`.
+    Env,
+}
+
+#[derive(Clone, Debug)]
+pub struct WorkspaceArchive {
+    workspace: PathBuf,
+    config_path: PathBuf,
+    log_dir: PathBuf,
+    /// WASM plugins; only imported with `include_config`.
+    plugins_dir: PathBuf,
+    /// Rebuildable data that is never exported.
+    skipped: Vec<PathBuf>,
+}
+
+impl WorkspaceArchive {
+    /// An archive of `workspace` with the default layout under it.
+    pub fn new(workspace: impl Into<PathBuf>, config_path: impl Into<PathBuf>) -> Self {
+        let workspace = workspace.into();
+        let data = workspace.join("data");
+        Self {
+            log_dir: workspace.join("logs"),
+            plugins_dir: workspace.join("plugins"),
+            skipped: vec![
+                data.join("tool-output"),
+                data.join("workspace-index"),
+                data.join("code-index"),
+            ],
+            config_path: config_path.into(),
+            workspace,
+        }
+    }
+
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            workspace: config.workspace.clone(),
+            config_path: config.config_path.clone(),
+            log_dir: config.log_dir.clone(),
+            plugins_dir: config.wasm_plugins_dir.clone(),
+            skipped: vec![
+                config.tool_output_dir.clone(),
+                config.workspace_index_dir.clone(),
+                config.code_index_dir.clone(),
+            ],
+        }
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    pub fn export_blocking(&self, options: ExportOptions) -> Result<WorkspaceExport> {
+        if !self.workspace.is_dir() {
+            anyhow::bail!("workspace {} does not exist", self.workspace.display());
+        }
+        let mut files = Vec::new();
+        let walker = WalkDir::new(&self.workspace)
+            .follow_links(false)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| !self.is_skipped(entry.path(), options.include_logs));
+        for entry in walker {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Some(path) = entry
+                .path()
+                .strip_prefix(&self.workspace)
+                .ok()
+                .and_then(archive_path)
+            else {
+                tracing::warn!(path = %entry.path().display(), "skipping file with a non-UTF-8 path");
+                continue;
+            };
+            let content = std::fs::read(entry.path())
+                .with_context(|| format!("failed to read {}", entry.path().display()))?;
+            let content = match self.secret_file(&path) {
+                Some(kind) => match scrub_secrets(kind, &content, options.secrets) {
+                    Some(content) => content,
+                    None => {
+                        tracing::warn!(path = %path, "skipping secrets file that could not be parsed");
+                        continue;
+                    }
+                },
+                None => content,
+            };
+            files.push((path, content));
+        }
+
+        let created_at = Utc::now();
+        let manifest = Manifest {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            created_at,
+            secrets: options.secrets,
+            include_logs: options.include_logs,
+            files: files
+                .iter()
+                .map(|(path, content)| ManifestEntry {
+                    path: path.clone(),
+                    bytes: content.len() as u64,
+                    sha256: sha256_hex(content),
+                })
+                .collect(),
+        };
+
+        let mtime = created_at.timestamp().max(0) as u64;
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        append_file(
+            &mut builder,
+            MANIFEST_PATH,
+            &serde_json::to_vec_pretty(&manifest)?,
+            mtime,
+        )?;
+        for (path, content) in &files {
+            append_file(
+                &mut builder,
+                &format!("{FILES_PREFIX}{path}"),
+                content,
+                mtime,
+            )?;
+        }
+        let archive = builder.into_inner()?.finish()?;
+
+        Ok(WorkspaceExport {
+            file_name: format!(
+                "chaos-bot-workspace-{}.tar.gz",
+                created_at.format("%Y%m%dT%H%M%SZ")
+            ),
+            archive,
+            files: files.len(),
+            bytes: files.iter().map(|(_, content)| content.len() as u64).sum(),
+            secrets: options.secrets,
+        })
+    }
+
+    pub fn import_blocking(&self, archive: &[u8], options: ImportOptions) -> Result<ImportReport> {
+        let (manifest, files) = read_archive(archive)?;
+        let mut report = ImportReport {
+            dry_run: options.dry_run,
+            secrets: manifest.secrets,
+            ..ImportReport::default()
+        };
+
+        let mut writes = Vec::new();
+        for (path, content) in files {
+            let target = self.workspace.join(&path);
+            if !options.include_config && self.runs_commands(&path) {
+                report.skipped.push(path);
+                continue;
+            }
+            ensure_no_symlinks(&self.workspace, &path)?;
+            let current = read_existing(&target)?;
+            let secret = self.secret_file(&path);
+            let content = match secret {
+                Some(kind) => restore_secrets(kind, content, current.as_deref()),
+                None => content,
+            };
+            if secret == Some(SecretFile::Config) {
+                serde_json::from_slice::<AgentFileConfig>(&content).map_err(|error| {
+                    InvalidArchive(format!("`{path}` is not a valid config: {error}"))
+                })?;
+            }
+
+            match &current {
+                None => report.added.push(path.clone()),
+                Some(current) if same_content(secret, current, &content) => {
+                    report.unchanged += 1;
+                    continue;
+                }
+                Some(current) => {
+                    // Secrets never show up in the diff, whatever the archive carried.
+                    let (before, after) = match secret {
+                        Some(kind) => (
+                            scrub_secrets(kind, current, SecretMode::Redact).unwrap_or_default(),
+                            scrub_secrets(kind, &content, SecretMode::Redact).unwrap_or_default(),
+                        ),
+                        None => (current.clone(), content.clone()),
+                    };
+                    report.modified.push(ModifiedFile {
+                        diff: text_diff(&path, &before, &after),
+                        path: path.clone(),
+                    });
+                }
+            }
+            if target == self.config_path {
+                report.restart_required = true;
+            }
+            writes.push((target, content));
+        }
+
+        if !options.dry_run {
+            apply_writes(&self.workspace, &writes)?;
+        }
+        Ok(report)
+    }
+
+    /// Files that decide which commands and plugins run: the config and the WASM plugins.
+    fn runs_commands(&self, path: &str) -> bool {
+        self.secret_file(path) == Some(SecretFile::Config)
+            || self.workspace.join(path).starts_with(&self.plugins_dir)
+    }
+
+    fn is_skipped(&self, path: &Path, include_logs: bool) -> bool {
+        if path == self.workspace {
+            return false;
+        }
+        path.file_name().is_some_and(|name| name == ".index")
+            || self.skipped.iter().any(|skipped| path == skipped)
+            || (!include_logs && path == self.log_dir)
+    }
+
+    fn secret_file(&self, path: &str) -> Option<SecretFile> {
+        if self.workspace.join(path) == self.config_path
+            || path == "config.json"
+            || path == "agent.json"
+        {
+            return Some(SecretFile::Config);
+        }
+        let is_env = path == ".env" || (path.starts_with(".env.") && path != ".env.example");
+        (is_env && !path.contains('/')).then_some(SecretFile::Env)
+    }
+}
+
+#[async_trait]
+impl WorkspaceArchivePort for WorkspaceArchive {
+    async fn export(&self, options: ExportOptions) -> Result<WorkspaceExport> {
+        let archive = self.clone();
+        tokio::task::spawn_blocking(move || archive.export_blocking(options)).await?
+    }
+
+    async fn import(&self, archive: Vec<u8>, options: ImportOptions) -> Result<ImportReport> {
+        let workspace = self.clone();
+        tokio::task::spawn_blocking(move || workspace.import_blocking(&archive, options)).await?
+    }
+}
+
+/// Unpacks and verifies an archive entirely in memory.
+fn read_archive(bytes: &[u8]) -> Result<(Manifest, BTreeMap<String, Vec<u8>>)> {
+    let invalid = |message: String| anyhow::Error::new(InvalidArchive(message));
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    let entries = archive
+        .entries()
+        .map_err(|error| invalid(format!("not a tar.gz file: {error}")))?;
+
+    let mut manifest = None;
+    let mut files = BTreeMap::new();
+    let mut unpacked = 0u64;
+    for (index, entry) in entries.enumerate() {
+        if index >= MAX_ENTRIES {
+            return Err(invalid(format!("more than {MAX_ENTRIES} entries")));
+        }
+        let mut entry = entry.map_err(|error| invalid(format!("unreadable entry: {error}")))?;
+        let path = String::from_utf8(entry.path_bytes().into_owned())
+            .map_err(|_| invalid("entry with a non-UTF-8 path".to_string()))?;
+        let kind = entry.header().entry_type();
+        if kind.is_dir() {
+            continue;
+        }
+        if !kind.is_file() {
+            return Err(invalid(format!("`{path}` is not a regular file")));
+        }
+        let size = entry
+            .header()
+            .size()
+            .map_err(|error| invalid(format!("`{path}` has a bad size: {error}")))?;
+        unpacked = unpacked.saturating_add(size);
+        if unpacked > MAX_UNPACKED_BYTES {
+            return Err(invalid(format!(
+                "unpacks to more than {MAX_UNPACKED_BYTES} bytes"
+            )));
+        }
+        let mut content = Vec::with_capacity(size as usize);
+        entry
+            .read_to_end(&mut content)
+            .map_err(|error| invalid(format!("unreadable entry `{path}`: {error}")))?;
+
+        if path == MANIFEST_PATH {
+            manifest = Some(
+                serde_json::from_slice::<Manifest>(&content)
+                    .map_err(|error| invalid(format!("bad manifest: {error}")))?,
+            );
+            continue;
+        }
+        let relative = path
+            .strip_prefix(FILES_PREFIX)
+            .ok_or_else(|| invalid(format!("unexpected entry `{path}`")))?;
+        if !is_safe_relative(relative) {
+            return Err(invalid(format!("unsafe path `{path}`")));
+        }
+        if files.insert(relative.to_string(), content).is_some() {
+            return Err(invalid(format!("duplicate entry `{path}`")));
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| invalid("missing manifest.json".to_string()))?;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(invalid(format!("unknown format `{}`", manifest.format)));
+    }
+    if manifest.version != ARCHIVE_VERSION {
+        return Err(invalid(format!(
+            "unsupported version {} (expected {ARCHIVE_VERSION})",
+            manifest.version
+        )));
+    }
+    if manifest.files.len() != files.len() {
+        return Err(invalid(format!(
+            "manifest lists {} files but the archive holds {}",
+            manifest.files.len(),
+            files.len()
+        )));
+    }
+    for listed in &manifest.files {
+        let content = files
+            .get(&listed.path)
+            .ok_or_else(|| invalid(format!("`{}` is missing", listed.path)))?;
+        if content.len() as u64 != listed.bytes || sha256_hex(content) != listed.sha256 {
+            return Err(invalid(format!(
+                "`{}` does not match its checksum",
+                listed.path
+            )));
+        }
+    }
+    Ok((manifest, files))
+}
+
+fn append_file<W: std::io::Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    content: &[u8],
+    mtime: u64,
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    builder
+        .append_data(&mut header, path, content)
+        .with_context(|| format!("failed to archive {path}"))
+}
+
+/// `/`-separated form of a workspace-relative path.
+fn archive_path(relative: &Path) -> Option<String> {
+    let parts = relative
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
+}
+
+fn is_safe_relative(path: &str) -> bool {
+    !path.is_empty()
+        && !path.contains('\\')
+        && !path.contains(':')
+        && path
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+}
+
+/// Rejects a path whose directories (or the file itself) are symlinks in the workspace, which
+/// would otherwise let an archive read or write outside it.
+fn ensure_no_symlinks(workspace: &Path, path: &str) -> Result<()> {
+    let mut current = workspace.to_path_buf();
+    for part in path.split('/') {
+        current.push(part);
+        match std::fs::symlink_metadata(&current) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                return Err(InvalidArchive(format!(
+                    "`{path}` passes through a symlink in the workspace"
+                ))
+                .into());
+            }
+            Ok(_) => {}
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to inspect {}", current.display()))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads a workspace file; `None` only when it does not exist, so an unreadable file is never
+/// mistaken for a new one.
+fn read_existing(path: &Path) -> Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Writes every file next to its target first and only then renames them into place. A failed
+/// write removes the staged files; a failed rename reports the files already replaced.
+fn apply_writes(workspace: &Path, writes: &[(PathBuf, Vec<u8>)]) -> Result<()> {
+    let root = workspace
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", workspace.display()))?;
+    let mut staged = Vec::with_capacity(writes.len());
+    for (target, content) in writes {
+        match stage(&root, target, content) {
+            Ok(temp) => staged.push((temp, target)),
+            Err(error) => {
+                discard(&staged);
+                return Err(error);
+            }
+        }
+    }
+
+    for (index, (temp, target)) in staged.iter().enumerate() {
+        if let Err(error) = std::fs::rename(temp, target) {
+            discard(&staged[index..]);
+            let applied = staged[..index]
+                .iter()
+                .map(|(_, target)| target.display().to_string())
+                .collect::<Vec<_>>();
+            return Err(error).with_context(|| {
+                format!(
+                    "failed to replace {}; already imported: [{}]",
+                    target.display(),
+                    applied.join(", ")
+                )
+            });
+        }
+    }
+    Ok(())
+}
+
+fn stage(root: &Path, target: &Path, content: &[u8]) -> Result<PathBuf> {
+    let parent = target
+        .parent()
+        .with_context(|| format!("{} has no parent directory", target.display()))?;
+    std::fs::create_dir_all(parent)
+        .with_context(|| format!("failed to create {}", parent.display()))?;
+    let resolved = parent
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", parent.display()))?;
+    if !resolved.starts_with(root) {
+        return Err(InvalidArchive(format!(
+            "`{}` resolves outside the workspace",
+            target.display()
+        ))
+        .into());
+    }
+    let mut temp = target.as_os_str().to_owned();
+    temp.push(".import-tmp");
+    let temp = PathBuf::from(temp);
+    std::fs::write(&temp, content)
+        .with_context(|| format!("failed to write {}", temp.display()))?;
+    Ok(temp)
+}
+
+fn discard(staged: &[(PathBuf, &PathBuf)]) {
+    for (temp, _) in staged {
+        let _ = std::fs::remove_file(temp);
+    }
+}
+
+/// Restoring a redacted config rewrites it, so configs count as equal when their JSON is.
+fn same_content(secret: Option<SecretFile>, current: &[u8], content: &[u8]) -> bool {
+    if current == content {
+        return true;
+    }
+    secret == Some(SecretFile::Config)
+        && matches!(
+            (
+                serde_json::from_slice::<Value>(current),
+                serde_json::from_slice::<Value>(content),
+            ),
+            (Ok(current), Ok(content)) if current == content
+        )
+}
+
+fn text_diff(path: &str, before: &[u8], after: &[u8]) -> Option<String> {
+    if before.len() > MAX_DIFF_BYTES || after.len() > MAX_DIFF_BYTES {
+        return None;
+    }
+    let before = std::str::from_utf8(before).ok()?;
+    let after = std::str::from_utf8(after).ok()?;
+    Some(
+        TextDiff::from_lines(before, after)
+            .unified_diff()
+            .context_radius(3)
+            .header(&format!("a/{path}"), &format!("b/{path}"))
+            .to_string(),
+    )
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The secrets file as written into an export; `None` when it cannot be parsed.
+fn scrub_secrets(kind: SecretFile, content: &[u8], mode: SecretMode) -> Option<Vec<u8>> {
+    if mode == SecretMode::Include {
+        return Some(content.to_vec());
+    }
+    match kind {
+        SecretFile::Config => {
+            let value: Value = serde_json::from_slice(content).ok()?;
+            let scrubbed = scrub_value(&value, &audit::redact_json(&value), mode)?;
+            let mut text = serde_json::to_vec_pretty(&scrubbed).ok()?;
+            text.push(b'\n');
+            Some(text)
+        }
+        SecretFile::Env => {
+            let text = std::str::from_utf8(content).ok()?;
+            let lines = env_lines(text);
+            let values = Value::Object(
+                lines
+                    .iter()
+                    .filter_map(|line| line.entry())
+                    .map(|(key, value)| (key.to_string(), Value::String(value.to_string())))
+                    .collect(),
+            );
+            let redacted = audit::redact_json(&values);
+            let mut out = String::new();
+            for line in &lines {
+                let secret = line.entry().and_then(|(key, value)| {
+                    (redacted[key] == REDACTED && value != REDACTED).then_some(line.prefix())
+                });
+                match (secret, mode) {
+                    (Some(_), SecretMode::Exclude) => continue,
+                    (Some(prefix), _) => out.push_str(&format!("{prefix}={REDACTED}")),
+                    (None, _) => out.push_str(line.0),
+                }
+                out.push('\n');
+            }
+            Some(out.into_bytes())
+        }
+    }
+}
+
+/// Keeps the structure of `original` but scrubs every value `redacted` marked as secret.
+/// Numbers and booleans under a secret key (such as `max_tokens`) are kept as they are.
+fn scrub_value(original: &Value, redacted: &Value, mode: SecretMode) -> Option<Value> {
+    if redacted.as_str() == Some(REDACTED) && original != redacted {
+        return scrub_secret(original, mode);
+    }
+    match (original, redacted) {
+        (Value::Object(original), Value::Object(redacted)) => Some(Value::Object(
+            original
+                .iter()
+                .filter_map(|(key, value)| {
+                    let scrubbed = scrub_value(value, redacted.get(key).unwrap_or(value), mode)?;
+                    Some((key.clone(), scrubbed))
+                })
+                .collect(),
+        )),
+        (Value::Array(original), Value::Array(redacted)) => Some(Value::Array(
+            original
+                .iter()
+                .zip(redacted)
+                .filter_map(|(value, redacted)| scrub_value(value, redacted, mode))
+                .collect(),
+        )),
+        _ => Some(original.clone()),
+    }
+}
+
+fn scrub_secret(value: &Value, mode: SecretMode) -> Option<Value> {
+    match value {
+        Value::String(_) if mode == SecretMode::Exclude => None,
+        Value::String(_) => Some(Value::String(REDACTED.to_string())),
+        Value::Object(object) => Some(Value::Object(
+            object
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), scrub_secret(value, mode)?)))
+                .collect::<Map<_, _>>(),
+        )),
+        Value::Array(items) => Some(Value::Array(
+            items
+                .iter()
+                .filter_map(|value| scrub_secret(value, mode))
+                .collect(),
+        )),
+        other => Some(other.clone()),
+    }
+}
+
+/// Fills placeholders in an imported secrets file from the local copy; placeholders without a
+/// local value are dropped.
+fn restore_secrets(kind: SecretFile, content: Vec<u8>, current: Option<&[u8]>) -> Vec<u8> {
+    let Ok(text) = std::str::from_utf8(&content) else {
+        return content;
+    };
+    if !text.contains(REDACTED) {
+        return content;
+    }
+    match kind {
+        SecretFile::Config => {
+            let Ok(incoming) = serde_json::from_str::<Value>(text) else {
+                return content;
+            };
+            let current = current.and_then(|current| serde_json::from_slice::<Value>(current).ok());
+            let restored = restore_value(&incoming, current.as_ref()).unwrap_or(Value::Null);
+            let mut text = serde_json::to_vec_pretty(&restored).unwrap_or(content);
+            text.push(b'\n');
+            text
+        }
+        SecretFile::Env => {
+            let current_text = current
+                .and_then(|current| std::str::from_utf8(current).ok())
+                .unwrap_or_default();
+            let current_lines = env_lines(current_text);
+            let mut out = String::new();
+            for line in env_lines(text) {
+                match line.entry() {
+                    Some((key, REDACTED)) => {
+                        let local = current_lines.iter().find(|local| {
+                            local.entry().is_some_and(|(local_key, value)| {
+                                local_key == key && value != REDACTED
+                            })
+                        });
+                        match local {
+                            Some(local) => out.push_str(local.0),
+                            None => continue,
+                        }
+                    }
+                    _ => out.push_str(line.0),
+                }
+                out.push('\n');
+            }
+            out.into_bytes()
+        }
+    }
+}
+
+fn restore_value(incoming: &Value, current: Option<&Value>) -> Option<Value> {
+    match incoming {
+        Value::String(text) if text == REDACTED => current
+            .filter(|current| current.as_str() != Some(REDACTED))
+            .cloned(),
+        Value::Object(object) => Some(Value::Object(
+            object
+                .iter()
+                .filter_map(|(key, value)| {
+                    let restored =
+                        restore_value(value, current.and_then(|current| current.get(key)))?;
+                    Some((key.clone(), restored))
+                })
+                .collect(),
+        )),
+        Value::Array(items) => Some(Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, value)| {
+                    restore_value(value, current.and_then(|current| current.get(index)))
+                })
+                .collect(),
+        )),
+        other => Some(other.clone()),
+    }
+}
+
+/// One line of a dotenv file.
+struct EnvLine<'a>(&'a str);
+
+impl<'a> EnvLine<'a> {
+    /// `KEY` and its unquoted value for assignments; `None` for comments and blank lines.
+    fn entry(&self) -> Option<(&'a str, &'a str)> {
+        let trimmed = self.0.trim_start();
+        if trimmed.starts_with('#') {
+            return None;
+        }
+        let (key, value) = trimmed.split_once('=')?;
+        let key = key.trim();
+        let key = key.strip_prefix("export ").unwrap_or(key).trim();
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| {
+                value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+            })
+            .unwrap_or(value);
+        (!key.is_empty()).then_some((key, value))
+    }
+
+    /// Everything before the `=`, e.g. `export API_KEY`.
+    fn prefix(&self) -> &'a str {
+        self.0.split_once('=').map_or(self.0, |(prefix, _)| prefix)
+    }
+}
+
+fn env_lines(text: &str) -> Vec<EnvLine<'_>> {
+    text.lines().map(EnvLine).collect()
+}
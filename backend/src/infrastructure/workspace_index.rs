@@ -0,0 +1,533 @@
+//! Retrieval index over text files in the working directory.
+//!
+//! Files are split into overlapping line windows and scored with BM25; when a
+//! [`TextEmbedder`] is configured each chunk also gets a vector and results blend both
+//! scores. The index lives in `<dir>/index.json`. Searches only read it; [`WorkspaceIndex::refresh`]
+//! brings it up to date incrementally (only files whose mtime or size changed are re-chunked,
+//! deleted files are dropped) and is run in the background by the runtime.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use crate::domain::ports::{WorkspaceHit, WorkspaceSearchPort};
+use crate::infrastructure::tooling::{is_binary, WalkFilters};
+
+const INDEX_VERSION: u32 = 1;
+const INDEX_FILE: &str = "index.json";
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+/// Weight of vector similarity in hybrid scores; BM25 gets the rest.
+const VECTOR_WEIGHT: f32 = 0.5;
+/// Chunks matched only by their vector must be at least this similar to the query.
+const MIN_VECTOR_SIMILARITY: f32 = 0.25;
+
+pub const DEFAULT_WORKSPACE_INDEX_MAX_FILES: usize = 2000;
+pub const DEFAULT_WORKSPACE_INDEX_MAX_FILE_BYTES: u64 = 512 * 1024;
+pub const DEFAULT_WORKSPACE_INDEX_CHUNK_LINES: usize = 40;
+
+/// A local, synchronous embedding function for chunk and query vectors.
+pub trait TextEmbedder: Send + Sync {
+    /// Identifies the embedding space; the index is rebuilt when it changes.
+    fn id(&self) -> String;
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Feature-hashing embedder over word unigrams and character trigrams. Needs no model and
+/// catches partial-word overlap ("configure" vs "config") that exact BM25 terms miss.
+pub struct HashedEmbedder {
+    dimensions: usize,
+}
+
+impl HashedEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(8),
+        }
+    }
+}
+
+impl Default for HashedEmbedder {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl TextEmbedder for HashedEmbedder {
+    fn id(&self) -> String {
+        format!("hashed-{}", self.dimensions)
+    }
+
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0f32; self.dimensions];
+        for token in tokenize(text) {
+            vector[fnv1a(token.as_bytes()) as usize % self.dimensions] += 1.0;
+            let chars: Vec<char> = format!(" {token} ").chars().collect();
+            for trigram in chars.windows(3) {
+                let trigram: String = trigram.iter().collect();
+                vector[fnv1a(trigram.as_bytes()) as usize % self.dimensions] += 0.5;
+            }
+        }
+        normalize(&mut vector);
+        vector
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|value| *value /= norm);
+    }
+}
+
+/// Lowercased alphanumeric words of two or more characters. camelCase and PascalCase
+/// words also yield their parts, so `buildSystemPrompt` matches "system prompt".
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let lower = word.to_lowercase();
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        let mut previous_lower = false;
+        for ch in word.chars() {
+            if ch.is_uppercase() && previous_lower && !current.is_empty() {
+                parts.push(std::mem::take(&mut current).to_lowercase());
+            }
+            previous_lower = ch.is_lowercase() || ch.is_ascii_digit();
+            current.push(ch);
+        }
+        if !parts.is_empty() {
+            parts.push(current.to_lowercase());
+            tokens.extend(parts.into_iter().filter(|part| part.chars().count() > 1));
+        }
+        if lower.chars().count() > 1 {
+            tokens.push(lower);
+        }
+    }
+    tokens
+}
+
+#[derive(Clone, Debug)]
+pub struct WorkspaceIndexOptions {
+    pub max_files: usize,
+    pub max_file_bytes: u64,
+    pub chunk_lines: usize,
+    /// Paths under the root that are never indexed (runtime data, logs, memory).
+    pub exclude: Vec<PathBuf>,
+}
+
+impl Default for WorkspaceIndexOptions {
+    fn default() -> Self {
+        Self {
+            max_files: DEFAULT_WORKSPACE_INDEX_MAX_FILES,
+            max_file_bytes: DEFAULT_WORKSPACE_INDEX_MAX_FILE_BYTES,
+            chunk_lines: DEFAULT_WORKSPACE_INDEX_CHUNK_LINES,
+            exclude: Vec::new(),
+        }
+    }
+}
+
+/// Outcome of bringing the index up to date.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorkspaceRefresh {
+    pub files: usize,
+    pub indexed: usize,
+    pub removed: usize,
+    /// Files left out because the `max_files` cap was reached.
+    pub skipped: usize,
+}
+
+/// Result of one walk over the root, applied to the index under the lock.
+#[derive(Default)]
+struct Scan {
+    seen: BTreeSet<String>,
+    changed: BTreeMap<String, IndexedFile>,
+    skipped: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    root: String,
+    embedder: Option<String>,
+    files: BTreeMap<String, IndexedFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedFile {
+    modified_ms: u128,
+    size: u64,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Chunk {
+    start_line: usize,
+    end_line: usize,
+    text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vector: Option<Vec<f32>>,
+    #[serde(skip)]
+    terms: HashMap<String, u32>,
+    #[serde(skip)]
+    length: usize,
+}
+
+impl Chunk {
+    fn new(start_line: usize, end_line: usize, text: String, vector: Option<Vec<f32>>) -> Self {
+        let mut chunk = Self {
+            start_line,
+            end_line,
+            text,
+            vector,
+            terms: HashMap::new(),
+            length: 0,
+        };
+        chunk.analyze();
+        chunk
+    }
+
+    fn analyze(&mut self) {
+        let tokens = tokenize(&self.text);
+        self.length = tokens.len();
+        self.terms.clear();
+        for token in tokens {
+            *self.terms.entry(token).or_default() += 1;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WorkspaceIndex {
+    root: PathBuf,
+    dir: PathBuf,
+    options: WorkspaceIndexOptions,
+    embedder: Option<Arc<dyn TextEmbedder>>,
+    state: Arc<Mutex<Option<IndexFile>>>,
+    /// Serializes refreshes; the walk itself runs without holding `state`.
+    refreshing: Arc<Mutex<()>>,
+}
+
+impl WorkspaceIndex {
+    pub fn new(
+        root: impl Into<PathBuf>,
+        dir: impl Into<PathBuf>,
+        options: WorkspaceIndexOptions,
+    ) -> Self {
+        Self {
+            root: root.into(),
+            dir: dir.into(),
+            options,
+            embedder: None,
+            state: Arc::new(Mutex::new(None)),
+            refreshing: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn with_embedder(mut self, embedder: Arc<dyn TextEmbedder>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    pub fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE)
+    }
+
+    /// Re-chunks changed files and persists the index. Blocking; searches keep using the
+    /// previous state until the changes are swapped in.
+    pub fn refresh(&self) -> Result<WorkspaceRefresh> {
+        let _refreshing = self
+            .refreshing
+            .lock()
+            .map_err(|_| anyhow!("workspace index lock poisoned"))?;
+        let known: HashMap<String, (u128, u64)> = self
+            .lock_state()?
+            .get_or_insert_with(|| self.load())
+            .files
+            .iter()
+            .map(|(path, file)| (path.clone(), (file.modified_ms, file.size)))
+            .collect();
+        let scan = self.scan(&known)?;
+
+        let mut state = self.lock_state()?;
+        let index = state.get_or_insert_with(|| self.load());
+        let mut refresh = WorkspaceRefresh {
+            indexed: scan.changed.len(),
+            skipped: scan.skipped,
+            ..WorkspaceRefresh::default()
+        };
+        index.files.extend(scan.changed);
+        let before = index.files.len();
+        index.files.retain(|path, _| scan.seen.contains(path));
+        refresh.removed = before - index.files.len();
+        refresh.files = index.files.len();
+
+        if refresh.indexed > 0 || refresh.removed > 0 {
+            let path = self.index_path();
+            let temp = path.with_extension("json.tmp");
+            std::fs::write(&temp, serde_json::to_vec(&*index)?)?;
+            std::fs::rename(&temp, &path)?;
+            tracing::debug!(
+                root = %self.root.display(),
+                files = refresh.files,
+                indexed = refresh.indexed,
+                removed = refresh.removed,
+                skipped = refresh.skipped,
+                "workspace index updated"
+            );
+        }
+        Ok(refresh)
+    }
+
+    /// Top `limit` chunks for `query`, best first, from the last refresh (or the persisted
+    /// index before the first one). Blocking; never walks the working directory.
+    pub fn search_blocking(&self, query: &str, limit: usize) -> Result<Vec<WorkspaceHit>> {
+        let mut state = self.lock_state()?;
+        state.get_or_insert_with(|| self.load());
+        let Some(index) = state.as_ref() else {
+            return Ok(Vec::new());
+        };
+
+        let query_terms: BTreeSet<String> = tokenize(query).into_iter().collect();
+        let query_vector = self.embedder.as_ref().map(|embedder| embedder.embed(query));
+        if query_terms.is_empty() && query_vector.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let chunks: Vec<(&str, &Chunk)> = index
+            .files
+            .iter()
+            .flat_map(|(path, file)| file.chunks.iter().map(move |chunk| (path.as_str(), chunk)))
+            .collect();
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+        let total = chunks.len() as f32;
+        let average_length =
+            (chunks.iter().map(|(_, chunk)| chunk.length).sum::<usize>() as f32 / total).max(1.0);
+        let idf: HashMap<&str, f32> = query_terms
+            .iter()
+            .map(|term| {
+                let frequency = chunks
+                    .iter()
+                    .filter(|(_, chunk)| chunk.terms.contains_key(term))
+                    .count() as f32;
+                let idf = ((total - frequency + 0.5) / (frequency + 0.5) + 1.0).ln();
+                (term.as_str(), idf)
+            })
+            .collect();
+
+        let mut scored: Vec<(f32, f32, &str, &Chunk)> = chunks
+            .iter()
+            .map(|(path, chunk)| {
+                let bm25 = idf
+                    .iter()
+                    .map(|(term, idf)| {
+                        let frequency = chunk.terms.get(*term).copied().unwrap_or(0) as f32;
+                        if frequency == 0.0 {
+                            return 0.0;
+                        }
+                        let norm = 1.0 - BM25_B + BM25_B * chunk.length as f32 / average_length;
+                        idf * frequency * (BM25_K1 + 1.0) / (frequency + BM25_K1 * norm)
+                    })
+                    .sum::<f32>();
+                let similarity = match (&query_vector, &chunk.vector) {
+                    (Some(query), Some(vector)) if query.len() == vector.len() => query
+                        .iter()
+                        .zip(vector)
+                        .map(|(a, b)| a * b)
+                        .sum::<f32>()
+                        .max(0.0),
+                    _ => 0.0,
+                };
+                (bm25, similarity, *path, *chunk)
+            })
+            .filter(|(bm25, similarity, _, _)| *bm25 > 0.0 || *similarity >= MIN_VECTOR_SIMILARITY)
+            .collect();
+
+        let max_bm25 = scored.iter().map(|(bm25, ..)| *bm25).fold(0f32, f32::max);
+        let blend = |bm25: f32, similarity: f32| {
+            if query_vector.is_none() {
+                bm25
+            } else {
+                let lexical = if max_bm25 > 0.0 { bm25 / max_bm25 } else { 0.0 };
+                (1.0 - VECTOR_WEIGHT) * lexical + VECTOR_WEIGHT * similarity
+            }
+        };
+        scored.sort_by(|a, b| {
+            blend(b.0, b.1)
+                .total_cmp(&blend(a.0, a.1))
+                .then_with(|| a.2.cmp(b.2))
+                .then_with(|| a.3.start_line.cmp(&b.3.start_line))
+        });
+
+        Ok(scored
+            .into_iter()
+            .take(limit)
+            .map(|(bm25, similarity, path, chunk)| WorkspaceHit {
+                path: path.to_string(),
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+                score: blend(bm25, similarity),
+                text: chunk.text.clone(),
+            })
+            .collect())
+    }
+
+    fn load(&self) -> IndexFile {
+        let root = self.root.to_string_lossy().to_string();
+        let embedder = self.embedder.as_ref().map(|embedder| embedder.id());
+        let loaded = std::fs::read(self.index_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<IndexFile>(&bytes).ok())
+            .filter(|index| {
+                index.version == INDEX_VERSION && index.root == root && index.embedder == embedder
+            });
+        match loaded {
+            Some(mut index) => {
+                for chunk in index
+                    .files
+                    .values_mut()
+                    .flat_map(|file| file.chunks.iter_mut())
+                {
+                    chunk.analyze();
+                }
+                index
+            }
+            None => IndexFile {
+                version: INDEX_VERSION,
+                root,
+                embedder,
+                files: BTreeMap::new(),
+            },
+        }
+    }
+
+    fn lock_state(&self) -> Result<std::sync::MutexGuard<'_, Option<IndexFile>>> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("workspace index lock poisoned"))
+    }
+
+    /// Walks the root and chunks every file that is new or differs from `known`.
+    fn scan(&self, known: &HashMap<String, (u128, u64)>) -> Result<Scan> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        let excluded: Vec<PathBuf> = self
+            .options
+            .exclude
+            .iter()
+            .chain(std::iter::once(&self.dir))
+            .filter_map(|path| std::fs::canonicalize(path).ok())
+            .collect();
+
+        let mut scan = Scan::default();
+        for entry in WalkFilters::default().walk(&self.root) {
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                continue;
+            }
+            if is_env_file(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let Ok(canonical) = std::fs::canonicalize(entry.path()) else {
+                continue;
+            };
+            if excluded
+                .iter()
+                .any(|excluded| canonical.starts_with(excluded))
+            {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.len() > self.options.max_file_bytes {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(&self.root)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .to_string();
+            if scan.seen.len() >= self.options.max_files {
+                scan.skipped += 1;
+                continue;
+            }
+            scan.seen.insert(relative.clone());
+
+            let modified_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |elapsed| elapsed.as_millis());
+            if known.get(&relative) == Some(&(modified_ms, metadata.len())) {
+                continue;
+            }
+            let chunks = std::fs::read(entry.path())
+                .ok()
+                .filter(|bytes| !is_binary(bytes))
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .map(|text| self.chunk(&text))
+                .unwrap_or_default();
+            scan.changed.insert(
+                relative,
+                IndexedFile {
+                    modified_ms,
+                    size: metadata.len(),
+                    chunks,
+                },
+            );
+        }
+        Ok(scan)
+    }
+
+    /// Windows of `chunk_lines` lines overlapping by a quarter, skipping blank windows.
+    fn chunk(&self, text: &str) -> Vec<Chunk> {
+        let lines: Vec<&str> = text.lines().collect();
+        let size = self.options.chunk_lines.max(1);
+        let step = (size - size / 4).max(1);
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < lines.len() {
+            let end = (start + size).min(lines.len());
+            let body = lines[start..end].join("\n");
+            if !body.trim().is_empty() {
+                let vector = self.embedder.as_ref().map(|embedder| embedder.embed(&body));
+                chunks.push(Chunk::new(start + 1, end, body, vector));
+            }
+            if end == lines.len() {
+                break;
+            }
+            start += step;
+        }
+        chunks
+    }
+}
+
+/// `.env` and `.env.*` hold secrets and are never indexed, even if hidden files are walked.
+fn is_env_file(name: &str) -> bool {
+    name == ".env" || name.starts_with(".env.")
+}
+
+#[async_trait]
+impl WorkspaceSearchPort for WorkspaceIndex {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<WorkspaceHit>> {
+        let index = self.clone();
+        let query = query.to_string();
+        tokio::task::spawn_blocking(move || index.search_blocking(&query, limit)).await?
+    }
+}
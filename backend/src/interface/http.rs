@@ -1,18 +1,28 @@
 use crate::application::agent::AgentLoop;
-use crate::application::{ChatService, ConfigService, SessionService};
+use crate::application::memory_service::DEFAULT_MEMORY_SEARCH_LIMIT;
+use crate::application::{
+    ChatService, CheckpointService, ConfigService, MemoryService, SessionService,
+    WorkspaceService,
+};
 use crate::domain::chat::{ChatCommand, ChatEvent, ToolEvent};
-use crate::domain::ports::ChannelDispatcherPort;
+use crate::domain::checkpoint::{Checkpoint, CheckpointDiff, CheckpointRestore};
+use crate::domain::memory::{MemoryFile, RollupReport};
+use crate::domain::ports::{ChannelDispatcherPort, MemoryHit, WorkspaceArchivePort};
 use crate::domain::config::{
     ConfigMutationInput, ConfigMutationResponse, ConfigRestartInput, ConfigStateResponse,
 };
 use crate::domain::AppError;
-use crate::domain::types::SessionState;
+use crate::domain::types::{SessionState, ToolSpec};
+use crate::domain::watch::WatchStatus;
+use crate::domain::workspace::{ExportOptions, ImportOptions, ImportReport, SecretMode};
 use crate::infrastructure::channels::telegram::TelegramWebhookUpdate;
 use crate::infrastructure::config::AgentFileConfig;
 use crate::infrastructure::session_store::SessionStore;
+use crate::infrastructure::workspace_archive::MAX_ARCHIVE_BYTES;
 use crate::runtime::config_runtime::ConfigRuntime;
-use axum::http::HeaderMap;
-use axum::extract::{Path, State};
+use axum::body::Bytes;
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
 use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::routing::{get, post};
 use axum::{Json, Router};
@@ -36,6 +46,7 @@ pub struct AppState {
     pub telegram_enabled: bool,
     pub telegram_polling: bool,
     pub telegram_api_base_url: String,
+    pub workspace_archive: Option<Arc<dyn WorkspaceArchivePort>>,
 }
 
 impl AppState {
@@ -56,6 +67,7 @@ impl AppState {
             telegram_enabled,
             telegram_polling,
             telegram_api_base_url,
+            workspace_archive: None,
         }
     }
 
@@ -77,9 +89,15 @@ impl AppState {
             telegram_enabled,
             telegram_polling,
             telegram_api_base_url,
+            workspace_archive: None,
         }
     }
 
+    pub fn with_workspace_archive(mut self, archive: Arc<dyn WorkspaceArchivePort>) -> Self {
+        self.workspace_archive = Some(archive);
+        self
+    }
+
     pub async fn current_agent(&self) -> Arc<AgentLoop> {
         self.agent.read().await.clone()
     }
@@ -91,6 +109,74 @@ pub struct ChatRequest {
     pub message: String,
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub struct ToolsQuery {
+    pub channel: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ToolsResponse {
+    pub channel: Option<String>,
+    pub tools: Vec<ToolSpec>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RollupQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Body of `GET`/`PUT /api/memory/curated`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CuratedMemoryBody {
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct MemoryFileQuery {
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryFileResponse {
+    pub path: String,
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct DeleteMemoryFileQuery {
+    /// Move the log to the memory archive instead of deleting it.
+    #[serde(default)]
+    pub archive: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct MemorySearchQuery {
+    #[serde(default)]
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct WorkspaceExportQuery {
+    #[serde(default)]
+    pub secrets: SecretMode,
+    #[serde(default)]
+    pub include_logs: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct WorkspaceImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Also restore `config.json` and `plugins/`.
+    #[serde(default)]
+    pub include_config: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: &'static str,
@@ -131,8 +217,35 @@ pub fn router(state: AppState) -> Router {
         .route("/api/channels/status", get(channel_status))
         .route("/api/chat", post(chat))
         .route("/api/channels/telegram/webhook", post(telegram_webhook))
+        .route("/api/tools", get(list_tools))
         .route("/api/sessions", post(create_session).get(list_sessions))
         .route("/api/sessions/:id", get(get_session).delete(delete_session))
+        .route("/api/sessions/:id/checkpoints", get(list_checkpoints))
+        .route(
+            "/api/sessions/:id/checkpoints/:checkpoint_id",
+            get(get_checkpoint_diff),
+        )
+        .route(
+            "/api/sessions/:id/checkpoints/:checkpoint_id/restore",
+            post(restore_checkpoint),
+        )
+        .route(
+            "/api/memory/curated",
+            get(get_curated_memory).put(put_curated_memory),
+        )
+        .route("/api/memory/files", get(list_memory_files))
+        .route(
+            "/api/memory/files/*path",
+            get(get_memory_file).delete(delete_memory_file),
+        )
+        .route("/api/memory/search", get(search_memory))
+        .route("/api/memory/rollup", post(run_memory_rollup))
+        .route("/api/watch/status", get(watch_status))
+        .route("/api/workspace/export", post(export_workspace))
+        .route(
+            "/api/workspace/import",
+            post(import_workspace).layer(DefaultBodyLimit::max(MAX_ARCHIVE_BYTES)),
+        )
         .route("/api/config", get(get_config))
         .route("/api/config/reset", post(reset_config))
         .route("/api/config/apply", post(apply_config))
@@ -173,6 +286,21 @@ async fn channel_status(
     }))
 }
 
+async fn list_tools(
+    State(state): State<AppState>,
+    Query(query): Query<ToolsQuery>,
+) -> Json<ToolsResponse> {
+    let tools = state
+        .current_agent()
+        .await
+        .tool_specs(query.channel.as_deref());
+    tracing::debug!(channel = ?query.channel, count = tools.len(), "api list tools");
+    Json(ToolsResponse {
+        channel: query.channel,
+        tools,
+    })
+}
+
 async fn create_session(State(state): State<AppState>) -> Json<SessionState> {
     let service = SessionService::new(state.sessions.clone());
     let session = service.create().await;
@@ -207,6 +335,199 @@ async fn delete_session(
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
+async fn get_curated_memory(
+    State(state): State<AppState>,
+) -> Result<Json<CuratedMemoryBody>, AppError> {
+    let service = MemoryService::new(state.current_agent().await.memory());
+    Ok(Json(CuratedMemoryBody {
+        content: service.curated().await?,
+    }))
+}
+
+async fn put_curated_memory(
+    State(state): State<AppState>,
+    Json(body): Json<CuratedMemoryBody>,
+) -> Result<Json<CuratedMemoryBody>, AppError> {
+    let service = MemoryService::new(state.current_agent().await.memory());
+    service.replace_curated(&body.content).await?;
+    tracing::info!(bytes = body.content.len(), "api replace curated memory");
+    Ok(Json(CuratedMemoryBody {
+        content: service.curated().await?,
+    }))
+}
+
+async fn list_memory_files(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<MemoryFile>>, AppError> {
+    let service = MemoryService::new(state.current_agent().await.memory());
+    let files = service.files().await?;
+    tracing::debug!(count = files.len(), "api list memory files");
+    Ok(Json(files))
+}
+
+async fn get_memory_file(
+    Path(path): Path<String>,
+    State(state): State<AppState>,
+    Query(query): Query<MemoryFileQuery>,
+) -> Result<Json<MemoryFileResponse>, AppError> {
+    let service = MemoryService::new(state.current_agent().await.memory());
+    let content = service
+        .file(&path, query.start_line, query.end_line)
+        .await?;
+    Ok(Json(MemoryFileResponse {
+        path,
+        start_line: query.start_line,
+        end_line: query.end_line,
+        content,
+    }))
+}
+
+async fn delete_memory_file(
+    Path(path): Path<String>,
+    State(state): State<AppState>,
+    Query(query): Query<DeleteMemoryFileQuery>,
+) -> Result<axum::http::StatusCode, AppError> {
+    let service = MemoryService::new(state.current_agent().await.memory());
+    service.delete_daily_log(&path, query.archive).await?;
+    tracing::info!(path = %path, archive = query.archive, "api delete memory file");
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+async fn search_memory(
+    State(state): State<AppState>,
+    Query(query): Query<MemorySearchQuery>,
+) -> Result<Json<Vec<MemoryHit>>, AppError> {
+    let service = MemoryService::new(state.current_agent().await.memory());
+    let hits = service
+        .search(
+            &query.q,
+            query.limit.unwrap_or(DEFAULT_MEMORY_SEARCH_LIMIT),
+        )
+        .await?;
+    tracing::debug!(hits = hits.len(), "api search memory");
+    Ok(Json(hits))
+}
+
+async fn run_memory_rollup(
+    State(state): State<AppState>,
+    Query(query): Query<RollupQuery>,
+) -> Result<Json<RollupReport>, AppError> {
+    let rollup = state
+        .current_agent()
+        .await
+        .memory_rollup()
+        .ok_or_else(|| AppError::service_unavailable("memory rollup is not configured"))?;
+    let report = rollup
+        .run(query.dry_run)
+        .await
+        .map_err(|error| AppError::internal(format!("memory rollup failed: {error}")))?;
+    tracing::info!(
+        dry_run = query.dry_run,
+        digests = report.digests.len(),
+        "api memory rollup"
+    );
+    Ok(Json(report))
+}
+
+/// Watched directories and recent external edits; `enabled` is `false` without a watcher.
+async fn watch_status(State(state): State<AppState>) -> Json<WatchStatus> {
+    let status = state
+        .current_agent()
+        .await
+        .file_watcher()
+        .map(|watcher| watcher.status())
+        .unwrap_or_default();
+    Json(status)
+}
+
+/// Streams the workspace as a `tar.gz` download.
+async fn export_workspace(
+    State(state): State<AppState>,
+    Query(query): Query<WorkspaceExportQuery>,
+) -> Result<([(header::HeaderName, HeaderValue); 2], Vec<u8>), AppError> {
+    let service = WorkspaceService::new(state.workspace_archive.clone());
+    let export = service
+        .export(ExportOptions {
+            secrets: query.secrets,
+            include_logs: query.include_logs,
+        })
+        .await?;
+    tracing::info!(
+        files = export.files,
+        bytes = export.bytes,
+        secrets = export.secrets.as_str(),
+        "api export workspace"
+    );
+    let disposition = HeaderValue::from_str(&format!(
+        "attachment; filename=\"{}\"",
+        export.file_name
+    ))
+    .map_err(|error| AppError::internal(format!("invalid export file name: {error}")))?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static("application/gzip")),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        export.archive,
+    ))
+}
+
+/// Restores a workspace archive sent as the raw request body.
+async fn import_workspace(
+    State(state): State<AppState>,
+    Query(query): Query<WorkspaceImportQuery>,
+    body: Bytes,
+) -> Result<Json<ImportReport>, AppError> {
+    let service = WorkspaceService::new(state.workspace_archive.clone());
+    let options = ImportOptions {
+        dry_run: query.dry_run,
+        include_config: query.include_config,
+    };
+    let report = service.import(body.to_vec(), options).await?;
+    tracing::info!(
+        dry_run = report.dry_run,
+        added = report.added.len(),
+        modified = report.modified.len(),
+        unchanged = report.unchanged,
+        skipped = report.skipped.len(),
+        "api import workspace"
+    );
+    Ok(Json(report))
+}
+
+async fn list_checkpoints(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Checkpoint>>, AppError> {
+    let service = CheckpointService::new(state.current_agent().await.checkpoints());
+    let checkpoints = service.list(&id).await?;
+    tracing::debug!(session_id = %id, count = checkpoints.len(), "api list checkpoints");
+    Ok(Json(checkpoints))
+}
+
+async fn get_checkpoint_diff(
+    Path((id, checkpoint_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<CheckpointDiff>, AppError> {
+    let service = CheckpointService::new(state.current_agent().await.checkpoints());
+    Ok(Json(service.diff(&id, &checkpoint_id).await?))
+}
+
+async fn restore_checkpoint(
+    Path((id, checkpoint_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<CheckpointRestore>, AppError> {
+    let service = CheckpointService::new(state.current_agent().await.checkpoints());
+    let restored = service.restore(&id, &checkpoint_id).await?;
+    tracing::info!(
+        session_id = %id,
+        checkpoint_id = %checkpoint_id,
+        path = %restored.restored.path,
+        "api restore checkpoint"
+    );
+    Ok(Json(restored))
+}
+
 async fn chat(
     State(state): State<AppState>,
     Json(payload): Json<ChatRequest>,
@@ -362,6 +683,9 @@ fn chat_event_to_sse(event: ChatEvent) -> Event {
             .data(json!({"session_id": session_id}).to_string()),
         ChatEvent::Delta(chunk) => Event::default().event("delta").data(chunk),
         ChatEvent::Tool(tool) => tool_event_to_sse(tool),
+        ChatEvent::Plan(plan) => Event::default()
+            .event("plan")
+            .data(json!({"plan": plan}).to_string()),
     }
 }
 
@@ -373,6 +697,7 @@ fn tool_event_to_sse(event: ToolEvent) -> Event {
             "args": event.call.arguments,
             "output": event.result.output,
             "is_error": event.result.is_error,
+            "truncation": event.result.truncation,
         })
         .to_string(),
     )
@@ -0,0 +1,287 @@
+//! MCP server over stdio: exposes the agent's tools, memory and a `chat` tool to editors
+//! and other agents.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::application::agent::AgentLoop;
+use crate::application::ChatService;
+use crate::domain::chat::ChatCommand;
+use crate::domain::tools::ToolSelection;
+use crate::domain::types::{SessionState, ToolSpec};
+use crate::infrastructure::mcp::PROTOCOL_VERSION;
+use crate::infrastructure::session_store::SessionStore;
+use crate::infrastructure::tooling::{MemoryReadCuratedTool, MemoryWriteCuratedTool, ToolRegistry};
+
+const CHAT_TOOL: &str = "chat";
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub struct McpServer {
+    agent: Arc<AgentLoop>,
+    chat: ChatService,
+    memory_tools: ToolRegistry,
+    /// Session used for direct tool calls, so checkpoints and spilled outputs group together.
+    tool_session_id: String,
+}
+
+impl McpServer {
+    pub fn new(agent: Arc<AgentLoop>) -> Self {
+        let mut memory_tools = ToolRegistry::new();
+        memory_tools.register_memory_tools();
+        memory_tools.register(MemoryReadCuratedTool);
+        memory_tools.register(MemoryWriteCuratedTool);
+
+        Self {
+            chat: ChatService::new(
+                Arc::new(RwLock::new(agent.clone())),
+                SessionStore::new(),
+                None,
+            ),
+            agent,
+            memory_tools,
+            tool_session_id: format!("mcp-{}", Uuid::new_v4()),
+        }
+    }
+
+    /// Reads newline-delimited JSON-RPC messages until EOF, writing one response line per
+    /// request. Requests are handled in order.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        tracing::info!(session_id = %self.tool_session_id, "mcp server started");
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => self.handle(message).await,
+                Err(error) => Some(error_response(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("parse error: {error}"),
+                )),
+            };
+            if let Some(response) = response {
+                let mut encoded = serde_json::to_vec(&response)?;
+                encoded.push(b'\n');
+                writer.write_all(&encoded).await?;
+                writer.flush().await?;
+            }
+        }
+        tracing::info!("mcp server input closed");
+        Ok(())
+    }
+
+    /// Handles one JSON-RPC message; notifications and responses produce no reply.
+    pub async fn handle(&self, message: Value) -> Option<Value> {
+        let method = message.get("method").and_then(Value::as_str);
+        let Some(id) = message.get("id").cloned() else {
+            if let Some(method) = method {
+                tracing::debug!(method, "mcp notification");
+            }
+            return None;
+        };
+        let Some(method) = method else {
+            return Some(error_response(id, INVALID_REQUEST, "missing method"));
+        };
+        let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+
+        tracing::debug!(method, "mcp request");
+        let result = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({"tools": self.tool_list()})),
+            "tools/call" => self.call_tool(&params).await,
+            other => Err((METHOD_NOT_FOUND, format!("method not found: {other}"))),
+        };
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested = params
+            .get("protocolVersion")
+            .and_then(Value::as_str)
+            .unwrap_or(PROTOCOL_VERSION);
+        let protocol_version = if SUPPORTED_PROTOCOL_VERSIONS.contains(&requested) {
+            requested
+        } else {
+            PROTOCOL_VERSION
+        };
+        json!({
+            "protocolVersion": protocol_version,
+            "capabilities": {"tools": {"listChanged": false}},
+            "serverInfo": {"name": "chaos-bot", "version": env!("CARGO_PKG_VERSION")},
+            "instructions": "Use `chat` to talk to chaos-bot with its personality and memory; \
+                             pass the returned session_id to continue a conversation. \
+                             memory_* tools read and update its long-term memory directly."
+        })
+    }
+
+    fn tool_list(&self) -> Vec<Value> {
+        let mut specs = vec![ToolSpec {
+            name: CHAT_TOOL.to_string(),
+            description: "Send a message to chaos-bot and get its reply (runs a full agent turn)"
+                .to_string(),
+            parameters_schema: json!({
+                "type": "object",
+                "properties": {
+                    "message": {"type": "string"},
+                    "session_id": {"type": "string", "description": "Continue an existing conversation"}
+                },
+                "required": ["message"]
+            }),
+        }];
+        specs.extend(self.tool_selection().filter(self.memory_tools.specs()));
+        let mut seen: HashSet<String> = specs.iter().map(|spec| spec.name.clone()).collect();
+        specs.extend(
+            self.agent
+                .tool_specs(None)
+                .into_iter()
+                .filter(|spec| seen.insert(spec.name.clone())),
+        );
+
+        specs
+            .into_iter()
+            .map(|spec| {
+                json!({
+                    "name": spec.name,
+                    "description": spec.description,
+                    "inputSchema": spec.parameters_schema,
+                })
+            })
+            .collect()
+    }
+
+    /// Tools a client may list and call directly: the agent's default selection, which also
+    /// covers the memory tools.
+    fn tool_selection(&self) -> &ToolSelection {
+        self.agent.tool_policy().for_channel(None)
+    }
+
+    async fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(Value::as_str).ok_or_else(|| {
+            (
+                INVALID_PARAMS,
+                "tools/call requires a tool name".to_string(),
+            )
+        })?;
+        let arguments = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+
+        if name == CHAT_TOOL {
+            return self.chat(arguments).await;
+        }
+
+        if !self.tool_selection().allows(name) {
+            return Err((INVALID_PARAMS, format!("tool not enabled: {name}")));
+        }
+
+        let tool_call_id = format!("mcp_{}", Uuid::new_v4().simple());
+        // Direct calls, wasm plugins included, see the memory scope of this session.
+        let context = self
+            .agent
+            .tool_context(&SessionState::new(self.tool_session_id.clone()));
+        let outcome = if self
+            .memory_tools
+            .specs()
+            .iter()
+            .any(|spec| spec.name == name)
+        {
+            self.memory_tools
+                .dispatch(&tool_call_id, name, arguments, &context)
+                .await
+        } else if self
+            .agent
+            .tool_specs(None)
+            .iter()
+            .any(|spec| spec.name == name)
+        {
+            self.agent
+                .tools()
+                .execute(&tool_call_id, name, arguments, &context)
+                .await
+        } else {
+            return Err((INVALID_PARAMS, format!("unknown tool: {name}")));
+        };
+
+        let is_error = outcome.as_ref().map_or(true, |result| result.is_error);
+        tracing::info!(tool_name = name, is_error, "mcp tool call");
+        Ok(match outcome {
+            Ok(result) => tool_result(result.output, result.is_error),
+            Err(error) => tool_result(format!("tool error: {error}"), true),
+        })
+    }
+
+    async fn chat(&self, arguments: Value) -> Result<Value, (i64, String)> {
+        let message = arguments
+            .get("message")
+            .and_then(Value::as_str)
+            .ok_or_else(|| (INVALID_PARAMS, "chat.message is required".to_string()))?
+            .to_string();
+        let session_id = arguments
+            .get("session_id")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        let result = self
+            .chat
+            .run_stream(
+                ChatCommand {
+                    session_id,
+                    message,
+                    channel: None,
+                },
+                |_| {},
+            )
+            .await;
+
+        Ok(match result {
+            Ok(result) => json!({
+                "content": [
+                    {"type": "text", "text": result.assistant_message},
+                    {"type": "text", "text": format!("[session_id: {}]", result.session_id)}
+                ],
+                "structuredContent": {
+                    "session_id": result.session_id,
+                    "reply": result.assistant_message,
+                    "finish_reason": result.finish_reason,
+                },
+                "isError": false
+            }),
+            Err(error) => tool_result(format!("chat failed: {}", error.message()), true),
+        })
+    }
+}
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({
+        "content": [{"type": "text", "text": text}],
+        "isError": is_error
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message}
+    })
+}
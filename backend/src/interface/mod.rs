@@ -1,3 +1,4 @@
 pub mod api;
 pub mod http;
+pub mod mcp;
 pub use api::*;
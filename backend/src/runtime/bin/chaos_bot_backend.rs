@@ -1,9 +1,10 @@
 use anyhow::Result;
 use chaos_bot_backend::infrastructure::config::AppConfig;
-use chaos_bot_backend::infrastructure::logging::init_logging;
+use chaos_bot_backend::infrastructure::logging::{init_logging_with_console, LogConsole};
 use chaos_bot_backend::interface::api::router;
-use chaos_bot_backend::runtime::{build_app_with_config_runtime, shutdown_signal};
+use chaos_bot_backend::runtime::{build_app_with_config_runtime, run_mcp_stdio, shutdown_signal};
 use chaos_bot_backend::runtime::config_runtime::RestartMode;
+use chaos_bot_backend::runtime::workspace_cli::{run_workspace_command, WorkspaceCommand};
 use tokio::net::TcpListener;
 use tracing::info;
 
@@ -11,8 +12,16 @@ use tracing::info;
 async fn main() -> Result<()> {
     let loaded = AppConfig::load_with_source()?;
     let config = loaded.app.clone();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mcp_mode = args.first().map(String::as_str) == Some("mcp");
+    let workspace_command = WorkspaceCommand::parse(&args).transpose()?;
 
-    let logging_runtime = init_logging(&config)?;
+    let console = if mcp_mode || workspace_command.is_some() {
+        LogConsole::Stderr
+    } else {
+        LogConsole::Stdout
+    };
+    let logging_runtime = init_logging_with_console(&config, console)?;
     info!(
         workspace = %config.workspace.display(),
         config_file = %config.config_path.display(),
@@ -23,6 +32,18 @@ async fn main() -> Result<()> {
         "chaos-bot logging initialized"
     );
 
+    if mcp_mode {
+        run_mcp_stdio(&config).await?;
+        drop(logging_runtime);
+        return Ok(());
+    }
+
+    if let Some(command) = workspace_command {
+        run_workspace_command(&config, command).await?;
+        drop(logging_runtime);
+        return Ok(());
+    }
+
     let restart_mode = if std::env::var("CHAOS_BOT_DISABLE_SELF_RESTART")
         .ok()
         .as_deref()
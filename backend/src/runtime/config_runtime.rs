@@ -6,7 +6,9 @@ use std::time::Duration;
 use tokio::sync::RwLock;
 
 use crate::application::agent::AgentLoop;
+use crate::domain::config::CommandChangeRejected;
 use crate::infrastructure::config::{read_config_file, write_config_file, AgentFileConfig, AppConfig, EnvSecrets};
+use crate::infrastructure::mcp::McpTransportSpec;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RestartMode {
@@ -27,6 +29,8 @@ pub struct ConfigRuntime {
     workspace_base: PathBuf,
     config_path: PathBuf,
     restart_mode: RestartMode,
+    /// Whether an apply may start commands the running config does not; never set from config.
+    allow_command_changes: bool,
 }
 
 #[derive(Clone)]
@@ -55,9 +59,15 @@ impl ConfigRuntime {
             workspace_base,
             config_path,
             restart_mode,
+            allow_command_changes: false,
         }
     }
 
+    pub fn with_command_changes_allowed(mut self, allowed: bool) -> Self {
+        self.allow_command_changes = allowed;
+        self
+    }
+
     pub fn config_path(&self) -> &Path {
         &self.config_path
     }
@@ -101,6 +111,10 @@ impl ConfigRuntime {
             self.workspace_base.clone(),
         );
         next_app.config_path = self.config_path.clone();
+        if !self.allow_command_changes {
+            let running = self.state.read().await.running_app.clone();
+            ensure_commands_unchanged(&running, &next_app)?;
+        }
 
         let next_agent = self.agent_factory.build_agent(&next_app).await?;
         write_config_with_backups(&self.config_path, &next)?;
@@ -187,7 +201,43 @@ fn rotate_backups(path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn backup_path(path: &Path, level: u8) -> PathBuf {
+/// Fails when `next` would start a command `running` does not: a stdio MCP server that is new
+/// or whose command, arguments or environment differ.
+fn ensure_commands_unchanged(running: &AppConfig, next: &AppConfig) -> Result<()> {
+    for server in &next.mcp_servers {
+        if !matches!(server.transport, McpTransportSpec::Stdio { .. }) {
+            continue;
+        }
+        let unchanged = running
+            .mcp_servers
+            .iter()
+            .any(|current| current.name == server.name && current.transport == server.transport);
+        if !unchanged {
+            return Err(CommandChangeRejected(format!(
+                "the command of mcp server `{}`",
+                server.name
+            ))
+            .into());
+        }
+    }
+    for plugin in &next.tool_plugins {
+        let unchanged = running.tool_plugins.iter().any(|current| {
+            current.name == plugin.name
+                && current.command == plugin.command
+                && current.args == plugin.args
+                && current.env == plugin.env
+        });
+        if !unchanged {
+            return Err(
+                CommandChangeRejected(format!("the command of plugin `{}`", plugin.name)).into(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `<config_file>.bak<level>`, the rotated copies kept by every config write.
+pub fn backup_path(path: &Path, level: u8) -> PathBuf {
     let file_name = path
         .file_name()
         .map(|name| name.to_string_lossy().to_string())
@@ -217,4 +267,59 @@ mod tests {
         assert!(bak1.exists());
         assert!(bak2.exists());
     }
+
+    #[test]
+    fn command_changes_are_detected() {
+        let app = |servers: serde_json::Value| {
+            let file: AgentFileConfig =
+                serde_json::from_value(serde_json::json!({"mcp": {"servers": servers}}))
+                    .expect("config");
+            AppConfig::from_inputs(file, EnvSecrets::default(), PathBuf::from("/tmp/commands"))
+        };
+        let running = app(serde_json::json!({"fs": {"command": "mcp-fs", "args": ["/srv"]}}));
+
+        assert!(ensure_commands_unchanged(&running, &running).is_ok());
+        assert!(ensure_commands_unchanged(&running, &app(serde_json::json!({}))).is_ok());
+        let remote = app(serde_json::json!({"remote": {"url": "http://localhost/mcp"}}));
+        assert!(ensure_commands_unchanged(&running, &remote).is_ok());
+
+        let changed = app(serde_json::json!({"fs": {"command": "mcp-fs", "args": ["/"]}}));
+        let error = ensure_commands_unchanged(&running, &changed).unwrap_err();
+        assert!(error.downcast_ref::<CommandChangeRejected>().is_some());
+        let added = app(serde_json::json!({
+            "fs": {"command": "mcp-fs", "args": ["/srv"]},
+            "shell": {"command": "sh"}
+        }));
+        assert!(ensure_commands_unchanged(&running, &added).is_err());
+    }
+
+    #[test]
+    fn plugin_command_changes_are_detected() {
+        let app = |plugins: serde_json::Value| {
+            let file: AgentFileConfig =
+                serde_json::from_value(serde_json::json!({"tools": {"plugins": plugins}}))
+                    .expect("config");
+            AppConfig::from_inputs(file, EnvSecrets::default(), PathBuf::from("/tmp/commands"))
+        };
+        let running = app(serde_json::json!([
+            {"name": "weather", "command": "/opt/weather", "description": "Weather"}
+        ]));
+
+        let described = app(serde_json::json!([
+            {"name": "weather", "command": "/opt/weather", "description": "Forecast", "timeout_secs": 5}
+        ]));
+        assert!(ensure_commands_unchanged(&running, &described).is_ok());
+        assert!(ensure_commands_unchanged(&running, &app(serde_json::json!([]))).is_ok());
+
+        let changed = app(serde_json::json!([
+            {"name": "weather", "command": "/opt/weather", "args": ["--debug"]}
+        ]));
+        let error = ensure_commands_unchanged(&running, &changed).unwrap_err();
+        assert!(error.to_string().contains("plugin `weather`"));
+        let added = app(serde_json::json!([
+            {"name": "weather", "command": "/opt/weather"},
+            {"name": "shell", "command": "sh"}
+        ]));
+        assert!(ensure_commands_unchanged(&running, &added).is_err());
+    }
 }
@@ -1,24 +1,44 @@
 pub mod bootstrap;
 pub mod config_runtime;
+pub mod workspace_cli;
 
 use anyhow::Result;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use tokio::sync::RwLock;
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep, Duration, Instant};
 
 use crate::application::ChatService;
 use crate::application::agent::{AgentConfig, AgentLoop};
-use crate::domain::ports::{MemoryPort, ToolExecutorPort};
+use crate::application::{MemoryExtractor, MemoryRollup};
+use crate::domain::config::ALLOW_CONFIG_COMMANDS_ENV;
+use crate::domain::memory::MemoryBackendKind;
+use crate::domain::ports::{
+    CheckpointPort, EmbeddingPort, FileWatchPort, MemoryPort, ToolExecutorPort,
+    WorkspaceSearchPort,
+};
 use crate::interface::api::AppState;
+use crate::interface::mcp::McpServer;
 use crate::infrastructure::channels::build_dispatcher;
+use crate::infrastructure::checkpoints::CheckpointStore;
 use crate::infrastructure::channels::telegram::poll_updates_once;
 use crate::runtime::bootstrap::bootstrap_runtime_dirs;
-use crate::infrastructure::config::{workspace_base_for, AgentFileConfig, AppConfig};
-use crate::runtime::config_runtime::{AgentFactory, ConfigRuntime, RestartMode};
+use crate::infrastructure::config::{
+    legacy_config_path_for_workspace, workspace_base_for, AgentFileConfig, AppConfig,
+};
+use crate::infrastructure::embedding::OpenAiEmbedding;
+use crate::infrastructure::file_watch::FileWatcher;
+use crate::runtime::config_runtime::{backup_path, AgentFactory, ConfigRuntime, RestartMode};
+use crate::infrastructure::mcp::register_mcp_tools;
 use crate::infrastructure::model;
 use crate::infrastructure::memory::MemoryStore;
+use crate::infrastructure::memory_facts::FactStore;
 use crate::infrastructure::personality::{PersonalityLoader, PersonalitySource};
-use crate::infrastructure::tooling::ToolRegistry;
+use crate::infrastructure::tooling::{
+    register_code_tools, register_git_tools, register_plugin_tools, register_sql_tools,
+    register_wasm_plugins, FetchTool, OutputSpill, SearchWorkspaceTool, ToolRegistry,
+};
+use crate::infrastructure::workspace_archive::WorkspaceArchive;
+use crate::infrastructure::workspace_index::{HashedEmbedder, WorkspaceIndex};
 
 struct BackendAgentFactory;
 
@@ -39,8 +59,10 @@ pub async fn build_app(config: &AppConfig) -> Result<AppState> {
         config.telegram_enabled,
         config.telegram_polling,
         config.telegram_api_base_url.clone(),
-    );
+    )
+    .with_workspace_archive(Arc::new(WorkspaceArchive::from_config(config)));
     maybe_spawn_telegram_poller(state.clone(), config);
+    spawn_memory_rollup_scheduler(state.clone());
     Ok(state)
 }
 
@@ -56,15 +78,20 @@ pub async fn build_app_with_config_runtime(
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let workspace_base = workspace_base_for(&cwd);
 
-    let runtime = Arc::new(ConfigRuntime::new(
-        agent_slot.clone(),
-        Arc::new(BackendAgentFactory),
-        file_config,
-        config.clone(),
-        workspace_base,
-        config.config_path.clone(),
-        restart_mode,
-    ));
+    let allow_command_changes =
+        std::env::var(ALLOW_CONFIG_COMMANDS_ENV).ok().as_deref() == Some("1");
+    let runtime = Arc::new(
+        ConfigRuntime::new(
+            agent_slot.clone(),
+            Arc::new(BackendAgentFactory),
+            file_config,
+            config.clone(),
+            workspace_base,
+            config.config_path.clone(),
+            restart_mode,
+        )
+        .with_command_changes_allowed(allow_command_changes),
+    );
 
     let state = AppState::with_config_runtime(
         agent_slot,
@@ -74,8 +101,10 @@ pub async fn build_app_with_config_runtime(
         config.telegram_enabled,
         config.telegram_polling,
         config.telegram_api_base_url.clone(),
-    );
+    )
+    .with_workspace_archive(Arc::new(WorkspaceArchive::from_config(config)));
     maybe_spawn_telegram_poller(state.clone(), config);
+    spawn_memory_rollup_scheduler(state.clone());
     Ok(state)
 }
 
@@ -83,27 +112,223 @@ pub async fn build_agent_loop(config: &AppConfig) -> Result<Arc<AgentLoop>> {
     bootstrap_runtime_dirs(config).await?;
     tokio::fs::create_dir_all(&config.memory_dir).await?;
 
-    let memory: Arc<dyn MemoryPort> = Arc::new(MemoryStore::new(
-        config.memory_dir.clone(),
-        config.memory_file.clone(),
-    ));
-    memory.ensure_layout().await?;
+    let (memory, memory_files) = build_memory(config).await?;
 
-    let personality: Arc<dyn PersonalitySource> =
-        Arc::new(PersonalityLoader::new(config.personality_dir.clone()));
+    let personality_loader = PersonalityLoader::new(config.personality_dir.clone());
+    let personality: Arc<dyn PersonalitySource> = Arc::new(personality_loader.clone());
     let provider = model::build_provider(config)?;
 
     let mut registry = ToolRegistry::new();
     registry.register_default_tools();
+    if config.fetch_enabled {
+        registry.register(FetchTool::new(config.fetch_policy.clone()));
+    }
+    if config.code_tools_enabled {
+        register_code_tools(&mut registry, config.code_index_dir.clone());
+    }
+    if config.git_tools_enabled {
+        register_git_tools(&mut registry, config.git_commit_enabled);
+    }
+    if config.sql_tools_enabled {
+        register_sql_tools(&mut registry, config.sql_limits);
+    }
+    let workspace_index = config.workspace_index_enabled.then(|| {
+        let index = build_workspace_index(config);
+        spawn_workspace_index_refresher(&index, config.workspace_index_refresh);
+        index as Arc<dyn WorkspaceSearchPort>
+    });
+    if let Some(index) = &workspace_index {
+        registry.register(SearchWorkspaceTool::new(index.clone()));
+    }
+    // Built-ins first: external tools never replace a name that is already registered.
+    register_plugin_tools(&mut registry, &config.tool_plugins).await;
+    if config.wasm_plugins_enabled {
+        register_wasm_plugins(&mut registry, &config.wasm_plugins_dir);
+    }
+    register_mcp_tools(&mut registry, &config.mcp_servers).await;
+    let registry = registry.with_output_spill(OutputSpill::new(
+        config.tool_output_dir.clone(),
+        config.tool_output_limits,
+    ));
     let tools: Arc<dyn ToolExecutorPort> = Arc::new(registry);
 
-    Ok(Arc::new(AgentLoop::new(
-        provider,
+    let mut agent = AgentLoop::new(
+        provider.clone(),
         tools,
         personality,
-        memory,
+        memory.clone(),
         AgentConfig::from(config),
-    )))
+    )
+    .with_tool_policy(config.tool_policy.clone())
+    .with_memory_scoping(config.memory_scoping);
+    if let Some(index) = workspace_index {
+        agent = agent.with_workspace_index(index, config.workspace_index_context_results);
+    }
+    let mut rollup = MemoryRollup::new(memory.clone(), config.memory_rollup.clone());
+    if config.memory_rollup.summarize {
+        rollup = rollup.with_summarizer(provider.clone(), config.model.clone());
+    }
+    agent = agent.with_memory_rollup(Arc::new(rollup));
+    if config.memory_extraction.enabled {
+        agent = agent.with_memory_extractor(Arc::new(MemoryExtractor::new(
+            provider.clone(),
+            memory.clone(),
+            config.model.clone(),
+            config.memory_extraction.clone(),
+        )));
+    }
+    if config.watch_enabled {
+        if let Some(watcher) = start_file_watcher(config, &personality_loader, &memory_files) {
+            agent = agent.with_file_watcher(watcher);
+        }
+    }
+    if config.checkpoints_enabled {
+        let checkpoints: Arc<dyn CheckpointPort> = Arc::new(CheckpointStore::new(
+            config.checkpoints_dir.clone(),
+            config.checkpoint_retention,
+        ));
+        agent = agent.with_checkpoints(checkpoints);
+    }
+
+    Ok(Arc::new(agent))
+}
+
+/// The configured memory backend and the markdown store under it.
+async fn build_memory(config: &AppConfig) -> Result<(Arc<dyn MemoryPort>, MemoryStore)> {
+    let files = build_memory_store(config);
+    files.ensure_layout().await?;
+    match config.memory_backend {
+        MemoryBackendKind::Files => Ok((Arc::new(files.clone()), files)),
+        MemoryBackendKind::Sqlite => {
+            let facts =
+                FactStore::open(&config.memory_facts_db, files.clone(), config.memory_facts)?;
+            facts.import_once().await?;
+            Ok((Arc::new(facts), files))
+        }
+    }
+}
+
+/// Watches the personality files, the memory directory and `MEMORY.md`; without a watcher
+/// both are reread on every request.
+fn start_file_watcher(
+    config: &AppConfig,
+    personality: &PersonalityLoader,
+    memory: &MemoryStore,
+) -> Option<Arc<dyn FileWatchPort>> {
+    let mut watcher = FileWatcher::new(config.watch_debounce)
+        .watch(
+            "personality",
+            config.personality_dir.clone(),
+            false,
+            Arc::new(personality.clone()),
+        )
+        .watch(
+            "memory",
+            config.memory_dir.clone(),
+            true,
+            Arc::new(memory.clone()),
+        );
+    if let Some(parent) = config.memory_file.parent() {
+        watcher = watcher.watch("memory", parent, false, Arc::new(memory.clone()));
+    }
+    match watcher.start() {
+        Ok(watcher) => Some(Arc::new(watcher)),
+        Err(error) => {
+            tracing::warn!(error = %error, "file watcher unavailable; rereading files on every request");
+            None
+        }
+    }
+}
+
+fn build_memory_store(config: &AppConfig) -> MemoryStore {
+    let store = MemoryStore::new(config.memory_dir.clone(), config.memory_file.clone())
+        .with_max_curated_bytes(config.memory_max_curated_bytes);
+    let embedder: Arc<dyn EmbeddingPort> = match config.memory_embedding.as_deref() {
+        None => return store,
+        Some("hashed") => Arc::new(HashedEmbedder::default()),
+        Some("openai") => {
+            let base_url = config
+                .memory_embedding_base_url
+                .clone()
+                .or_else(|| std::env::var("OPENAI_BASE_URL").ok())
+                .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+            let mut embedding =
+                OpenAiEmbedding::new(base_url, config.memory_embedding_model.clone());
+            if let Some(api_key) = &config.openai_api_key {
+                embedding = embedding.with_api_key(api_key.clone());
+            }
+            Arc::new(embedding)
+        }
+        Some(other) => {
+            tracing::warn!(embedding = other, "unknown memory embedding; using keyword search");
+            return store;
+        }
+    };
+    tracing::info!(embedder = %embedder.id(), "memory hybrid search enabled");
+    store.with_embeddings(embedder, config.memory_hybrid)
+}
+
+fn build_workspace_index(config: &AppConfig) -> Arc<WorkspaceIndex> {
+    let mut options = config.workspace_index_options.clone();
+    options.exclude = vec![
+        config.workspace.join("data"),
+        config.log_dir.clone(),
+        config.memory_dir.clone(),
+        config.memory_file.clone(),
+        config.personality_dir.clone(),
+    ];
+    // The config carries the secrets block; `.env` files are skipped by the index itself.
+    for config_file in [
+        config.config_path.clone(),
+        legacy_config_path_for_workspace(&config.workspace),
+    ] {
+        options.exclude.push(backup_path(&config_file, 1));
+        options.exclude.push(backup_path(&config_file, 2));
+        options.exclude.push(config_file);
+    }
+    let mut index = WorkspaceIndex::new(
+        config.working_dir.clone(),
+        config.workspace_index_dir.clone(),
+        options,
+    );
+    match config.workspace_index_embedding.as_deref() {
+        None => {}
+        Some("hashed") => index = index.with_embedder(Arc::new(HashedEmbedder::default())),
+        Some(other) => {
+            tracing::warn!(embedding = other, "unknown workspace index embedding; using BM25 only")
+        }
+    }
+    Arc::new(index)
+}
+
+/// Refreshes `index` now and every `interval` so searches never walk the working directory.
+/// The task holds a weak reference and ends once the agent that owns the index is replaced.
+fn spawn_workspace_index_refresher(index: &Arc<WorkspaceIndex>, interval: Duration) {
+    let index: Weak<WorkspaceIndex> = Arc::downgrade(index);
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            let Some(index) = index.upgrade() else {
+                break;
+            };
+            match tokio::task::spawn_blocking(move || index.refresh()).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(error)) => tracing::warn!(error = %error, "workspace index refresh failed"),
+                Err(error) => tracing::warn!(error = %error, "workspace index refresh panicked"),
+            }
+        }
+    });
+}
+
+/// Runs chaos-bot as an MCP server on stdin/stdout until the client closes stdin.
+pub async fn run_mcp_stdio(config: &AppConfig) -> Result<()> {
+    let agent = build_agent_loop(config).await?;
+    let server = McpServer::new(agent);
+    server
+        .serve(tokio::io::BufReader::new(tokio::io::stdin()), tokio::io::stdout())
+        .await
 }
 
 pub async fn shutdown_signal() {
@@ -130,6 +355,47 @@ pub async fn shutdown_signal() {
     }
 }
 
+/// How often the rollup scheduler re-reads the current agent's options.
+const ROLLUP_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Runs the current agent's memory rollup every `memory.rollup.interval_hours`. The scheduler
+/// lives as long as the process and idles while rollup is disabled, so enabling it or changing
+/// the interval through `/api/config/apply` takes effect without a restart.
+fn spawn_memory_rollup_scheduler(state: AppState) {
+    tokio::spawn(async move {
+        let mut last_run: Option<Instant> = None;
+        let mut active = false;
+        loop {
+            let rollup = state
+                .current_agent()
+                .await
+                .memory_rollup()
+                .filter(|rollup| rollup.options().enabled);
+            match rollup {
+                Some(rollup) => {
+                    if !active {
+                        tracing::info!("memory rollup scheduler active");
+                        active = true;
+                    }
+                    let due = last_run.is_none_or(|at| at.elapsed() >= rollup.options().interval);
+                    if due {
+                        last_run = Some(Instant::now());
+                        if let Err(error) = rollup.run(false).await {
+                            tracing::warn!(error = %error, "scheduled memory rollup failed");
+                        }
+                    }
+                }
+                None if active => {
+                    tracing::info!("memory rollup disabled; scheduler idle");
+                    active = false;
+                }
+                None => {}
+            }
+            sleep(ROLLUP_POLL_INTERVAL).await;
+        }
+    });
+}
+
 fn maybe_spawn_telegram_poller(state: AppState, config: &AppConfig) {
     if !config.telegram_enabled || !config.telegram_polling {
         return;
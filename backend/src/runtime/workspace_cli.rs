@@ -0,0 +1,133 @@
+//! `export` and `import` subcommands of the backend binary, for moving a workspace between
+//! machines without a running server.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use crate::domain::ports::WorkspaceArchivePort;
+use crate::domain::workspace::{ExportOptions, ImportOptions};
+use crate::infrastructure::config::AppConfig;
+use crate::infrastructure::workspace_archive::{WorkspaceArchive, MAX_ARCHIVE_BYTES};
+
+pub const WORKSPACE_USAGE: &str = "usage:
+  chaos-bot-backend export [FILE] [--secrets redact|exclude|include] [--include-logs]
+  chaos-bot-backend import FILE [--dry-run] [--include-config]";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WorkspaceCommand {
+    /// Writes the archive to `output`, or a timestamped file in the current directory.
+    Export {
+        output: Option<PathBuf>,
+        options: ExportOptions,
+    },
+    Import {
+        input: PathBuf,
+        options: ImportOptions,
+    },
+}
+
+impl WorkspaceCommand {
+    /// Parses the arguments after the binary name; `None` when they are not a workspace
+    /// subcommand.
+    pub fn parse(args: &[String]) -> Option<Result<Self>> {
+        let (command, rest) = args.split_first()?;
+        match command.as_str() {
+            "export" => Some(parse_export(rest)),
+            "import" => Some(parse_import(rest)),
+            _ => None,
+        }
+    }
+}
+
+fn parse_export(args: &[String]) -> Result<WorkspaceCommand> {
+    let mut output = None;
+    let mut options = ExportOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--include-logs" => options.include_logs = true,
+            "--secrets" => {
+                let mode = args
+                    .next()
+                    .with_context(|| format!("--secrets needs a value\n{WORKSPACE_USAGE}"))?;
+                options.secrets = mode.parse()?;
+            }
+            flag if flag.starts_with("--") => {
+                anyhow::bail!("unknown option `{flag}`\n{WORKSPACE_USAGE}")
+            }
+            path if output.is_none() => output = Some(PathBuf::from(path)),
+            extra => anyhow::bail!("unexpected argument `{extra}`\n{WORKSPACE_USAGE}"),
+        }
+    }
+    Ok(WorkspaceCommand::Export { output, options })
+}
+
+fn parse_import(args: &[String]) -> Result<WorkspaceCommand> {
+    let mut input = None;
+    let mut options = ImportOptions::default();
+    for arg in args {
+        match arg.as_str() {
+            "--dry-run" => options.dry_run = true,
+            "--include-config" => options.include_config = true,
+            flag if flag.starts_with("--") => {
+                anyhow::bail!("unknown option `{flag}`\n{WORKSPACE_USAGE}")
+            }
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => anyhow::bail!("unexpected argument `{extra}`\n{WORKSPACE_USAGE}"),
+        }
+    }
+    let input =
+        input.with_context(|| format!("import needs an archive file\n{WORKSPACE_USAGE}"))?;
+    Ok(WorkspaceCommand::Import { input, options })
+}
+
+/// Runs `command` against the configured workspace and prints the outcome to stdout.
+pub async fn run_workspace_command(config: &AppConfig, command: WorkspaceCommand) -> Result<()> {
+    let archive = WorkspaceArchive::from_config(config);
+    match command {
+        WorkspaceCommand::Export { output, options } => {
+            let export = archive.export(options).await?;
+            let output = output.unwrap_or_else(|| PathBuf::from(&export.file_name));
+            tokio::fs::write(&output, &export.archive)
+                .await
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            tracing::info!(
+                path = %output.display(),
+                files = export.files,
+                bytes = export.bytes,
+                secrets = export.secrets.as_str(),
+                "workspace exported"
+            );
+            println!(
+                "exported {} files ({} bytes, secrets {}) to {}",
+                export.files,
+                export.bytes,
+                export.secrets.as_str(),
+                output.display()
+            );
+        }
+        WorkspaceCommand::Import { input, options } => {
+            let bytes = tokio::fs::read(&input)
+                .await
+                .with_context(|| format!("failed to read {}", input.display()))?;
+            if bytes.len() > MAX_ARCHIVE_BYTES {
+                anyhow::bail!(
+                    "{} is larger than {MAX_ARCHIVE_BYTES} bytes",
+                    input.display()
+                );
+            }
+            let report = archive.import(bytes, options).await?;
+            tracing::info!(
+                path = %input.display(),
+                dry_run = options.dry_run,
+                added = report.added.len(),
+                modified = report.modified.len(),
+                unchanged = report.unchanged,
+                skipped = report.skipped.len(),
+                "workspace imported"
+            );
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    Ok(())
+}
@@ -7,7 +7,10 @@ use axum::{Json, Router};
 use chaos_bot_backend::interface::api::router;
 use chaos_bot_backend::infrastructure::channels::telegram::TelegramConnector;
 use chaos_bot_backend::infrastructure::channels::ChannelDispatcherRegistry;
+use chaos_bot_backend::infrastructure::file_watch::FileWatcher;
 use chaos_bot_backend::infrastructure::model::LlmStreamEvent;
+use chaos_bot_backend::infrastructure::tooling::{OutputLimits, OutputSpill};
+use chaos_bot_backend::infrastructure::workspace_archive::WorkspaceArchive;
 use chaos_bot_backend::domain::types::{SessionState, ToolCall};
 use serde_json::{json, Value};
 use std::sync::{Arc, Mutex};
@@ -228,6 +231,246 @@ async fn chat_sse_with_tool_calls() {
     assert!(text.contains("mock_tool"));
 }
 
+#[tokio::test]
+async fn chat_sse_streams_plan_updates_and_persists_session_plan() {
+    let tool_call = ToolCall {
+        id: "tc_plan".to_string(),
+        name: "todo_write".to_string(),
+        arguments: json!({"todos": [{"content": "Inspect logs", "status": "in_progress"}]}),
+    };
+    let provider = MockStreamProvider::tool_then_text(tool_call, "On it");
+
+    let mut registry = chaos_bot_backend::infrastructure::tooling::ToolRegistry::new();
+    registry.register_plan_tools();
+
+    let (_temp, state) = build_test_state_with_registry(Arc::new(provider), registry);
+    let app = router(state);
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/chat")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"session_id": "plan-session", "message": "debug it"}).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8_lossy(&body);
+    let plan_event = text
+        .split("\n\n")
+        .find(|chunk| chunk.contains("event: plan"))
+        .expect("plan event");
+    let data = plan_event
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap();
+    let payload: Value = serde_json::from_str(data).unwrap();
+    assert_eq!(
+        payload["plan"],
+        json!([{"content": "Inspect logs", "status": "in_progress"}])
+    );
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/api/sessions/plan-session")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let session: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(session["plan"], payload["plan"]);
+}
+
+#[tokio::test]
+async fn chat_sse_tool_call_reports_output_truncation() {
+    let tool_call = ToolCall {
+        id: "tc_1".to_string(),
+        name: "mock_tool".to_string(),
+        arguments: json!({}),
+    };
+    let provider = MockStreamProvider::tool_then_text(tool_call, "After tool");
+
+    let temp = tempfile::tempdir().unwrap();
+    let big_output = (1..=500).map(|i| format!("row {i}")).collect::<Vec<_>>().join("\n");
+    let mut registry = chaos_bot_backend::infrastructure::tooling::ToolRegistry::new();
+    registry.register(MockTool::fixed("mock_tool", &big_output));
+    let registry = registry.with_output_spill(OutputSpill::new(
+        temp.path().join("tool-output"),
+        OutputLimits {
+            max_bytes: 512,
+            ..OutputLimits::default()
+        },
+    ));
+
+    let (_state_temp, state) = build_test_state_with_registry(Arc::new(provider), registry);
+    let app = router(state);
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/chat")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"message": "do something"}).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8_lossy(&body);
+    let tool_event = text
+        .split("\n\n")
+        .find(|chunk| chunk.contains("event: tool_call"))
+        .expect("tool_call event");
+    let data = tool_event
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap();
+    let payload: Value = serde_json::from_str(data).unwrap();
+    assert_eq!(payload["truncation"]["total_lines"], 500);
+    assert!(payload["truncation"]["handle"]
+        .as_str()
+        .unwrap()
+        .starts_with("out-"));
+    assert!(payload["output"].as_str().unwrap().len() < big_output.len());
+}
+
+// -------------------------------------------------------------------------
+// File checkpoints — list, diff and restore after a write tool call
+// -------------------------------------------------------------------------
+
+#[tokio::test]
+async fn checkpoints_list_diff_and_restore_after_write() {
+    let tool_call = ToolCall {
+        id: "tc_write".to_string(),
+        name: "write".to_string(),
+        arguments: json!({"path": "notes.txt", "content": "rewritten\n"}),
+    };
+    let provider = MockStreamProvider::tool_then_text(tool_call, "Wrote it");
+
+    let mut registry = chaos_bot_backend::infrastructure::tooling::ToolRegistry::new();
+    registry.register(chaos_bot_backend::infrastructure::tooling::WriteTool);
+
+    let (temp, state) = build_test_state_with_checkpoints(Arc::new(provider), registry);
+    let notes = temp.path().join("notes.txt");
+    std::fs::write(&notes, "original\n").unwrap();
+    let app = router(state);
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/chat")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"session_id": "cp-session", "message": "rewrite notes"}).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&notes).unwrap(), "rewritten\n");
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/api/sessions/cp-session/checkpoints")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let checkpoints: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(checkpoints.as_array().unwrap().len(), 1);
+    assert_eq!(checkpoints[0]["tool_call_id"], "tc_write");
+    let checkpoint_id = checkpoints[0]["id"].as_str().unwrap().to_string();
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/api/sessions/cp-session/checkpoints/{checkpoint_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let diff: Value = serde_json::from_slice(&body).unwrap();
+    assert!(diff["diff"].as_str().unwrap().contains("-original"));
+    assert!(diff["diff"].as_str().unwrap().contains("+rewritten"));
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/sessions/cp-session/checkpoints/{checkpoint_id}/restore"
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(std::fs::read_to_string(&notes).unwrap(), "original\n");
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/sessions/cp-session/checkpoints/missing/restore")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn checkpoints_unavailable_without_store() {
+    let provider = MockStreamProvider::text("hi");
+    let (_temp, state) = build_test_state(Arc::new(provider));
+    let app = router(state);
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/api/sessions/any/checkpoints")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
 // -------------------------------------------------------------------------
 // Chat with existing session (conversation accumulates)
 // -------------------------------------------------------------------------
@@ -475,6 +718,109 @@ async fn config_api_apply_reset_restart_lifecycle() {
     assert_eq!(restart_json["restart_scheduled"], false);
 }
 
+#[tokio::test]
+async fn config_apply_rejects_new_mcp_commands() {
+    let (temp, state, config_path) = build_test_state_with_config_runtime().await;
+    let app = router(state);
+    let apply = |config: Value| {
+        let app = app.clone();
+        async move {
+            let res = app
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/api/config/apply")
+                        .header("content-type", "application/json")
+                        .body(Body::from(json!({ "config": config }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = res.status();
+            let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<Value>(&body).unwrap())
+        }
+    };
+    let mut config: Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let marker = temp.path().join("spawned");
+
+    config["mcp"]["servers"] = json!({
+        "local": {"command": "touch", "args": [marker.display().to_string()]}
+    });
+    let (status, body) = apply(config.clone()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body.to_string()
+            .contains("mcp server `local`; edit the config file and restart"),
+        "{body}"
+    );
+    assert!(body
+        .to_string()
+        .contains("CHAOS_BOT_ALLOW_CONFIG_COMMANDS=1"));
+    assert!(!std::fs::read_to_string(&config_path)
+        .unwrap()
+        .contains("touch"));
+    assert!(!marker.exists());
+
+    // Remote servers start no process and can still be added.
+    config["mcp"]["servers"] = json!({"remote": {"url": "http://127.0.0.1:9/mcp"}});
+    let (status, body) = apply(config).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[tokio::test]
+async fn tools_api_reflects_applied_tool_policy() {
+    let (_temp, state, _config_path) = build_test_state_with_config_runtime().await;
+    let app = router(state);
+
+    let list_tools = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let res = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            let json: Value = serde_json::from_slice(&body).unwrap();
+            json["tools"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|tool| tool["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(list_tools("/api/tools").await, vec!["bash", "read"]);
+
+    let config_res = app
+        .clone()
+        .oneshot(Request::builder().uri("/api/config").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = to_bytes(config_res.into_body(), usize::MAX).await.unwrap();
+    let mut running = serde_json::from_slice::<Value>(&body).unwrap()["running"].clone();
+    running["tools"]["channels"] = json!({"telegram": {"preset": "read_only"}});
+
+    let apply_res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/config/apply")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "config": running }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(apply_res.status(), StatusCode::OK);
+
+    assert_eq!(list_tools("/api/tools").await, vec!["bash", "read"]);
+    assert_eq!(list_tools("/api/tools?channel=telegram").await, vec!["read"]);
+}
+
 fn one_turn_stream(reply: &str) -> Vec<LlmStreamEvent> {
     vec![
         LlmStreamEvent {
@@ -794,3 +1140,229 @@ async fn telegram_webhook_ignores_malformed_update_without_text() {
     let json: Value = serde_json::from_slice(&body).unwrap();
     assert_eq!(json["ignored"], true);
 }
+
+// -------------------------------------------------------------------------
+// Memory API
+// -------------------------------------------------------------------------
+
+async fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let request = Request::builder().method(method).uri(uri);
+    let request = match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, value)
+}
+
+#[tokio::test]
+async fn memory_api_reads_edits_lists_and_searches() {
+    let (_temp, state) = build_test_state(Arc::new(MockStreamProvider::text("hi")));
+    let memory = state.current_agent().await.memory();
+    memory
+        .write_file("2026-03-02.md", "- talked about rust\n- and about tea\n- then lunch\n")
+        .await
+        .unwrap();
+    memory.write_file("digest-2026-W06.md", "# Memory digest\n").await.unwrap();
+    let app = router(state);
+
+    let (status, body) = send(
+        &app,
+        "PUT",
+        "/api/memory/curated",
+        Some(json!({"content": "# Memory\n\n## Preferences\n- green tea\n"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["content"], "# Memory\n\n## Preferences\n- green tea\n");
+    let (_, body) = send(&app, "GET", "/api/memory/curated", None).await;
+    assert!(body["content"].as_str().unwrap().contains("green tea"));
+
+    let (status, body) = send(&app, "GET", "/api/memory/files", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let paths: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| file["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, vec!["2026-03-02.md", "MEMORY.md", "digest-2026-W06.md"]);
+    assert_eq!(body[0]["date"], "2026-03-02");
+    assert!(body[0]["bytes"].as_u64().unwrap() > 0);
+
+    let (status, body) = send(
+        &app,
+        "GET",
+        "/api/memory/files/2026-03-02.md?start_line=2&end_line=3",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["content"], "- and about tea\n- then lunch");
+    let (_, body) = send(&app, "GET", "/api/memory/files/2026-03-02.md?start_line=3", None).await;
+    assert_eq!(body["content"], "- then lunch");
+
+    let (status, body) = send(&app, "GET", "/api/memory/search?q=tea&limit=1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    let (status, _) = send(&app, "GET", "/api/memory/search?q=%20", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn memory_api_deletes_only_daily_logs_and_rejects_traversal() {
+    let (temp, state) = build_test_state(Arc::new(MockStreamProvider::text("hi")));
+    std::fs::write(temp.path().join("secret.txt"), "top secret").unwrap();
+    let memory = state.current_agent().await.memory();
+    memory.write_file("2026-03-02.md", "- a day\n").await.unwrap();
+    memory.write_file("2026-03-03.md", "- another day\n").await.unwrap();
+    memory.write_file("notes.md", "- a note\n").await.unwrap();
+    let app = router(state);
+
+    for uri in [
+        "/api/memory/files/..%2Fsecret.txt",
+        "/api/memory/files/.index%2Fmemory-index.json",
+        "/api/memory/files/%2Fetc%2Fpasswd",
+    ] {
+        let (status, _) = send(&app, "GET", uri, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+    let (status, _) = send(&app, "GET", "/api/memory/files/missing.md", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, "DELETE", "/api/memory/files/notes.md", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "DELETE", "/api/memory/files/MEMORY.md", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "DELETE", "/api/memory/files/2026-03-02.md", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(
+        &app,
+        "DELETE",
+        "/api/memory/files/2026-03-03.md?archive=true",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "DELETE", "/api/memory/files/2026-03-02.md", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, body) = send(&app, "GET", "/api/memory/files", None).await;
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert!(temp.path().join("memory/.archive/2026-03-03.md").exists());
+}
+
+#[tokio::test]
+async fn watch_status_reports_the_agent_watcher() {
+    let (temp, state) = build_test_state(Arc::new(MockStreamProvider::text("hi")));
+    let app = router(state.clone());
+    let (status, body) = send(&app, "GET", "/api/watch/status", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enabled"], json!(false));
+
+    let (_, loader) = temp_personality_dir();
+    let watcher = FileWatcher::new(std::time::Duration::from_millis(20))
+        .watch("personality", temp.path().join("personality"), false, Arc::new(loader))
+        .start()
+        .unwrap();
+    let agent = state.current_agent().await;
+    *state.agent.write().await = Arc::new((*agent).clone().with_file_watcher(Arc::new(watcher)));
+    let (_, body) = send(&app, "GET", "/api/watch/status", None).await;
+    assert_eq!(body["enabled"], json!(true));
+    assert_eq!(body["debounce_ms"], json!(20));
+    assert_eq!(body["targets"][0]["name"], json!("personality"));
+    assert_eq!(body["changes"], json!(0));
+}
+
+#[tokio::test]
+async fn workspace_export_and_import_round_trip() {
+    let (_temp, state) = build_test_state(Arc::new(MockStreamProvider::text("hi")));
+    let (status, _) = send(&router(state.clone()), "POST", "/api/workspace/export", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    let source = tempfile::tempdir().unwrap();
+    std::fs::write(source.path().join("MEMORY.md"), "- Prefers tea\n").unwrap();
+    std::fs::write(
+        source.path().join("config.json"),
+        json!({ "secrets": { "openai_api_key": "sk-live" } }).to_string(),
+    )
+    .unwrap();
+    let app = router(state.clone().with_workspace_archive(Arc::new(WorkspaceArchive::new(
+        source.path(),
+        source.path().join("config.json"),
+    ))));
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/workspace/export?secrets=redact")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/gzip");
+    let disposition = response.headers()["content-disposition"].to_str().unwrap();
+    assert!(disposition.starts_with("attachment; filename=\"chaos-bot-workspace-"));
+    let archive = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!String::from_utf8_lossy(&archive).contains("sk-live"));
+
+    let target = tempfile::tempdir().unwrap();
+    let app = router(state.with_workspace_archive(Arc::new(WorkspaceArchive::new(
+        target.path(),
+        target.path().join("config.json"),
+    ))));
+    let import = |uri: &str, body: Vec<u8>| {
+        let app = app.clone();
+        let request = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/gzip")
+            .body(Body::from(body))
+            .unwrap();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<Value>(&bytes).unwrap())
+        }
+    };
+
+    let (status, body) = import("/api/workspace/import?dry_run=true", archive.to_vec()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["dry_run"], json!(true));
+    assert_eq!(body["added"], json!(["MEMORY.md"]));
+    assert_eq!(body["skipped"], json!(["config.json"]));
+    assert_eq!(body["restart_required"], json!(false));
+    assert!(!target.path().join("MEMORY.md").exists());
+
+    let (status, body) = import(
+        "/api/workspace/import?dry_run=true&include_config=true",
+        archive.to_vec(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["added"], json!(["MEMORY.md", "config.json"]));
+    assert_eq!(body["skipped"], json!([]));
+    assert_eq!(body["restart_required"], json!(true));
+
+    let (status, body) = import("/api/workspace/import", archive.to_vec()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["dry_run"], json!(false));
+    assert_eq!(
+        std::fs::read_to_string(target.path().join("MEMORY.md")).unwrap(),
+        "- Prefers tea\n"
+    );
+
+    let (status, body) = import("/api/workspace/import", b"garbage".to_vec()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.to_string().contains("invalid workspace archive"), "{body}");
+}
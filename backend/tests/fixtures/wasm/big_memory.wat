@@ -0,0 +1,4 @@
+;; Asks for 128 MiB of linear memory up front, above the default 64 MiB limit.
+(module
+  (memory (export "memory") 2048)
+  (func (export "_start")))
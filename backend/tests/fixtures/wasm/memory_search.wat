@@ -0,0 +1,21 @@
+;; Searches memory for "teal" through the host API and prints the JSON hits. When the call
+;; is refused the host message goes to stderr and the module exits with status 1.
+(module
+  (import "chaos" "memory_search" (func $memory_search (param i32 i32) (result i32)))
+  (import "chaos" "result_read" (func $result_read (param i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 100) "teal")
+  (func (export "_start")
+    (local $status i32)
+    (local $len i32)
+    (local.set $status (call $memory_search (i32.const 100) (i32.const 4)))
+    (local.set $len (call $result_read (i32.const 1024) (i32.const 8192)))
+    (i32.store (i32.const 0) (i32.const 1024))
+    (i32.store (i32.const 4) (local.get $len))
+    (if (i32.lt_s (local.get $status) (i32.const 0))
+      (then
+        (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 20)))
+        (call $proc_exit (i32.const 1))))
+    (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 20)))))
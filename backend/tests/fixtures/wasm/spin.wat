@@ -0,0 +1,5 @@
+;; Never returns; used to exercise fuel limits.
+(module
+  (memory (export "memory") 1)
+  (func (export "_start")
+    (loop $forever (br $forever))))
@@ -0,0 +1,28 @@
+;; Creates `out.txt` in the preopened working directory (fd 3) and prints `ok`.
+(module
+  (import "wasi_snapshot_preview1" "path_open"
+    (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 100) "out.txt")
+  (data (i32.const 200) "written by wasm")
+  (data (i32.const 300) "ok")
+  (data (i32.const 400) "cannot create out.txt")
+  (func (export "_start")
+    (local $fd i32)
+    ;; oflags CREAT|TRUNC, rights FD_WRITE
+    (if (call $path_open (i32.const 3) (i32.const 0) (i32.const 100) (i32.const 7)
+          (i32.const 9) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 16))
+      (then
+        (i32.store (i32.const 0) (i32.const 400))
+        (i32.store (i32.const 4) (i32.const 21))
+        (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 20)))
+        (call $proc_exit (i32.const 1))))
+    (local.set $fd (i32.load (i32.const 16)))
+    (i32.store (i32.const 0) (i32.const 200))
+    (i32.store (i32.const 4) (i32.const 15))
+    (drop (call $fd_write (local.get $fd) (i32.const 0) (i32.const 1) (i32.const 20)))
+    (i32.store (i32.const 0) (i32.const 300))
+    (i32.store (i32.const 4) (i32.const 2))
+    (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 20)))))
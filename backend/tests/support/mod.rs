@@ -4,7 +4,9 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use chaos_bot_backend::application::agent::{AgentConfig, AgentLoop};
+use chaos_bot_backend::domain::ports::CheckpointPort;
 use chaos_bot_backend::interface::api::AppState;
+use chaos_bot_backend::infrastructure::checkpoints::CheckpointStore;
 use chaos_bot_backend::infrastructure::config::{
     write_config_file, AgentChannelsConfig, AgentFileConfig, AgentLlmConfig,
     AgentLoggingConfig, AgentSecretsConfig, AgentServerConfig, AppConfig, EnvSecrets,
@@ -300,6 +302,25 @@ pub fn build_test_state_with_registry(
     (temp, state)
 }
 
+/// Like `build_test_state_with_registry`, with a checkpoint store under `<temp>/checkpoints`.
+pub fn build_test_state_with_checkpoints(
+    provider: Arc<dyn LlmProvider>,
+    registry: ToolRegistry,
+) -> (TempDir, AppState) {
+    let (temp, agent) = build_test_agent_with_registry(provider, registry);
+    let checkpoints: Arc<dyn CheckpointPort> =
+        Arc::new(CheckpointStore::new(temp.path().join("checkpoints"), 50));
+    let state = AppState::new(
+        Arc::new(agent.with_checkpoints(checkpoints)),
+        None,
+        None,
+        false,
+        false,
+        "https://api.telegram.org".to_string(),
+    );
+    (temp, state)
+}
+
 pub fn build_test_agent(provider: Arc<dyn LlmProvider>) -> (TempDir, AgentLoop) {
     build_test_agent_with_registry(provider, ToolRegistry::new())
 }
@@ -348,9 +369,13 @@ impl AgentFactory for RuntimeTestAgentFactory {
         let personality: Arc<dyn PersonalitySource> =
             Arc::new(PersonalityLoader::new(config.personality_dir.clone()));
 
+        let mut registry = ToolRegistry::new();
+        registry.register(MockTool::fixed("read", "read output"));
+        registry.register(MockTool::fixed("bash", "bash output"));
+
         let agent = AgentLoop::new(
             Arc::new(MockStreamProvider::text("runtime factory")),
-            Arc::new(ToolRegistry::new()),
+            Arc::new(registry),
             personality,
             memory,
             AgentConfig {
@@ -361,7 +386,8 @@ impl AgentFactory for RuntimeTestAgentFactory {
                 token_budget: config.token_budget,
                 working_dir: config.working_dir.clone(),
             },
-        );
+        )
+        .with_tool_policy(config.tool_policy.clone());
 
         Ok(Arc::new(agent))
     }
@@ -393,6 +419,7 @@ pub async fn build_test_state_with_config_runtime() -> (TempDir, AppState, PathB
         },
         channels: AgentChannelsConfig::default(),
         secrets: AgentSecretsConfig::default(),
+        ..AgentFileConfig::default()
     };
 
     let mut app_config = AppConfig::from_inputs(
@@ -3,7 +3,9 @@ mod support;
 use chaos_bot_backend::application::agent::AgentLoop;
 use chaos_bot_backend::infrastructure::model::LlmStreamEvent;
 use chaos_bot_backend::infrastructure::memory::MemoryHit;
-use chaos_bot_backend::domain::types::{Message, SessionState, ToolCall};
+use chaos_bot_backend::infrastructure::workspace_index::{WorkspaceIndex, WorkspaceIndexOptions};
+use chaos_bot_backend::domain::tools::{ToolPolicy, ToolSelection};
+use chaos_bot_backend::domain::types::{Message, PlanStatus, SessionState, ToolCall};
 use serde_json::json;
 use std::sync::Arc;
 use support::*;
@@ -14,7 +16,7 @@ use support::*;
 
 #[test]
 fn build_system_prompt_without_memory() {
-    let prompt = AgentLoop::build_system_prompt("You are helpful.", &[]);
+    let prompt = AgentLoop::build_system_prompt("You are helpful.", &[], &[]);
     assert_eq!(prompt, "You are helpful.");
     assert!(!prompt.contains("Memory Context"));
 }
@@ -26,14 +28,16 @@ fn build_system_prompt_with_memory() {
             path: "MEMORY.md".into(),
             line: 1,
             snippet: "fact: important".into(),
+            score: 2.0,
         },
         MemoryHit {
             path: "2024-01-01.md".into(),
             line: 5,
             snippet: "log entry".into(),
+            score: 1.0,
         },
     ];
-    let prompt = AgentLoop::build_system_prompt("Base prompt.", &hits);
+    let prompt = AgentLoop::build_system_prompt("Base prompt.", &hits, &[]);
     assert!(prompt.contains("Base prompt."));
     assert!(prompt.contains("Relevant Memory Context"));
     assert!(prompt.contains("fact: important"));
@@ -47,18 +51,35 @@ fn build_system_prompt_limits_to_6_hits() {
             path: format!("file{i}.md"),
             line: i,
             snippet: format!("hit {i}"),
+            score: 0.0,
         })
         .collect();
-    let prompt = AgentLoop::build_system_prompt("Base.", &hits);
-    // Should only contain first 6 hits
+    let prompt = AgentLoop::build_system_prompt("Base.", &hits, &[]);
+    // Equal scores keep search order: only the first 6 hits
     assert!(prompt.contains("hit 0"));
     assert!(prompt.contains("hit 5"));
     assert!(!prompt.contains("hit 6"));
 }
 
+#[test]
+fn build_system_prompt_takes_best_scored_hits() {
+    let hits: Vec<MemoryHit> = (0..10)
+        .map(|i| MemoryHit {
+            path: format!("file{i}.md"),
+            line: i,
+            snippet: format!("hit {i}"),
+            score: i as f32,
+        })
+        .collect();
+    let prompt = AgentLoop::build_system_prompt("Base.", &hits, &[]);
+    assert!(prompt.contains("- file9.md:9: hit 9\n- file8.md:8: hit 8"));
+    assert!(prompt.contains("hit 4"));
+    assert!(!prompt.contains("hit 3"));
+}
+
 #[test]
 fn build_system_prompt_trims_personality() {
-    let prompt = AgentLoop::build_system_prompt("  padded  \n\n", &[]);
+    let prompt = AgentLoop::build_system_prompt("  padded  \n\n", &[], &[]);
     assert_eq!(prompt, "padded");
 }
 
@@ -167,6 +188,125 @@ async fn run_with_tool_calls_loops() {
     assert_eq!(output.tool_events[0].call.name, "mock_tool");
 }
 
+#[tokio::test]
+async fn run_uses_channel_tool_selection_and_rejects_disabled_tools() {
+    let tool_call = ToolCall {
+        id: "tc_1".to_string(),
+        name: "bash".to_string(),
+        arguments: json!({}),
+    };
+    let provider = Arc::new(MockStreamProvider::tool_then_text(tool_call, "Done!"));
+    let mut registry = chaos_bot_backend::infrastructure::tooling::ToolRegistry::new();
+    registry.register(MockTool::fixed("read", "read output"));
+    registry.register(MockTool::fixed("bash", "bash output"));
+
+    let (_temp, agent) = build_test_agent_with_registry(provider.clone(), registry);
+    let mut policy = ToolPolicy::default();
+    policy
+        .channels
+        .insert("telegram".to_string(), ToolSelection::from_preset(Some("read_only"), None));
+    let agent = agent.with_tool_policy(policy);
+
+    let names = |specs: Vec<chaos_bot_backend::domain::types::ToolSpec>| {
+        specs.into_iter().map(|spec| spec.name).collect::<Vec<_>>()
+    };
+    assert_eq!(names(agent.tool_specs(None)), vec!["bash", "read"]);
+    assert_eq!(names(agent.tool_specs(Some("telegram"))), vec!["read"]);
+
+    let mut session = SessionState::new("s1");
+    session.channel = Some("telegram".to_string());
+    let output = agent.run(&mut session, "run ls".to_string()).await.unwrap();
+
+    let result = &output.tool_events[0].result;
+    assert!(result.is_error);
+    assert_eq!(result.output, "tool error: tool not enabled: bash");
+    let captured = provider.captured.lock().unwrap();
+    assert_eq!(names(captured[0].tools.clone()), vec!["read"]);
+}
+
+#[tokio::test]
+async fn run_injects_workspace_context_into_system_prompt() {
+    let provider = Arc::new(MockStreamProvider::text("Hello!"));
+    let (temp, agent) = build_test_agent(provider.clone());
+    let root = temp.path().join("work");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("runbook.md"), "Rotate the signing keys every quarter.\n").unwrap();
+    let index = WorkspaceIndex::new(&root, temp.path().join("index"), WorkspaceIndexOptions::default());
+    index.refresh().unwrap();
+    let agent = agent.with_workspace_index(Arc::new(index.clone()), 2);
+
+    let mut session = SessionState::new("s1");
+    agent
+        .run(&mut session, "when do we rotate signing keys?".to_string())
+        .await
+        .unwrap();
+
+    {
+        let captured = provider.captured.lock().unwrap();
+        let system = &captured[0].messages[0].content;
+        assert!(system.contains("# Relevant Workspace Context"), "{system}");
+        assert!(system.contains("- runbook.md:1-1: Rotate the signing keys every quarter."));
+    }
+
+    // Channels that cannot call `search_workspace` get no passages either.
+    let provider = Arc::new(MockStreamProvider::text("Hello!"));
+    let (_other, agent) = build_test_agent(provider.clone());
+    let mut policy = ToolPolicy::default();
+    policy
+        .channels
+        .insert("telegram".to_string(), ToolSelection::from_preset(Some("none"), None));
+    let agent = agent
+        .with_workspace_index(Arc::new(index), 2)
+        .with_tool_policy(policy);
+    let mut session = SessionState::new("s2");
+    session.channel = Some("telegram".to_string());
+    agent
+        .run(&mut session, "when do we rotate signing keys?".to_string())
+        .await
+        .unwrap();
+
+    let captured = provider.captured.lock().unwrap();
+    let system = &captured[0].messages[0].content;
+    assert!(!system.contains("# Relevant Workspace Context"), "{system}");
+}
+
+#[tokio::test]
+async fn run_stream_tracks_plan_and_reinjects_it_into_system_prompt() {
+    let tool_call = ToolCall {
+        id: "tc_plan".to_string(),
+        name: "todo_write".to_string(),
+        arguments: json!({"todos": [
+            {"content": "Read the config", "status": "done"},
+            {"content": "Patch the loader", "status": "in_progress"}
+        ]}),
+    };
+    let provider = Arc::new(MockStreamProvider::tool_then_text(tool_call, "Working on it"));
+    let mut registry = chaos_bot_backend::infrastructure::tooling::ToolRegistry::new();
+    registry.register_plan_tools();
+
+    let (_temp, agent) = build_test_agent_with_registry(provider.clone(), registry);
+    let mut session = SessionState::new("s1");
+    let mut plans = Vec::new();
+    agent
+        .run_stream(&mut session, "fix the loader".to_string(), |event| {
+            if let chaos_bot_backend::application::agent::AgentStreamEvent::Plan(plan) = event {
+                plans.push(plan);
+            }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(plans.len(), 1);
+    assert_eq!(session.plan, plans[0]);
+    assert_eq!(session.plan[1].status, PlanStatus::InProgress);
+
+    let captured = provider.captured.lock().unwrap();
+    assert!(!captured[0].messages[0].content.contains("# Current Plan"));
+    let system = &captured[1].messages[0].content;
+    assert!(system.contains("# Current Plan"), "{system}");
+    assert!(system.contains("1. [x] Read the config\n2. [~] Patch the loader\n(1/2 done)"));
+}
+
 // -------------------------------------------------------------------------
 // run_stream delivers events
 // -------------------------------------------------------------------------
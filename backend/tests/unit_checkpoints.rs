@@ -0,0 +1,303 @@
+use chaos_bot_backend::domain::checkpoint::FileSnapshot;
+use chaos_bot_backend::domain::ports::CheckpointPort;
+use chaos_bot_backend::infrastructure::checkpoints::{content_hash, CheckpointStore};
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{EditTool, Tool, ToolContext, WriteTool};
+use serde_json::json;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+fn snapshot(session_id: &str, path: &str, before: Option<&str>, after: &str) -> FileSnapshot {
+    FileSnapshot {
+        session_id: session_id.to_string(),
+        tool_call_id: Some("tc_1".to_string()),
+        tool_name: "write".to_string(),
+        path: path.to_string(),
+        before: before.map(|content| content.as_bytes().to_vec()),
+        after: after.as_bytes().to_vec(),
+    }
+}
+
+fn blob_count(store: &CheckpointStore) -> usize {
+    std::fs::read_dir(store.root().join("blobs"))
+        .map(|entries| entries.count())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn record_and_list_newest_first() {
+    let temp = tempdir().unwrap();
+    let store = CheckpointStore::new(temp.path().join("checkpoints"), 10);
+    let file = temp.path().join("a.txt");
+    let path = file.to_string_lossy().to_string();
+
+    let first = store
+        .record(snapshot("s1", &path, None, "one"))
+        .await
+        .unwrap();
+    let second = store
+        .record(snapshot("s1", &path, Some("one"), "two"))
+        .await
+        .unwrap();
+
+    let listed = store.list("s1").await.unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0].id, second.id);
+    assert_eq!(listed[1].id, first.id);
+    assert!(first.before_hash.is_none());
+    assert_eq!(
+        second.before_hash.as_deref(),
+        Some(content_hash("one").as_str())
+    );
+    assert!(store.list("other").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn blobs_are_deduplicated_by_content_hash() {
+    let temp = tempdir().unwrap();
+    let store = CheckpointStore::new(temp.path().join("checkpoints"), 10);
+
+    store
+        .record(snapshot("s1", "/tmp/a", Some("same"), "same"))
+        .await
+        .unwrap();
+    store
+        .record(snapshot("s2", "/tmp/b", Some("same"), "same"))
+        .await
+        .unwrap();
+
+    assert_eq!(blob_count(&store), 1);
+}
+
+#[tokio::test]
+async fn retention_prunes_oldest_and_unreferenced_blobs() {
+    let temp = tempdir().unwrap();
+    let store = CheckpointStore::new(temp.path().join("checkpoints"), 2);
+
+    store
+        .record(snapshot("s1", "/tmp/a", None, "v1"))
+        .await
+        .unwrap();
+    store
+        .record(snapshot("s1", "/tmp/a", Some("v1"), "v2"))
+        .await
+        .unwrap();
+    store
+        .record(snapshot("s1", "/tmp/a", Some("v2"), "v3"))
+        .await
+        .unwrap();
+
+    let listed = store.list("s1").await.unwrap();
+    assert_eq!(listed.len(), 2);
+    // v1 is still referenced as the "before" of the second checkpoint.
+    assert_eq!(blob_count(&store), 3);
+
+    store
+        .record(snapshot("s1", "/tmp/a", Some("v3"), "v4"))
+        .await
+        .unwrap();
+    assert_eq!(blob_count(&store), 3);
+}
+
+#[tokio::test]
+async fn diff_reports_unified_changes() {
+    let temp = tempdir().unwrap();
+    let store = CheckpointStore::new(temp.path().join("checkpoints"), 10);
+    let file = temp.path().join("notes.txt");
+    std::fs::write(&file, "alpha\ngamma\n").unwrap();
+
+    let checkpoint = store
+        .record(snapshot(
+            "s1",
+            &file.to_string_lossy(),
+            Some("alpha\nbeta\n"),
+            "alpha\ngamma\n",
+        ))
+        .await
+        .unwrap();
+
+    let diff = store.diff("s1", &checkpoint.id).await.unwrap().unwrap();
+    assert!(diff.diff.contains("-beta"));
+    assert!(diff.diff.contains("+gamma"));
+    assert_eq!(diff.additions, 1);
+    assert_eq!(diff.deletions, 1);
+    assert!(diff.current_matches_after);
+
+    assert!(store.diff("s1", "missing").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn restore_writes_previous_content_and_records_undo() {
+    let temp = tempdir().unwrap();
+    let store = CheckpointStore::new(temp.path().join("checkpoints"), 10);
+    let file = temp.path().join("notes.txt");
+    std::fs::write(&file, "broken").unwrap();
+
+    let checkpoint = store
+        .record(snapshot(
+            "s1",
+            &file.to_string_lossy(),
+            Some("original"),
+            "broken",
+        ))
+        .await
+        .unwrap();
+
+    let restored = store.restore("s1", &checkpoint.id).await.unwrap().unwrap();
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "original");
+    assert_eq!(restored.restored.id, checkpoint.id);
+    assert_eq!(restored.undo.tool_name, "restore");
+
+    store
+        .restore("s1", &restored.undo.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "broken");
+}
+
+#[tokio::test]
+async fn restore_removes_file_created_by_checkpoint() {
+    let temp = tempdir().unwrap();
+    let store = CheckpointStore::new(temp.path().join("checkpoints"), 10);
+    let file = temp.path().join("new.txt");
+    std::fs::write(&file, "created").unwrap();
+
+    let checkpoint = store
+        .record(snapshot("s1", &file.to_string_lossy(), None, "created"))
+        .await
+        .unwrap();
+    store.restore("s1", &checkpoint.id).await.unwrap().unwrap();
+    assert!(!file.exists());
+}
+
+#[tokio::test]
+async fn write_and_edit_tools_record_checkpoints() {
+    let temp = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let store = Arc::new(CheckpointStore::new(temp.path().join("checkpoints"), 10));
+    let checkpoints: Arc<dyn CheckpointPort> = store.clone();
+    let ctx = ToolContext::new(temp.path().to_path_buf(), memory)
+        .with_session("s1")
+        .with_checkpoints(Some(checkpoints))
+        .for_call("tc_write");
+
+    WriteTool
+        .execute(json!({"path": "f.txt", "content": "hello world"}), &ctx)
+        .await
+        .unwrap();
+    EditTool
+        .execute(
+            json!({"path": "f.txt", "find": "world", "replace": "chaos"}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+    let listed = store.list("s1").await.unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0].tool_name, "edit");
+    assert_eq!(listed[1].tool_name, "write");
+    assert_eq!(listed[1].tool_call_id.as_deref(), Some("tc_write"));
+
+    store.restore("s1", &listed[0].id).await.unwrap().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(temp.path().join("f.txt")).unwrap(),
+        "hello world"
+    );
+}
+
+#[tokio::test]
+async fn tools_without_session_skip_checkpoints() {
+    let temp = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let store = Arc::new(CheckpointStore::new(temp.path().join("checkpoints"), 10));
+    let checkpoints: Arc<dyn CheckpointPort> = store.clone();
+    let ctx =
+        ToolContext::new(temp.path().to_path_buf(), memory).with_checkpoints(Some(checkpoints));
+
+    WriteTool
+        .execute(json!({"path": "f.txt", "content": "data"}), &ctx)
+        .await
+        .unwrap();
+    assert!(!store.root().exists());
+}
+
+#[tokio::test]
+async fn restoring_a_write_over_a_binary_file_brings_its_bytes_back() {
+    let temp = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let store = Arc::new(CheckpointStore::new(temp.path().join("checkpoints"), 10));
+    let checkpoints: Arc<dyn CheckpointPort> = store.clone();
+    let ctx = ToolContext::new(temp.path().to_path_buf(), memory)
+        .with_session("s1")
+        .with_checkpoints(Some(checkpoints))
+        .for_call("tc_write");
+    let original = vec![0x89, b'P', b'N', b'G', 0xff, 0xfe, 0x00, 0x80];
+    let file = temp.path().join("image.bin");
+    std::fs::write(&file, &original).unwrap();
+
+    WriteTool
+        .execute(json!({"path": "image.bin", "content": "text"}), &ctx)
+        .await
+        .unwrap();
+    let listed = store.list("s1").await.unwrap();
+    assert!(listed[0].before_hash.is_some());
+    assert_eq!(
+        store
+            .diff("s1", &listed[0].id)
+            .await
+            .unwrap()
+            .unwrap()
+            .deletions,
+        1
+    );
+
+    store.restore("s1", &listed[0].id).await.unwrap().unwrap();
+    assert_eq!(std::fs::read(&file).unwrap(), original);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn write_skips_the_checkpoint_of_an_unreadable_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let store = Arc::new(CheckpointStore::new(temp.path().join("checkpoints"), 10));
+    let checkpoints: Arc<dyn CheckpointPort> = store.clone();
+    let ctx = ToolContext::new(temp.path().to_path_buf(), memory)
+        .with_session("s1")
+        .with_checkpoints(Some(checkpoints));
+    let file = temp.path().join("secret.txt");
+    std::fs::write(&file, "keep me").unwrap();
+    std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o200)).unwrap();
+    if std::fs::read(&file).is_ok() {
+        // Running as root: permissions are not enforced.
+        return;
+    }
+
+    let result = WriteTool
+        .execute(
+            json!({"path": "secret.txt", "content": "overwritten"}),
+            &ctx,
+        )
+        .await;
+    std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o600)).unwrap();
+    // Recording it as absent would make a restore delete it, so there is no checkpoint.
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "overwritten");
+    assert!(store.list("s1").await.unwrap().is_empty());
+}
@@ -0,0 +1,305 @@
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{
+    extract_symbols, CodeLanguage, CodeOutlineTool, FindSymbolTool, IndexRefresh, SymbolIndex,
+    Tool, ToolContext,
+};
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::{tempdir, TempDir};
+
+const RUST_SOURCE: &str = r#"use std::fmt;
+
+pub const LIMIT: usize = 3;
+
+pub struct Counter {
+    value: usize,
+}
+
+impl Counter {
+    pub fn new() -> Self {
+        Self { value: 0 }
+    }
+
+    pub fn increment(&mut self) {
+        self.value += 1;
+    }
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+pub trait Reset {
+    fn reset(&mut self);
+}
+
+mod helpers {
+    pub fn clamp(value: usize) -> usize {
+        value.min(super::LIMIT)
+    }
+}
+
+macro_rules! twice {
+    ($e:expr) => {
+        $e * 2
+    };
+}
+"#;
+
+const TS_SOURCE: &str = r#"export interface Shape {
+  area(): number;
+}
+
+export class Circle implements Shape {
+  constructor(private radius: number) {}
+
+  area(): number {
+    return Math.PI * this.radius ** 2;
+  }
+}
+
+export type Point = { x: number; y: number };
+
+export function distance(a: Point, b: Point): number {
+  return Math.hypot(a.x - b.x, a.y - b.y);
+}
+
+export const scale = (p: Point, k: number): Point => ({ x: p.x * k, y: p.y * k });
+
+enum Color {
+  Red,
+}
+"#;
+
+const PY_SOURCE: &str = r#"import functools
+
+
+class Greeter:
+    def __init__(self, name):
+        self.name = name
+
+    def greet(self):
+        def shout(text):
+            return text.upper()
+        return shout(f"hello {self.name}")
+
+
+@functools.cache
+def answer():
+    return 42
+"#;
+
+fn summary(language: CodeLanguage, source: &str) -> Vec<String> {
+    extract_symbols(language, source)
+        .unwrap()
+        .into_iter()
+        .map(|symbol| {
+            format!(
+                "{} {} {}-{} {}",
+                symbol.kind,
+                symbol.name,
+                symbol.start_line,
+                symbol.end_line,
+                symbol.container.unwrap_or_default()
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn extract_symbols_outlines_rust() {
+    assert_eq!(
+        summary(CodeLanguage::Rust, RUST_SOURCE),
+        vec![
+            "const LIMIT 3-3",
+            "struct Counter 5-7",
+            "impl Counter 9-17",
+            "method new 10-12 Counter",
+            "method increment 14-16 Counter",
+            "impl fmt::Display for Counter 19-23",
+            "method fmt 20-22 Counter",
+            "trait Reset 25-27",
+            "method reset 26-26 Reset",
+            "module helpers 29-33",
+            "function clamp 30-32 helpers",
+            "macro twice 35-39",
+        ]
+    );
+}
+
+#[test]
+fn extract_symbols_outlines_typescript() {
+    assert_eq!(
+        summary(CodeLanguage::TypeScript, TS_SOURCE),
+        vec![
+            "interface Shape 1-3",
+            "method area 2-2 Shape",
+            "class Circle 5-11",
+            "method constructor 6-6 Circle",
+            "method area 8-10 Circle",
+            "type Point 13-13",
+            "function distance 15-17",
+            "function scale 19-19",
+            "enum Color 21-23",
+        ]
+    );
+}
+
+#[test]
+fn extract_symbols_outlines_python() {
+    assert_eq!(
+        summary(CodeLanguage::Python, PY_SOURCE),
+        vec![
+            "class Greeter 4-11",
+            "method __init__ 5-6 Greeter",
+            "method greet 8-11 Greeter",
+            "function answer 15-16",
+        ]
+    );
+}
+
+fn workspace() -> (TempDir, ToolContext) {
+    let temp = tempdir().unwrap();
+    let root = temp.path().join("work");
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("web")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), RUST_SOURCE).unwrap();
+    std::fs::write(root.join("web/shapes.ts"), TS_SOURCE).unwrap();
+    std::fs::write(root.join("greeter.py"), PY_SOURCE).unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    (temp, ToolContext::new(root, memory))
+}
+
+#[tokio::test]
+async fn code_outline_renders_nested_symbols() {
+    let (_temp, context) = workspace();
+    let outline = CodeOutlineTool
+        .execute(json!({"path": "src/lib.rs"}), &context)
+        .await
+        .unwrap();
+    assert!(outline
+        .output
+        .starts_with("src/lib.rs (rust, 12 symbols)\n  3-3 const LIMIT"));
+    assert!(outline
+        .output
+        .contains("  9-17 impl Counter\n    10-12 method new"));
+
+    std::fs::write(context.root_dir.join("notes.txt"), "text").unwrap();
+    let error = CodeOutlineTool
+        .execute(json!({"path": "notes.txt"}), &context)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("does not support"));
+
+    std::fs::write(context.root_dir.join("../outside.rs"), "fn outside() {}").unwrap();
+    let error = CodeOutlineTool
+        .execute(json!({"path": "../outside.rs"}), &context)
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("escapes working directory"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn find_symbol_ranks_and_filters_definitions() {
+    let (temp, context) = workspace();
+    let tool = FindSymbolTool::new(Arc::new(SymbolIndex::new(temp.path().join("index"))));
+
+    let area = tool
+        .execute(json!({"name": "area"}), &context)
+        .await
+        .unwrap();
+    assert_eq!(
+        area.output,
+        "web/shapes.ts:2-2 method Shape.area\nweb/shapes.ts:8-10 method Circle.area"
+    );
+
+    let qualified = tool
+        .execute(json!({"name": "Counter::new"}), &context)
+        .await
+        .unwrap();
+    assert_eq!(qualified.output, "src/lib.rs:10-12 method Counter::new");
+
+    let structs = tool
+        .execute(json!({"name": "count", "kind": "struct"}), &context)
+        .await
+        .unwrap();
+    assert_eq!(structs.output, "src/lib.rs:5-7 struct Counter");
+
+    let scoped = tool
+        .execute(json!({"name": "e", "path": "web", "limit": 2}), &context)
+        .await
+        .unwrap();
+    let lines: Vec<&str> = scoped.output.lines().collect();
+    assert_eq!(lines.len(), 3, "{}", scoped.output);
+    assert!(lines.iter().take(2).all(|line| line.starts_with("web/")));
+    assert!(lines[2].starts_with("[showing 2 of"));
+
+    let none = tool
+        .execute(json!({"name": "missing_thing"}), &context)
+        .await
+        .unwrap();
+    assert_eq!(none.output, "no symbols matching missing_thing");
+}
+
+fn touch(path: &Path, content: &str) {
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn symbol_index_reparses_only_changed_files() {
+    let (temp, context) = workspace();
+    let root = &context.root_dir;
+    std::fs::write(root.join(".gitignore"), "generated/\n").unwrap();
+    std::fs::create_dir_all(root.join("generated")).unwrap();
+    touch(&root.join("generated/skip.rs"), "fn skipped() {}\n");
+
+    let index = SymbolIndex::new(temp.path().join("index"));
+    assert_eq!(
+        index.refresh(root).unwrap(),
+        IndexRefresh {
+            files: 3,
+            parsed: 3,
+            removed: 0
+        }
+    );
+    assert!(index.index_path(root).exists());
+    assert_eq!(index.refresh(root).unwrap().parsed, 0);
+
+    // A fresh handle reads the persisted index instead of reparsing.
+    let reopened = SymbolIndex::new(temp.path().join("index"));
+    assert_eq!(reopened.refresh(root).unwrap().parsed, 0);
+
+    touch(&root.join("greeter.py"), "def renamed():\n    pass\n");
+    std::fs::remove_file(root.join("web/shapes.ts")).unwrap();
+    assert_eq!(
+        reopened.refresh(root).unwrap(),
+        IndexRefresh {
+            files: 2,
+            parsed: 1,
+            removed: 1
+        }
+    );
+
+    let found = reopened.search(root, "renamed", None, None).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, "greeter.py");
+    assert!(reopened
+        .search(root, "Greeter", None, None)
+        .unwrap()
+        .is_empty());
+    assert!(reopened
+        .search(root, "skipped", None, None)
+        .unwrap()
+        .is_empty());
+}
@@ -1,8 +1,17 @@
+use chaos_bot_backend::domain::memory::{
+    ExtractionTrigger, MemoryBackendKind, MemoryScoping, RetentionMode, RollupOptions,
+    RollupPeriod,
+};
+use chaos_bot_backend::domain::tools::ToolPolicy;
 use chaos_bot_backend::infrastructure::config::{
     default_config_path_for_workspace, default_workspace_path, AgentChannelsConfig,
     AgentFileConfig, AgentLlmConfig, AgentLoggingConfig, AgentSecretsConfig, AgentServerConfig,
     AgentTelegramConfig, AppConfig, EnvSecrets,
 };
+use chaos_bot_backend::infrastructure::file_watch::DEFAULT_WATCH_DEBOUNCE;
+use chaos_bot_backend::infrastructure::memory_facts::FactStoreOptions;
+use chaos_bot_backend::infrastructure::memory_vectors::HybridSearchOptions;
+use chaos_bot_backend::infrastructure::tooling::SqlLimits;
 use serial_test::serial;
 use std::path::{Path, PathBuf};
 use tempfile::tempdir;
@@ -331,6 +340,7 @@ fn from_inputs_supports_injected_config_source() {
             gemini_api_key: None,
             telegram_bot_token: Some("telegram-json".to_string()),
         },
+        ..AgentFileConfig::default()
     };
     let env_secrets = EnvSecrets {
         openai_api_key: Some("env-key".to_string()),
@@ -381,6 +391,7 @@ fn from_inputs_applies_telegram_channel_and_secret_settings() {
             gemini_api_key: None,
             telegram_bot_token: Some("bot-token-json".to_string()),
         },
+        ..AgentFileConfig::default()
     };
     let env_secrets = EnvSecrets {
         openai_api_key: None,
@@ -392,7 +403,10 @@ fn from_inputs_applies_telegram_channel_and_secret_settings() {
     let config = AppConfig::from_inputs(file_config, env_secrets, home);
 
     assert!(config.telegram_enabled);
-    assert_eq!(config.telegram_webhook_secret.as_deref(), Some("secret-123"));
+    assert_eq!(
+        config.telegram_webhook_secret.as_deref(),
+        Some("secret-123")
+    );
     assert_eq!(
         config.telegram_webhook_base_url.as_deref(),
         Some("https://example.test/hook")
@@ -403,3 +417,514 @@ fn from_inputs_applies_telegram_channel_and_secret_settings() {
     );
     assert_eq!(config.telegram_bot_token.as_deref(), Some("bot-token-json"));
 }
+
+#[test]
+#[serial]
+fn from_inputs_applies_tool_output_and_fetch_settings() {
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "tools": {
+            "output": {"max_bytes": 2048, "head_lines": 10},
+            "fetch": {
+                "allow_domains": ["example.com"],
+                "deny_domains": ["ads.example.com"],
+                "allow_private_networks": true,
+                "max_bytes": 4096,
+                "timeout_secs": 3
+            }
+        }
+    }))
+    .unwrap();
+
+    let config = AppConfig::from_inputs(
+        file_config,
+        EnvSecrets::default(),
+        PathBuf::from("/tmp/home-base-tools"),
+    );
+
+    assert_eq!(config.tool_output_limits.max_bytes, 2048);
+    assert_eq!(config.tool_output_limits.head_lines, 10);
+    assert_eq!(config.tool_output_limits.tail_lines, 30);
+    assert!(config.fetch_enabled);
+    assert_eq!(config.fetch_policy.allow_domains, vec!["example.com"]);
+    assert_eq!(config.fetch_policy.deny_domains, vec!["ads.example.com"]);
+    assert!(config.fetch_policy.allow_private_networks);
+    assert_eq!(config.fetch_policy.max_bytes, 4096);
+    assert_eq!(
+        config.fetch_policy.timeout,
+        std::time::Duration::from_secs(3)
+    );
+}
+
+#[test]
+#[serial]
+fn from_inputs_builds_mcp_server_specs() {
+    use chaos_bot_backend::infrastructure::mcp::McpTransportSpec;
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "mcp": {
+            "servers": {
+                "files": {"command": "mcp-files", "args": ["--root", "/data"], "env": {"TOKEN": "x"}},
+                "remote": {"url": "https://mcp.example/mcp", "headers": {"Authorization": "Bearer t"}, "timeout_secs": 5},
+                "disabled": {"command": "nope", "enabled": false},
+                "invalid": {}
+            }
+        }
+    }))
+    .unwrap();
+
+    let config = AppConfig::from_inputs(
+        file_config,
+        EnvSecrets::default(),
+        PathBuf::from("/tmp/home-base-mcp"),
+    );
+
+    let names: Vec<&str> = config
+        .mcp_servers
+        .iter()
+        .map(|spec| spec.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["files", "remote"]);
+    match &config.mcp_servers[0].transport {
+        McpTransportSpec::Stdio { command, args, env } => {
+            assert_eq!(command, "mcp-files");
+            assert_eq!(args, &vec!["--root".to_string(), "/data".to_string()]);
+            assert_eq!(env.get("TOKEN").map(String::as_str), Some("x"));
+        }
+        other => panic!("expected stdio transport, got {other:?}"),
+    }
+    match &config.mcp_servers[1].transport {
+        McpTransportSpec::Http { url, headers } => {
+            assert_eq!(url, "https://mcp.example/mcp");
+            assert_eq!(
+                headers.get("Authorization").map(String::as_str),
+                Some("Bearer t")
+            );
+        }
+        other => panic!("expected http transport, got {other:?}"),
+    }
+    assert_eq!(
+        config.mcp_servers[1].timeout,
+        std::time::Duration::from_secs(5)
+    );
+}
+
+#[test]
+fn from_inputs_builds_tool_plugin_specs() {
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "tools": {
+            "plugins": [
+                {
+                    "name": "weather",
+                    "command": "/opt/plugins/weather",
+                    "args": ["--units", "metric"],
+                    "env": {"API_KEY": "k"},
+                    "description": "Current weather",
+                    "parameters_schema": {"type": "object", "properties": {"city": {"type": "string"}}},
+                    "timeout_secs": 5
+                },
+                {"name": "described", "command": "/opt/plugins/described"},
+                {"name": "off", "command": "/opt/plugins/off", "enabled": false},
+                {"name": "", "command": "/opt/plugins/unnamed"}
+            ]
+        }
+    }))
+    .unwrap();
+
+    let config = AppConfig::from_inputs(
+        file_config,
+        EnvSecrets::default(),
+        PathBuf::from("/tmp/home-base-plugins"),
+    );
+
+    let names: Vec<&str> = config
+        .tool_plugins
+        .iter()
+        .map(|spec| spec.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["weather", "described"]);
+    let weather = &config.tool_plugins[0];
+    assert_eq!(
+        weather.args,
+        vec!["--units".to_string(), "metric".to_string()]
+    );
+    assert_eq!(weather.env.get("API_KEY").map(String::as_str), Some("k"));
+    assert_eq!(weather.description.as_deref(), Some("Current weather"));
+    assert_eq!(weather.timeout, std::time::Duration::from_secs(5));
+    let described = &config.tool_plugins[1];
+    assert!(described.description.is_none());
+    assert!(described.parameters_schema.is_none());
+    assert_eq!(
+        described.timeout,
+        std::time::Duration::from_secs(
+            chaos_bot_backend::infrastructure::tooling::DEFAULT_PLUGIN_TIMEOUT_SECS
+        )
+    );
+}
+
+#[test]
+fn from_inputs_resolves_wasm_plugin_directory() {
+    let base = PathBuf::from("/tmp/home-base-wasm");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert!(defaults.wasm_plugins_enabled);
+    assert_eq!(
+        defaults.wasm_plugins_dir,
+        defaults.workspace.join("plugins")
+    );
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "tools": {"wasm": {"enabled": false, "directory": "sandboxed"}}
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert!(!config.wasm_plugins_enabled);
+    assert_eq!(config.wasm_plugins_dir, config.workspace.join("sandboxed"));
+}
+
+#[test]
+fn from_inputs_builds_tool_policy_from_presets_and_channels() {
+    let base = PathBuf::from("/tmp/home-base-tool-policy");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert_eq!(defaults.tool_policy, ToolPolicy::default());
+    assert!(defaults
+        .tool_policy
+        .for_channel(Some("telegram"))
+        .allows("bash"));
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "tools": {
+            "preset": "memory",
+            "enabled": ["fetch"],
+            "channels": {"telegram": {"preset": "none"}}
+        }
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+
+    let default = config.tool_policy.for_channel(None);
+    for name in ["memory_get", "memory_search", "fetch", "read_output"] {
+        assert!(default.allows(name), "{name}");
+    }
+    assert!(!default.allows("bash"));
+    assert_eq!(config.tool_policy.for_channel(Some("web")), default);
+
+    let telegram = config.tool_policy.for_channel(Some("telegram"));
+    assert!(!telegram.allows("memory_get"));
+    assert!(!telegram.allows("read_output"));
+}
+
+#[test]
+fn from_inputs_reads_git_tool_settings() {
+    let base = PathBuf::from("/tmp/home-base-git-tools");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert!(defaults.git_tools_enabled);
+    assert!(!defaults.git_commit_enabled);
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "tools": {"git": {"allow_commit": true}}
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert!(config.git_tools_enabled);
+    assert!(config.git_commit_enabled);
+}
+
+#[test]
+fn from_inputs_resolves_code_index_directory() {
+    let base = PathBuf::from("/tmp/home-base-code-index");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert!(defaults.code_tools_enabled);
+    assert_eq!(
+        defaults.code_index_dir,
+        defaults.workspace.join("data/code-index")
+    );
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "tools": {"code": {"enabled": false, "index_dir": "cache/symbols"}}
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert!(!config.code_tools_enabled);
+    assert_eq!(
+        config.code_index_dir,
+        config.workspace.join("cache/symbols")
+    );
+}
+
+#[test]
+fn from_inputs_applies_workspace_index_settings() {
+    let base = PathBuf::from("/tmp/home-base-workspace-index");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert!(defaults.workspace_index_enabled);
+    assert_eq!(
+        defaults.workspace_index_dir,
+        defaults.workspace.join("data/workspace-index")
+    );
+    assert_eq!(defaults.workspace_index_context_results, 0);
+    assert_eq!(
+        defaults.workspace_index_refresh,
+        std::time::Duration::from_secs(60)
+    );
+    assert!(defaults.workspace_index_embedding.is_none());
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "workspace_index": {
+            "directory": "cache/rag",
+            "max_files": 50,
+            "chunk_lines": 0,
+            "context_results": 2,
+            "refresh_secs": 0,
+            "embedding": "hashed"
+        }
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base.clone());
+    assert_eq!(
+        config.workspace_index_dir,
+        config.workspace.join("cache/rag")
+    );
+    assert_eq!(config.workspace_index_options.max_files, 50);
+    assert_eq!(config.workspace_index_options.chunk_lines, 1);
+    assert_eq!(config.workspace_index_context_results, 2);
+    assert_eq!(
+        config.workspace_index_refresh,
+        std::time::Duration::from_secs(1)
+    );
+    assert_eq!(config.workspace_index_embedding.as_deref(), Some("hashed"));
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "workspace_index": {"enabled": false, "embedding": "none"}
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert!(!config.workspace_index_enabled);
+    assert!(config.workspace_index_embedding.is_none());
+}
+
+#[test]
+fn from_inputs_reads_sql_tool_limits() {
+    let base = PathBuf::from("/tmp/home-base-sql-tools");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert!(defaults.sql_tools_enabled);
+    assert_eq!(defaults.sql_limits, SqlLimits::default());
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "tools": {"sql": {"enabled": false, "max_rows": 0, "timeout_secs": 3}}
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert!(!config.sql_tools_enabled);
+    assert_eq!(config.sql_limits.max_rows, 1);
+    assert_eq!(config.sql_limits.timeout, std::time::Duration::from_secs(3));
+}
+
+#[test]
+fn from_inputs_reads_memory_embedding_settings() {
+    let base = PathBuf::from("/tmp/home-base-memory");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert!(defaults.memory_embedding.is_none());
+    assert_eq!(defaults.memory_embedding_model, "text-embedding-3-small");
+    assert!(defaults.memory_embedding_base_url.is_none());
+    assert_eq!(defaults.memory_hybrid, HybridSearchOptions::default());
+    assert_eq!(defaults.memory_max_curated_bytes, 16 * 1024);
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "memory": {
+            "embedding": "openai",
+            "embedding_model": "nomic-embed-text",
+            "embedding_base_url": "http://localhost:11434/v1",
+            "vector_weight": 1.5,
+            "min_similarity": 0.4,
+            "max_curated_bytes": 4096
+        }
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base.clone());
+    assert_eq!(config.memory_embedding.as_deref(), Some("openai"));
+    assert_eq!(config.memory_embedding_model, "nomic-embed-text");
+    assert_eq!(
+        config.memory_embedding_base_url.as_deref(),
+        Some("http://localhost:11434/v1")
+    );
+    assert_eq!(config.memory_hybrid.vector_weight, 1.0);
+    assert_eq!(config.memory_hybrid.min_similarity, 0.4);
+    assert_eq!(config.memory_max_curated_bytes, 4096);
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "memory": {"embedding": "none"}
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert!(config.memory_embedding.is_none());
+}
+
+#[test]
+fn from_inputs_reads_memory_extraction_settings() {
+    let base = PathBuf::from("/tmp/home-base-extraction");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert!(!defaults.memory_extraction.enabled);
+    assert_eq!(
+        defaults.memory_extraction.trigger,
+        ExtractionTrigger::AfterRun
+    );
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "memory": {
+            "extraction": {
+                "enabled": true,
+                "trigger": "idle",
+                "idle_secs": 120,
+                "channels": ["telegram"],
+                "model": "gpt-4o-mini",
+                "min_turns": 0
+            }
+        }
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    let extraction = config.memory_extraction;
+    assert!(extraction.enabled);
+    assert_eq!(extraction.trigger, ExtractionTrigger::Idle);
+    assert_eq!(extraction.idle, std::time::Duration::from_secs(120));
+    assert!(extraction.applies_to(Some("telegram")));
+    assert!(!extraction.applies_to(None));
+    assert_eq!(extraction.model.as_deref(), Some("gpt-4o-mini"));
+    assert_eq!(extraction.min_turns, 1);
+}
+
+#[test]
+fn from_inputs_reads_memory_rollup_settings() {
+    let base = PathBuf::from("/tmp/home-base-rollup");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert_eq!(defaults.memory_rollup, RollupOptions::default());
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "memory": {
+            "rollup": {
+                "enabled": true,
+                "after_days": 30,
+                "period": "month",
+                "retention": "delete",
+                "interval_hours": 6,
+                "summarize": true
+            }
+        }
+    }))
+    .unwrap();
+    let rollup = AppConfig::from_inputs(file_config, EnvSecrets::default(), base).memory_rollup;
+    assert!(rollup.enabled);
+    assert_eq!(rollup.after_days, 30);
+    assert_eq!(rollup.period, RollupPeriod::Month);
+    assert_eq!(rollup.retention, RetentionMode::Delete);
+    assert_eq!(rollup.interval, std::time::Duration::from_secs(6 * 60 * 60));
+    assert!(rollup.summarize);
+}
+
+#[test]
+fn from_inputs_reads_memory_scope() {
+    let base = PathBuf::from("/tmp/home-base-scope");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert_eq!(defaults.memory_scoping, MemoryScoping::Global);
+
+    let file_config: AgentFileConfig =
+        serde_json::from_value(serde_json::json!({"memory": {"scope": "user"}})).unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert_eq!(config.memory_scoping, MemoryScoping::User);
+}
+
+#[test]
+fn from_inputs_reads_memory_backend_settings() {
+    let base = PathBuf::from("/tmp/home-base-facts");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert_eq!(defaults.memory_backend, MemoryBackendKind::Files);
+    assert_eq!(defaults.memory_facts, FactStoreOptions::default());
+    assert_eq!(
+        defaults.memory_facts_db,
+        defaults.workspace.join("data/memory-facts.db")
+    );
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "memory": {
+            "backend": "sqlite",
+            "facts": {
+                "path": "state/facts.db",
+                "prompt_limit": 10,
+                "min_confidence": 1.5,
+                "import_logs": true
+            }
+        }
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert_eq!(config.memory_backend, MemoryBackendKind::Sqlite);
+    assert_eq!(
+        config.memory_facts_db,
+        config.workspace.join("state/facts.db")
+    );
+    assert_eq!(config.memory_facts.prompt_limit, 10);
+    assert_eq!(config.memory_facts.min_confidence, 1.0);
+    assert!(config.memory_facts.import_logs);
+}
+
+#[test]
+fn from_inputs_reads_watch_settings() {
+    let base = PathBuf::from("/tmp/home-base-watch");
+    let defaults = AppConfig::from_inputs(
+        AgentFileConfig::default(),
+        EnvSecrets::default(),
+        base.clone(),
+    );
+    assert!(defaults.watch_enabled);
+    assert_eq!(defaults.watch_debounce, DEFAULT_WATCH_DEBOUNCE);
+
+    let file_config: AgentFileConfig = serde_json::from_value(serde_json::json!({
+        "watch": {"enabled": false, "debounce_ms": 1000}
+    }))
+    .unwrap();
+    let config = AppConfig::from_inputs(file_config, EnvSecrets::default(), base);
+    assert!(!config.watch_enabled);
+    assert_eq!(config.watch_debounce, std::time::Duration::from_secs(1));
+}
@@ -0,0 +1,402 @@
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{
+    parse_page_ranges, DocumentFormat, ReadTool, Tool, ToolContext,
+};
+use serde_json::json;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::{tempdir, TempDir};
+use zip::write::SimpleFileOptions;
+
+fn make_context() -> (TempDir, ToolContext) {
+    let temp = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let context = ToolContext::new(temp.path().to_path_buf(), memory);
+    (temp, context)
+}
+
+fn zip_archive(parts: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in parts {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+/// A PDF with one Helvetica text line per page and a correct xref table.
+fn pdf(pages: &[&str]) -> Vec<u8> {
+    let font_id = 3 + pages.len() * 2;
+    let kids: Vec<String> = (0..pages.len())
+        .map(|index| format!("{} 0 R", 3 + index * 2))
+        .collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+    ];
+    for (index, text) in pages.iter().enumerate() {
+        let content_id = 4 + index * 2;
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents {content_id} 0 R /Resources << /Font << /F1 {font_id} 0 R >> >> >>"
+        ));
+        let stream = format!("BT /F1 12 Tf 72 720 Td ({text}) Tj ET");
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{stream}\nendstream",
+            stream.len()
+        ));
+    }
+    objects.push(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    );
+
+    let mut output = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(output.len());
+        output.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", index + 1).as_bytes());
+    }
+    let xref = output.len();
+    output.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        output.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    output.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .as_bytes(),
+    );
+    output
+}
+
+const DOCX_BODY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:body>
+<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Quarterly Report</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Revenue grew </w:t></w:r><w:r><w:t>12% &amp; costs fell.</w:t></w:r></w:p>
+<w:tbl>
+<w:tr><w:tc><w:p><w:r><w:t>Region</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Total</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:p><w:r><w:t>EMEA</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>42</w:t></w:r></w:p></w:tc></w:tr>
+</w:tbl>
+<w:p><w:r><w:t>Done.</w:t></w:r></w:p>
+</w:body>
+</w:document>"#;
+
+fn xlsx() -> Vec<u8> {
+    let sheet = |rows: &str| {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>{rows}</sheetData></worksheet>"#
+        )
+    };
+    let sales = sheet(
+        r#"<row r="1"><c r="A1" t="inlineStr"><is><t>Region</t></is></c><c r="B1" t="inlineStr"><is><t>Units</t></is></c></row>
+<row r="2"><c r="A2" t="inlineStr"><is><t>North</t></is></c><c r="B2"><v>10</v></c></row>
+<row r="3"><c r="A3" t="inlineStr"><is><t>South</t></is></c><c r="B3"><v>7.5</v></c></row>"#,
+    );
+    let notes = sheet(r#"<row r="1"><c r="A1" t="inlineStr"><is><t>Owner</t></is></c></row>"#);
+    zip_archive(&[
+        (
+            "[Content_Types].xml",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>
+<Override PartName="/xl/worksheets/sheet2.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>
+</Types>"#,
+        ),
+        (
+            "_rels/.rels",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+</Relationships>"#,
+        ),
+        (
+            "xl/workbook.xml",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+<sheets><sheet name="Sales" sheetId="1" r:id="rId1"/><sheet name="Notes" sheetId="2" r:id="rId2"/></sheets>
+</workbook>"#,
+        ),
+        (
+            "xl/_rels/workbook.xml.rels",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet2.xml"/>
+</Relationships>"#,
+        ),
+        ("xl/worksheets/sheet1.xml", &sales),
+        ("xl/worksheets/sheet2.xml", &notes),
+    ])
+}
+
+fn epub() -> Vec<u8> {
+    let chapter = |title: &str, body: &str| {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml"><head><title>{title}</title></head>
+<body><h1>{title}</h1><p>{body}</p></body></html>"#
+        )
+    };
+    let one = chapter("Arrival", "The ship docked at dawn.");
+    let two = chapter("Departure", "They left before the storm.");
+    zip_archive(&[
+        ("mimetype", "application/epub+zip"),
+        (
+            "META-INF/container.xml",
+            r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#,
+        ),
+        (
+            "OEBPS/content.opf",
+            r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+<manifest>
+<item id="c2" href="text/two.xhtml" media-type="application/xhtml+xml"/>
+<item id="c1" href="text/one.xhtml" media-type="application/xhtml+xml"/>
+</manifest>
+<spine><itemref idref="c1"/><itemref idref="c2"/></spine>
+</package>"#,
+        ),
+        ("OEBPS/text/one.xhtml", &one),
+        ("OEBPS/text/two.xhtml", &two),
+    ])
+}
+
+async fn read(context: &ToolContext, args: serde_json::Value) -> anyhow::Result<String> {
+    ReadTool
+        .execute(args, context)
+        .await
+        .map(|result| result.output)
+}
+
+#[test]
+fn detect_uses_magic_bytes_before_extensions() {
+    let path = Path::new;
+    assert_eq!(
+        DocumentFormat::detect(path("report.txt"), &pdf(&["x"])),
+        Some(DocumentFormat::Pdf)
+    );
+    assert_eq!(
+        DocumentFormat::detect(path("upload.bin"), &xlsx()),
+        Some(DocumentFormat::Spreadsheet)
+    );
+    assert_eq!(
+        DocumentFormat::detect(path("book"), &epub()),
+        Some(DocumentFormat::Epub)
+    );
+    assert_eq!(
+        DocumentFormat::detect(path("data.TSV"), b"a\tb"),
+        Some(DocumentFormat::Csv { delimiter: b'\t' })
+    );
+    assert_eq!(
+        DocumentFormat::detect(path("notes.md"), b"# notes"),
+        Some(DocumentFormat::Text)
+    );
+    assert_eq!(
+        DocumentFormat::detect(path("archive.zip"), &zip_archive(&[("a.txt", "a")])),
+        None
+    );
+    assert_eq!(
+        DocumentFormat::detect(path("image.png"), b"\x89PNG\r\n\x1a\n\0\0"),
+        None
+    );
+}
+
+#[test]
+fn parse_page_ranges_accepts_lists_and_open_ranges() {
+    assert_eq!(parse_page_ranges("2", 5).unwrap(), vec![2]);
+    assert_eq!(parse_page_ranges("1-2, 4-", 5).unwrap(), vec![1, 2, 4, 5]);
+    assert_eq!(parse_page_ranges("3,1-3", 5).unwrap(), vec![3, 1, 2]);
+    let error = parse_page_ranges("4-9", 5).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("out of range (document has 5 pages)"));
+    assert!(parse_page_ranges("0", 5).is_err());
+    assert!(parse_page_ranges("x", 5).is_err());
+}
+
+#[tokio::test]
+async fn read_extracts_pdf_pages() {
+    let (_temp, context) = make_context();
+    std::fs::write(
+        context.root_dir.join("report.pdf"),
+        pdf(&["Executive summary", "Detailed findings", "Appendix"]),
+    )
+    .unwrap();
+
+    let all = read(&context, json!({"path": "report.pdf"})).await.unwrap();
+    assert!(
+        all.starts_with("--- page 1 of 3 ---\nExecutive summary"),
+        "{all}"
+    );
+    assert!(all.contains("--- page 3 of 3 ---\nAppendix"));
+
+    let selected = read(&context, json!({"path": "report.pdf", "pages": "2"}))
+        .await
+        .unwrap();
+    assert_eq!(selected, "--- page 2 of 3 ---\nDetailed findings");
+
+    let error = read(&context, json!({"path": "report.pdf", "pages": "5"}))
+        .await
+        .unwrap_err();
+    assert!(format!("{error:#}").contains("out of range"), "{error:#}");
+}
+
+#[tokio::test]
+async fn read_extracts_docx_paragraphs_headings_and_tables() {
+    let (_temp, context) = make_context();
+    std::fs::write(
+        context.root_dir.join("report.docx"),
+        zip_archive(&[("word/document.xml", DOCX_BODY)]),
+    )
+    .unwrap();
+
+    let text = read(&context, json!({"path": "report.docx"}))
+        .await
+        .unwrap();
+    assert_eq!(
+        text,
+        "# Quarterly Report\nRevenue grew 12% & costs fell.\n| Region | Total |\n| --- | --- |\n| EMEA | 42 |\n\nDone."
+    );
+
+    let lines = read(
+        &context,
+        json!({"path": "report.docx", "start_line": 3, "end_line": 3}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(lines, "| Region | Total |");
+}
+
+#[tokio::test]
+async fn read_renders_spreadsheets_and_csv_as_tables() {
+    let (_temp, context) = make_context();
+    std::fs::write(context.root_dir.join("sales.xlsx"), xlsx()).unwrap();
+    std::fs::write(
+        context.root_dir.join("people.csv"),
+        "name,role\nAda,\"engineer, lead\"\nGrace|Hopper,admiral\n",
+    )
+    .unwrap();
+
+    let all = read(&context, json!({"path": "sales.xlsx"})).await.unwrap();
+    assert_eq!(
+        all,
+        "## Sheet: Sales\n| Region | Units |\n| --- | --- |\n| North | 10 |\n| South | 7.5 |\n\n## Sheet: Notes\n| Owner |\n| --- |"
+    );
+    let by_name = read(&context, json!({"path": "sales.xlsx", "sheet": "notes"}))
+        .await
+        .unwrap();
+    assert_eq!(by_name, "## Sheet: Notes\n| Owner |\n| --- |");
+    let by_index = read(&context, json!({"path": "sales.xlsx", "sheet": 1}))
+        .await
+        .unwrap();
+    assert!(by_index.starts_with("## Sheet: Sales"));
+    let error = read(&context, json!({"path": "sales.xlsx", "sheet": "Missing"}))
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{error:#}").contains("sheets: Sales, Notes"),
+        "{error:#}"
+    );
+
+    let csv = read(&context, json!({"path": "people.csv"})).await.unwrap();
+    assert_eq!(
+        csv,
+        "| name | role |\n| --- | --- |\n| Ada | engineer, lead |\n| Grace\\|Hopper | admiral |"
+    );
+    let raw = read(&context, json!({"path": "people.csv", "raw": true}))
+        .await
+        .unwrap();
+    assert!(raw.starts_with("name,role\nAda,\"engineer, lead\""));
+}
+
+#[tokio::test]
+async fn read_extracts_epub_chapters_and_html() {
+    let (_temp, context) = make_context();
+    std::fs::write(context.root_dir.join("novel.epub"), epub()).unwrap();
+    std::fs::write(
+        context.root_dir.join("page.html"),
+        "<html><body><h2>Status</h2><p>All <b>systems</b> go.</p><script>ignored()</script></body></html>",
+    )
+    .unwrap();
+
+    let book = read(&context, json!({"path": "novel.epub"})).await.unwrap();
+    let arrival = book.find("The ship docked at dawn.").unwrap();
+    let departure = book.find("They left before the storm.").unwrap();
+    assert!(book.starts_with("--- chapter 1 of 2 ---"), "{book}");
+    assert!(arrival < departure);
+
+    let second = read(&context, json!({"path": "novel.epub", "pages": "2"}))
+        .await
+        .unwrap();
+    assert!(second.starts_with("--- chapter 2 of 2 ---"));
+    assert!(!second.contains("docked"));
+
+    let html = read(&context, json!({"path": "page.html"})).await.unwrap();
+    assert!(html.contains("Status"), "{html}");
+    assert!(html.contains("systems"));
+    assert!(!html.contains("<p>") && !html.contains("<b>"));
+    let raw = read(&context, json!({"path": "page.html", "raw": true}))
+        .await
+        .unwrap();
+    assert!(raw.starts_with("<html><body><h2>"));
+}
+
+#[tokio::test]
+async fn read_rejects_unsupported_binaries_and_misplaced_selectors() {
+    let (_temp, context) = make_context();
+    std::fs::write(
+        context.root_dir.join("photo.png"),
+        b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR",
+    )
+    .unwrap();
+    std::fs::write(context.root_dir.join("latin1.txt"), b"caf\xe9 au lait").unwrap();
+    std::fs::write(context.root_dir.join("notes.txt"), "plain").unwrap();
+
+    let error = read(&context, json!({"path": "photo.png"}))
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("binary file in an unsupported format"),
+        "{error}"
+    );
+
+    let lossy = read(&context, json!({"path": "latin1.txt"})).await.unwrap();
+    assert_eq!(lossy, "caf\u{fffd} au lait");
+
+    let error = read(&context, json!({"path": "notes.txt", "pages": "1"}))
+        .await
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("read.pages only applies to pdf and epub"));
+    let error = read(&context, json!({"path": "notes.txt", "sheet": "1"}))
+        .await
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("read.sheet only applies to spreadsheet"));
+}
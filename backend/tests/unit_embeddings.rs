@@ -0,0 +1,235 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::{Json, Router};
+use chaos_bot_backend::domain::ports::{EmbeddingPort, MemoryHit};
+use chaos_bot_backend::infrastructure::embedding::OpenAiEmbedding;
+use chaos_bot_backend::infrastructure::memory::MemoryStore;
+use chaos_bot_backend::infrastructure::memory_vectors::{blend_hits, HybridSearchOptions};
+use chaos_bot_backend::infrastructure::workspace_index::HashedEmbedder;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+use tokio::net::TcpListener;
+
+/// Hashed embedder that records every batch it is asked to embed.
+#[derive(Default)]
+struct CountingEmbedder {
+    inner: HashedEmbedder,
+    batches: Mutex<Vec<Vec<String>>>,
+}
+
+#[async_trait]
+impl EmbeddingPort for CountingEmbedder {
+    fn id(&self) -> String {
+        "counting".to_string()
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.batches.lock().unwrap().push(texts.to_vec());
+        self.inner.embed(texts).await
+    }
+}
+
+struct FailingEmbedder;
+
+#[async_trait]
+impl EmbeddingPort for FailingEmbedder {
+    fn id(&self) -> String {
+        "failing".to_string()
+    }
+
+    async fn embed(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Err(anyhow!("embedding service unavailable"))
+    }
+}
+
+fn hit(path: &str, line: usize, score: f32) -> MemoryHit {
+    MemoryHit {
+        path: path.to_string(),
+        line,
+        snippet: format!("{path}:{line}"),
+        score,
+    }
+}
+
+#[tokio::test]
+async fn hashed_embedder_is_an_offline_embedding_port() {
+    let embedder = HashedEmbedder::new(64);
+    assert_eq!(EmbeddingPort::id(&embedder), "hashed-64");
+    let texts = vec![
+        "deploy the cluster".to_string(),
+        "deploy the cluster".to_string(),
+    ];
+    let vectors = EmbeddingPort::embed(&embedder, &texts).await.unwrap();
+    assert_eq!(vectors.len(), 2);
+    assert_eq!(vectors[0].len(), 64);
+    assert_eq!(vectors[0], vectors[1]);
+}
+
+/// `(authorization header, request body)` for every request the embeddings stub received.
+type SeenRequests = Arc<Mutex<Vec<(Option<String>, Value)>>>;
+
+#[tokio::test]
+async fn openai_embedding_calls_compatible_endpoint() {
+    let captured: SeenRequests = Arc::default();
+    let app = Router::new().route(
+        "/v1/embeddings",
+        post({
+            let captured = captured.clone();
+            move |headers: HeaderMap, Json(body): Json<Value>| {
+                let captured = captured.clone();
+                async move {
+                    let auth = headers
+                        .get("authorization")
+                        .map(|value| value.to_str().unwrap().to_string());
+                    captured.lock().unwrap().push((auth, body));
+                    // Out of order on purpose: clients must sort by `index`.
+                    Json(json!({"data": [
+                        {"index": 1, "embedding": [0.0, 1.0]},
+                        {"index": 0, "embedding": [1.0, 0.0]}
+                    ]}))
+                }
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service())
+            .await
+            .unwrap();
+    });
+
+    let embedding =
+        OpenAiEmbedding::new(format!("http://{addr}/v1/"), "test-model").with_api_key("sk-test");
+    assert_eq!(
+        embedding.id(),
+        format!("openai:test-model@http://{addr}/v1")
+    );
+    let vectors = embedding
+        .embed(&["first".to_string(), "second".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+
+    {
+        let captured = captured.lock().unwrap();
+        assert_eq!(captured[0].0.as_deref(), Some("Bearer sk-test"));
+        assert_eq!(
+            captured[0].1,
+            json!({"model": "test-model", "input": ["first", "second"]})
+        );
+    }
+
+    let error = embedding
+        .embed(&["only one".to_string()])
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("2 vectors for 1 inputs"),
+        "{error}"
+    );
+    assert!(embedding.embed(&[]).await.unwrap().is_empty());
+}
+
+#[test]
+fn blend_hits_normalises_bm25_and_filters_weak_vector_matches() {
+    let keyword = vec![hit("a.md", 1, 4.0), hit("a.md", 2, 2.0)];
+    let vector = vec![
+        hit("a.md", 2, 0.9),
+        hit("b.md", 1, 0.8),
+        hit("b.md", 2, 0.1),
+    ];
+    let blended = blend_hits(keyword, vector, HybridSearchOptions::default());
+    let ranked: Vec<(&str, usize, f32)> = blended
+        .iter()
+        .map(|hit| (hit.path.as_str(), hit.line, hit.score))
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![("a.md", 2, 0.7), ("a.md", 1, 0.5), ("b.md", 1, 0.4)]
+    );
+
+    let keyword_only = blend_hits(
+        vec![hit("a.md", 1, 3.0)],
+        vec![hit("b.md", 1, 0.99)],
+        HybridSearchOptions {
+            vector_weight: 0.0,
+            min_similarity: 0.3,
+        },
+    );
+    assert_eq!(keyword_only[0].score, 1.0);
+    assert_eq!(keyword_only[1].score, 0.0);
+}
+
+#[tokio::test]
+async fn hybrid_memory_search_finds_partial_word_matches() {
+    let temp = tempdir().unwrap();
+    let embedder = Arc::new(CountingEmbedder::default());
+    let store = MemoryStore::new(temp.path().join("memory"), temp.path().join("MEMORY.md"))
+        .with_embeddings(
+            embedder.clone(),
+            HybridSearchOptions {
+                vector_weight: 0.5,
+                min_similarity: 0.1,
+            },
+        );
+    store.ensure_layout().await.unwrap();
+    store
+        .write_curated(
+            "# Notes\n- Kubernetes deployment runs on Fridays\n- Favourite tea is oolong\n",
+        )
+        .await
+        .unwrap();
+
+    // BM25 has no term in common with "deploys"; it only reaches the deployment line through
+    // the embedder's character trigrams.
+    let keyword_store = MemoryStore::new(temp.path().join("memory"), temp.path().join("MEMORY.md"));
+    assert!(keyword_store.search("deploys").await.unwrap().is_empty());
+
+    let hits = store.search("deploys").await.unwrap();
+    assert!(!hits.is_empty());
+    assert!(
+        hits[0].snippet.contains("Kubernetes deployment"),
+        "{hits:?}"
+    );
+    assert!(hits[0].score > 0.0);
+    assert!(store
+        .memory_dir()
+        .join(".index/memory-vectors.json")
+        .exists());
+}
+
+#[tokio::test]
+async fn memory_vectors_only_embed_new_lines() {
+    let temp = tempdir().unwrap();
+    let embedder = Arc::new(CountingEmbedder::default());
+    let store = MemoryStore::new(temp.path().join("memory"), temp.path().join("MEMORY.md"))
+        .with_embeddings(embedder.clone(), HybridSearchOptions::default());
+    store.ensure_layout().await.unwrap();
+    store.write_curated("- likes hiking\n").await.unwrap();
+    store.append_daily_log("planned a trip").await.unwrap();
+    embedder.batches.lock().unwrap().clear();
+
+    store.append_daily_log("booked the hotel").await.unwrap();
+    let batches = embedder.batches.lock().unwrap().clone();
+    assert_eq!(batches, vec![vec!["- booked the hotel".to_string()]]);
+}
+
+#[tokio::test]
+async fn memory_search_falls_back_to_keywords_when_embedding_fails() {
+    let temp = tempdir().unwrap();
+    let store = MemoryStore::new(temp.path().join("memory"), temp.path().join("MEMORY.md"))
+        .with_embeddings(Arc::new(FailingEmbedder), HybridSearchOptions::default());
+    store.ensure_layout().await.unwrap();
+    store
+        .write_curated("- the wifi password is on the fridge\n")
+        .await
+        .unwrap();
+
+    let hits = store.search("wifi password").await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].line, 1);
+}
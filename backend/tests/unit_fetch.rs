@@ -0,0 +1,281 @@
+use axum::http::header::{CONTENT_TYPE, LOCATION};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{
+    is_private_ip, FetchPolicy, FetchTool, Tool, ToolContext,
+};
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::{tempdir, TempDir};
+use tokio::net::TcpListener;
+
+async fn spawn_server() -> SocketAddr {
+    let app = Router::new()
+        .route(
+            "/page",
+            get(|| async {
+                (
+                    [(CONTENT_TYPE, "text/html; charset=utf-8")],
+                    "<html><head><style>body{color:red}</style><script>var x=1;</script></head>\
+                     <body><h1>Title</h1><p>Hello <b>world</b>.</p></body></html>",
+                )
+            }),
+        )
+        .route(
+            "/data.json",
+            get(|| async { ([(CONTENT_TYPE, "application/json")], r#"{"ok":true}"#) }),
+        )
+        .route(
+            "/big",
+            get(|| async { ([(CONTENT_TYPE, "text/plain")], "a".repeat(10_000)) }),
+        )
+        .route(
+            "/binary",
+            get(|| async { ([(CONTENT_TYPE, "image/png")], vec![0u8, 1, 2, 3]) }),
+        )
+        .route(
+            "/redirect",
+            get(|| async { (StatusCode::FOUND, [(LOCATION, "/page")]) }),
+        )
+        .route(
+            "/loop",
+            get(|| async { (StatusCode::FOUND, [(LOCATION, "/loop")]) }),
+        )
+        .route(
+            "/missing",
+            get(|| async { (StatusCode::NOT_FOUND, "nope").into_response() }),
+        )
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "late"
+            }),
+        );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    addr
+}
+
+fn context() -> (TempDir, ToolContext) {
+    let temp = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let ctx = ToolContext::new(temp.path().to_path_buf(), memory);
+    (temp, ctx)
+}
+
+fn local_policy() -> FetchPolicy {
+    FetchPolicy {
+        allow_private_networks: true,
+        ..FetchPolicy::default()
+    }
+}
+
+#[tokio::test]
+async fn fetch_converts_html_to_text() {
+    let addr = spawn_server().await;
+    let (_temp, ctx) = context();
+
+    let result = FetchTool::new(local_policy())
+        .execute(json!({"url": format!("http://{addr}/page")}), &ctx)
+        .await
+        .unwrap();
+    assert!(!result.is_error);
+    assert!(result.output.contains("Status: 200"));
+    assert!(result.output.contains("# Title"));
+    assert!(result.output.contains("Hello"));
+    assert!(!result.output.contains("<p>"));
+    assert!(!result.output.contains("color:red"));
+    assert!(!result.output.contains("var x"));
+
+    let raw = FetchTool::new(local_policy())
+        .execute(
+            json!({"url": format!("http://{addr}/page"), "raw": true}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert!(raw.output.contains("<h1>Title</h1>"));
+}
+
+#[tokio::test]
+async fn fetch_returns_text_content_and_marks_http_errors() {
+    let addr = spawn_server().await;
+    let (_temp, ctx) = context();
+    let tool = FetchTool::new(local_policy());
+
+    let result = tool
+        .execute(json!({"url": format!("http://{addr}/data.json")}), &ctx)
+        .await
+        .unwrap();
+    assert!(result.output.contains("Content-Type: application/json"));
+    assert!(result.output.ends_with(r#"{"ok":true}"#));
+
+    let missing = tool
+        .execute(json!({"url": format!("http://{addr}/missing")}), &ctx)
+        .await
+        .unwrap();
+    assert!(missing.is_error);
+    assert!(missing.output.contains("Status: 404"));
+}
+
+#[tokio::test]
+async fn fetch_rejects_unsupported_content_types() {
+    let addr = spawn_server().await;
+    let (_temp, ctx) = context();
+
+    let error = FetchTool::new(local_policy())
+        .execute(json!({"url": format!("http://{addr}/binary")}), &ctx)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("unsupported content type"));
+}
+
+#[tokio::test]
+async fn fetch_truncates_body_at_size_limit() {
+    let addr = spawn_server().await;
+    let (_temp, ctx) = context();
+    let policy = FetchPolicy {
+        max_bytes: 100,
+        ..local_policy()
+    };
+
+    let result = FetchTool::new(policy)
+        .execute(json!({"url": format!("http://{addr}/big")}), &ctx)
+        .await
+        .unwrap();
+    assert!(result.output.contains("[body truncated at 100 bytes]"));
+    assert!(result.output.ends_with(&"a".repeat(100)));
+    assert!(!result.output.contains(&"a".repeat(101)));
+}
+
+#[tokio::test]
+async fn fetch_times_out() {
+    let addr = spawn_server().await;
+    let (_temp, ctx) = context();
+    let policy = FetchPolicy {
+        timeout: Duration::from_millis(200),
+        ..local_policy()
+    };
+
+    let error = FetchTool::new(policy)
+        .execute(json!({"url": format!("http://{addr}/slow")}), &ctx)
+        .await
+        .unwrap_err();
+    let message = format!("{error:#}");
+    assert!(message.contains("timed out") || message.contains("request failed"));
+}
+
+#[tokio::test]
+async fn fetch_follows_redirects_with_a_limit() {
+    let addr = spawn_server().await;
+    let (_temp, ctx) = context();
+    let tool = FetchTool::new(local_policy());
+
+    let result = tool
+        .execute(json!({"url": format!("http://{addr}/redirect")}), &ctx)
+        .await
+        .unwrap();
+    assert!(result.output.contains(&format!("URL: http://{addr}/page")));
+    assert!(result.output.contains("# Title"));
+
+    let error = tool
+        .execute(json!({"url": format!("http://{addr}/loop")}), &ctx)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("too many redirects"));
+}
+
+#[tokio::test]
+async fn fetch_blocks_private_networks_by_default() {
+    let addr = spawn_server().await;
+    let (_temp, ctx) = context();
+    let tool = FetchTool::new(FetchPolicy::default());
+
+    for url in [
+        format!("http://{addr}/page"),
+        format!("http://localhost:{}/page", addr.port()),
+        "http://[::1]/".to_string(),
+        "http://10.0.0.1/".to_string(),
+    ] {
+        let error = tool.execute(json!({"url": url}), &ctx).await.unwrap_err();
+        assert!(
+            error.to_string().contains("private or local address"),
+            "{url}: {error}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn fetch_applies_domain_allow_and_deny_lists() {
+    let addr = spawn_server().await;
+    let (_temp, ctx) = context();
+    let url = format!("http://localhost:{}/page", addr.port());
+
+    let allowed = FetchTool::new(FetchPolicy {
+        allow_domains: vec!["localhost".to_string()],
+        ..local_policy()
+    });
+    assert!(allowed.execute(json!({"url": url}), &ctx).await.is_ok());
+
+    let not_listed = FetchTool::new(FetchPolicy {
+        allow_domains: vec!["example.com".to_string()],
+        ..local_policy()
+    });
+    let error = not_listed
+        .execute(json!({"url": url}), &ctx)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("not in the allow list"));
+
+    let denied = FetchTool::new(FetchPolicy {
+        allow_domains: vec!["localhost".to_string()],
+        deny_domains: vec!["localhost".to_string()],
+        ..local_policy()
+    });
+    let error = denied.execute(json!({"url": url}), &ctx).await.unwrap_err();
+    assert!(error.to_string().contains("domain is denied"));
+}
+
+#[tokio::test]
+async fn fetch_rejects_non_http_schemes() {
+    let (_temp, ctx) = context();
+    let error = FetchTool::new(local_policy())
+        .execute(json!({"url": "file:///etc/passwd"}), &ctx)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("unsupported url scheme"));
+}
+
+#[test]
+fn private_ip_classification() {
+    for ip in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "::1",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:127.0.0.1",
+    ] {
+        assert!(is_private_ip(ip.parse::<IpAddr>().unwrap()), "{ip}");
+    }
+    for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+        assert!(!is_private_ip(ip.parse::<IpAddr>().unwrap()), "{ip}");
+    }
+}
@@ -0,0 +1,134 @@
+mod support;
+
+use chaos_bot_backend::infrastructure::file_watch::{FileWatcher, WatchedCache};
+use chaos_bot_backend::infrastructure::personality::PersonalityLoader;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use support::{temp_memory_store, temp_personality_dir};
+
+#[tokio::test]
+async fn personality_sections_are_cached_while_watched() {
+    let (_temp, loader) = temp_personality_dir();
+    let soul = loader.dir().join("SOUL.md");
+    std::fs::write(&soul, "You are calm.").unwrap();
+    loader.set_watched(true);
+    assert!(loader.system_prompt().await.unwrap().contains("calm"));
+
+    std::fs::write(&soul, "You are cheerful.").unwrap();
+    assert!(loader.system_prompt().await.unwrap().contains("calm"));
+    loader.invalidate(std::slice::from_ref(&soul));
+    assert!(loader.system_prompt().await.unwrap().contains("cheerful"));
+
+    // Without a watcher every request rereads the files.
+    loader.set_watched(false);
+    std::fs::write(&soul, "You are terse.").unwrap();
+    assert!(loader.system_prompt().await.unwrap().contains("terse"));
+
+    assert!(loader.affected_by(&soul));
+    assert!(!loader.affected_by(&loader.dir().join("notes.md")));
+    assert!(!loader.affected_by(&loader.dir().join("nested/SOUL.md")));
+}
+
+#[tokio::test]
+async fn memory_search_skips_rescans_until_invalidated() {
+    let (temp, store) = temp_memory_store();
+    store.ensure_layout().await.unwrap();
+    store.set_watched(true);
+    assert!(store.search("tea").await.unwrap().is_empty());
+
+    let curated = temp.path().join("MEMORY.md");
+    std::fs::write(
+        &curated,
+        "# Long-Term Memory\n\n## Preferences\n- Prefers tea\n",
+    )
+    .unwrap();
+    assert!(store.search("tea").await.unwrap().is_empty());
+    store.invalidate(std::slice::from_ref(&curated));
+    assert_eq!(store.search("tea").await.unwrap().len(), 1);
+
+    // Writes through the store keep the index current without an invalidation.
+    store.append_daily_log("more tea please").await.unwrap();
+    assert_eq!(store.search("tea").await.unwrap().len(), 2);
+
+    let memory_dir = store.memory_dir().to_path_buf();
+    assert!(store.affected_by(&curated));
+    assert!(store.affected_by(&memory_dir.join("2026-03-02.md")));
+    assert!(store.affected_by(&memory_dir.join(".scopes/users/telegram/1/MEMORY.md")));
+    assert!(!store.affected_by(&memory_dir.join(".index/memory-index.json")));
+    assert!(!store.affected_by(&memory_dir.join(".audit.jsonl")));
+    assert!(!store.affected_by(&memory_dir));
+}
+
+/// Polls `check` for up to five seconds.
+async fn eventually<F, Fut>(what: &str, mut check: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    for _ in 0..200 {
+        if check().await {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    panic!("timed out waiting for {what}");
+}
+
+#[tokio::test]
+async fn watcher_invalidates_caches_after_external_edits() {
+    let (temp, store) = temp_memory_store();
+    store.ensure_layout().await.unwrap();
+    let personality_dir = temp.path().join("personality");
+    let loader = PersonalityLoader::new(&personality_dir);
+    let watcher = FileWatcher::new(Duration::from_millis(50))
+        .watch(
+            "personality",
+            &personality_dir,
+            false,
+            Arc::new(loader.clone()),
+        )
+        .watch("memory", store.memory_dir(), true, Arc::new(store.clone()))
+        .watch("memory", temp.path(), false, Arc::new(store.clone()))
+        .start()
+        .unwrap();
+    let status = watcher.status();
+    assert!(status.enabled);
+    assert_eq!(status.debounce_ms, 50);
+    assert_eq!(status.targets.len(), 3);
+
+    assert_eq!(loader.system_prompt().await.unwrap(), "");
+    std::fs::write(personality_dir.join("SOUL.md"), "You are calm.").unwrap();
+    eventually("the new personality", || async {
+        loader.system_prompt().await.unwrap().contains("calm")
+    })
+    .await;
+    std::fs::write(personality_dir.join("SOUL.md"), "You are cheerful.").unwrap();
+    eventually("the edited personality", || async {
+        loader.system_prompt().await.unwrap().contains("cheerful")
+    })
+    .await;
+
+    assert!(store.search("tea").await.unwrap().is_empty());
+    std::fs::write(
+        temp.path().join("MEMORY.md"),
+        "# Long-Term Memory\n\n## Preferences\n- Prefers tea\n",
+    )
+    .unwrap();
+    eventually("the memory edit", || async {
+        !store.search("tea").await.unwrap().is_empty()
+    })
+    .await;
+
+    let status = watcher.status();
+    assert!(status.changes >= 3, "{status:?}");
+    let last = status.recent.last().unwrap();
+    assert_eq!(last.target, "memory");
+    assert!(last.paths[0].ends_with("MEMORY.md"), "{last:?}");
+    assert!(status.last_change.is_some());
+
+    // Once the watcher is gone the caches stop trusting themselves.
+    drop(watcher);
+    std::fs::write(personality_dir.join("SOUL.md"), "You are terse.").unwrap();
+    assert!(loader.system_prompt().await.unwrap().contains("terse"));
+}
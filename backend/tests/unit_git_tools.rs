@@ -0,0 +1,269 @@
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{
+    register_git_tools, GitCommitTool, GitDiffTool, GitLogTool, GitShowTool, GitStatusTool, Tool,
+    ToolContext, ToolRegistry,
+};
+use serde_json::json;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use tempfile::{tempdir, TempDir};
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+/// A repository with one commit containing `tracked.txt` and `sub/inner.txt`.
+fn repo() -> (TempDir, ToolContext) {
+    let temp = tempdir().unwrap();
+    let root = temp.path().join("repo");
+    std::fs::create_dir_all(root.join("sub")).unwrap();
+    git(&root, &["init", "--quiet", "--initial-branch=main"]);
+    git(&root, &["config", "user.name", "Test User"]);
+    git(&root, &["config", "user.email", "test@example.com"]);
+    std::fs::write(root.join("tracked.txt"), "one\n").unwrap();
+    std::fs::write(root.join("sub/inner.txt"), "inner\n").unwrap();
+    git(&root, &["add", "."]);
+    git(&root, &["commit", "--quiet", "-m", "initial commit"]);
+
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    (temp, ToolContext::new(root, memory))
+}
+
+#[tokio::test]
+async fn git_status_groups_changes() {
+    let (_temp, context) = repo();
+    let clean = GitStatusTool.execute(json!({}), &context).await.unwrap();
+    assert!(clean.output.starts_with("branch: main"), "{}", clean.output);
+    assert!(clean.output.contains("working tree clean"));
+
+    std::fs::write(context.root_dir.join("tracked.txt"), "two\n").unwrap();
+    std::fs::write(context.root_dir.join("staged.txt"), "new\n").unwrap();
+    std::fs::write(context.root_dir.join("untracked.txt"), "?\n").unwrap();
+    git(&context.root_dir, &["add", "staged.txt"]);
+
+    let status = GitStatusTool.execute(json!({}), &context).await.unwrap();
+    assert!(!status.is_error);
+    assert!(
+        status
+            .output
+            .contains("staged (1):\n  added:    staged.txt"),
+        "{}",
+        status.output
+    );
+    assert!(status
+        .output
+        .contains("unstaged (1):\n  modified: tracked.txt"));
+    assert!(status.output.contains("untracked (1):\n  untracked.txt"));
+}
+
+#[tokio::test]
+async fn git_diff_covers_worktree_staged_and_pathspecs() {
+    let (_temp, context) = repo();
+    let none = GitDiffTool.execute(json!({}), &context).await.unwrap();
+    assert_eq!(none.output, "no changes");
+
+    std::fs::write(context.root_dir.join("tracked.txt"), "two\n").unwrap();
+    std::fs::write(context.root_dir.join("sub/inner.txt"), "changed\n").unwrap();
+    git(&context.root_dir, &["add", "sub/inner.txt"]);
+
+    let worktree = GitDiffTool.execute(json!({}), &context).await.unwrap();
+    assert!(
+        worktree.output.contains("tracked.txt | 2 +-"),
+        "{}",
+        worktree.output
+    );
+    assert!(worktree.output.contains("-one\n+two"));
+    assert!(!worktree.output.contains("inner.txt"));
+
+    let staged = GitDiffTool
+        .execute(json!({"staged": true}), &context)
+        .await
+        .unwrap();
+    assert!(staged.output.contains("+changed"));
+    assert!(!staged.output.contains("tracked.txt"));
+
+    let against_head = GitDiffTool
+        .execute(json!({"base": "HEAD", "paths": ["sub"]}), &context)
+        .await
+        .unwrap();
+    assert!(against_head.output.contains("sub/inner.txt"));
+    assert!(!against_head.output.contains("tracked.txt"));
+}
+
+#[tokio::test]
+async fn git_log_and_show_describe_commits() {
+    let (_temp, context) = repo();
+    std::fs::write(context.root_dir.join("tracked.txt"), "two\n").unwrap();
+    git(
+        &context.root_dir,
+        &["commit", "--quiet", "-am", "update tracked"],
+    );
+
+    let log = GitLogTool.execute(json!({}), &context).await.unwrap();
+    let lines: Vec<&str> = log.output.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", log.output);
+    assert!(lines[0].ends_with("Test User\tupdate tracked"));
+    assert!(lines[1].ends_with("initial commit"));
+
+    let limited = GitLogTool
+        .execute(json!({"limit": 1, "paths": ["sub"]}), &context)
+        .await
+        .unwrap();
+    assert!(limited.output.trim_end().ends_with("initial commit"));
+    assert_eq!(limited.output.lines().count(), 1);
+
+    let show = GitShowTool.execute(json!({}), &context).await.unwrap();
+    assert!(show.output.contains("Author: Test User <test@example.com>"));
+    assert!(show.output.contains("update tracked"));
+    assert!(show.output.contains("-one\n+two"));
+
+    let stat = GitShowTool
+        .execute(json!({"rev": "HEAD~1", "stat_only": true}), &context)
+        .await
+        .unwrap();
+    assert!(stat.output.contains("initial commit"));
+    assert!(stat.output.contains("sub/inner.txt"));
+    assert!(!stat.output.contains("+inner"));
+}
+
+#[tokio::test]
+async fn git_tools_confine_paths_and_revisions() {
+    let (temp, context) = repo();
+    std::fs::write(temp.path().join("outside.txt"), "outside\n").unwrap();
+
+    for paths in [
+        json!(["../outside.txt"]),
+        json!(["sub/../../outside.txt"]),
+        json!([temp.path().join("outside.txt").to_string_lossy()]),
+        json!([":(top)"]),
+        json!(["--output=leak"]),
+    ] {
+        let error = GitDiffTool
+            .execute(json!({"paths": paths.clone()}), &context)
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("path escapes working directory")
+                || error.to_string().contains("invalid git path"),
+            "{paths}: {error}"
+        );
+    }
+
+    let error = GitShowTool
+        .execute(json!({"rev": "--output=/tmp/leak"}), &context)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("not a valid revision"));
+}
+
+#[tokio::test]
+async fn git_tools_in_a_subdirectory_only_see_that_subdirectory() {
+    let (_temp, repo_context) = repo();
+    std::fs::write(repo_context.root_dir.join("tracked.txt"), "two\n").unwrap();
+    std::fs::write(repo_context.root_dir.join("sub/inner.txt"), "changed\n").unwrap();
+    let context = ToolContext::new(
+        repo_context.root_dir.join("sub"),
+        repo_context.memory.clone(),
+    );
+
+    let status = GitStatusTool.execute(json!({}), &context).await.unwrap();
+    assert!(
+        status.output.contains("modified: inner.txt"),
+        "{}",
+        status.output
+    );
+    assert!(!status.output.contains("tracked.txt"));
+
+    let diff = GitDiffTool.execute(json!({}), &context).await.unwrap();
+    assert!(diff.output.contains("inner.txt"));
+    assert!(!diff.output.contains("tracked.txt"));
+
+    let error = GitDiffTool
+        .execute(json!({"paths": ["../tracked.txt"]}), &context)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("path escapes working directory"));
+}
+
+#[tokio::test]
+async fn git_commit_stages_requested_paths() {
+    let (_temp, context) = repo();
+    let nothing = GitCommitTool
+        .execute(json!({"message": "empty"}), &context)
+        .await
+        .unwrap();
+    assert!(nothing.is_error);
+    assert_eq!(nothing.output, "nothing staged to commit");
+
+    std::fs::write(context.root_dir.join("tracked.txt"), "two\n").unwrap();
+    std::fs::write(context.root_dir.join("other.txt"), "other\n").unwrap();
+    let commit = GitCommitTool
+        .execute(
+            json!({"message": "update tracked", "paths": ["tracked.txt"]}),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert!(!commit.is_error);
+    assert!(
+        commit.output.contains("update tracked"),
+        "{}",
+        commit.output
+    );
+
+    let status = GitStatusTool.execute(json!({}), &context).await.unwrap();
+    assert!(!status.output.contains("tracked.txt"), "{}", status.output);
+    assert!(status.output.contains("untracked (1):\n  other.txt"));
+
+    GitCommitTool
+        .execute(json!({"message": "add rest", "all": true}), &context)
+        .await
+        .unwrap();
+    let clean = GitStatusTool.execute(json!({}), &context).await.unwrap();
+    assert!(clean.output.contains("working tree clean"));
+
+    let error = GitCommitTool
+        .execute(json!({"message": "  "}), &context)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("git_commit.message is required"));
+}
+
+#[tokio::test]
+async fn git_commit_is_opt_in_and_errors_outside_a_repository() {
+    let names = |allow_commit| {
+        let mut registry = ToolRegistry::new();
+        register_git_tools(&mut registry, allow_commit);
+        let mut names: Vec<String> = registry.specs().into_iter().map(|spec| spec.name).collect();
+        names.sort();
+        names
+    };
+    assert!(!names(false).contains(&"git_commit".to_string()));
+    assert_eq!(names(false).len(), 4);
+    assert!(names(true).contains(&"git_commit".to_string()));
+
+    let temp = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let context = ToolContext::new(temp.path().to_path_buf(), memory);
+    let error = GitStatusTool
+        .execute(json!({}), &context)
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("not a git repository"),
+        "{error}"
+    );
+}
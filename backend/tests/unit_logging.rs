@@ -49,6 +49,7 @@ fn init_logging_writes_to_workspace_log_file() {
             llm: AgentLlmConfig::default(),
             channels: AgentChannelsConfig::default(),
             secrets: AgentSecretsConfig::default(),
+            ..AgentFileConfig::default()
         },
         EnvSecrets::default(),
         workspace_base.clone(),
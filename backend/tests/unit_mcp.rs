@@ -0,0 +1,340 @@
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use chaos_bot_backend::infrastructure::mcp::{
+    namespaced_tool_name, register_mcp_tools, McpClient, McpServerSpec, McpTransportSpec,
+};
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{ToolContext, ToolRegistry};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::{tempdir, TempDir};
+use tokio::net::TcpListener;
+
+/// When set, the `mcp_stub_server` test turns this test binary into a stdio MCP server.
+const STUB_ENV: &str = "CHAOS_BOT_MCP_STUB";
+
+fn stub_response(message: &Value) -> Value {
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    match message["method"].as_str().unwrap_or_default() {
+        "initialize" => json!({
+            "result": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "stub", "version": "0.0.0"}
+            }
+        }),
+        // Two pages, to exercise cursor handling.
+        "tools/list" if params.get("cursor").is_none() => json!({
+            "result": {
+                "tools": [
+                    {"name": "echo", "description": "Echo text", "inputSchema": {
+                        "type": "object", "properties": {"text": {"type": "string"}}
+                    }},
+                    {"name": "add", "inputSchema": {
+                        "type": "object", "properties": {"a": {"type": "number"}, "b": {"type": "number"}}
+                    }}
+                ],
+                "nextCursor": "page-2"
+            }
+        }),
+        "tools/list" => json!({
+            "result": {"tools": [{"name": "fail"}, {"name": "crash"}]}
+        }),
+        "tools/call" => {
+            let args = &params["arguments"];
+            match params["name"].as_str().unwrap_or_default() {
+                "echo" => json!({"result": {"content": [{"type": "text", "text": args["text"]}]}}),
+                "add" => {
+                    let sum = args["a"].as_f64().unwrap_or(0.0) + args["b"].as_f64().unwrap_or(0.0);
+                    json!({"result": {"content": [{"type": "text", "text": sum.to_string()}]}})
+                }
+                "fail" => json!({
+                    "result": {"content": [{"type": "text", "text": "boom"}], "isError": true}
+                }),
+                "crash" => std::process::exit(3),
+                other => {
+                    json!({"error": {"code": -32602, "message": format!("unknown tool {other}")}})
+                }
+            }
+        }
+        other => {
+            json!({"error": {"code": -32601, "message": format!("method not found: {other}")}})
+        }
+    }
+}
+
+#[test]
+fn mcp_stub_server() {
+    if std::env::var_os(STUB_ENV).is_none() {
+        return;
+    }
+    let mut stdout = std::io::stdout();
+    for line in std::io::stdin().lock().lines() {
+        let Ok(message) = serde_json::from_str::<Value>(&line.unwrap()) else {
+            continue;
+        };
+        let Some(id) = message.get("id").cloned() else {
+            continue;
+        };
+        let mut reply = stub_response(&message);
+        reply["jsonrpc"] = json!("2.0");
+        reply["id"] = id;
+        writeln!(stdout, "{reply}").unwrap();
+        stdout.flush().unwrap();
+    }
+    std::process::exit(0);
+}
+
+/// Re-runs this test binary as the stub server. libtest still prints a banner line, which
+/// the client skips as non-JSON output.
+fn stdio_spec(name: &str) -> McpServerSpec {
+    McpServerSpec {
+        name: name.to_string(),
+        transport: McpTransportSpec::Stdio {
+            command: std::env::current_exe()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+            args: [
+                "mcp_stub_server",
+                "--exact",
+                "--nocapture",
+                "--quiet",
+                "--test-threads=1",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+            env: HashMap::from([(STUB_ENV.to_string(), "1".to_string())]),
+        },
+        timeout: Duration::from_secs(10),
+    }
+}
+
+fn context() -> (TempDir, ToolContext) {
+    let temp = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let ctx = ToolContext::new(temp.path().to_path_buf(), memory);
+    (temp, ctx)
+}
+
+#[tokio::test]
+async fn stdio_server_tools_are_registered_and_callable() {
+    let mut registry = ToolRegistry::new();
+    let registered = register_mcp_tools(&mut registry, &[stdio_spec("stub")]).await;
+    assert_eq!(registered, 4);
+
+    let mut names: Vec<String> = registry.specs().into_iter().map(|spec| spec.name).collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "mcp__stub__add",
+            "mcp__stub__crash",
+            "mcp__stub__echo",
+            "mcp__stub__fail"
+        ]
+    );
+    let echo_spec = registry
+        .specs()
+        .into_iter()
+        .find(|spec| spec.name == "mcp__stub__echo")
+        .unwrap();
+    assert_eq!(echo_spec.description, "Echo text");
+    assert_eq!(
+        echo_spec.parameters_schema["properties"]["text"]["type"],
+        "string"
+    );
+
+    let (_temp, ctx) = context();
+    let echo = registry
+        .dispatch("tc_1", "mcp__stub__echo", json!({"text": "hello"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(echo.output, "hello");
+    assert!(!echo.is_error);
+
+    let sum = registry
+        .dispatch("tc_2", "mcp__stub__add", json!({"a": 2, "b": 3}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(sum.output, "5");
+
+    let failed = registry
+        .dispatch("tc_3", "mcp__stub__fail", json!({}), &ctx)
+        .await
+        .unwrap();
+    assert!(failed.is_error);
+    assert_eq!(failed.output, "boom");
+}
+
+#[tokio::test]
+async fn crashed_stdio_server_is_restarted_on_next_call() {
+    let client = McpClient::new(stdio_spec("stub"));
+    assert_eq!(client.list_tools().await.unwrap().len(), 4);
+
+    let crashed = client.call_tool("crash", json!({})).await;
+    assert!(crashed.is_err());
+
+    let echoed = client
+        .call_tool("echo", json!({"text": "back again"}))
+        .await
+        .unwrap();
+    assert_eq!(echoed.output, "back again");
+    assert_eq!(client.restart_count(), 1);
+}
+
+#[tokio::test]
+async fn unavailable_servers_are_skipped() {
+    let broken = McpServerSpec {
+        name: "broken".to_string(),
+        transport: McpTransportSpec::Stdio {
+            command: "/nonexistent/mcp-server".to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+        },
+        timeout: Duration::from_secs(1),
+    };
+
+    let mut registry = ToolRegistry::new();
+    let registered = register_mcp_tools(&mut registry, &[broken, stdio_spec("ok")]).await;
+    assert_eq!(registered, 4);
+    assert!(registry
+        .specs()
+        .iter()
+        .all(|spec| spec.name.starts_with("mcp__ok__")));
+}
+
+#[tokio::test]
+async fn colliding_tool_names_are_skipped() {
+    let mut registry = ToolRegistry::new();
+    registry.register_coding_tools();
+    let registered = register_mcp_tools(&mut registry, &[stdio_spec("ok"), stdio_spec("ok")]).await;
+    assert_eq!(registered, 4);
+    assert_eq!(registry.specs().len(), 8);
+
+    let (_temp, ctx) = context();
+    let echoed = registry
+        .dispatch("tc_1", "mcp__ok__echo", json!({"text": "first"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(echoed.output, "first");
+}
+
+/// `(method, mcp-session-id header)` for every request the HTTP stub received.
+type SeenRequests = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+#[derive(Clone, Default)]
+struct HttpStubState {
+    session_headers: SeenRequests,
+}
+
+async fn http_stub(
+    axum::extract::State(state): axum::extract::State<HttpStubState>,
+    headers: HeaderMap,
+    Json(message): Json<Value>,
+) -> Response {
+    let method = message["method"].as_str().unwrap_or_default().to_string();
+    state.session_headers.lock().unwrap().push((
+        method.clone(),
+        headers
+            .get("mcp-session-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    ));
+
+    let Some(id) = message.get("id").cloned() else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+    let mut reply = stub_response(&message);
+    reply["jsonrpc"] = json!("2.0");
+    reply["id"] = id;
+
+    match method.as_str() {
+        "initialize" => ([("mcp-session-id", "session-1")], Json(reply)).into_response(),
+        // Tool calls answer as an SSE stream with a progress notification first.
+        "tools/call" => (
+            [("content-type", "text/event-stream")],
+            format!(
+                "event: message\ndata: {}\n\nevent: message\ndata: {reply}\n\n",
+                json!({"jsonrpc": "2.0", "method": "notifications/progress", "params": {}})
+            ),
+        )
+            .into_response(),
+        _ => Json(reply).into_response(),
+    }
+}
+
+#[tokio::test]
+async fn http_server_handles_json_and_sse_responses_with_session() {
+    let state = HttpStubState::default();
+    let app = Router::new()
+        .route("/mcp", post(http_stub))
+        .with_state(state.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let spec = McpServerSpec {
+        name: "remote".to_string(),
+        transport: McpTransportSpec::Http {
+            url: format!("http://{addr}/mcp"),
+            headers: HashMap::new(),
+        },
+        timeout: Duration::from_secs(5),
+    };
+    let mut registry = ToolRegistry::new();
+    assert_eq!(register_mcp_tools(&mut registry, &[spec]).await, 4);
+
+    let (_temp, ctx) = context();
+    let echo = registry
+        .dispatch(
+            "tc_1",
+            "mcp__remote__echo",
+            json!({"text": "over http"}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(echo.output, "over http");
+
+    let seen = state.session_headers.lock().unwrap().clone();
+    assert_eq!(seen[0], ("initialize".to_string(), None));
+    assert!(seen[1..]
+        .iter()
+        .all(|(_, session)| session.as_deref() == Some("session-1")));
+    assert!(seen
+        .iter()
+        .any(|(method, _)| method == "notifications/initialized"));
+}
+
+#[test]
+fn tool_names_are_namespaced_and_sanitized() {
+    assert_eq!(
+        namespaced_tool_name("github", "list_issues"),
+        "mcp__github__list_issues"
+    );
+    let sanitized = namespaced_tool_name("my server", "a.b/c");
+    assert!(
+        sanitized.starts_with("mcp__my_server__a_b_c_"),
+        "{sanitized}"
+    );
+    assert_ne!(sanitized, namespaced_tool_name("my_server", "a_b_c"));
+    assert_ne!(sanitized, namespaced_tool_name("my server", "a/b.c"));
+
+    let long = namespaced_tool_name("s", &"x".repeat(100));
+    assert_eq!(long.len(), 64);
+    let longer = namespaced_tool_name("s", &"x".repeat(101));
+    assert_eq!(longer.len(), 64);
+    assert_ne!(long, longer);
+}
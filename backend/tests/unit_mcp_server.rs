@@ -0,0 +1,345 @@
+mod support;
+
+use chaos_bot_backend::domain::memory::MemoryScoping;
+use chaos_bot_backend::domain::tools::{ToolPolicy, ToolSelection};
+use chaos_bot_backend::infrastructure::model::LlmStreamEvent;
+use chaos_bot_backend::infrastructure::tooling::{wasm_engine, ToolRegistry, WasmPluginTool};
+use chaos_bot_backend::interface::mcp::McpServer;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use support::*;
+use tempfile::TempDir;
+
+fn reply(text: &str) -> Vec<LlmStreamEvent> {
+    vec![
+        LlmStreamEvent {
+            delta: text.to_string(),
+            tool_call: None,
+            done: false,
+            usage: None,
+        },
+        LlmStreamEvent {
+            delta: String::new(),
+            tool_call: None,
+            done: true,
+            usage: None,
+        },
+    ]
+}
+
+fn build_server(provider: Arc<MockStreamProvider>) -> (TempDir, McpServer) {
+    let mut registry = ToolRegistry::new();
+    registry.register(MockTool::fixed("echo", "echoed"));
+    let (temp, agent) = build_test_agent_with_registry(provider, registry);
+    (temp, McpServer::new(Arc::new(agent)))
+}
+
+fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+}
+
+async fn call(server: &McpServer, name: &str, arguments: Value) -> Value {
+    let response = server
+        .handle(request(
+            1,
+            "tools/call",
+            json!({"name": name, "arguments": arguments}),
+        ))
+        .await
+        .expect("response");
+    response["result"].clone()
+}
+
+#[tokio::test]
+async fn initialize_and_notifications() {
+    let (_temp, server) = build_server(Arc::new(MockStreamProvider::new(vec![])));
+
+    let response = server
+        .handle(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2024-11-05", "capabilities": {}}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+    assert_eq!(response["result"]["serverInfo"]["name"], "chaos-bot");
+    assert!(response["result"]["capabilities"]["tools"].is_object());
+
+    let notification = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+    assert!(server.handle(notification).await.is_none());
+
+    let ping = server.handle(request(2, "ping", json!({}))).await.unwrap();
+    assert_eq!(ping["result"], json!({}));
+}
+
+#[tokio::test]
+async fn tools_list_includes_chat_memory_and_registry_tools() {
+    let (_temp, server) = build_server(Arc::new(MockStreamProvider::new(vec![])));
+
+    let response = server
+        .handle(request(1, "tools/list", json!({})))
+        .await
+        .unwrap();
+    let names: Vec<&str> = response["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(names[0], "chat");
+    for expected in [
+        "memory_search",
+        "memory_get",
+        "memory_read_curated",
+        "memory_write_curated",
+        "echo",
+    ] {
+        assert!(names.contains(&expected), "missing {expected}: {names:?}");
+    }
+    let tool = &response["result"]["tools"][0];
+    assert_eq!(tool["inputSchema"]["required"], json!(["message"]));
+}
+
+#[tokio::test]
+async fn memory_tools_write_and_read_curated_memory() {
+    let (_temp, server) = build_server(Arc::new(MockStreamProvider::new(vec![])));
+
+    let written = call(
+        &server,
+        "memory_write_curated",
+        json!({"content": "# Memory\n\nfavorite color: teal\n"}),
+    )
+    .await;
+    assert_eq!(written["isError"], false);
+
+    let read = call(&server, "memory_read_curated", json!({})).await;
+    assert!(read["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("favorite color: teal"));
+
+    let search = call(&server, "memory_search", json!({"keyword": "teal"})).await;
+    assert!(search["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("teal"));
+}
+
+#[tokio::test]
+async fn registry_tools_are_callable() {
+    let (_temp, server) = build_server(Arc::new(MockStreamProvider::new(vec![])));
+
+    let result = call(&server, "echo", json!({})).await;
+    assert_eq!(result["isError"], false);
+    assert_eq!(result["content"][0]["text"], "echoed");
+}
+
+#[tokio::test]
+async fn tool_policy_limits_listed_and_callable_tools() {
+    let mut registry = ToolRegistry::new();
+    registry.register(MockTool::fixed("echo", "echoed"));
+    let (_temp, agent) =
+        build_test_agent_with_registry(Arc::new(MockStreamProvider::new(vec![])), registry);
+    let policy = ToolPolicy {
+        default: ToolSelection::from_preset(Some("read_only"), None),
+        ..ToolPolicy::default()
+    };
+    let server = McpServer::new(Arc::new(agent.with_tool_policy(policy)));
+
+    let response = server
+        .handle(request(1, "tools/list", json!({})))
+        .await
+        .unwrap();
+    let names: Vec<&str> = response["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert!(
+        names.contains(&"chat") && names.contains(&"memory_search"),
+        "{names:?}"
+    );
+    assert!(!names.contains(&"memory_write_curated"), "{names:?}");
+    assert!(!names.contains(&"echo"), "{names:?}");
+
+    for name in ["memory_write_curated", "echo"] {
+        let response = server
+            .handle(request(
+                2,
+                "tools/call",
+                json!({"name": name, "arguments": {"content": "# Memory\n"}}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], -32602);
+        assert!(response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("tool not enabled"));
+    }
+}
+
+#[tokio::test]
+async fn direct_memory_calls_use_the_configured_scope() {
+    let (temp, agent) = build_test_agent_with_registry(
+        Arc::new(MockStreamProvider::new(vec![])),
+        ToolRegistry::new(),
+    );
+    let server = McpServer::new(Arc::new(agent.with_memory_scoping(MemoryScoping::Session)));
+
+    let written = call(
+        &server,
+        "memory_write_curated",
+        json!({"content": "# Memory\n\nscoped note\n"}),
+    )
+    .await;
+    assert_eq!(written["isError"], false);
+    let global = std::fs::read_to_string(temp.path().join("MEMORY.md")).unwrap_or_default();
+    assert!(!global.contains("scoped note"), "{global}");
+
+    let read = call(&server, "memory_read_curated", json!({})).await;
+    assert!(read["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("scoped note"));
+}
+
+#[tokio::test]
+async fn wasm_memory_search_uses_the_configured_scope() {
+    let plugin = tempfile::tempdir().unwrap();
+    std::fs::copy(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/wasm/memory_search.wasm"),
+        plugin.path().join("recall.wasm"),
+    )
+    .unwrap();
+    std::fs::write(
+        plugin.path().join("manifest.json"),
+        json!({"name": "recall", "description": "recall", "capabilities": {"memory_search": true}})
+            .to_string(),
+    )
+    .unwrap();
+    let mut registry = ToolRegistry::new();
+    registry.register(WasmPluginTool::load(&wasm_engine().unwrap(), plugin.path()).unwrap());
+    let (_temp, agent) =
+        build_test_agent_with_registry(Arc::new(MockStreamProvider::new(vec![])), registry);
+    let server = McpServer::new(Arc::new(agent.with_memory_scoping(MemoryScoping::Session)));
+
+    let written = call(
+        &server,
+        "memory_write_curated",
+        json!({"content": "# Memory\n\nscoped teal note\n"}),
+    )
+    .await;
+    assert_eq!(written["isError"], false);
+
+    let found = call(&server, "recall", json!({})).await;
+    assert_eq!(found["isError"], false, "{found}");
+    let hits = found["content"][0]["text"].as_str().unwrap();
+    assert!(hits.contains("scoped teal note"), "{hits}");
+}
+
+#[tokio::test]
+async fn chat_tool_runs_a_turn_and_continues_sessions() {
+    let provider = Arc::new(MockStreamProvider::new(vec![
+        reply("hello from chaos-bot"),
+        reply("second reply"),
+    ]));
+    let (_temp, server) = build_server(provider.clone());
+
+    let first = call(&server, "chat", json!({"message": "hi"})).await;
+    assert_eq!(first["isError"], false);
+    assert_eq!(first["content"][0]["text"], "hello from chaos-bot");
+    let session_id = first["structuredContent"]["session_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(first["content"][1]["text"]
+        .as_str()
+        .unwrap()
+        .contains(&session_id));
+
+    let second = call(
+        &server,
+        "chat",
+        json!({"message": "again", "session_id": session_id}),
+    )
+    .await;
+    assert_eq!(second["structuredContent"]["session_id"], session_id);
+    assert_eq!(second["content"][0]["text"], "second reply");
+
+    let captured = provider.captured.lock().unwrap();
+    let history: Vec<&str> = captured[1]
+        .messages
+        .iter()
+        .map(|message| message.content.as_str())
+        .collect();
+    assert!(history.contains(&"hi"));
+    assert!(history.contains(&"hello from chaos-bot"));
+}
+
+#[tokio::test]
+async fn errors_for_unknown_methods_tools_and_bad_arguments() {
+    let (_temp, server) = build_server(Arc::new(MockStreamProvider::new(vec![])));
+
+    let response = server
+        .handle(request(1, "resources/list", json!({})))
+        .await
+        .unwrap();
+    assert_eq!(response["error"]["code"], -32601);
+
+    let response = server
+        .handle(request(
+            2,
+            "tools/call",
+            json!({"name": "nope", "arguments": {}}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response["error"]["code"], -32602);
+
+    let response = server
+        .handle(request(
+            3,
+            "tools/call",
+            json!({"name": "chat", "arguments": {}}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response["error"]["code"], -32602);
+
+    let failed = call(&server, "memory_get", json!({})).await;
+    assert_eq!(failed["isError"], true);
+}
+
+#[tokio::test]
+async fn serve_answers_requests_line_by_line() {
+    let (_temp, server) = build_server(Arc::new(MockStreamProvider::new(vec![])));
+
+    let input = [
+        request(1, "initialize", json!({})).to_string(),
+        json!({"jsonrpc": "2.0", "method": "notifications/initialized"}).to_string(),
+        "not json".to_string(),
+        request(2, "tools/list", json!({})).to_string(),
+    ]
+    .join("\n");
+    let mut output = Vec::new();
+    server
+        .serve(input.as_bytes(), &mut output)
+        .await
+        .expect("serve");
+
+    let responses: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[1]["error"]["code"], -32700);
+    assert_eq!(responses[2]["id"], 2);
+}
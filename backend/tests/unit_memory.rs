@@ -1,4 +1,6 @@
+use chaos_bot_backend::domain::memory::{EditOrigin, MemoryEdit};
 use chaos_bot_backend::infrastructure::memory::MemoryStore;
+use chaos_bot_backend::infrastructure::memory_index::memory_terms;
 use tempfile::tempdir;
 
 fn make_store() -> (tempfile::TempDir, MemoryStore) {
@@ -179,3 +181,270 @@ async fn memory_dir_and_curated_file_accessors() {
     assert_eq!(store.memory_dir(), memory_dir);
     assert_eq!(store.curated_file(), curated_file);
 }
+
+// -------------------------------------------------------------------------
+// Ranked search and the memory index
+// -------------------------------------------------------------------------
+
+#[test]
+fn memory_terms_drop_stop_words_and_segment_cjk() {
+    assert_eq!(
+        memory_terms("What did we decide about the deployTarget?"),
+        vec!["decide", "deploy", "target", "deploytarget"]
+    );
+    let terms = memory_terms("我们决定使用PostgreSQL作为数据库");
+    assert!(terms.contains(&"数据库".to_string()), "{terms:?}");
+    assert!(terms.contains(&"postgresql".to_string()), "{terms:?}");
+    assert!(!terms.contains(&"我们".to_string()), "{terms:?}");
+}
+
+#[tokio::test]
+async fn search_ranks_multi_word_queries() {
+    let (_temp, store) = make_store();
+    store.ensure_layout().await.unwrap();
+    store
+        .write_curated(
+            "# Notes\n- The team prefers tabs\n- Deploy target is the staging cluster\n- Deploy window is Friday for the staging cluster in eu-west\n",
+        )
+        .await
+        .unwrap();
+    store
+        .append_daily_log("Talked about the deploy pipeline")
+        .await
+        .unwrap();
+
+    let hits = store
+        .search("What did we decide about the staging cluster deploy?")
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 3);
+    assert!(hits[0].snippet.contains("staging cluster"));
+    assert!(hits[1].snippet.contains("staging cluster"));
+    assert!(hits[2].snippet.contains("pipeline"));
+    assert!(hits[0].score >= hits[1].score && hits[1].score > hits[2].score);
+
+    assert!(store.search("what is it").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn search_matches_chinese_text() {
+    let (_temp, store) = make_store();
+    store.ensure_layout().await.unwrap();
+    store
+        .write_curated("- 用户喜欢喝绿茶\n- 项目使用PostgreSQL作为数据库\n")
+        .await
+        .unwrap();
+
+    let hits = store.search("我们用的是什么数据库？").await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].line, 2);
+}
+
+#[tokio::test]
+async fn search_index_persists_and_tracks_external_edits() {
+    let (temp, store) = make_store();
+    store.ensure_layout().await.unwrap();
+    store
+        .write_curated("- favourite colour is teal\n")
+        .await
+        .unwrap();
+    let log = store
+        .append_daily_log("booked flights to Lisbon")
+        .await
+        .unwrap();
+
+    let index_file = store.memory_dir().join(".index/memory-index.json");
+    assert!(index_file.exists());
+    let hits = store.search("lisbon").await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, log.to_string_lossy());
+
+    // A fresh store loads the persisted index and still notices edits made outside it.
+    std::fs::write(&log, "- cancelled the Lisbon trip, going to Porto\n").unwrap();
+    let reopened = MemoryStore::new(temp.path().join("memory"), temp.path().join("MEMORY.md"));
+    let hits = reopened.search("porto").await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].snippet.contains("Porto"));
+
+    std::fs::remove_file(&log).unwrap();
+    assert!(reopened.search("lisbon").await.unwrap().is_empty());
+    assert_eq!(reopened.search("teal").await.unwrap().len(), 1);
+}
+
+fn remember(section: &str, fact: &str) -> MemoryEdit {
+    MemoryEdit::Remember {
+        section: section.to_string(),
+        fact: fact.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn edit_curated_touches_only_the_target_section() {
+    let (_temp, store) = make_store();
+    store
+        .write_curated(
+            "# Long-Term Memory\n\nNotes kept by hand.\n\n## People\n- Ana runs ops\n### Contacts\n- ana@example.com\n\n## Projects\n- chaos-bot\n",
+        )
+        .await
+        .unwrap();
+
+    store
+        .edit_curated(
+            remember("projects", "- migrating to Postgres"),
+            EditOrigin::default(),
+        )
+        .await
+        .unwrap();
+    store
+        .edit_curated(
+            MemoryEdit::Forget {
+                section: "## People".to_string(),
+                fact: Some("ANA@".to_string()),
+                expected: None,
+            },
+            EditOrigin::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        store.read_curated().await.unwrap(),
+        "# Long-Term Memory\n\nNotes kept by hand.\n\n## People\n- Ana runs ops\n### Contacts\n\n## Projects\n- chaos-bot\n- migrating to Postgres\n"
+    );
+    let hits = store.search("postgres").await.unwrap();
+    assert_eq!(hits.len(), 1);
+}
+
+#[tokio::test]
+async fn edit_curated_enforces_size_limit_but_allows_shrinking() {
+    let temp = tempdir().unwrap();
+    let store = MemoryStore::new(temp.path().join("memory"), temp.path().join("MEMORY.md"))
+        .with_max_curated_bytes(64);
+    store
+        .write_curated(&format!("## Notes\n- {}\n", "x".repeat(80)))
+        .await
+        .unwrap();
+
+    let error = store
+        .edit_curated(remember("Notes", "one more"), EditOrigin::default())
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("byte limit"), "{error}");
+
+    let change = store
+        .edit_curated(
+            MemoryEdit::Update {
+                section: "Notes".to_string(),
+                content: "- short".to_string(),
+                expected: None,
+            },
+            EditOrigin::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(change.after.as_deref(), Some("- short"));
+    assert_eq!(store.changes(10).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn concurrent_edits_of_different_sections_are_all_kept() {
+    let (_temp, store) = make_store();
+    store.ensure_layout().await.unwrap();
+    let edits = (0..8).map(|index| {
+        let store = store.clone();
+        tokio::spawn(async move {
+            store
+                .edit_curated(
+                    remember(&format!("Section {}", index % 4), &format!("fact {index}")),
+                    EditOrigin::default(),
+                )
+                .await
+        })
+    });
+    for edit in futures::future::join_all(edits).await {
+        edit.unwrap().unwrap();
+    }
+
+    let content = store.read_curated().await.unwrap();
+    for index in 0..8 {
+        assert!(content.contains(&format!("- fact {index}\n")), "{content}");
+    }
+    assert_eq!(store.changes(3).await.unwrap().len(), 3);
+    // The audit log mentions every fact too, but is not searchable memory.
+    let hits = store.search("fact").await.unwrap();
+    assert!(
+        hits.iter().all(|hit| hit.path.ends_with("MEMORY.md")),
+        "{hits:?}"
+    );
+}
+
+#[tokio::test]
+async fn list_write_and_remove_memory_files() {
+    let (_temp, store) = make_store();
+    store
+        .write_file("2026-02-01.md", "- a day\n")
+        .await
+        .unwrap();
+    store
+        .write_file("notes/ideas.md", "- an idea\n")
+        .await
+        .unwrap();
+
+    let files = store.list_files().await.unwrap();
+    let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(paths, vec!["2026-02-01.md", "MEMORY.md", "notes/ideas.md"]);
+    assert_eq!(files[0].date, chrono::NaiveDate::from_ymd_opt(2026, 2, 1));
+    assert_eq!(files[0].bytes, 8);
+    assert!(files[2].date.is_none());
+
+    for invalid in [
+        "../escape.md",
+        "/etc/passwd",
+        ".index/memory-index.json",
+        "",
+    ] {
+        assert!(store.write_file(invalid, "x").await.is_err(), "{invalid}");
+        assert!(
+            store.remove_file(invalid, false).await.is_err(),
+            "{invalid}"
+        );
+    }
+    assert!(store.remove_file("MEMORY.md", false).await.is_err());
+
+    store.remove_file("notes/ideas.md", true).await.unwrap();
+    assert!(store.archive_dir().join("notes/ideas.md").exists());
+    assert!(store.search("idea").await.unwrap().is_empty());
+    store.remove_file("2026-02-01.md", false).await.unwrap();
+    assert!(store.remove_file("2026-02-01.md", false).await.is_err());
+    assert_eq!(store.list_files().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn get_file_rejects_paths_outside_the_memory_directory() {
+    let (temp, store) = make_store();
+    store.ensure_layout().await.unwrap();
+    std::fs::write(temp.path().join("secret.txt"), "top secret").unwrap();
+    let outside = temp.path().join("secret.txt");
+
+    for path in [
+        "../secret.txt",
+        outside.to_str().unwrap(),
+        ".index/memory-index.json",
+    ] {
+        let error = store.get_file(path, None, None).await.unwrap_err();
+        assert!(
+            error.to_string().contains("invalid memory path"),
+            "{path}: {error}"
+        );
+    }
+
+    // Search hits carry absolute paths of the store's own files, which stay readable.
+    let log = store.append_daily_log("visited the museum").await.unwrap();
+    let content = store
+        .get_file(log.to_str().unwrap(), None, None)
+        .await
+        .unwrap();
+    assert!(content.contains("museum"));
+    let curated = store.curated_file().to_str().unwrap().to_string();
+    assert!(store.get_file(&curated, Some(1), Some(1)).await.is_ok());
+}
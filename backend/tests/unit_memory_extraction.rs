@@ -0,0 +1,272 @@
+mod support;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chaos_bot_backend::application::MemoryExtractor;
+use chaos_bot_backend::domain::memory::{ExtractionTrigger, MemoryExtractionOptions, MemoryScope};
+use chaos_bot_backend::domain::types::{Message, SessionState};
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::model::{
+    LlmProvider, LlmRequest, LlmResponse, LlmStream, LlmStreamEvent,
+};
+use futures::stream;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use support::build_test_agent;
+use tempfile::tempdir;
+
+/// Streams `reply` for chat runs and answers extraction requests with `extraction`.
+struct ExtractionProvider {
+    reply: String,
+    extraction: String,
+    requests: Mutex<Vec<LlmRequest>>,
+}
+
+impl ExtractionProvider {
+    fn new(reply: &str, extraction: &str) -> Arc<Self> {
+        Arc::new(Self {
+            reply: reply.to_string(),
+            extraction: extraction.to_string(),
+            requests: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ExtractionProvider {
+    fn name(&self) -> &'static str {
+        "extraction"
+    }
+
+    async fn chat(&self, request: LlmRequest) -> Result<LlmResponse> {
+        self.requests.lock().unwrap().push(request);
+        Ok(LlmResponse {
+            message: Message::assistant(self.extraction.clone()),
+            tool_calls: Vec::new(),
+            usage: None,
+            finish_reason: Some("stop".to_string()),
+        })
+    }
+
+    async fn chat_stream(&self, _request: LlmRequest) -> Result<LlmStream> {
+        let events = vec![
+            Ok(LlmStreamEvent {
+                delta: self.reply.clone(),
+                tool_call: None,
+                done: false,
+                usage: None,
+            }),
+            Ok(LlmStreamEvent {
+                delta: String::new(),
+                tool_call: None,
+                done: true,
+                usage: None,
+            }),
+        ];
+        Ok(Box::pin(stream::iter(events)))
+    }
+}
+
+fn enabled() -> MemoryExtractionOptions {
+    MemoryExtractionOptions {
+        enabled: true,
+        ..MemoryExtractionOptions::default()
+    }
+}
+
+fn conversation() -> Vec<Message> {
+    vec![
+        Message::user("I'm vegetarian, and we deploy on Fridays"),
+        Message::assistant("Noted!"),
+    ]
+}
+
+#[tokio::test]
+async fn extract_saves_new_facts_once_and_skips_known_ones() {
+    let temp = tempdir().unwrap();
+    let store = MemoryStore::new(temp.path().join("memory"), temp.path().join("MEMORY.md"));
+    store
+        .write_curated("# Long-Term Memory\n\n## Work\n- We deploy on Fridays\n")
+        .await
+        .unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(store.clone());
+    let provider = ExtractionProvider::new(
+        "",
+        "```json\n{\"facts\": [\n  {\"section\": \"Preferences\", \"fact\": \"User is vegetarian\"},\n  {\"section\": \"Work\", \"fact\": \"we deploy on fridays\"}\n]}\n```",
+    );
+    let extractor = MemoryExtractor::new(provider.clone(), memory, "chat-model", enabled());
+
+    let changes = extractor
+        .extract("s1", &MemoryScope::Global, &conversation())
+        .await
+        .unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].section, "Preferences");
+    assert_eq!(changes[0].session_id.as_deref(), Some("s1"));
+    assert!(store
+        .read_curated()
+        .await
+        .unwrap()
+        .contains("## Preferences\n- User is vegetarian"));
+
+    let requests = provider.requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].model, "chat-model");
+    assert!(requests[0].messages[1]
+        .content
+        .contains("- We deploy on Fridays"));
+    assert!(requests[0].messages[1]
+        .content
+        .contains("User: I'm vegetarian, and we deploy on Fridays"));
+
+    // Nothing new since the last extraction: no model call.
+    assert!(extractor
+        .extract("s1", &MemoryScope::Global, &conversation())
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(provider.requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn failed_extraction_is_retried_with_the_same_turns() {
+    let temp = tempdir().unwrap();
+    let store = MemoryStore::new(temp.path().join("memory"), temp.path().join("MEMORY.md"));
+    let memory: Arc<dyn MemoryBackend> = Arc::new(store);
+    let provider = ExtractionProvider::new("", "Sorry, I can't help with that.");
+    let extractor = MemoryExtractor::new(provider.clone(), memory, "chat-model", enabled());
+
+    for _ in 0..2 {
+        assert!(extractor
+            .extract("s1", &MemoryScope::Global, &conversation())
+            .await
+            .is_err());
+    }
+    let requests = provider.requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].messages[1]
+        .content
+        .contains("User: I'm vegetarian, and we deploy on Fridays"));
+    assert_eq!(extractor.tracked_sessions(), 1);
+}
+
+#[tokio::test]
+async fn extract_waits_for_min_turns_and_uses_configured_model() {
+    let (_temp, store) = support::temp_memory_store();
+    let provider = ExtractionProvider::new("", "{\"facts\": []}");
+    let extractor = MemoryExtractor::new(
+        provider.clone(),
+        Arc::new(store),
+        "chat-model",
+        MemoryExtractionOptions {
+            min_turns: 2,
+            model: Some("small-model".to_string()),
+            ..enabled()
+        },
+    );
+
+    let mut messages = conversation();
+    assert!(extractor
+        .extract("s1", &MemoryScope::Global, &messages)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(provider.requests.lock().unwrap().is_empty());
+
+    messages.push(Message::user("also, call me Sam"));
+    extractor
+        .extract("s1", &MemoryScope::Global, &messages)
+        .await
+        .unwrap();
+    let requests = provider.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].model, "small-model");
+    assert!(requests[0].messages[1].content.contains("vegetarian"));
+}
+
+#[test]
+fn extraction_options_filter_channels() {
+    let mut options = enabled();
+    assert!(options.applies_to(None));
+    options.channels = Some(["telegram".to_string()].into_iter().collect());
+    assert!(options.applies_to(Some("telegram")));
+    assert!(!options.applies_to(None));
+    options.enabled = false;
+    assert!(!options.applies_to(Some("telegram")));
+}
+
+#[tokio::test]
+async fn agent_schedules_extraction_after_runs() {
+    let provider = ExtractionProvider::new(
+        "Enjoy your trip!",
+        "{\"facts\": [{\"section\": \"Travel\", \"fact\": \"Going to Lisbon in May\"}]}",
+    );
+    let (_temp, agent) = build_test_agent(provider.clone());
+    let extractor = Arc::new(MemoryExtractor::new(
+        provider.clone(),
+        agent.memory(),
+        "chat-model",
+        MemoryExtractionOptions {
+            trigger: ExtractionTrigger::Idle,
+            idle: Duration::from_millis(50),
+            ..enabled()
+        },
+    ));
+    let memory = agent.memory();
+    let agent = agent.with_memory_extractor(extractor.clone());
+
+    let mut session = SessionState::new("s1");
+    agent
+        .run(&mut session, "I'm going to Lisbon in May".to_string())
+        .await
+        .unwrap();
+    agent
+        .run(&mut session, "Any tips?".to_string())
+        .await
+        .unwrap();
+
+    // The audit entry is written last, so once it exists the extraction is complete.
+    let mut changes = Vec::new();
+    for _ in 0..100 {
+        changes = memory.changes(10).await.unwrap();
+        if !changes.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].session_id.as_deref(), Some("s1"));
+    let saved = memory.read_curated().await.unwrap();
+    assert!(
+        saved.contains("## Travel\n- Going to Lisbon in May"),
+        "{saved}"
+    );
+    // The idle timer of the first run was superseded by the second one.
+    let requests = provider.requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].messages[1].content.contains("User: Any tips?"));
+
+    // Finished sessions are forgotten; the next run is extracted from where it starts.
+    for _ in 0..100 {
+        if extractor.tracked_sessions() == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(extractor.tracked_sessions(), 0);
+    agent
+        .run(&mut session, "Booked the flight".to_string())
+        .await
+        .unwrap();
+    for _ in 0..100 {
+        if provider.requests.lock().unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let requests = provider.requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 2);
+    let transcript = &requests[1].messages[1].content;
+    assert!(transcript.contains("User: Booked the flight"), "{transcript}");
+    assert!(!transcript.contains("User: I'm going to Lisbon"), "{transcript}");
+}
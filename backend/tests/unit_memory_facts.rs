@@ -0,0 +1,302 @@
+mod support;
+
+use chaos_bot_backend::application::agent::{AgentConfig, AgentLoop};
+use chaos_bot_backend::domain::memory::{EditOrigin, MemoryEdit, MemoryScope};
+use chaos_bot_backend::domain::ports::MemoryPort;
+use chaos_bot_backend::domain::types::{Role, SessionState};
+use chaos_bot_backend::infrastructure::memory_facts::{
+    FactImport, FactStore, FactStoreOptions, NewFact, LOG_FACT_CONFIDENCE,
+};
+use chaos_bot_backend::infrastructure::tooling::ToolRegistry;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use support::{temp_memory_store, MockPersonality, MockStreamProvider};
+use tempfile::TempDir;
+
+async fn temp_fact_store(options: FactStoreOptions) -> (TempDir, FactStore) {
+    let (temp, files) = temp_memory_store();
+    files.ensure_layout().await.unwrap();
+    let store = FactStore::open(temp.path().join("data/memory-facts.db"), files, options).unwrap();
+    (temp, store)
+}
+
+#[tokio::test]
+async fn import_carries_markdown_memory_over_once() {
+    let (temp, store) = temp_fact_store(FactStoreOptions {
+        import_logs: true,
+        ..FactStoreOptions::default()
+    })
+    .await;
+    std::fs::write(
+        temp.path().join("MEMORY.md"),
+        "# Long-Term Memory\n\nIntro text.\n\n## Preferences\n- Prefers tea over coffee\n- Uses vim\n\n## Team\n- Deploys happen on Thursday\n",
+    )
+    .unwrap();
+    std::fs::write(
+        temp.path().join("memory/2026-03-02.md"),
+        "- asked about the release\n",
+    )
+    .unwrap();
+
+    let report = store.import_once().await.unwrap();
+    assert_eq!(
+        report,
+        Some(FactImport {
+            imported: 4,
+            skipped: 0
+        })
+    );
+    assert_eq!(store.import_once().await.unwrap(), None);
+    assert_eq!(
+        store.import_markdown(true).await.unwrap(),
+        FactImport {
+            imported: 0,
+            skipped: 4
+        }
+    );
+
+    let facts = store.facts().await.unwrap();
+    let log = facts
+        .iter()
+        .find(|fact| fact.subject == "Log 2026-03-02")
+        .unwrap();
+    assert_eq!(log.content, "asked about the release");
+    assert_eq!(log.confidence, LOG_FACT_CONFIDENCE);
+    assert_eq!(
+        store.read_curated().await.unwrap(),
+        "# Long-Term Memory\n\n## Preferences\n- Prefers tea over coffee\n- Uses vim\n\n## Team\n- Deploys happen on Thursday\n\n## Log 2026-03-02\n- asked about the release\n"
+    );
+
+    let hits = store.search("tea").await.unwrap();
+    assert_eq!(hits[0].path, "MEMORY.md");
+    assert_eq!(hits[0].line, 4);
+    assert_eq!(hits[0].snippet, "Preferences: Prefers tea over coffee");
+    assert_eq!(
+        store.get_file("MEMORY.md", Some(4), Some(4)).await.unwrap(),
+        "- Prefers tea over coffee"
+    );
+}
+
+#[tokio::test]
+async fn curated_writes_and_edits_sync_fact_rows() {
+    let (_temp, store) = temp_fact_store(FactStoreOptions::default()).await;
+    store
+        .write_curated("## Preferences\n- Prefers tea\n- Uses vim\n")
+        .await
+        .unwrap();
+    let tea = store.facts().await.unwrap()[0].clone();
+
+    store
+        .write_curated("## preferences\n- Prefers tea\n- Uses helix\n\n## Team\n- Five people\n")
+        .await
+        .unwrap();
+    let facts = store.facts().await.unwrap();
+    assert_eq!(facts[0], tea, "unchanged facts keep their row");
+    assert_eq!(
+        facts
+            .iter()
+            .map(|fact| (fact.subject.as_str(), fact.content.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Preferences", "Prefers tea"),
+            ("Preferences", "Uses helix"),
+            ("Team", "Five people"),
+        ]
+    );
+
+    let origin = EditOrigin {
+        session_id: Some("s1".to_string()),
+        tool_call_id: Some("tc_1".to_string()),
+    };
+    let change = store
+        .edit_curated(
+            MemoryEdit::Remember {
+                section: "Team".to_string(),
+                fact: "Deploys on Thursday".to_string(),
+            },
+            origin.clone(),
+        )
+        .await
+        .unwrap();
+    assert!(change.changed);
+    assert_eq!(
+        change.after.as_deref(),
+        Some("- Five people\n- Deploys on Thursday")
+    );
+    let added = store.facts().await.unwrap().pop().unwrap();
+    assert_eq!(added.source_session.as_deref(), Some("s1"));
+
+    store
+        .edit_curated(
+            MemoryEdit::Forget {
+                section: "Preferences".to_string(),
+                fact: None,
+                expected: None,
+            },
+            origin,
+        )
+        .await
+        .unwrap();
+    assert!(!store.read_curated().await.unwrap().contains("Preferences"));
+    assert!(store.search("helix").await.unwrap().is_empty());
+    let changes = store.changes(10).await.unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[1].action, "forget");
+}
+
+#[tokio::test]
+async fn expired_facts_are_hidden_and_pruned() {
+    let (_temp, store) = temp_fact_store(FactStoreOptions::default()).await;
+    store
+        .add_fact(NewFact {
+            expires_at: Some(Utc::now() - Duration::minutes(1)),
+            ..NewFact::new("Plans", "Travelling this week")
+        })
+        .await
+        .unwrap();
+    let kept = store
+        .add_fact(NewFact {
+            expires_at: Some(Utc::now() + Duration::days(1)),
+            ..NewFact::new("Plans", "Out of office tomorrow")
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(store.facts().await.unwrap(), vec![kept]);
+    assert!(store.search("travelling").await.unwrap().is_empty());
+    assert_eq!(store.prune_expired().await.unwrap(), 1);
+    assert_eq!(store.prune_expired().await.unwrap(), 0);
+    assert!(store.add_fact(NewFact::new("Plans", "  ")).await.is_err());
+}
+
+#[tokio::test]
+async fn search_puts_fact_and_log_scores_on_one_scale() {
+    let (temp, store) = temp_fact_store(FactStoreOptions::default()).await;
+    store
+        .add_fact(NewFact::new("Preferences", "Prefers tea over coffee"))
+        .await
+        .unwrap();
+    store
+        .add_fact(NewFact {
+            confidence: 0.6,
+            ..NewFact::new("Preferences", "Tea in the afternoon, tea after dinner")
+        })
+        .await
+        .unwrap();
+    std::fs::write(
+        temp.path().join("memory/2026-03-02.md"),
+        "- brewed tea for the team\n- tea tasting on Friday, tea again on Monday\n",
+    )
+    .unwrap();
+
+    let hits = store.search("tea").await.unwrap();
+    let best = |facts: bool| {
+        hits.iter()
+            .filter(|hit| (hit.path == "MEMORY.md") == facts)
+            .map(|hit| hit.score)
+            .fold(0.0f32, f32::max)
+    };
+    assert_eq!(hits.len(), 4, "{hits:?}");
+    assert_eq!(best(true), 1.0);
+    assert_eq!(best(false), 1.0);
+    assert!(hits.iter().all(|hit| hit.score > 0.0 && hit.score <= 1.0));
+}
+
+#[tokio::test]
+async fn scoped_fact_stores_are_isolated_and_share_global_facts() {
+    let (_temp, store) = temp_fact_store(FactStoreOptions::default()).await;
+    let alice = store.scoped(&MemoryScope::User {
+        channel: "telegram".to_string(),
+        user_id: "1".to_string(),
+    });
+    store
+        .add_fact(NewFact::new("Team", "Deploys happen on Thursday"))
+        .await
+        .unwrap();
+    alice
+        .add_fact(NewFact::new("Preferences", "Prefers tea"))
+        .await
+        .unwrap();
+
+    assert!(!store.read_curated().await.unwrap().contains("tea"));
+    assert!(!alice.read_curated().await.unwrap().contains("Thursday"));
+    assert!(store.search("tea").await.unwrap().is_empty());
+    assert_eq!(alice.search("thursday").await.unwrap().len(), 1);
+
+    let prompt = alice.prompt_memory().await.unwrap().unwrap();
+    assert!(
+        prompt.contains("## Team\n- Deploys happen on Thursday"),
+        "{prompt}"
+    );
+    assert!(prompt.contains("## Preferences\n- Prefers tea"), "{prompt}");
+}
+
+#[tokio::test]
+async fn prompt_memory_keeps_the_most_confident_facts() {
+    let (_temp, store) = temp_fact_store(FactStoreOptions {
+        prompt_limit: 2,
+        ..FactStoreOptions::default()
+    })
+    .await;
+    assert_eq!(store.prompt_memory().await.unwrap(), None);
+    for (content, confidence) in [
+        ("Likes jazz", 0.6),
+        ("Hates spam", 0.2),
+        ("Is a pilot", 0.9),
+        ("Lives in Oslo", 0.8),
+    ] {
+        store
+            .add_fact(NewFact {
+                confidence,
+                ..NewFact::new("Profile", content)
+            })
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        store.prompt_memory().await.unwrap().as_deref(),
+        Some("## Profile\n- Is a pilot\n- Lives in Oslo")
+    );
+}
+
+#[tokio::test]
+async fn agent_puts_fact_memory_in_the_system_prompt() {
+    let (temp, store) = temp_fact_store(FactStoreOptions::default()).await;
+    store
+        .add_fact(NewFact::new("Preferences", "Answers in French"))
+        .await
+        .unwrap();
+    let provider = Arc::new(MockStreamProvider::text("Bonjour"));
+    let memory: Arc<dyn MemoryPort> = Arc::new(store);
+    let agent = AgentLoop::new(
+        provider.clone(),
+        Arc::new(ToolRegistry::new()),
+        Arc::new(MockPersonality::new("You are a test bot.")),
+        memory,
+        AgentConfig {
+            model: "mock-model".to_string(),
+            temperature: 0.0,
+            max_tokens: 128,
+            max_iterations: 2,
+            token_budget: 4096,
+            working_dir: temp.path().to_path_buf(),
+        },
+    );
+
+    let mut session = SessionState::new("s1");
+    agent.run(&mut session, "hello".to_string()).await.unwrap();
+
+    let requests = provider.captured.lock().unwrap();
+    let system = requests[0]
+        .messages
+        .iter()
+        .find(|message| message.role == Role::System)
+        .unwrap();
+    assert!(system.content.starts_with(
+        "You are a test bot.\n\n# Curated Memory\n## Preferences\n- Answers in French"
+    ));
+    assert_eq!(
+        AgentLoop::with_curated_memory("prompt", None),
+        "prompt".to_string()
+    );
+}
@@ -0,0 +1,228 @@
+mod support;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use chaos_bot_backend::application::MemoryRollup;
+use chaos_bot_backend::domain::memory::{RetentionMode, RollupOptions, RollupPeriod};
+use chaos_bot_backend::domain::types::Message;
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::model::{LlmProvider, LlmRequest, LlmResponse, LlmStream};
+use chaos_bot_backend::interface::api::{router, AppState};
+use chrono::NaiveDate;
+use serde_json::Value;
+use std::sync::Arc;
+use support::{build_test_agent, temp_memory_store, MockStreamProvider};
+use tower::util::ServiceExt;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn options(period: RollupPeriod, retention: RetentionMode) -> RollupOptions {
+    RollupOptions {
+        period,
+        retention,
+        ..RollupOptions::default()
+    }
+}
+
+async fn write_logs(store: &MemoryStore, days: &[&str]) {
+    store.ensure_layout().await.unwrap();
+    for day in days {
+        store
+            .write_file(&format!("{day}.md"), &format!("- notes from {day}\n"))
+            .await
+            .unwrap();
+    }
+}
+
+/// Summarises every request into a fixed bullet list.
+struct Summarizer;
+
+#[async_trait]
+impl LlmProvider for Summarizer {
+    fn name(&self) -> &'static str {
+        "summarizer"
+    }
+
+    async fn chat(&self, request: LlmRequest) -> Result<LlmResponse> {
+        let days = request.messages[1].content.matches("## ").count();
+        Ok(LlmResponse {
+            message: Message::assistant(format!("- {days} days of planning")),
+            tool_calls: Vec::new(),
+            usage: None,
+            finish_reason: Some("stop".to_string()),
+        })
+    }
+
+    async fn chat_stream(&self, _request: LlmRequest) -> Result<LlmStream> {
+        anyhow::bail!("unused")
+    }
+}
+
+#[tokio::test]
+async fn weekly_rollup_archives_complete_old_weeks() {
+    let (_temp, store) = temp_memory_store();
+    // 2026-02-02..08 is ISO week 6, 2026-02-09..15 week 7, 2026-02-20 is in week 8.
+    write_logs(
+        &store,
+        &["2026-02-03", "2026-02-05", "2026-02-12", "2026-02-20"],
+    )
+    .await;
+    let memory: Arc<dyn MemoryBackend> = Arc::new(store.clone());
+    let rollup = MemoryRollup::new(memory, options(RollupPeriod::Week, RetentionMode::Archive));
+    let today = date("2026-03-01");
+
+    let planned = rollup.run_at(today, true).await.unwrap();
+    assert!(planned.dry_run);
+    let periods: Vec<&str> = planned.digests.iter().map(|d| d.period.as_str()).collect();
+    assert_eq!(periods, vec!["2026-W06", "2026-W07"]);
+    assert_eq!(
+        planned.digests[0].sources,
+        vec!["2026-02-03.md", "2026-02-05.md"]
+    );
+    assert!(store.memory_dir().join("2026-02-03.md").exists());
+    assert!(!store.memory_dir().join("digest-2026-W06.md").exists());
+
+    let report = rollup.run_at(today, false).await.unwrap();
+    assert_eq!(report.digests.len(), 2);
+    let digest = std::fs::read_to_string(store.memory_dir().join("digest-2026-W06.md")).unwrap();
+    assert_eq!(
+        digest,
+        "# Memory digest 2026-W06\n\nSources: 2026-02-03, 2026-02-05\n\n## 2026-02-03\n- notes from 2026-02-03\n\n## 2026-02-05\n- notes from 2026-02-05\n"
+    );
+    assert!(!store.memory_dir().join("2026-02-03.md").exists());
+    assert!(store.archive_dir().join("2026-02-03.md").exists());
+    assert!(store.memory_dir().join("2026-02-20.md").exists());
+
+    // Archived logs leave search; the digest takes their place.
+    let hits = store.search("2026-02-12").await.unwrap();
+    assert!(hits[0].path.ends_with("digest-2026-W07.md"), "{hits:?}");
+    assert!(
+        hits.iter()
+            .all(|hit| !hit.path.ends_with("2026-02-12.md") && !hit.path.contains(".archive")),
+        "{hits:?}"
+    );
+
+    assert!(rollup
+        .run_at(today, false)
+        .await
+        .unwrap()
+        .digests
+        .is_empty());
+}
+
+#[tokio::test]
+async fn keep_retention_never_duplicates_days_and_extends_digests() {
+    let (_temp, store) = temp_memory_store();
+    write_logs(&store, &["2026-01-05", "2026-01-20"]).await;
+    let rollup = MemoryRollup::new(
+        Arc::new(store.clone()),
+        options(RollupPeriod::Month, RetentionMode::Keep),
+    );
+    let today = date("2026-03-01");
+
+    assert_eq!(rollup.run_at(today, false).await.unwrap().digests.len(), 1);
+    assert!(rollup
+        .run_at(today, false)
+        .await
+        .unwrap()
+        .digests
+        .is_empty());
+
+    // A log that shows up later for the same month is appended, not re-rolled.
+    write_logs(&store, &["2026-01-28"]).await;
+    let report = rollup.run_at(today, false).await.unwrap();
+    assert_eq!(report.digests[0].sources.len(), 3);
+    let digest = std::fs::read_to_string(store.memory_dir().join("digest-2026-01.md")).unwrap();
+    assert!(digest.contains("Sources: 2026-01-05, 2026-01-20, 2026-01-28\n"));
+    assert_eq!(digest.matches("## 2026-01-05").count(), 1);
+    assert!(digest.contains("## 2026-01-28"));
+    assert!(store.memory_dir().join("2026-01-05.md").exists());
+}
+
+#[tokio::test]
+async fn delete_retention_with_summary() {
+    let (_temp, store) = temp_memory_store();
+    write_logs(&store, &["2026-02-03", "2026-02-04", "2026-02-24"]).await;
+    let rollup = MemoryRollup::new(
+        Arc::new(store.clone()),
+        RollupOptions {
+            summarize: true,
+            after_days: 0,
+            ..options(RollupPeriod::Week, RetentionMode::Delete)
+        },
+    )
+    .with_summarizer(Arc::new(Summarizer), "small-model");
+
+    let report = rollup.run_at(date("2026-02-10"), false).await.unwrap();
+    assert_eq!(report.digests.len(), 1);
+    assert!(report.digests[0].summarized);
+    let digest = std::fs::read_to_string(store.memory_dir().join("digest-2026-W06.md")).unwrap();
+    assert!(
+        digest.ends_with("## Summary\n- 2 days of planning\n"),
+        "{digest}"
+    );
+    assert!(!store.memory_dir().join("2026-02-03.md").exists());
+    assert!(!store.archive_dir().join("2026-02-03.md").exists());
+    assert!(store.memory_dir().join("2026-02-24.md").exists());
+}
+
+#[tokio::test]
+async fn rollup_endpoint_supports_dry_run() {
+    let (_temp, agent) = build_test_agent(Arc::new(MockStreamProvider::text("hi")));
+    let rollup = MemoryRollup::new(agent.memory(), RollupOptions::default());
+    let memory = agent.memory();
+    memory.ensure_layout().await.unwrap();
+    memory
+        .write_file("2020-01-01.md", "- long ago\n")
+        .await
+        .unwrap();
+    let app = router(AppState::new(
+        Arc::new(agent.with_memory_rollup(Arc::new(rollup))),
+        None,
+        None,
+        false,
+        false,
+        "https://api.telegram.org".to_string(),
+    ));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/memory/rollup?dry_run=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value =
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["dry_run"], true);
+    assert_eq!(body["retention"], "archive");
+    assert_eq!(body["digests"][0]["period"], "2020-W01");
+    assert_eq!(body["digests"][0]["sources"][0], "2020-01-01.md");
+
+    let files = memory.list_files().await.unwrap();
+    assert!(files.iter().any(|file| file.path == "2020-01-01.md"));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/memory/rollup")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let files = memory.list_files().await.unwrap();
+    let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(paths, vec!["MEMORY.md", "digest-2020-W01.md"]);
+}
@@ -0,0 +1,168 @@
+mod support;
+
+use chaos_bot_backend::domain::memory::{EditOrigin, MemoryEdit, MemoryScope, MemoryScoping};
+use chaos_bot_backend::domain::types::{SessionState, ToolCall};
+use chaos_bot_backend::infrastructure::tooling::ToolRegistry;
+use serde_json::json;
+use std::sync::Arc;
+use support::{build_test_agent_with_registry, temp_memory_store, MockStreamProvider};
+
+fn telegram_user(user_id: &str) -> MemoryScope {
+    MemoryScope::User {
+        channel: "telegram".to_string(),
+        user_id: user_id.to_string(),
+    }
+}
+
+fn remember(section: &str, fact: &str) -> MemoryEdit {
+    MemoryEdit::Remember {
+        section: section.to_string(),
+        fact: fact.to_string(),
+    }
+}
+
+#[test]
+fn scoping_assigns_scopes_from_session_and_channel_user() {
+    assert_eq!(
+        MemoryScoping::Global.scope_for("s1", Some("telegram"), Some("42")),
+        MemoryScope::Global
+    );
+    assert_eq!(
+        MemoryScoping::User.scope_for("s1", Some("telegram"), Some("42")),
+        telegram_user("42")
+    );
+    assert_eq!(
+        MemoryScoping::User.scope_for("s1", None, None),
+        MemoryScope::Global
+    );
+    assert_eq!(
+        MemoryScoping::Session.scope_for("s1", Some("telegram"), Some("42")),
+        MemoryScope::Session {
+            session_id: "s1".to_string()
+        }
+    );
+    let escaped = telegram_user("../42").relative_dir().unwrap();
+    assert!(escaped.starts_with("users/telegram/___42_"), "{escaped}");
+    assert_eq!(
+        telegram_user("42").relative_dir().as_deref(),
+        Some("users/telegram/42")
+    );
+    let dirs: std::collections::BTreeSet<String> = ["a.b", "a_b", "a/b"]
+        .into_iter()
+        .map(|user_id| telegram_user(user_id).relative_dir().unwrap())
+        .collect();
+    assert_eq!(dirs.len(), 3, "{dirs:?}");
+    assert_eq!(MemoryScope::Global.relative_dir(), None);
+}
+
+#[tokio::test]
+async fn scopes_are_isolated_and_search_merges_global_memory() {
+    let (_temp, store) = temp_memory_store();
+    store
+        .edit_curated(
+            remember("Team", "The deploy day is Thursday"),
+            EditOrigin::default(),
+        )
+        .await
+        .unwrap();
+    let alice = store.scoped(&telegram_user("1"));
+    let bob = store.scoped(&telegram_user("2"));
+    alice
+        .edit_curated(
+            remember("Preferences", "Prefers tea over coffee"),
+            EditOrigin::default(),
+        )
+        .await
+        .unwrap();
+    alice.append_daily_log("asked about tea").await.unwrap();
+
+    assert!(alice
+        .memory_dir()
+        .starts_with(store.memory_dir().join(".scopes/users/telegram/1")));
+    assert!(alice.read_curated().await.unwrap().contains("tea"));
+    assert!(!bob.read_curated().await.unwrap().contains("tea"));
+    assert!(!store.read_curated().await.unwrap().contains("tea"));
+
+    assert!(!alice.search("tea").await.unwrap().is_empty());
+    assert!(bob.search("tea").await.unwrap().is_empty());
+    assert!(store.search("tea").await.unwrap().is_empty());
+    let hits = bob.search("deploy Thursday").await.unwrap();
+    assert!(hits[0].snippet.contains("Thursday"), "{hits:?}");
+
+    // Global files stay readable through a scope; scoped files are not global files.
+    let daily = format!("{}.md", chrono::Utc::now().format("%Y-%m-%d"));
+    assert!(alice
+        .get_file(&daily, None, None)
+        .await
+        .unwrap()
+        .contains("tea"));
+    assert!(bob.get_file(&daily, None, None).await.is_err());
+    assert!(store
+        .list_files()
+        .await
+        .unwrap()
+        .iter()
+        .all(|file| file.path == "MEMORY.md"));
+    assert_eq!(alice.changes(10).await.unwrap().len(), 1);
+    assert_eq!(store.changes(10).await.unwrap().len(), 1);
+
+    // Scoping resolves from the global store, so it never nests.
+    let again = alice.scoped(&telegram_user("2"));
+    assert_eq!(again.memory_dir(), bob.memory_dir());
+    assert_eq!(
+        alice.scoped(&MemoryScope::Global).memory_dir(),
+        store.memory_dir()
+    );
+}
+
+#[tokio::test]
+async fn agent_runs_tools_and_daily_log_in_the_channel_user_scope() {
+    let tool_call = ToolCall {
+        id: "tc_1".to_string(),
+        name: "memory_remember".to_string(),
+        arguments: json!({"section": "Preferences", "fact": "Likes short answers"}),
+    };
+    let provider = MockStreamProvider::tool_then_text(tool_call, "Noted.");
+    let mut registry = ToolRegistry::new();
+    registry.register_memory_tools();
+    let (_temp, agent) = build_test_agent_with_registry(Arc::new(provider), registry);
+    let agent = agent.with_memory_scoping(MemoryScoping::User);
+
+    let mut session = SessionState::new("s1");
+    session.channel = Some("telegram".to_string());
+    session.user_id = Some("42".to_string());
+    assert_eq!(agent.memory_scope(&session), telegram_user("42"));
+    agent
+        .run(&mut session, "keep it short".to_string())
+        .await
+        .unwrap();
+
+    let memory = agent.memory();
+    let scoped = memory.scoped(&telegram_user("42"));
+    assert!(scoped
+        .read_curated()
+        .await
+        .unwrap()
+        .contains("Likes short answers"));
+    assert!(!memory
+        .read_curated()
+        .await
+        .unwrap()
+        .contains("Likes short answers"));
+    assert_eq!(
+        scoped.changes(10).await.unwrap()[0].session_id.as_deref(),
+        Some("s1")
+    );
+    assert!(scoped
+        .list_files()
+        .await
+        .unwrap()
+        .iter()
+        .any(|file| file.date.is_some()));
+    assert!(memory
+        .list_files()
+        .await
+        .unwrap()
+        .iter()
+        .all(|file| file.date.is_none()));
+}
@@ -0,0 +1,243 @@
+use chaos_bot_backend::domain::types::SessionState;
+use chaos_bot_backend::infrastructure::config::AppConfig;
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{
+    register_plugin_tools, PluginSpec, PluginTool, Tool, ToolContext, ToolRegistry,
+};
+use chaos_bot_backend::runtime::build_agent_loop;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::{tempdir, TempDir};
+
+fn write_script(dir: &Path, name: &str, body: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn spec(name: &str, command: &Path) -> PluginSpec {
+    PluginSpec {
+        name: name.to_string(),
+        command: command.to_string_lossy().to_string(),
+        args: Vec::new(),
+        env: HashMap::new(),
+        description: Some("test plugin".to_string()),
+        parameters_schema: Some(json!({"type": "object", "properties": {}})),
+        timeout: Duration::from_secs(10),
+    }
+}
+
+fn context(root: &Path) -> (TempDir, ToolContext) {
+    let memory_root = tempdir().unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        memory_root.path().join("memory"),
+        memory_root.path().join("MEMORY.md"),
+    ));
+    (memory_root, ToolContext::new(root.to_path_buf(), memory))
+}
+
+#[tokio::test]
+async fn plugin_reads_args_from_stdin_and_runs_in_working_dir() {
+    let temp = tempdir().unwrap();
+    let script = write_script(
+        temp.path(),
+        "echo-args",
+        r#"cat > received.json
+printf '{"output": "cwd=%s"}' "$(pwd)""#,
+    );
+    let workdir = temp.path().join("work");
+    std::fs::create_dir_all(&workdir).unwrap();
+
+    let tool = PluginTool::load(spec("echo_args", &script)).await.unwrap();
+    let (_memory, context) = context(&workdir);
+    let result = tool
+        .execute(json!({"text": "hello"}), &context)
+        .await
+        .unwrap();
+
+    assert_eq!(result.name, "echo_args");
+    assert!(!result.is_error);
+    assert_eq!(
+        result.output,
+        format!("cwd={}", workdir.canonicalize().unwrap().display())
+    );
+    let received: Value =
+        serde_json::from_str(&std::fs::read_to_string(workdir.join("received.json")).unwrap())
+            .unwrap();
+    assert_eq!(received, json!({"text": "hello"}));
+}
+
+#[tokio::test]
+async fn plugin_describe_handshake_fills_description_and_schema() {
+    let temp = tempdir().unwrap();
+    let script = write_script(
+        temp.path(),
+        "described",
+        r#"if [ "$1" = "--describe" ]; then
+  echo '{"description": "Adds numbers", "parameters": {"type": "object", "properties": {"a": {"type": "integer"}}, "required": ["a"]}}'
+  exit 0
+fi
+echo '{"output": "ok"}'"#,
+    );
+    let mut spec = spec("adder", &script);
+    spec.description = None;
+    spec.parameters_schema = None;
+
+    let tool = PluginTool::load(spec).await.unwrap();
+    assert_eq!(tool.description(), "Adds numbers");
+    assert_eq!(tool.parameters_schema()["required"], json!(["a"]));
+}
+
+#[tokio::test]
+async fn plugin_environment_is_scrubbed() {
+    std::env::set_var("CHAOS_BOT_PLUGIN_SECRET", "leaked");
+    let temp = tempdir().unwrap();
+    let script = write_script(
+        temp.path(),
+        "env-dump",
+        r#"printf '{"output": "secret=%s extra=%s path=%s"}' "$CHAOS_BOT_PLUGIN_SECRET" "$EXTRA" "${PATH:+set}""#,
+    );
+    let mut spec = spec("env_dump", &script);
+    spec.env
+        .insert("EXTRA".to_string(), "configured".to_string());
+
+    let tool = PluginTool::load(spec).await.unwrap();
+    let (_memory, context) = context(temp.path());
+    let result = tool.execute(json!({}), &context).await.unwrap();
+
+    assert_eq!(result.output, "secret= extra=configured path=set");
+}
+
+#[tokio::test]
+async fn plugin_errors_timeouts_and_invalid_output() {
+    let temp = tempdir().unwrap();
+    let (_memory, context) = context(temp.path());
+
+    let failing = write_script(
+        temp.path(),
+        "failing",
+        r#"echo '{"output": "bad input", "is_error": true}'"#,
+    );
+    let result = PluginTool::load(spec("failing", &failing))
+        .await
+        .unwrap()
+        .execute(json!({}), &context)
+        .await
+        .unwrap();
+    assert!(result.is_error);
+    assert_eq!(result.output, "bad input");
+
+    let crashing = write_script(temp.path(), "crashing", "echo boom >&2\nexit 3");
+    let error = PluginTool::load(spec("crashing", &crashing))
+        .await
+        .unwrap()
+        .execute(json!({}), &context)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("boom"), "{error}");
+
+    let garbage = write_script(temp.path(), "garbage", "echo not-json");
+    let error = PluginTool::load(spec("garbage", &garbage))
+        .await
+        .unwrap()
+        .execute(json!({}), &context)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("invalid JSON"), "{error}");
+
+    let slow = write_script(temp.path(), "slow", "sleep 5");
+    let mut slow_spec = spec("slow", &slow);
+    slow_spec.timeout = Duration::from_millis(200);
+    let error = PluginTool::load(slow_spec)
+        .await
+        .unwrap()
+        .execute(json!({}), &context)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("timed out"), "{error}");
+}
+
+#[tokio::test]
+async fn register_plugin_tools_skips_broken_plugins() {
+    let temp = tempdir().unwrap();
+    let good = write_script(temp.path(), "good", r#"echo '{"output": "ok"}'"#);
+    let mut missing = spec("missing", &temp.path().join("does-not-exist"));
+    missing.description = None;
+
+    let mut registry = ToolRegistry::new();
+    let registered = register_plugin_tools(&mut registry, &[spec("good", &good), missing]).await;
+
+    assert_eq!(registered, 1);
+    let names: Vec<String> = registry.specs().into_iter().map(|spec| spec.name).collect();
+    assert_eq!(names, vec!["good".to_string()]);
+}
+
+#[tokio::test]
+async fn plugin_tools_cannot_shadow_registered_tools() {
+    let temp = tempdir().unwrap();
+    let script = write_script(temp.path(), "plugin", r#"echo '{"output": "plugin"}'"#);
+    let mut registry = ToolRegistry::new();
+    registry.register_default_tools();
+    let builtins = registry.specs().len();
+
+    let mut second = spec("lookup", &script);
+    second.description = Some("second lookup".to_string());
+    let registered = register_plugin_tools(
+        &mut registry,
+        &[
+            spec("bash", &script),
+            spec("read_output", &script),
+            spec("lookup", &script),
+            second,
+            spec("look up", &script),
+        ],
+    )
+    .await;
+
+    assert_eq!(registered, 1);
+    let specs = registry.specs();
+    assert_eq!(specs.len(), builtins + 1);
+    let bash = specs.iter().find(|spec| spec.name == "bash").unwrap();
+    assert_ne!(bash.description, "test plugin");
+    let lookup = specs.iter().find(|spec| spec.name == "lookup").unwrap();
+    assert_eq!(lookup.description, "test plugin");
+}
+
+#[tokio::test]
+async fn build_agent_loop_registers_configured_plugins() {
+    let temp = tempdir().unwrap();
+    let script = write_script(temp.path(), "hello", r#"echo '{"output": "hi"}'"#);
+    let config = AppConfig {
+        provider: "mock".to_string(),
+        workspace: temp.path().to_path_buf(),
+        log_dir: temp.path().join("logs"),
+        working_dir: temp.path().to_path_buf(),
+        personality_dir: temp.path().join("personality"),
+        memory_dir: temp.path().join("memory"),
+        memory_file: temp.path().join("MEMORY.md"),
+        checkpoints_dir: temp.path().join("data/checkpoints"),
+        tool_output_dir: temp.path().join("data/tool-output"),
+        tool_plugins: vec![spec("hello", &script)],
+        ..AppConfig::default()
+    };
+
+    let agent = build_agent_loop(&config).await.unwrap();
+    let tools = agent.tools();
+    assert!(tools.specs().iter().any(|spec| spec.name == "hello"));
+
+    let result = tools
+        .execute(
+            "tc_1",
+            "hello",
+            Value::Null,
+            &agent.tool_context(&SessionState::new("s1")),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.output, "hi");
+}
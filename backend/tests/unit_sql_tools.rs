@@ -0,0 +1,248 @@
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{
+    SqlLimits, SqlQueryTool, SqlSchemaTool, Tool, ToolContext,
+};
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::{tempdir, TempDir};
+
+/// A working directory with `shop.db` (customers, orders, a view) and `regions.csv`.
+fn workspace() -> (TempDir, ToolContext) {
+    let temp = tempdir().unwrap();
+    let root = temp.path().join("work");
+    std::fs::create_dir_all(root.join("data")).unwrap();
+    let connection = Connection::open(root.join("data/shop.db")).unwrap();
+    connection
+        .execute_batch(
+            "CREATE TABLE customers (id INTEGER PRIMARY KEY, name TEXT NOT NULL, region TEXT);
+             CREATE TABLE orders (id INTEGER PRIMARY KEY, customer_id INTEGER, total REAL, note BLOB);
+             CREATE VIEW big_orders AS SELECT * FROM orders WHERE total > 100;
+             INSERT INTO customers VALUES (1, 'Ada', 'eu'), (2, 'Grace', 'us'), (3, 'Linus', NULL);
+             INSERT INTO orders VALUES (1, 1, 250.5, x'0102'), (2, 1, 20, NULL), (3, 2, 99.99, NULL);",
+        )
+        .unwrap();
+    std::fs::write(
+        root.join("data/regions.csv"),
+        "code,label\neu,Europe\nus,United States\n",
+    )
+    .unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    (temp, ToolContext::new(root, memory))
+}
+
+async fn query(context: &ToolContext, args: Value) -> anyhow::Result<String> {
+    SqlQueryTool::new(SqlLimits::default())
+        .execute(args, context)
+        .await
+        .map(|result| result.output)
+}
+
+#[tokio::test]
+async fn sql_query_renders_markdown_tables() {
+    let (_temp, context) = workspace();
+    let output = query(
+        &context,
+        json!({
+            "database": "data/shop.db",
+            "query": "SELECT c.name, o.total, o.note, c.region FROM orders o JOIN customers c ON c.id = o.customer_id ORDER BY o.id;"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        output,
+        "| name | total | note | region |\n| --- | --- | --- | --- |\n| Ada | 250.5 | <blob 2 bytes> | eu |\n| Ada | 20 | NULL | eu |\n| Grace | 99.99 | NULL | us |\n(3 rows)"
+    );
+
+    let empty = query(
+        &context,
+        json!({"database": "data/shop.db", "query": "SELECT id FROM customers WHERE id > 10"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(empty, "(no rows)\ncolumns: id");
+}
+
+#[tokio::test]
+async fn sql_query_applies_row_limit() {
+    let (_temp, context) = workspace();
+    let output = query(
+        &context,
+        json!({"database": "data/shop.db", "query": "SELECT name FROM customers ORDER BY id", "limit": 2}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        output,
+        "| name |\n| --- |\n| Ada |\n| Grace |\n[showing first 2 rows; add LIMIT/WHERE or raise limit for more]"
+    );
+
+    let capped = SqlQueryTool::new(SqlLimits {
+        max_rows: 1,
+        ..SqlLimits::default()
+    })
+    .execute(
+        json!({"database": "data/shop.db", "query": "SELECT name FROM customers", "limit": 50}),
+        &context,
+    )
+    .await
+    .unwrap();
+    assert!(capped.output.contains("[showing first 1 rows"));
+}
+
+#[tokio::test]
+async fn sql_query_mounts_csv_files_as_tables() {
+    let (_temp, context) = workspace();
+    let joined = query(
+        &context,
+        json!({
+            "database": "data/shop.db",
+            "csv": ["data/regions.csv"],
+            "query": "SELECT c.name, r.label FROM customers c JOIN regions r ON r.code = c.region ORDER BY c.id"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        joined,
+        "| name | label |\n| --- | --- |\n| Ada | Europe |\n| Grace | United States |\n(2 rows)"
+    );
+
+    let csv_only = query(
+        &context,
+        json!({"csv": "data/regions.csv", "query": "SELECT count(*) AS n FROM regions"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(csv_only, "| n |\n| --- |\n| 2 |\n(1 row)");
+}
+
+#[tokio::test]
+async fn sql_query_only_runs_single_selects() {
+    let (_temp, context) = workspace();
+    for statement in [
+        "DELETE FROM customers",
+        "INSERT INTO customers VALUES (9, 'Eve', 'eu')",
+        "PRAGMA journal_mode = WAL",
+        "ATTACH DATABASE 'other.db' AS other",
+        "WITH gone AS (SELECT 1) DELETE FROM customers",
+        "SELECT 1; DELETE FROM customers",
+    ] {
+        let error = query(
+            &context,
+            json!({"database": "data/shop.db", "query": statement}),
+        )
+        .await
+        .unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.contains("only runs SELECT") || message.contains("single statement"),
+            "{statement}: {message}"
+        );
+    }
+
+    let connection = Connection::open(context.root_dir.join("data/shop.db")).unwrap();
+    let count: i64 = connection
+        .query_row("SELECT count(*) FROM customers", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 3);
+}
+
+#[tokio::test]
+async fn sql_query_times_out_long_queries() {
+    let (_temp, context) = workspace();
+    let tool = SqlQueryTool::new(SqlLimits {
+        max_rows: 10,
+        timeout: Duration::from_millis(200),
+    });
+    let error = tool
+        .execute(
+            json!({
+                "database": "data/shop.db",
+                "query": "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT count(*) FROM n"
+            }),
+            &context,
+        )
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("timed out"), "{error}");
+}
+
+#[tokio::test]
+async fn sql_tools_confine_paths_and_require_a_source() {
+    let (temp, context) = workspace();
+    Connection::open(temp.path().join("outside.db"))
+        .unwrap()
+        .execute_batch("CREATE TABLE secrets (value TEXT)")
+        .unwrap();
+
+    let error = query(
+        &context,
+        json!({"database": "../outside.db", "query": "SELECT * FROM secrets"}),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("escapes working directory"),
+        "{error}"
+    );
+
+    let error = SqlSchemaTool
+        .execute(json!({"csv": ["../outside.db"]}), &context)
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("escapes working directory"),
+        "{error}"
+    );
+
+    let error = query(&context, json!({"query": "SELECT 1"}))
+        .await
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("sql_query needs a database or csv files"));
+}
+
+#[tokio::test]
+async fn sql_schema_lists_tables_views_and_csv_columns() {
+    let (_temp, context) = workspace();
+    let schema = SqlSchemaTool
+        .execute(
+            json!({"database": "data/shop.db", "csv": ["data/regions.csv"]}),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        schema.output,
+        "view big_orders\n  id INTEGER\n  customer_id INTEGER\n  total REAL\n  note BLOB\n\
+         table customers\n  id INTEGER primary key\n  name TEXT not null\n  region TEXT\n\
+         table orders\n  id INTEGER primary key\n  customer_id INTEGER\n  total REAL\n  note BLOB\n\
+         table regions\n  code TEXT\n  label TEXT"
+    );
+
+    let one = SqlSchemaTool
+        .execute(
+            json!({"database": "data/shop.db", "table": "CUSTOMERS"}),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert!(one.output.starts_with("table customers\n"));
+    assert!(!one.output.contains("orders"));
+
+    let missing = SqlSchemaTool
+        .execute(
+            json!({"database": "data/shop.db", "table": "nope"}),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert_eq!(missing.output, "no table named nope");
+}
@@ -0,0 +1,278 @@
+mod support;
+
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{
+    OutputLimits, OutputSpill, ToolContext, ToolRegistry,
+};
+use serde_json::json;
+use std::sync::Arc;
+use support::MockTool;
+use tempfile::{tempdir, TempDir};
+
+fn limits(max_bytes: usize) -> OutputLimits {
+    OutputLimits {
+        max_bytes,
+        head_lines: 3,
+        tail_lines: 2,
+    }
+}
+
+fn numbered_lines(count: usize) -> String {
+    (1..=count)
+        .map(|i| format!("line {i}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn registry_with(temp: &TempDir, output: String, max_bytes: usize) -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    registry.register(MockTool::fixed("big", &output));
+    registry.with_output_spill(OutputSpill::new(
+        temp.path().join("tool-output"),
+        limits(max_bytes),
+    ))
+}
+
+fn context(temp: &TempDir, session_id: &str) -> ToolContext {
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    ToolContext::new(temp.path().to_path_buf(), memory).with_session(session_id)
+}
+
+#[tokio::test]
+async fn small_outputs_pass_through_unchanged() {
+    let temp = tempdir().unwrap();
+    let registry = registry_with(&temp, "short".to_string(), 1024);
+
+    let result = registry
+        .dispatch("tc_1", "big", json!({}), &context(&temp, "s1"))
+        .await
+        .unwrap();
+    assert_eq!(result.output, "short");
+    assert!(result.truncation.is_none());
+    assert!(!temp.path().join("tool-output").exists());
+}
+
+#[tokio::test]
+async fn large_outputs_are_spilled_with_head_tail_preview() {
+    let temp = tempdir().unwrap();
+    let registry = registry_with(&temp, numbered_lines(100), 200);
+
+    let result = registry
+        .dispatch("tc_1", "big", json!({}), &context(&temp, "s1"))
+        .await
+        .unwrap();
+    let truncation = result.truncation.expect("output should be truncated");
+    assert_eq!(truncation.total_lines, 100);
+    assert_eq!(truncation.head_lines, 3);
+    assert_eq!(truncation.tail_lines, 2);
+    assert!(truncation.handle.starts_with("out-"));
+
+    let lines: Vec<&str> = result.output.lines().collect();
+    assert_eq!(&lines[..3], &["line 1", "line 2", "line 3"]);
+    assert!(lines[3].contains(&truncation.handle));
+    assert!(lines[3].contains("offset=4"));
+    assert_eq!(&lines[4..], &["line 99", "line 100"]);
+
+    let spilled = temp
+        .path()
+        .join("tool-output/s1")
+        .join(format!("{}.txt", truncation.handle));
+    assert_eq!(
+        std::fs::read_to_string(spilled).unwrap(),
+        numbered_lines(100)
+    );
+}
+
+#[tokio::test]
+async fn single_long_line_preview_respects_byte_budget() {
+    let temp = tempdir().unwrap();
+    let registry = registry_with(&temp, "x".repeat(10_000), 300);
+
+    let result = registry
+        .dispatch("tc_1", "big", json!({}), &context(&temp, "s1"))
+        .await
+        .unwrap();
+    assert!(result.truncation.is_some());
+    assert!(result.output.len() < 600);
+}
+
+#[tokio::test]
+async fn read_output_pages_through_spilled_output() {
+    let temp = tempdir().unwrap();
+    let registry = registry_with(&temp, numbered_lines(100), 200);
+    let ctx = context(&temp, "s1");
+
+    let handle = registry
+        .dispatch("tc_1", "big", json!({}), &ctx)
+        .await
+        .unwrap()
+        .truncation
+        .unwrap()
+        .handle;
+
+    let page = registry
+        .dispatch(
+            "tc_2",
+            "read_output",
+            json!({"handle": handle, "offset": 4, "limit": 3}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert!(page.truncation.is_none());
+    assert_eq!(
+        page.output,
+        "line 4\nline 5\nline 6\n[showing lines 4-6 of 100; continue with offset=7]"
+    );
+
+    let last = registry
+        .dispatch(
+            "tc_3",
+            "read_output",
+            json!({"handle": handle, "offset": 99, "limit": 10}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(last.output, "line 99\nline 100");
+
+    // Pages are capped by the byte budget even when a larger limit is requested.
+    let capped = registry
+        .dispatch(
+            "tc_4",
+            "read_output",
+            json!({"handle": handle, "limit": 100}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert!(capped.output.contains("continue with offset="));
+
+    let past_end = registry
+        .dispatch(
+            "tc_5",
+            "read_output",
+            json!({"handle": handle, "offset": 500}),
+            &ctx,
+        )
+        .await;
+    assert!(past_end.is_err());
+}
+
+#[tokio::test]
+async fn read_output_reaches_every_part_of_a_long_line() {
+    let temp = tempdir().unwrap();
+    let original = format!("first\n{}\nlast", "0123456789é".repeat(1_000));
+    let registry = registry_with(&temp, original.clone(), 300);
+    let ctx = context(&temp, "s1");
+
+    let preview = registry
+        .dispatch("tc_1", "big", json!({}), &ctx)
+        .await
+        .unwrap();
+    let truncation = preview.truncation.unwrap();
+    assert!(truncation.total_lines > 3);
+    assert!(preview.output.contains("Use read_output"));
+
+    let mut rebuilt = String::new();
+    let mut offset = 1;
+    for call in 0.. {
+        assert!(call < 1_000, "paging did not terminate");
+        let page = registry
+            .dispatch(
+                &format!("tc_page_{call}"),
+                "read_output",
+                json!({"handle": truncation.handle, "offset": offset, "limit": 50}),
+                &ctx,
+            )
+            .await
+            .unwrap()
+            .output;
+        let Some((text, hint)) = page.rsplit_once("\n[showing lines ") else {
+            rebuilt.push_str(&page);
+            break;
+        };
+        rebuilt.push_str(text);
+        if !hint.contains("the last line continues") {
+            rebuilt.push('\n');
+        }
+        let next = hint.rsplit_once("offset=").unwrap().1.trim_end_matches(']');
+        offset = next.parse::<usize>().unwrap();
+        assert!(offset <= truncation.total_lines);
+    }
+    assert_eq!(rebuilt, original);
+}
+
+#[tokio::test]
+async fn single_line_preview_only_hints_when_more_remains() {
+    let temp = tempdir().unwrap();
+    let registry = registry_with(&temp, "x".repeat(400), 300);
+
+    let result = registry
+        .dispatch("tc_1", "big", json!({}), &context(&temp, "s1"))
+        .await
+        .unwrap();
+    let truncation = result.truncation.unwrap();
+    let hinted: usize = result
+        .output
+        .rsplit_once("offset=")
+        .unwrap()
+        .1
+        .split(' ')
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(hinted <= truncation.total_lines);
+    assert!(truncation.head_lines + truncation.tail_lines < truncation.total_lines);
+}
+
+#[tokio::test]
+async fn read_output_is_scoped_to_session_and_rejects_bad_handles() {
+    let temp = tempdir().unwrap();
+    let registry = registry_with(&temp, numbered_lines(100), 200);
+
+    let handle = registry
+        .dispatch("tc_1", "big", json!({}), &context(&temp, "s1"))
+        .await
+        .unwrap()
+        .truncation
+        .unwrap()
+        .handle;
+
+    let other_session = registry
+        .dispatch(
+            "tc_2",
+            "read_output",
+            json!({"handle": handle}),
+            &context(&temp, "s2"),
+        )
+        .await;
+    assert!(other_session.is_err());
+
+    let traversal = registry
+        .dispatch(
+            "tc_3",
+            "read_output",
+            json!({"handle": "../s1/out-abc"}),
+            &context(&temp, "s1"),
+        )
+        .await;
+    assert!(traversal.is_err());
+}
+
+#[test]
+fn read_output_is_only_registered_with_spill() {
+    let temp = tempdir().unwrap();
+    let plain = ToolRegistry::new();
+    assert!(plain.specs().iter().all(|spec| spec.name != "read_output"));
+
+    let spilled = ToolRegistry::new().with_output_spill(OutputSpill::new(temp.path(), limits(100)));
+    assert!(spilled
+        .specs()
+        .iter()
+        .any(|spec| spec.name == "read_output"));
+}
@@ -40,9 +40,11 @@ fn registry_register_default_tools_count() {
     // ReadTool registered by both coding and read-only, but HashMap deduplicates
     // coding: read, write, edit, bash
     // read-only: read, grep, find, ls
-    // memory: memory_get, memory_search
-    // unique: read, write, edit, bash, grep, find, ls, memory_get, memory_search = 9
-    assert_eq!(reg.specs().len(), 9);
+    // memory: memory_get, memory_search, memory_remember, memory_update, memory_forget
+    // plan: todo_write, todo_read
+    // unique: read, write, edit, bash, grep, find, ls, memory_get, memory_search,
+    // memory_remember, memory_update, memory_forget, todo_write, todo_read = 14
+    assert_eq!(reg.specs().len(), 14);
 }
 
 #[tokio::test]
@@ -325,6 +327,141 @@ async fn grep_tool_case_insensitive() {
     assert_eq!(lines.len(), 2);
 }
 
+#[tokio::test]
+async fn grep_tool_respects_gitignore_and_ignore_files() {
+    let (_temp, ctx) = make_context();
+    std::fs::write(ctx.root_dir.join(".gitignore"), "target/\n").unwrap();
+    std::fs::write(ctx.root_dir.join(".ignore"), "node_modules/\n").unwrap();
+    std::fs::create_dir_all(ctx.root_dir.join("target")).unwrap();
+    std::fs::create_dir_all(ctx.root_dir.join("node_modules/pkg")).unwrap();
+    std::fs::write(ctx.root_dir.join("target/out.txt"), "needle").unwrap();
+    std::fs::write(ctx.root_dir.join("node_modules/pkg/index.js"), "needle").unwrap();
+    std::fs::write(ctx.root_dir.join("src.txt"), "needle").unwrap();
+
+    let result = GrepTool
+        .execute(json!({"pattern": "needle"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output, "src.txt:1:needle");
+
+    let result = GrepTool
+        .execute(json!({"pattern": "needle", "no_ignore": true}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output.lines().count(), 3);
+}
+
+#[tokio::test]
+async fn grep_tool_regex_and_case_sensitive_modes() {
+    let (_temp, ctx) = make_context();
+    std::fs::write(
+        ctx.root_dir.join("file.txt"),
+        "fn main()\nFN other()\nlet x = 1;",
+    )
+    .unwrap();
+
+    let result = GrepTool
+        .execute(json!({"pattern": "^fn \\w+", "mode": "regex"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output.lines().count(), 2);
+
+    let result = GrepTool
+        .execute(
+            json!({"pattern": "^fn \\w+", "mode": "regex", "case_sensitive": true}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.output, "file.txt:1:fn main()");
+
+    // Literal mode treats regex metacharacters verbatim.
+    let result = GrepTool
+        .execute(json!({"pattern": "main()"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output, "file.txt:1:fn main()");
+
+    let result = GrepTool
+        .execute(json!({"pattern": "(", "mode": "regex"}), &ctx)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn grep_tool_include_exclude_globs() {
+    let (_temp, ctx) = make_context();
+    std::fs::create_dir_all(ctx.root_dir.join("src")).unwrap();
+    std::fs::write(ctx.root_dir.join("src/lib.rs"), "todo").unwrap();
+    std::fs::write(ctx.root_dir.join("src/gen.rs"), "todo").unwrap();
+    std::fs::write(ctx.root_dir.join("notes.md"), "todo").unwrap();
+
+    let result = GrepTool
+        .execute(
+            json!({"pattern": "todo", "include": ["*.rs"], "exclude": ["**/gen.rs"]}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.output, "src/lib.rs:1:todo");
+}
+
+#[tokio::test]
+async fn grep_tool_context_lines() {
+    let (_temp, ctx) = make_context();
+    std::fs::write(
+        ctx.root_dir.join("file.txt"),
+        "a\nb\nhit\nc\nd\ne\nf\nhit\ng",
+    )
+    .unwrap();
+
+    let result = GrepTool
+        .execute(json!({"pattern": "hit", "context": 1}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(
+        result.output,
+        "file.txt-2-b\nfile.txt:3:hit\nfile.txt-4-c\n--\nfile.txt-7-f\nfile.txt:8:hit\nfile.txt-9-g"
+    );
+}
+
+#[tokio::test]
+async fn grep_tool_skips_binary_files() {
+    let (_temp, ctx) = make_context();
+    std::fs::write(ctx.root_dir.join("blob.bin"), b"needle\0\x01\x02").unwrap();
+    std::fs::write(ctx.root_dir.join("text.txt"), "needle").unwrap();
+
+    let result = GrepTool
+        .execute(json!({"pattern": "needle"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output, "text.txt:1:needle\n[skipped 1 binary files]");
+}
+
+#[tokio::test]
+async fn grep_tool_truncates_with_continuation_offset() {
+    let (_temp, ctx) = make_context();
+    let content = (1..=5).map(|i| format!("match {i}")).collect::<Vec<_>>();
+    std::fs::write(ctx.root_dir.join("file.txt"), content.join("\n")).unwrap();
+
+    let result = GrepTool
+        .execute(json!({"pattern": "match", "max_results": 2}), &ctx)
+        .await
+        .unwrap();
+    let lines: Vec<&str> = result.output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[2].contains("continue with offset=2"));
+
+    let result = GrepTool
+        .execute(
+            json!({"pattern": "match", "max_results": 2, "offset": 4}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.output, "file.txt:5:match 5");
+}
+
 // -------------------------------------------------------------------------
 // FindTool
 // -------------------------------------------------------------------------
@@ -340,6 +477,42 @@ async fn find_tool_finds_files() {
     assert!(result.output.contains("test.rs"));
 }
 
+#[tokio::test]
+async fn find_tool_glob_pattern_and_type_filter() {
+    let (_temp, ctx) = make_context();
+    std::fs::create_dir_all(ctx.root_dir.join("src/nested")).unwrap();
+    std::fs::write(ctx.root_dir.join("src/a.rs"), "").unwrap();
+    std::fs::write(ctx.root_dir.join("src/nested/b.rs"), "").unwrap();
+    std::fs::write(ctx.root_dir.join("src/c.txt"), "").unwrap();
+
+    let result = FindTool
+        .execute(json!({"pattern": "**/*.rs"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output, "src/a.rs\nsrc/nested/b.rs");
+
+    let result = FindTool
+        .execute(json!({"pattern": "nest", "type": "dir"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output, "src/nested/");
+}
+
+#[tokio::test]
+async fn find_tool_respects_gitignore() {
+    let (_temp, ctx) = make_context();
+    std::fs::write(ctx.root_dir.join(".gitignore"), "build/\n").unwrap();
+    std::fs::create_dir_all(ctx.root_dir.join("build")).unwrap();
+    std::fs::write(ctx.root_dir.join("build/app.rs"), "").unwrap();
+    std::fs::write(ctx.root_dir.join("app.rs"), "").unwrap();
+
+    let result = FindTool
+        .execute(json!({"pattern": "*.rs"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output, "app.rs");
+}
+
 // -------------------------------------------------------------------------
 // LsTool
 // -------------------------------------------------------------------------
@@ -370,6 +543,23 @@ async fn ls_tool_sorted_output() {
     assert!(a_pos < z_pos);
 }
 
+#[tokio::test]
+async fn ls_tool_hides_ignored_and_hidden_entries_by_default() {
+    let (_temp, ctx) = make_context();
+    std::fs::write(ctx.root_dir.join(".gitignore"), "dist/\n").unwrap();
+    std::fs::create_dir(ctx.root_dir.join("dist")).unwrap();
+    std::fs::write(ctx.root_dir.join("visible.txt"), "").unwrap();
+
+    let result = LsTool.execute(json!({}), &ctx).await.unwrap();
+    assert_eq!(result.output, "visible.txt");
+
+    let result = LsTool
+        .execute(json!({"hidden": true, "no_ignore": true}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result.output, ".gitignore\ndist/\nvisible.txt");
+}
+
 // -------------------------------------------------------------------------
 // MemoryGetTool
 // -------------------------------------------------------------------------
@@ -409,6 +599,137 @@ async fn memory_search_tool_finds_hits() {
     assert!(result.output.contains("important"));
 }
 
+// -------------------------------------------------------------------------
+// MemoryRememberTool / MemoryUpdateTool / MemoryForgetTool
+// -------------------------------------------------------------------------
+
+#[tokio::test]
+async fn memory_write_tools_edit_sections_and_record_origin() {
+    let (_temp, ctx) = make_context();
+    let ctx = ctx.with_session("s1");
+
+    let remembered = MemoryRememberTool
+        .execute(json!({"section": "Preferences", "fact": "likes green tea"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(
+        remembered.output,
+        "Section \"Preferences\" is now:\n- likes green tea"
+    );
+    let repeated = MemoryRememberTool
+        .execute(json!({"section": "preferences", "fact": "Likes green tea"}), &ctx)
+        .await
+        .unwrap();
+    assert!(repeated.output.contains("already has this"));
+
+    let stale = MemoryUpdateTool
+        .execute(
+            json!({"section": "Preferences", "content": "- likes oolong", "expected": "- likes coffee"}),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(stale.to_string().contains("changed since it was read"));
+    MemoryUpdateTool
+        .execute(
+            json!({"section": "Preferences", "content": "- likes oolong", "expected": "- likes green tea"}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+    let forgotten = MemoryForgetTool
+        .execute(json!({"section": "Preferences"}), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(forgotten.output, "Removed section \"Preferences\"");
+    assert!(MemoryForgetTool
+        .execute(json!({"section": "Preferences"}), &ctx)
+        .await
+        .is_err());
+
+    let changes = ctx.memory.changes(10).await.unwrap();
+    let actions: Vec<&str> = changes.iter().map(|change| change.action.as_str()).collect();
+    assert_eq!(actions, vec!["remember", "remember", "update", "forget"]);
+    assert!(changes.iter().all(|change| change.session_id.as_deref() == Some("s1")));
+    assert!(!changes[1].changed);
+}
+
+// -------------------------------------------------------------------------
+// TodoWriteTool / TodoReadTool
+// -------------------------------------------------------------------------
+
+#[tokio::test]
+async fn todo_tools_replace_and_render_session_plan() {
+    let (_temp, ctx) = make_context();
+    let plan = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let ctx = ctx.with_plan(plan.clone());
+
+    let empty = TodoReadTool.execute(json!({}), &ctx).await.unwrap();
+    assert_eq!(empty.output, "(no plan)");
+
+    let written = TodoWriteTool
+        .execute(
+            json!({"todos": [
+                {"content": "Write   tests", "status": "completed"},
+                {"content": "Ship it", "status": "in_progress"},
+                {"content": "Announce"}
+            ]}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        written.output,
+        "1. [x] Write tests\n2. [~] Ship it\n3. [ ] Announce\n(1/3 done)"
+    );
+    assert_eq!(plan.lock().unwrap().len(), 3);
+
+    let read = TodoReadTool.execute(json!({}), &ctx).await.unwrap();
+    assert_eq!(read.output, written.output);
+}
+
+#[tokio::test]
+async fn todo_write_validates_items_and_requires_session() {
+    let (_temp, ctx) = make_context();
+    let error = TodoWriteTool
+        .execute(json!({"todos": []}), &ctx)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "todo_write is only available inside a chat session"
+    );
+
+    let ctx = ctx.with_plan(Arc::new(std::sync::Mutex::new(Vec::new())));
+    let error = TodoWriteTool
+        .execute(
+            json!({"todos": [{"content": "x", "status": "blocked"}]}),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("unknown status blocked"));
+
+    let error = TodoWriteTool
+        .execute(
+            json!({"todos": [{"content": "  ", "status": "pending"}]}),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("every item needs content"));
+
+    let too_many = (0..51)
+        .map(|i| json!({"content": format!("step {i}"), "status": "pending"}))
+        .collect::<Vec<_>>();
+    let error = TodoWriteTool
+        .execute(json!({"todos": too_many}), &ctx)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("at most 50 items"));
+}
+
 // -------------------------------------------------------------------------
 // Tool trait metadata
 // -------------------------------------------------------------------------
@@ -425,6 +746,8 @@ fn tool_names_and_descriptions() {
         Box::new(LsTool),
         Box::new(MemoryGetTool),
         Box::new(MemorySearchTool),
+        Box::new(TodoWriteTool),
+        Box::new(TodoReadTool),
     ];
 
     for tool in &tools {
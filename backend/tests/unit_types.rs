@@ -109,6 +109,7 @@ fn tool_result_serde() {
         name: "read".into(),
         output: "ok".into(),
         is_error: false,
+        truncation: None,
     };
     let json = serde_json::to_string(&result).unwrap();
     let back: ToolResult = serde_json::from_str(&json).unwrap();
@@ -224,3 +225,19 @@ fn channel_delivery_serde_roundtrip() {
     let back: ChannelDelivery = serde_json::from_str(&json).unwrap();
     assert_eq!(back.external_message_id.as_deref(), Some("99"));
 }
+
+#[test]
+fn path_segment_keeps_safe_ids_and_hashes_rewritten_ones() {
+    assert_eq!(path_segment("session-1_a"), "session-1_a");
+
+    let escaped = path_segment("../etc");
+    assert!(escaped.starts_with("___etc_"), "{escaped}");
+    assert_eq!(escaped.len(), "___etc_".len() + 8);
+    assert!(path_segment("").starts_with('_'));
+
+    let segments = [path_segment("a.b"), path_segment("a_b"), path_segment("a/b")];
+    assert_eq!(segments[1], "a_b");
+    assert_ne!(segments[0], segments[1]);
+    assert_ne!(segments[0], segments[2]);
+    assert_ne!(segments[1], segments[2]);
+}
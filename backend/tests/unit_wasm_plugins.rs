@@ -0,0 +1,261 @@
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{
+    register_wasm_plugins, wasm_engine, Tool, ToolContext, ToolRegistry, WasmPluginTool,
+};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::{tempdir, TempDir};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/wasm")
+        .join(format!("{name}.wasm"))
+}
+
+/// Installs the `fixture` module as plugin `name` under `plugins_dir` with extra manifest
+/// fields merged in.
+fn install(plugins_dir: &Path, name: &str, fixture_name: &str, manifest: Value) -> PathBuf {
+    let dir = plugins_dir.join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(fixture(fixture_name), dir.join(format!("{name}.wasm"))).unwrap();
+    let mut full = json!({"name": name, "description": format!("{name} plugin")});
+    for (key, value) in manifest.as_object().unwrap() {
+        full[key] = value.clone();
+    }
+    std::fs::write(dir.join("manifest.json"), full.to_string()).unwrap();
+    dir
+}
+
+struct Harness {
+    _temp: TempDir,
+    plugins: PathBuf,
+    workdir: PathBuf,
+    context: ToolContext,
+}
+
+fn harness() -> Harness {
+    let temp = tempdir().unwrap();
+    let plugins = temp.path().join("plugins");
+    let workdir = temp.path().join("work");
+    std::fs::create_dir_all(&plugins).unwrap();
+    std::fs::create_dir_all(&workdir).unwrap();
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    Harness {
+        context: ToolContext::new(workdir.clone(), memory),
+        _temp: temp,
+        plugins,
+        workdir,
+    }
+}
+
+fn load(dir: &Path) -> WasmPluginTool {
+    WasmPluginTool::load(&wasm_engine().unwrap(), dir).unwrap()
+}
+
+#[tokio::test]
+async fn wasm_plugin_receives_arguments_on_stdin() {
+    let h = harness();
+    let dir = install(
+        &h.plugins,
+        "echo",
+        "echo",
+        json!({"parameters_schema": {"type": "object", "properties": {"text": {"type": "string"}}}}),
+    );
+
+    let tool = load(&dir);
+    assert_eq!(tool.name(), "echo");
+    assert_eq!(tool.description(), "echo plugin");
+    assert!(tool.parameters_schema()["properties"]["text"].is_object());
+
+    let result = tool
+        .execute(json!({"text": "hi"}), &h.context)
+        .await
+        .unwrap();
+    assert!(!result.is_error);
+    let echoed: Value = serde_json::from_str(&result.output).unwrap();
+    assert_eq!(echoed, json!({"text": "hi"}));
+}
+
+#[tokio::test]
+async fn wasm_plugin_json_response_sets_output_and_error_flag() {
+    let h = harness();
+    let dir = install(&h.plugins, "echo", "echo", json!({}));
+
+    let result = load(&dir)
+        .execute(
+            json!({"output": "from plugin", "is_error": true}),
+            &h.context,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.output, "from plugin");
+    assert!(result.is_error);
+}
+
+#[tokio::test]
+async fn wasm_plugin_filesystem_access_follows_capabilities() {
+    let h = harness();
+
+    let denied = install(&h.plugins, "no_fs", "write_file", json!({}));
+    let result = load(&denied).execute(json!({}), &h.context).await.unwrap();
+    assert!(result.is_error);
+    assert!(result.output.contains("cannot create out.txt"));
+
+    let read_only = install(
+        &h.plugins,
+        "read_only",
+        "write_file",
+        json!({"capabilities": {"read_working_dir": true}}),
+    );
+    let result = load(&read_only)
+        .execute(json!({}), &h.context)
+        .await
+        .unwrap();
+    assert!(result.is_error);
+    assert!(!h.workdir.join("out.txt").exists());
+
+    let writable = install(
+        &h.plugins,
+        "writable",
+        "write_file",
+        json!({"capabilities": {"write_working_dir": true}}),
+    );
+    let result = load(&writable)
+        .execute(json!({}), &h.context)
+        .await
+        .unwrap();
+    assert!(!result.is_error, "{}", result.output);
+    assert_eq!(result.output, "ok");
+    assert_eq!(
+        std::fs::read_to_string(h.workdir.join("out.txt")).unwrap(),
+        "written by wasm"
+    );
+}
+
+#[tokio::test]
+async fn wasm_plugin_memory_search_requires_capability() {
+    let h = harness();
+    h.context
+        .memory
+        .write_curated("# Memory\n\nfavorite color: teal\n")
+        .await
+        .unwrap();
+
+    let denied = install(&h.plugins, "no_memory", "memory_search", json!({}));
+    let result = load(&denied).execute(json!({}), &h.context).await.unwrap();
+    assert!(result.is_error);
+    assert!(result
+        .output
+        .contains("capability not granted: memory_search"));
+
+    let granted = install(
+        &h.plugins,
+        "recall",
+        "memory_search",
+        json!({"capabilities": {"memory_search": true}}),
+    );
+    let result = load(&granted).execute(json!({}), &h.context).await.unwrap();
+    assert!(!result.is_error, "{}", result.output);
+    let hits: Value = serde_json::from_str(&result.output).unwrap();
+    assert!(hits[0]["snippet"]
+        .as_str()
+        .unwrap()
+        .contains("favorite color: teal"));
+}
+
+#[tokio::test]
+async fn wasm_plugin_stops_when_fuel_runs_out() {
+    let h = harness();
+    let dir = install(
+        &h.plugins,
+        "spin",
+        "spin",
+        json!({"limits": {"fuel": 100000, "timeout_secs": 5}}),
+    );
+
+    let error = load(&dir).execute(json!({}), &h.context).await.unwrap_err();
+    assert!(error.to_string().contains("ran out of fuel"), "{error}");
+}
+
+#[tokio::test]
+async fn wasm_plugin_memory_limit_is_enforced() {
+    let h = harness();
+    let dir = install(&h.plugins, "big", "big_memory", json!({}));
+    assert!(load(&dir).execute(json!({}), &h.context).await.is_err());
+
+    let raised = install(
+        &h.plugins,
+        "big_allowed",
+        "big_memory",
+        json!({"limits": {"memory_mb": 256}}),
+    );
+    let result = load(&raised).execute(json!({}), &h.context).await.unwrap();
+    assert!(!result.is_error);
+}
+
+#[tokio::test]
+async fn register_wasm_plugins_skips_invalid_plugins() {
+    let h = harness();
+    install(&h.plugins, "echo", "echo", json!({}));
+    let broken = h.plugins.join("broken");
+    std::fs::create_dir_all(&broken).unwrap();
+    std::fs::write(broken.join("manifest.json"), r#"{"name": "broken"}"#).unwrap();
+    std::fs::write(broken.join("broken.wasm"), b"not wasm").unwrap();
+    let escaping = h.plugins.join("escaping");
+    std::fs::create_dir_all(&escaping).unwrap();
+    std::fs::write(
+        escaping.join("manifest.json"),
+        r#"{"name": "escaping", "module": "../echo/echo.wasm"}"#,
+    )
+    .unwrap();
+
+    let mut registry = ToolRegistry::new();
+    assert_eq!(register_wasm_plugins(&mut registry, &h.plugins), 1);
+    let names: Vec<String> = registry.specs().into_iter().map(|spec| spec.name).collect();
+    assert_eq!(names, vec!["echo".to_string()]);
+
+    assert_eq!(
+        register_wasm_plugins(&mut ToolRegistry::new(), &h.plugins.join("missing")),
+        0
+    );
+}
+
+#[tokio::test]
+async fn wasm_plugins_cannot_shadow_registered_tools() {
+    let h = harness();
+    install(&h.plugins, "read", "echo", json!({}));
+    install(&h.plugins, "echo", "echo", json!({}));
+    install(
+        &h.plugins,
+        "echo2",
+        "echo",
+        json!({"name": "echo", "module": "echo2.wasm"}),
+    );
+    install(
+        &h.plugins,
+        "dotted",
+        "echo",
+        json!({"name": "my.tool", "module": "dotted.wasm"}),
+    );
+    install(
+        &h.plugins,
+        "long",
+        "echo",
+        json!({"name": "x".repeat(65), "module": "long.wasm"}),
+    );
+
+    let mut registry = ToolRegistry::new();
+    registry.register_default_tools();
+    let before = registry.specs();
+    assert_eq!(register_wasm_plugins(&mut registry, &h.plugins), 1);
+
+    let specs = registry.specs();
+    assert_eq!(specs.len(), before.len() + 1);
+    let read = specs.iter().find(|spec| spec.name == "read").unwrap();
+    assert_ne!(read.description, "read plugin");
+    assert!(specs.iter().any(|spec| spec.name == "echo"));
+}
@@ -0,0 +1,523 @@
+use chaos_bot_backend::domain::audit::REDACTED;
+use chaos_bot_backend::domain::workspace::{
+    ExportOptions, ImportOptions, InvalidArchive, SecretMode,
+};
+use chaos_bot_backend::infrastructure::workspace_archive::WorkspaceArchive;
+use chaos_bot_backend::runtime::workspace_cli::WorkspaceCommand;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Imports that also restore `config.json` and `plugins/`.
+const DRY_RUN_ALL: ImportOptions = ImportOptions {
+    dry_run: true,
+    include_config: true,
+};
+const IMPORT_ALL: ImportOptions = ImportOptions {
+    dry_run: false,
+    include_config: true,
+};
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+fn config(model: &str, api_key: &str) -> String {
+    json!({
+        "llm": { "model": model, "max_tokens": 512 },
+        "secrets": { "openai_api_key": api_key }
+    })
+    .to_string()
+}
+
+fn sample_workspace() -> (TempDir, WorkspaceArchive) {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path();
+    write(root, "config.json", &config("gpt-4o", "sk-live"));
+    write(root, ".env", "OPENAI_API_KEY=sk-env\nLOG_LEVEL=debug\n");
+    write(root, ".env.example", "OPENAI_API_KEY=\n");
+    write(root, "personality/SOUL.md", "You are calm.\n");
+    write(
+        root,
+        "MEMORY.md",
+        "# Long-Term Memory\n\n## Preferences\n- Prefers tea\n",
+    );
+    write(root, "memory/2026-03-02.md", "- asked about the release\n");
+    write(root, "memory/.index/memory-index.json", "{}");
+    write(root, "logs/chaos-bot.log", "started\n");
+    write(root, "data/tool-output/call-1.txt", "spilled output");
+    write(root, "data/sessions/s1.json", "{}");
+    let archive = WorkspaceArchive::new(root, root.join("config.json"));
+    (temp, archive)
+}
+
+fn unpack(archive: &[u8]) -> BTreeMap<String, Vec<u8>> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+    tar.entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let path = String::from_utf8(entry.path_bytes().into_owned()).unwrap();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).unwrap();
+            (path, content)
+        })
+        .collect()
+}
+
+/// Packs entries as-is, without the path checks of `tar::Builder::append_data`.
+fn pack(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, content) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, *content).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn json_entry(files: &BTreeMap<String, Vec<u8>>, path: &str) -> Value {
+    serde_json::from_slice(&files[path]).unwrap()
+}
+
+fn text_entry(files: &BTreeMap<String, Vec<u8>>, path: &str) -> String {
+    String::from_utf8(files[path].clone()).unwrap()
+}
+
+#[test]
+fn export_skips_rebuildable_data_and_redacts_secrets() {
+    let (_temp, archive) = sample_workspace();
+    let export = archive.export_blocking(ExportOptions::default()).unwrap();
+    assert!(export.file_name.starts_with("chaos-bot-workspace-"));
+    assert!(export.file_name.ends_with(".tar.gz"));
+    assert_eq!(export.secrets, SecretMode::Redact);
+
+    let files = unpack(&export.archive);
+    assert_eq!(
+        files.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec![
+            "manifest.json",
+            "workspace/.env",
+            "workspace/.env.example",
+            "workspace/MEMORY.md",
+            "workspace/config.json",
+            "workspace/data/sessions/s1.json",
+            "workspace/memory/2026-03-02.md",
+            "workspace/personality/SOUL.md",
+        ]
+    );
+    assert_eq!(export.files, 7);
+
+    let manifest = json_entry(&files, "manifest.json");
+    assert_eq!(manifest["format"], json!("chaos-bot-workspace"));
+    assert_eq!(manifest["version"], json!(1));
+    assert_eq!(manifest["secrets"], json!("redact"));
+    assert_eq!(manifest["files"].as_array().unwrap().len(), 7);
+
+    let config = json_entry(&files, "workspace/config.json");
+    assert_eq!(config["secrets"]["openai_api_key"], json!(REDACTED));
+    assert_eq!(config["llm"]["max_tokens"], json!(512));
+    assert_eq!(
+        text_entry(&files, "workspace/.env"),
+        format!("OPENAI_API_KEY={REDACTED}\nLOG_LEVEL=debug\n")
+    );
+    assert_eq!(
+        text_entry(&files, "workspace/.env.example"),
+        "OPENAI_API_KEY=\n"
+    );
+}
+
+#[test]
+fn export_secret_modes_and_logs() {
+    let (_temp, archive) = sample_workspace();
+
+    let excluded = unpack(
+        &archive
+            .export_blocking(ExportOptions {
+                secrets: SecretMode::Exclude,
+                include_logs: true,
+            })
+            .unwrap()
+            .archive,
+    );
+    assert_eq!(
+        json_entry(&excluded, "workspace/config.json")["secrets"],
+        json!({})
+    );
+    assert_eq!(text_entry(&excluded, "workspace/.env"), "LOG_LEVEL=debug\n");
+    assert!(excluded.contains_key("workspace/logs/chaos-bot.log"));
+    assert!(!excluded.contains_key("workspace/data/tool-output/call-1.txt"));
+
+    let included = unpack(
+        &archive
+            .export_blocking(ExportOptions {
+                secrets: SecretMode::Include,
+                include_logs: false,
+            })
+            .unwrap()
+            .archive,
+    );
+    assert_eq!(
+        json_entry(&included, "workspace/config.json")["secrets"]["openai_api_key"],
+        json!("sk-live")
+    );
+    assert!(text_entry(&included, "workspace/.env").contains("sk-env"));
+    assert!(!included.contains_key("workspace/logs/chaos-bot.log"));
+}
+
+#[test]
+fn import_dry_run_diffs_then_restores_keeping_local_secrets() {
+    let (_source, archive) = sample_workspace();
+    let export = archive.export_blocking(ExportOptions::default()).unwrap();
+    // Filling the placeholders back in reformats the config but changes nothing.
+    let back = archive
+        .import_blocking(&export.archive, DRY_RUN_ALL)
+        .unwrap();
+    assert!(back.modified.is_empty(), "{back:?}");
+    assert_eq!(back.unchanged, 7);
+
+    let target = tempfile::tempdir().unwrap();
+    let root = target.path();
+    write(root, "config.json", &config("gpt-4o-mini", "sk-other"));
+    write(root, "personality/SOUL.md", "You are calm.\n");
+    write(root, "notes.txt", "local only");
+    let restore = WorkspaceArchive::new(root, root.join("config.json"));
+
+    let report = restore
+        .import_blocking(&export.archive, DRY_RUN_ALL)
+        .unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.secrets, SecretMode::Redact);
+    assert_eq!(
+        report.added,
+        vec![
+            ".env",
+            ".env.example",
+            "MEMORY.md",
+            "data/sessions/s1.json",
+            "memory/2026-03-02.md",
+        ]
+    );
+    assert_eq!(report.unchanged, 1);
+    assert_eq!(report.modified.len(), 1);
+    assert_eq!(report.modified[0].path, "config.json");
+    let diff = report.modified[0].diff.as_deref().unwrap();
+    assert!(diff.contains("-    \"model\": \"gpt-4o-mini\""), "{diff}");
+    assert!(diff.contains("+    \"model\": \"gpt-4o\""), "{diff}");
+    assert!(
+        !diff.contains("sk-"),
+        "secrets leaked into the diff: {diff}"
+    );
+    assert!(report.restart_required);
+    assert!(!root.join("MEMORY.md").exists(), "dry run wrote files");
+
+    let report = restore
+        .import_blocking(&export.archive, IMPORT_ALL)
+        .unwrap();
+    assert!(!report.dry_run);
+    assert_eq!(report.added.len(), 5);
+    assert_eq!(
+        std::fs::read_to_string(root.join("MEMORY.md")).unwrap(),
+        "# Long-Term Memory\n\n## Preferences\n- Prefers tea\n"
+    );
+    let config: Value =
+        serde_json::from_str(&std::fs::read_to_string(root.join("config.json")).unwrap()).unwrap();
+    assert_eq!(config["llm"]["model"], json!("gpt-4o"));
+    assert_eq!(config["secrets"]["openai_api_key"], json!("sk-other"));
+    // No local value for the redacted key, so it is dropped rather than written as a placeholder.
+    assert_eq!(
+        std::fs::read_to_string(root.join(".env")).unwrap(),
+        "LOG_LEVEL=debug\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("notes.txt")).unwrap(),
+        "local only"
+    );
+
+    let again = restore
+        .import_blocking(&export.archive, IMPORT_ALL)
+        .unwrap();
+    assert!(again.added.is_empty());
+    assert!(again.modified.is_empty());
+    assert!(!again.restart_required);
+    assert_eq!(again.unchanged, 7);
+}
+
+#[test]
+fn import_skips_config_and_plugins_unless_included() {
+    let target = tempfile::tempdir().unwrap();
+    let root = target.path();
+    write(root, "config.json", &config("gpt-4o-mini", "sk-local"));
+    let restore = WorkspaceArchive::new(root, root.join("config.json"));
+    let archive = archive_of(&[
+        ("MEMORY.md", b"# Memory\n"),
+        ("config.json", config("gpt-4o", "sk-other").as_bytes()),
+        ("plugins/tool.wasm", b"\0asm"),
+    ]);
+
+    let report = restore
+        .import_blocking(&archive, ImportOptions::default())
+        .unwrap();
+    assert_eq!(report.added, vec!["MEMORY.md"]);
+    assert!(report.modified.is_empty());
+    assert_eq!(report.skipped, vec!["config.json", "plugins/tool.wasm"]);
+    assert!(!report.restart_required);
+    assert!(std::fs::read_to_string(root.join("config.json"))
+        .unwrap()
+        .contains("gpt-4o-mini"));
+    assert!(!root.join("plugins").exists());
+
+    let report = restore.import_blocking(&archive, IMPORT_ALL).unwrap();
+    assert!(report.skipped.is_empty());
+    assert_eq!(report.added, vec!["plugins/tool.wasm"]);
+    assert_eq!(report.modified[0].path, "config.json");
+    assert!(report.restart_required);
+    assert_eq!(
+        std::fs::read(root.join("plugins/tool.wasm")).unwrap(),
+        b"\0asm"
+    );
+}
+
+fn assert_invalid(restore: &WorkspaceArchive, archive: &[u8], message: &str) {
+    let error = restore.import_blocking(archive, IMPORT_ALL).unwrap_err();
+    let invalid = error
+        .downcast_ref::<InvalidArchive>()
+        .unwrap_or_else(|| panic!("expected an invalid archive error, got {error:#}"));
+    assert!(invalid.0.contains(message), "{invalid}");
+}
+
+#[test]
+fn import_rejects_invalid_archives() {
+    let (_source, archive) = sample_workspace();
+    let export = archive.export_blocking(ExportOptions::default()).unwrap();
+    let files = unpack(&export.archive);
+    let manifest = files["manifest.json"].clone();
+    let target = tempfile::tempdir().unwrap();
+    let restore = WorkspaceArchive::new(target.path(), target.path().join("config.json"));
+
+    assert_invalid(&restore, b"not an archive", "");
+    assert_invalid(
+        &restore,
+        &pack(&[("workspace/MEMORY.md", b"hi")]),
+        "missing manifest",
+    );
+
+    let mut tampered: Vec<(&str, &[u8])> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_slice()))
+        .collect();
+    for entry in &mut tampered {
+        if entry.0 == "workspace/MEMORY.md" {
+            entry.1 = b"# Long-Term Memory\n\n## Preferences\n- Prefers coffee\n";
+        }
+    }
+    assert_invalid(&restore, &pack(&tampered), "checksum");
+
+    let mut extra = tampered.clone();
+    extra.retain(|entry| entry.0 != "workspace/MEMORY.md");
+    extra.push(("workspace/../escape.txt", b"x"));
+    assert_invalid(&restore, &pack(&extra), "unsafe path");
+    assert_invalid(
+        &restore,
+        &pack(&[("manifest.json", &manifest), ("/etc/passwd", b"x")]),
+        "unexpected entry",
+    );
+
+    let mut future: Value = serde_json::from_slice(&manifest).unwrap();
+    future["version"] = json!(2);
+    let future = serde_json::to_vec(&future).unwrap();
+    assert_invalid(
+        &restore,
+        &pack(&[("manifest.json", &future)]),
+        "unsupported version",
+    );
+
+    let broken = b"{ not json".as_slice();
+    let broken_manifest = json!({
+        "format": "chaos-bot-workspace",
+        "version": 1,
+        "created_at": "2026-03-02T10:00:00Z",
+        "secrets": "include",
+        "files": [{
+            "path": "config.json",
+            "bytes": broken.len(),
+            "sha256": Sha256::digest(broken)
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>()
+        }]
+    });
+    let broken_manifest = serde_json::to_vec(&broken_manifest).unwrap();
+    assert_invalid(
+        &restore,
+        &pack(&[
+            ("manifest.json", &broken_manifest),
+            ("workspace/config.json", broken),
+        ]),
+        "not a valid config",
+    );
+
+    assert_eq!(std::fs::read_dir(target.path()).unwrap().count(), 0);
+}
+
+/// An archive carrying `files` as-is, with a matching manifest.
+fn archive_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let manifest = json!({
+        "format": "chaos-bot-workspace",
+        "version": 1,
+        "created_at": "2026-03-02T10:00:00Z",
+        "secrets": "include",
+        "files": files
+            .iter()
+            .map(|(path, content)| json!({
+                "path": path,
+                "bytes": content.len(),
+                "sha256": Sha256::digest(content)
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<String>()
+            }))
+            .collect::<Vec<_>>()
+    });
+    let manifest = serde_json::to_vec(&manifest).unwrap();
+    let paths: Vec<String> = files
+        .iter()
+        .map(|(path, _)| format!("workspace/{path}"))
+        .collect();
+    let mut entries: Vec<(&str, &[u8])> = vec![("manifest.json", &manifest)];
+    entries.extend(
+        paths
+            .iter()
+            .zip(files)
+            .map(|(path, (_, content))| (path.as_str(), *content)),
+    );
+    pack(&entries)
+}
+
+#[cfg(unix)]
+#[test]
+fn import_refuses_paths_through_symlinks() {
+    let outside = tempfile::tempdir().unwrap();
+    write(outside.path(), "secret.txt", "outside");
+    let target = tempfile::tempdir().unwrap();
+    let root = target.path();
+    std::os::unix::fs::symlink(outside.path(), root.join("link")).unwrap();
+    std::os::unix::fs::symlink(outside.path().join("secret.txt"), root.join("notes.txt")).unwrap();
+    let restore = WorkspaceArchive::new(root, root.join("config.json"));
+
+    for path in ["link/evil.txt", "link/nested/evil.txt", "notes.txt"] {
+        let archive = archive_of(&[("MEMORY.md", b"# Memory\n"), (path, b"x")]);
+        assert_invalid(&restore, &archive, "symlink");
+        let error = restore.import_blocking(&archive, DRY_RUN_ALL).unwrap_err();
+        assert!(
+            error.downcast_ref::<InvalidArchive>().is_some(),
+            "{error:#}"
+        );
+    }
+    assert!(!outside.path().join("evil.txt").exists());
+    assert!(!outside.path().join("nested").exists());
+    assert_eq!(
+        std::fs::read_to_string(outside.path().join("secret.txt")).unwrap(),
+        "outside"
+    );
+    assert!(!root.join("MEMORY.md").exists());
+}
+
+#[test]
+fn failed_import_leaves_the_workspace_untouched() {
+    let target = tempfile::tempdir().unwrap();
+    let root = target.path();
+    write(root, "MEMORY.md", "# Local\n");
+    // A directory in the way of the last staged file makes the import fail part-way.
+    std::fs::create_dir(root.join("z.txt.import-tmp")).unwrap();
+    let restore = WorkspaceArchive::new(root, root.join("config.json"));
+
+    let archive = archive_of(&[
+        ("MEMORY.md", b"# Imported\n"),
+        ("a.txt", b"new"),
+        ("memory/2026-03-02.md", b"- note\n"),
+        ("z.txt", b"last"),
+    ]);
+    assert!(restore.import_blocking(&archive, IMPORT_ALL).is_err());
+    assert_eq!(
+        std::fs::read_to_string(root.join("MEMORY.md")).unwrap(),
+        "# Local\n"
+    );
+    let mut names: Vec<String> = std::fs::read_dir(root)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["MEMORY.md", "memory", "z.txt.import-tmp"]);
+    assert_eq!(std::fs::read_dir(root.join("memory")).unwrap().count(), 0);
+}
+
+#[test]
+fn workspace_subcommands_parse() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    assert!(WorkspaceCommand::parse(&args(&["mcp"])).is_none());
+    assert!(WorkspaceCommand::parse(&[]).is_none());
+    assert_eq!(
+        WorkspaceCommand::parse(&args(&["export"]))
+            .unwrap()
+            .unwrap(),
+        WorkspaceCommand::Export {
+            output: None,
+            options: ExportOptions::default(),
+        }
+    );
+    assert_eq!(
+        WorkspaceCommand::parse(&args(&[
+            "export",
+            "backup.tar.gz",
+            "--secrets",
+            "exclude",
+            "--include-logs"
+        ]))
+        .unwrap()
+        .unwrap(),
+        WorkspaceCommand::Export {
+            output: Some(PathBuf::from("backup.tar.gz")),
+            options: ExportOptions {
+                secrets: SecretMode::Exclude,
+                include_logs: true,
+            },
+        }
+    );
+    assert_eq!(
+        WorkspaceCommand::parse(&args(&["import", "backup.tar.gz", "--dry-run"]))
+            .unwrap()
+            .unwrap(),
+        WorkspaceCommand::Import {
+            input: PathBuf::from("backup.tar.gz"),
+            options: ImportOptions {
+                dry_run: true,
+                include_config: false,
+            },
+        }
+    );
+    assert!(WorkspaceCommand::parse(&args(&["import"]))
+        .unwrap()
+        .is_err());
+    assert!(
+        WorkspaceCommand::parse(&args(&["export", "--secrets", "plain"]))
+            .unwrap()
+            .is_err()
+    );
+    assert!(WorkspaceCommand::parse(&args(&["import", "a", "b"]))
+        .unwrap()
+        .is_err());
+}
@@ -0,0 +1,263 @@
+use chaos_bot_backend::domain::ports::WorkspaceSearchPort;
+use chaos_bot_backend::infrastructure::memory::{MemoryBackend, MemoryStore};
+use chaos_bot_backend::infrastructure::tooling::{SearchWorkspaceTool, Tool, ToolContext};
+use chaos_bot_backend::infrastructure::workspace_index::{
+    tokenize, HashedEmbedder, WorkspaceIndex, WorkspaceIndexOptions, WorkspaceRefresh,
+};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::{tempdir, TempDir};
+
+fn write(root: &Path, relative: &str, content: &str) {
+    let path = root.join(relative);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+/// A working directory with a few documents, plus an index directory beside it.
+fn workspace() -> (TempDir, PathBuf, PathBuf) {
+    let temp = tempdir().unwrap();
+    let root = temp.path().join("work");
+    write(
+        &root,
+        "docs/deploy.md",
+        "# Deployment\n\nThe service is deployed with docker compose.\nRun `docker compose up -d` on the host.\n",
+    );
+    write(
+        &root,
+        "docs/billing.md",
+        "# Billing\n\nInvoices are generated monthly by the billing worker.\n",
+    );
+    write(
+        &root,
+        "src/server.rs",
+        "fn start_server() {\n    // binds the http listener\n}\n",
+    );
+    let index_dir = temp.path().join("index");
+    (temp, root, index_dir)
+}
+
+#[test]
+fn tokenize_splits_words_and_camel_case() {
+    assert_eq!(
+        tokenize("buildSystemPrompt, snake_case x HTTP2"),
+        vec![
+            "build",
+            "system",
+            "prompt",
+            "buildsystemprompt",
+            "snake",
+            "case",
+            "http2"
+        ]
+    );
+}
+
+#[test]
+fn search_ranks_matching_chunks_with_bm25() {
+    let (_temp, root, index_dir) = workspace();
+    let index = WorkspaceIndex::new(&root, &index_dir, WorkspaceIndexOptions::default());
+    index.refresh().unwrap();
+
+    let hits = index
+        .search_blocking("docker compose deployment", 5)
+        .unwrap();
+    assert_eq!(hits[0].path, "docs/deploy.md");
+    assert_eq!((hits[0].start_line, hits[0].end_line), (1, 4));
+    assert!(hits[0].text.contains("docker compose up"));
+    assert!(hits.iter().all(|hit| hit.path != "docs/billing.md"));
+
+    let hits = index.search_blocking("http listener", 5).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, "src/server.rs");
+
+    assert!(index.search_blocking("kubernetes", 5).unwrap().is_empty());
+    assert!(index.search_blocking("  ", 5).unwrap().is_empty());
+}
+
+#[test]
+fn long_files_are_split_into_overlapping_chunks() {
+    let (_temp, root, index_dir) = workspace();
+    let mut long = (1..=100)
+        .map(|line| format!("filler line {line}"))
+        .collect::<Vec<_>>();
+    long[89] = "the secret ingredient is cardamom".to_string();
+    write(&root, "notes/long.txt", &long.join("\n"));
+    let options = WorkspaceIndexOptions {
+        chunk_lines: 40,
+        ..WorkspaceIndexOptions::default()
+    };
+    let index = WorkspaceIndex::new(&root, &index_dir, options);
+    index.refresh().unwrap();
+
+    let hits = index.search_blocking("cardamom", 5).unwrap();
+    let ranges: Vec<(usize, usize)> = hits
+        .iter()
+        .map(|hit| (hit.start_line, hit.end_line))
+        .collect();
+    assert_eq!(ranges, vec![(61, 100)]);
+}
+
+#[test]
+fn refresh_is_incremental_and_persisted() {
+    let (_temp, root, index_dir) = workspace();
+    write(&root, ".gitignore", "build/\n");
+    write(&root, "build/output.txt", "docker artifacts\n");
+    write(&root, "data/cache.txt", "docker cache\n");
+    std::fs::write(root.join("image.bin"), [0u8, 159, 146, 150]).unwrap();
+    let options = WorkspaceIndexOptions {
+        exclude: vec![root.join("data")],
+        ..WorkspaceIndexOptions::default()
+    };
+
+    let index = WorkspaceIndex::new(&root, &index_dir, options.clone());
+    let first = index.refresh().unwrap();
+    // Binary files are tracked so they are not re-read, but yield no chunks.
+    assert_eq!(first.indexed, 4, "{first:?}");
+    assert_eq!(index.refresh().unwrap().indexed, 0);
+    assert!(index.index_path().exists());
+
+    let hits = index.search_blocking("docker", 10).unwrap();
+    assert!(
+        hits.iter().all(|hit| hit.path == "docs/deploy.md"),
+        "{hits:?}"
+    );
+
+    let reopened = WorkspaceIndex::new(&root, &index_dir, options);
+    write(
+        &root,
+        "docs/billing.md",
+        "# Billing\n\nInvoices now ship via docker too.\n",
+    );
+    std::fs::remove_file(root.join("src/server.rs")).unwrap();
+    assert_eq!(
+        reopened.refresh().unwrap(),
+        WorkspaceRefresh {
+            files: 3,
+            indexed: 1,
+            removed: 1,
+            skipped: 0
+        }
+    );
+    let paths: Vec<String> = reopened
+        .search_blocking("docker", 10)
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.path)
+        .collect();
+    assert!(paths.contains(&"docs/billing.md".to_string()));
+    assert!(reopened.search_blocking("listener", 5).unwrap().is_empty());
+}
+
+#[test]
+fn search_reads_the_last_refresh_without_walking() {
+    let (_temp, root, index_dir) = workspace();
+    let index = WorkspaceIndex::new(&root, &index_dir, WorkspaceIndexOptions::default());
+    assert!(index.search_blocking("docker", 5).unwrap().is_empty());
+    assert!(!index.index_path().exists());
+
+    index.refresh().unwrap();
+    write(
+        &root,
+        "docs/ops.md",
+        "Rotate the signing keys every quarter.\n",
+    );
+    assert!(index.search_blocking("signing keys", 5).unwrap().is_empty());
+    index.refresh().unwrap();
+    assert_eq!(
+        index.search_blocking("signing keys", 5).unwrap()[0].path,
+        "docs/ops.md"
+    );
+
+    // A fresh process serves the persisted index until its first refresh.
+    let reopened = WorkspaceIndex::new(&root, &index_dir, WorkspaceIndexOptions::default());
+    assert_eq!(
+        reopened.search_blocking("signing keys", 5).unwrap()[0].path,
+        "docs/ops.md"
+    );
+}
+
+#[test]
+fn size_caps_limit_indexed_files() {
+    let (_temp, root, index_dir) = workspace();
+    write(&root, "big.txt", &"docker ".repeat(200));
+    let options = WorkspaceIndexOptions {
+        max_files: 2,
+        max_file_bytes: 512,
+        ..WorkspaceIndexOptions::default()
+    };
+    let index = WorkspaceIndex::new(&root, &index_dir, options);
+
+    let refresh = index.refresh().unwrap();
+    assert_eq!(refresh.files, 2);
+    assert_eq!(refresh.skipped, 1);
+    assert!(index
+        .search_blocking("docker", 5)
+        .unwrap()
+        .iter()
+        .all(|hit| hit.path != "big.txt"));
+}
+
+#[test]
+fn hashed_embeddings_match_related_word_forms() {
+    let (_temp, root, index_dir) = workspace();
+    write(
+        &root,
+        "docs/network.md",
+        "Reconnecting websocket transport with exponential backoff.\n",
+    );
+
+    let lexical = WorkspaceIndex::new(&root, &index_dir, WorkspaceIndexOptions::default());
+    lexical.refresh().unwrap();
+    assert!(lexical
+        .search_blocking("websockets reconnect", 5)
+        .unwrap()
+        .is_empty());
+
+    let hybrid = WorkspaceIndex::new(
+        &root,
+        index_dir.join("hybrid"),
+        WorkspaceIndexOptions::default(),
+    )
+    .with_embedder(Arc::new(HashedEmbedder::default()));
+    hybrid.refresh().unwrap();
+    let hits = hybrid.search_blocking("websockets reconnect", 5).unwrap();
+    assert_eq!(hits[0].path, "docs/network.md", "{hits:?}");
+}
+
+#[tokio::test]
+async fn search_workspace_tool_formats_passages() {
+    let (temp, root, index_dir) = workspace();
+    let index = WorkspaceIndex::new(&root, &index_dir, WorkspaceIndexOptions::default());
+    index.refresh().unwrap();
+    let index: Arc<dyn WorkspaceSearchPort> = Arc::new(index);
+    let memory: Arc<dyn MemoryBackend> = Arc::new(MemoryStore::new(
+        temp.path().join("memory"),
+        temp.path().join("MEMORY.md"),
+    ));
+    let context = ToolContext::new(root, memory);
+    let tool = SearchWorkspaceTool::new(index);
+
+    let result = tool
+        .execute(json!({"query": "billing invoices", "limit": 1}), &context)
+        .await
+        .unwrap();
+    assert!(
+        result.output.starts_with("docs/billing.md:1-3 (score "),
+        "{}",
+        result.output
+    );
+    assert!(result.output.contains("generated monthly"));
+
+    let none = tool
+        .execute(json!({"query": "kubernetes"}), &context)
+        .await
+        .unwrap();
+    assert_eq!(none.output, "no workspace passages matching kubernetes");
+
+    let error = tool.execute(json!({}), &context).await.unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("search_workspace.query is required"));
+}
@@ -15,7 +15,7 @@ export type RuntimeCommand =
   | "config.get"
   | "config.apply";
 
-export type StreamEventType = "session" | "delta" | "tool_call" | "done" | "error";
+export type StreamEventType = "session" | "delta" | "tool_call" | "plan" | "done" | "error";
 
 export type RuntimeErrorCode =
   | "NETWORK_UNAVAILABLE"
@@ -134,9 +134,17 @@ export interface SessionMessage {
   tool_call_id?: string;
 }
 
+export type PlanStatus = "pending" | "in_progress" | "done";
+
+export interface PlanItem {
+  content: string;
+  status: PlanStatus;
+}
+
 export interface SessionState {
   id: string;
   messages: SessionMessage[];
+  plan?: PlanItem[];
   created_at: string;
   updated_at: string;
 }
@@ -39,6 +39,7 @@ function parseSseBlock(block: string): { event: StreamEventType; data: string }
         value === "session" ||
         value === "delta" ||
         value === "tool_call" ||
+        value === "plan" ||
         value === "done" ||
         value === "error"
       ) {
@@ -180,7 +181,7 @@ export function createHttpAdapter(): RuntimeAdapter {
 
           const parsed = parseSseBlock(block);
           let payload: unknown = parsed.data;
-          if (["session", "tool_call", "done", "error"].includes(parsed.event)) {
+          if (["session", "tool_call", "plan", "done", "error"].includes(parsed.event)) {
             try {
               payload = JSON.parse(parsed.data);
             } catch (error) {
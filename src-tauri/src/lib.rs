@@ -159,7 +159,7 @@ fn parse_sse_block(block: &str) -> Option<(String, Value)> {
         return None;
     }
 
-    if ["session", "tool_call", "done", "error"].contains(&event.as_str()) {
+    if ["session", "tool_call", "plan", "done", "error"].contains(&event.as_str()) {
         let json = serde_json::from_str::<Value>(&data).ok()?;
         return Some((event, json));
     }